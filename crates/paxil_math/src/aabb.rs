@@ -0,0 +1,157 @@
+use super::mat4::Mat4;
+use super::sphere::Sphere;
+use super::vec3::Vec3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Default for Aabb {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl Aabb {
+    // inverted bounds, so extending with any point gives that point
+    pub const EMPTY: Self = Self {
+        min: Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+        max: Vec3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+    };
+
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_center_half_extents(center: Vec3, half_extents: Vec3) -> Self {
+        Self::new(center - half_extents, center + half_extents)
+    }
+
+    pub fn from_points(points: &[Vec3]) -> Self {
+        points.iter().fold(Self::EMPTY, |aabb, p| aabb.extend(*p))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    pub fn get_center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn get_size(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    pub fn get_half_extents(&self) -> Vec3 {
+        self.get_size() * 0.5
+    }
+
+    pub fn get_corners(&self) -> [Vec3; 8] {
+        let (a, b) = (self.min, self.max);
+        [
+            Vec3::new(a.x, a.y, a.z),
+            Vec3::new(b.x, a.y, a.z),
+            Vec3::new(a.x, b.y, a.z),
+            Vec3::new(b.x, b.y, a.z),
+            Vec3::new(a.x, a.y, b.z),
+            Vec3::new(b.x, a.y, b.z),
+            Vec3::new(a.x, b.y, b.z),
+            Vec3::new(b.x, b.y, b.z),
+        ]
+    }
+
+    pub fn extend(&self, p: Vec3) -> Self {
+        Self::new(self.min.min(p), self.max.max(p))
+    }
+
+    pub fn union(&self, other: &Aabb) -> Self {
+        Self::new(self.min.min(other.min), self.max.max(other.max))
+    }
+
+    pub fn intersection(&self, other: &Aabb) -> Option<Self> {
+        let result = Self::new(self.min.max(other.min), self.max.min(other.max));
+        (!result.is_empty()).then_some(result)
+    }
+
+    pub fn contains_point(&self, p: Vec3) -> bool {
+        p.x >= self.min.x
+            && p.y >= self.min.y
+            && p.z >= self.min.z
+            && p.x <= self.max.x
+            && p.y <= self.max.y
+            && p.z <= self.max.z
+    }
+
+    pub fn contains_aabb(&self, other: &Aabb) -> bool {
+        self.contains_point(other.min) && self.contains_point(other.max)
+    }
+
+    pub fn intersects_aabb(&self, other: &Aabb) -> bool {
+        self.min.x <= other.max.x
+            && self.max.x >= other.min.x
+            && self.min.y <= other.max.y
+            && self.max.y >= other.min.y
+            && self.min.z <= other.max.z
+            && self.max.z >= other.min.z
+    }
+
+    pub fn intersects_sphere(&self, sphere: &Sphere) -> bool {
+        self.closest_point(sphere.center).distance(sphere.center) <= sphere.radius
+    }
+
+    pub fn closest_point(&self, p: Vec3) -> Vec3 {
+        p.clamp(self.min, self.max)
+    }
+
+    pub fn distance_to_point(&self, p: Vec3) -> f32 {
+        self.closest_point(p).distance(p)
+    }
+
+    // bounds of the transformed box, which is generally larger than the box itself
+    pub fn transform(&self, m: &Mat4) -> Self {
+        let center = m.transform_point3(self.get_center());
+        let e = self.get_half_extents();
+        let half_extents = Vec3::new(
+            m.cols[0].x.abs() * e.x + m.cols[1].x.abs() * e.y + m.cols[2].x.abs() * e.z,
+            m.cols[0].y.abs() * e.x + m.cols[1].y.abs() * e.y + m.cols[2].y.abs() * e.z,
+            m.cols[0].z.abs() * e.x + m.cols[1].z.abs() * e.y + m.cols[2].z.abs() * e.z,
+        );
+        Self::from_center_half_extents(center, half_extents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aabb_queries() {
+        let aabb = Aabb::from_points(&[Vec3::new(1.0, -2.0, 0.0), Vec3::new(-1.0, 2.0, 3.0)]);
+        assert_eq!(
+            aabb,
+            Aabb::new(Vec3::new(-1.0, -2.0, 0.0), Vec3::new(1.0, 2.0, 3.0))
+        );
+        assert!(Aabb::EMPTY.is_empty());
+        assert!(aabb.contains_point(Vec3::new(0.0, 0.0, 1.0)));
+        assert_eq!(
+            aabb.closest_point(Vec3::new(5.0, 0.0, -5.0)),
+            Vec3::new(1.0, 0.0, 0.0)
+        );
+        assert!(aabb.intersects_sphere(&Sphere::new(Vec3::new(2.0, 0.0, 1.0), 1.0)));
+        assert!(!aabb.intersects_sphere(&Sphere::new(Vec3::new(3.0, 0.0, 1.0), 1.0)));
+    }
+
+    #[test]
+    fn transformed_aabb_bounds_rotated_corners() {
+        let aabb = Aabb::new(-Vec3::ONE, Vec3::ONE);
+        let m = Mat4::from_translation(Vec3::new(5.0, 0.0, 0.0))
+            * Mat4::from_rotation_z(std::f32::consts::FRAC_PI_4);
+        let bounds = aabb.transform(&m);
+        let s = std::f32::consts::SQRT_2;
+        assert!(bounds.min.abs_diff_eq(Vec3::new(5.0 - s, -s, -1.0), 1e-5));
+        assert!(bounds.max.abs_diff_eq(Vec3::new(5.0 + s, s, 1.0), 1e-5));
+    }
+}
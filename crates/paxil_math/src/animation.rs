@@ -0,0 +1,601 @@
+use super::color::Color;
+use super::quat::Quat;
+use super::transform::Transform;
+use super::vec2::Vec2;
+use super::vec3::Vec3;
+use super::vec4::Vec4;
+
+// values that tweens and keyframe tracks can animate
+pub trait Interpolate: Copy {
+    fn interpolate(a: &Self, b: &Self, t: f32) -> Self;
+    // tangents are derivatives per second, duration is the length of the segment
+    fn hermite(p0: &Self, m0: &Self, p1: &Self, m1: &Self, duration: f32, t: f32) -> Self;
+    // (b - a) / dt, used to derive Catmull-Rom tangents
+    fn slope(a: &Self, b: &Self, dt: f32) -> Self;
+}
+
+fn hermite_basis(t: f32) -> (f32, f32, f32, f32) {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (
+        2.0 * t3 - 3.0 * t2 + 1.0,
+        t3 - 2.0 * t2 + t,
+        -2.0 * t3 + 3.0 * t2,
+        t3 - t2,
+    )
+}
+
+macro_rules! impl_interpolate {
+    ($($t:ty),+) => {
+        $(
+            impl Interpolate for $t {
+                fn interpolate(a: &Self, b: &Self, t: f32) -> Self {
+                    *a + (*b - *a) * t
+                }
+
+                fn hermite(p0: &Self, m0: &Self, p1: &Self, m1: &Self, duration: f32, t: f32) -> Self {
+                    let (h00, h10, h01, h11) = hermite_basis(t);
+                    *p0 * h00 + *m0 * (h10 * duration) + *p1 * h01 + *m1 * (h11 * duration)
+                }
+
+                fn slope(a: &Self, b: &Self, dt: f32) -> Self {
+                    (*b - *a) * (1.0 / dt)
+                }
+            }
+        )+
+    };
+}
+
+impl_interpolate!(f32, Vec2, Vec3, Vec4);
+
+impl Interpolate for Quat {
+    fn interpolate(a: &Self, b: &Self, t: f32) -> Self {
+        a.slerp(*b, t)
+    }
+
+    // component-wise on the same hemisphere, then renormalized
+    fn hermite(p0: &Self, m0: &Self, p1: &Self, m1: &Self, duration: f32, t: f32) -> Self {
+        let p0v = p0.to_vec4();
+        let mut p1v = p1.to_vec4();
+        if p0v.dot(p1v) < 0.0 {
+            p1v = -p1v;
+        }
+        let v = Vec4::hermite(&p0v, &m0.to_vec4(), &p1v, &m1.to_vec4(), duration, t);
+        Quat::from_xyzw(v.x, v.y, v.z, v.w).normalize()
+    }
+
+    fn slope(a: &Self, b: &Self, dt: f32) -> Self {
+        let av = a.to_vec4();
+        let mut bv = b.to_vec4();
+        if av.dot(bv) < 0.0 {
+            bv = -bv;
+        }
+        let v = Vec4::slope(&av, &bv, dt);
+        Quat::from_xyzw(v.x, v.y, v.z, v.w)
+    }
+}
+
+// in sRGB, use Color::mix for perceptual blends
+impl Interpolate for Color {
+    fn interpolate(a: &Self, b: &Self, t: f32) -> Self {
+        a.lerp(*b, t)
+    }
+
+    fn hermite(p0: &Self, m0: &Self, p1: &Self, m1: &Self, duration: f32, t: f32) -> Self {
+        let (p0, m0, p1, m1) = (p0.to_vec4(), m0.to_vec4(), p1.to_vec4(), m1.to_vec4());
+        Vec4::hermite(&p0, &m0, &p1, &m1, duration, t).into()
+    }
+
+    fn slope(a: &Self, b: &Self, dt: f32) -> Self {
+        Vec4::slope(&a.to_vec4(), &b.to_vec4(), dt).into()
+    }
+}
+
+impl Interpolate for Transform {
+    fn interpolate(a: &Self, b: &Self, t: f32) -> Self {
+        a.lerp(b, t)
+    }
+
+    fn hermite(p0: &Self, m0: &Self, p1: &Self, m1: &Self, duration: f32, t: f32) -> Self {
+        Self {
+            translation: Vec3::hermite(
+                &p0.translation,
+                &m0.translation,
+                &p1.translation,
+                &m1.translation,
+                duration,
+                t,
+            ),
+            rotation: Quat::hermite(
+                &p0.rotation,
+                &m0.rotation,
+                &p1.rotation,
+                &m1.rotation,
+                duration,
+                t,
+            ),
+            scale: Vec3::hermite(&p0.scale, &m0.scale, &p1.scale, &m1.scale, duration, t),
+        }
+    }
+
+    fn slope(a: &Self, b: &Self, dt: f32) -> Self {
+        Self {
+            translation: Vec3::slope(&a.translation, &b.translation, dt),
+            rotation: Quat::slope(&a.rotation, &b.rotation, dt),
+            scale: Vec3::slope(&a.scale, &b.scale, dt),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EaseMode {
+    In,
+    Out,
+    InOut,
+}
+
+// Robert Penner's easing equations, t is expected in [0, 1]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    Quad(EaseMode),
+    Cubic(EaseMode),
+    Quart(EaseMode),
+    Quint(EaseMode),
+    Sine(EaseMode),
+    Expo(EaseMode),
+    Circ(EaseMode),
+    Back(EaseMode),
+    Elastic(EaseMode),
+    Bounce(EaseMode),
+    // CSS cubic-bezier(x1, y1, x2, y2), the end points are (0, 0) and (1, 1)
+    CubicBezier(f32, f32, f32, f32),
+}
+
+impl Easing {
+    pub const EASE: Self = Self::CubicBezier(0.25, 0.1, 0.25, 1.0);
+    pub const EASE_IN: Self = Self::CubicBezier(0.42, 0.0, 1.0, 1.0);
+    pub const EASE_OUT: Self = Self::CubicBezier(0.0, 0.0, 0.58, 1.0);
+    pub const EASE_IN_OUT: Self = Self::CubicBezier(0.42, 0.0, 0.58, 1.0);
+
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match *self {
+            Easing::Linear => t,
+            Easing::Quad(mode) => ease(mode, t, |t| t * t),
+            Easing::Cubic(mode) => ease(mode, t, |t| t * t * t),
+            Easing::Quart(mode) => ease(mode, t, |t| t * t * t * t),
+            Easing::Quint(mode) => ease(mode, t, |t| t * t * t * t * t),
+            Easing::Sine(mode) => ease(mode, t, |t| 1.0 - (t * std::f32::consts::FRAC_PI_2).cos()),
+            Easing::Expo(mode) => ease(mode, t, |t| {
+                if t <= 0.0 {
+                    0.0
+                } else {
+                    2f32.powf(10.0 * t - 10.0)
+                }
+            }),
+            Easing::Circ(mode) => ease(mode, t, |t| 1.0 - (1.0 - t * t).max(0.0).sqrt()),
+            Easing::Back(mode) => ease(mode, t, |t| {
+                const C1: f32 = 1.70158;
+                const C3: f32 = C1 + 1.0;
+                C3 * t * t * t - C1 * t * t
+            }),
+            Easing::Elastic(mode) => ease(mode, t, |t| {
+                if t <= 0.0 || t >= 1.0 {
+                    return t;
+                }
+                let c4 = std::f32::consts::TAU / 3.0;
+                -(2f32.powf(10.0 * t - 10.0)) * ((t * 10.0 - 10.75) * c4).sin()
+            }),
+            Easing::Bounce(mode) => ease(mode, t, |t| 1.0 - bounce_out(1.0 - t)),
+            Easing::CubicBezier(x1, y1, x2, y2) => cubic_bezier(x1, y1, x2, y2, t),
+        }
+    }
+}
+
+// builds the out and in-out variants from the in curve
+fn ease<F: Fn(f32) -> f32>(mode: EaseMode, t: f32, ease_in: F) -> f32 {
+    match mode {
+        EaseMode::In => ease_in(t),
+        EaseMode::Out => 1.0 - ease_in(1.0 - t),
+        EaseMode::InOut => {
+            if t < 0.5 {
+                ease_in(2.0 * t) * 0.5
+            } else {
+                1.0 - ease_in(2.0 - 2.0 * t) * 0.5
+            }
+        }
+    }
+}
+
+fn bounce_out(t: f32) -> f32 {
+    const N1: f32 = 7.5625;
+    const D1: f32 = 2.75;
+    if t < 1.0 / D1 {
+        N1 * t * t
+    } else if t < 2.0 / D1 {
+        let t = t - 1.5 / D1;
+        N1 * t * t + 0.75
+    } else if t < 2.5 / D1 {
+        let t = t - 2.25 / D1;
+        N1 * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D1;
+        N1 * t * t + 0.984375
+    }
+}
+
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    let bezier = |a: f32, b: f32, s: f32| {
+        let u = 1.0 - s;
+        3.0 * u * u * s * a + 3.0 * u * s * s * b + s * s * s
+    };
+    let derivative = |a: f32, b: f32, s: f32| {
+        let u = 1.0 - s;
+        3.0 * u * u * a + 6.0 * u * s * (b - a) + 3.0 * s * s * (1.0 - b)
+    };
+
+    // Newton first, bisection when the slope is too flat to converge
+    let mut s = x;
+    for _ in 0..8 {
+        let error = bezier(x1, x2, s) - x;
+        if error.abs() < 1e-6 {
+            return bezier(y1, y2, s);
+        }
+        let d = derivative(x1, x2, s);
+        if d.abs() < 1e-6 {
+            break;
+        }
+        s -= error / d;
+    }
+    let (mut lo, mut hi) = (0.0f32, 1.0f32);
+    s = x;
+    for _ in 0..32 {
+        let value = bezier(x1, x2, s);
+        if (value - x).abs() < 1e-6 {
+            break;
+        }
+        if value < x {
+            lo = s;
+        } else {
+            hi = s;
+        }
+        s = (lo + hi) * 0.5;
+    }
+    bezier(y1, y2, s)
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LoopMode {
+    #[default]
+    Once,
+    Loop,
+    PingPong,
+}
+
+impl LoopMode {
+    // maps a time since the start onto [0, duration]
+    pub fn apply(&self, time: f32, duration: f32) -> f32 {
+        if duration <= 0.0 {
+            return 0.0;
+        }
+        match self {
+            LoopMode::Once => time.clamp(0.0, duration),
+            LoopMode::Loop => time.rem_euclid(duration),
+            LoopMode::PingPong => {
+                let t = time.rem_euclid(2.0 * duration);
+                if t > duration {
+                    2.0 * duration - t
+                } else {
+                    t
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Tween<T: Interpolate> {
+    pub from: T,
+    pub to: T,
+    pub duration: f32,
+    pub delay: f32,
+    pub easing: Easing,
+    pub loop_mode: LoopMode,
+    elapsed: f32,
+}
+
+impl<T: Interpolate> Tween<T> {
+    pub fn new(from: T, to: T, duration: f32) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            delay: 0.0,
+            easing: Easing::Linear,
+            loop_mode: LoopMode::Once,
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    pub fn with_delay(mut self, delay: f32) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn with_loop_mode(mut self, loop_mode: LoopMode) -> Self {
+        self.loop_mode = loop_mode;
+        self
+    }
+
+    // advances by the frame delta in seconds and returns the new value
+    pub fn update(&mut self, delta: f32) -> T {
+        self.elapsed += delta;
+        self.get_value()
+    }
+
+    pub fn seek(&mut self, elapsed: f32) {
+        self.elapsed = elapsed;
+    }
+
+    pub fn reset(&mut self) {
+        self.elapsed = 0.0;
+    }
+
+    pub fn get_elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    // linear progress in [0, 1] after delay and looping, get_value applies the easing
+    pub fn get_progress(&self) -> f32 {
+        if self.duration <= 0.0 {
+            return if self.elapsed >= self.delay { 1.0 } else { 0.0 };
+        }
+        let time = (self.elapsed - self.delay).max(0.0);
+        self.loop_mode.apply(time, self.duration) / self.duration
+    }
+
+    pub fn get_value(&self) -> T {
+        T::interpolate(&self.from, &self.to, self.easing.apply(self.get_progress()))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.loop_mode == LoopMode::Once && self.elapsed >= self.delay + self.duration
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeyframeInterpolation {
+    Step,
+    #[default]
+    Linear,
+    CubicHermite,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Keyframe<T> {
+    pub time: f32,
+    pub value: T,
+    // tangents arriving at and leaving the key, None derives a Catmull-Rom tangent
+    // from the neighbouring keys
+    pub in_tangent: Option<T>,
+    pub out_tangent: Option<T>,
+}
+
+#[derive(Clone, Debug)]
+pub struct KeyframeTrack<T: Interpolate> {
+    keys: Vec<Keyframe<T>>,
+    pub interpolation: KeyframeInterpolation,
+    pub loop_mode: LoopMode,
+}
+
+impl<T: Interpolate> Default for KeyframeTrack<T> {
+    fn default() -> Self {
+        Self::new(KeyframeInterpolation::Linear)
+    }
+}
+
+impl<T: Interpolate> KeyframeTrack<T> {
+    pub fn new(interpolation: KeyframeInterpolation) -> Self {
+        Self {
+            keys: Vec::new(),
+            interpolation,
+            loop_mode: LoopMode::Once,
+        }
+    }
+
+    pub fn with_loop_mode(mut self, loop_mode: LoopMode) -> Self {
+        self.loop_mode = loop_mode;
+        self
+    }
+
+    // keeps the keys sorted, a key at an existing time replaces it
+    pub fn add_key(&mut self, time: f32, value: T) {
+        self.insert(Keyframe {
+            time,
+            value,
+            in_tangent: None,
+            out_tangent: None,
+        });
+    }
+
+    pub fn add_key_with_tangent(&mut self, time: f32, value: T, tangent: T) {
+        self.add_key_with_tangents(time, value, tangent, tangent);
+    }
+
+    pub fn add_key_with_tangents(&mut self, time: f32, value: T, in_tangent: T, out_tangent: T) {
+        self.insert(Keyframe {
+            time,
+            value,
+            in_tangent: Some(in_tangent),
+            out_tangent: Some(out_tangent),
+        });
+    }
+
+    fn insert(&mut self, key: Keyframe<T>) {
+        let index = self.keys.partition_point(|k| k.time < key.time);
+        match self.keys.get_mut(index) {
+            Some(existing) if existing.time == key.time => *existing = key,
+            _ => self.keys.insert(index, key),
+        }
+    }
+
+    pub fn get_keys(&self) -> &[Keyframe<T>] {
+        &self.keys
+    }
+
+    pub fn get_start_time(&self) -> f32 {
+        self.keys.first().map(|k| k.time).unwrap_or(0.0)
+    }
+
+    pub fn get_duration(&self) -> f32 {
+        match (self.keys.first(), self.keys.last()) {
+            (Some(first), Some(last)) => last.time - first.time,
+            _ => 0.0,
+        }
+    }
+
+    // time is in seconds since the track started, looping is applied from the first key
+    pub fn sample(&self, time: f32) -> Option<T> {
+        let first = self.keys.first()?;
+        let local = first.time + self.loop_mode.apply(time - first.time, self.get_duration());
+        let index = self.keys.partition_point(|k| k.time <= local);
+        if index == 0 {
+            return Some(first.value);
+        }
+        if index >= self.keys.len() {
+            return self.keys.last().map(|k| k.value);
+        }
+        let (k0, k1) = (&self.keys[index - 1], &self.keys[index]);
+        let duration = k1.time - k0.time;
+        let t = (local - k0.time) / duration;
+        Some(match self.interpolation {
+            KeyframeInterpolation::Step => k0.value,
+            KeyframeInterpolation::Linear => T::interpolate(&k0.value, &k1.value, t),
+            KeyframeInterpolation::CubicHermite => {
+                let m0 = self.get_tangent(index - 1, k0.out_tangent);
+                let m1 = self.get_tangent(index, k1.in_tangent);
+                T::hermite(&k0.value, &m0, &k1.value, &m1, duration, t)
+            }
+        })
+    }
+
+    fn get_tangent(&self, index: usize, tangent: Option<T>) -> T {
+        if let Some(tangent) = tangent {
+            return tangent;
+        }
+        let key = &self.keys[index];
+        let prev = &self.keys[index.saturating_sub(1)];
+        let next = &self.keys[(index + 1).min(self.keys.len() - 1)];
+        if next.time > prev.time {
+            T::slope(&prev.value, &next.value, next.time - prev.time)
+        } else {
+            T::slope(&key.value, &key.value, 1.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn easings_hit_end_points() {
+        let modes = [EaseMode::In, EaseMode::Out, EaseMode::InOut];
+        for mode in modes {
+            for easing in [
+                Easing::Quad(mode),
+                Easing::Cubic(mode),
+                Easing::Quart(mode),
+                Easing::Quint(mode),
+                Easing::Sine(mode),
+                Easing::Expo(mode),
+                Easing::Circ(mode),
+                Easing::Back(mode),
+                Easing::Elastic(mode),
+                Easing::Bounce(mode),
+            ] {
+                assert!(easing.apply(0.0).abs() < 1e-4, "{:?}", easing);
+                assert!((easing.apply(1.0) - 1.0).abs() < 1e-4, "{:?}", easing);
+            }
+        }
+        assert_eq!(Easing::Quad(EaseMode::In).apply(0.5), 0.25);
+        assert_eq!(Easing::Quad(EaseMode::Out).apply(0.5), 0.75);
+    }
+
+    #[test]
+    fn cubic_bezier_matches_known_curves() {
+        let linear = Easing::CubicBezier(0.25, 0.25, 0.75, 0.75);
+        for i in 0..=10 {
+            let t = i as f32 / 10.0;
+            assert!((linear.apply(t) - t).abs() < 1e-4);
+        }
+        // symmetric curve passes through the middle
+        assert!((Easing::EASE_IN_OUT.apply(0.5) - 0.5).abs() < 1e-4);
+        assert!(Easing::EASE_IN.apply(0.25) < 0.25);
+    }
+
+    #[test]
+    fn tween_loops_and_finishes() {
+        let mut tween = Tween::new(0.0f32, 10.0, 2.0).with_delay(1.0);
+        assert_eq!(tween.update(0.5), 0.0);
+        assert_eq!(tween.update(1.5), 5.0);
+        assert!(!tween.is_finished());
+        assert_eq!(tween.update(5.0), 10.0);
+        assert!(tween.is_finished());
+
+        let mut ping_pong =
+            Tween::new(Vec2::ZERO, Vec2::ONE, 1.0).with_loop_mode(LoopMode::PingPong);
+        assert_eq!(ping_pong.update(1.25), Vec2::splat(0.75));
+    }
+
+    #[test]
+    fn keyframe_track_sampling() {
+        let mut track = KeyframeTrack::new(KeyframeInterpolation::Linear);
+        track.add_key(1.0, 10.0f32);
+        track.add_key(0.0, 0.0);
+        track.add_key(2.0, 0.0);
+        assert_eq!(track.get_duration(), 2.0);
+        assert_eq!(track.sample(-1.0), Some(0.0));
+        assert_eq!(track.sample(0.5), Some(5.0));
+        assert_eq!(track.sample(1.5), Some(5.0));
+
+        track.interpolation = KeyframeInterpolation::Step;
+        assert_eq!(track.sample(0.99), Some(0.0));
+        assert_eq!(track.sample(1.0), Some(10.0));
+
+        track.interpolation = KeyframeInterpolation::CubicHermite;
+        assert_eq!(track.sample(1.0), Some(10.0));
+        // the peak key gets a flat Catmull-Rom tangent, so the curve overshoots linear
+        assert!(track.sample(0.75).unwrap() > 7.5);
+
+        let track = track.with_loop_mode(LoopMode::Loop);
+        assert_eq!(track.sample(3.0), Some(10.0));
+    }
+
+    #[test]
+    fn hermite_with_explicit_tangents_is_linear() {
+        let mut track = KeyframeTrack::new(KeyframeInterpolation::CubicHermite);
+        track.add_key_with_tangent(0.0, Vec3::ZERO, Vec3::X);
+        track.add_key_with_tangent(4.0, Vec3::new(4.0, 0.0, 0.0), Vec3::X);
+        let v = track.sample(1.0).unwrap();
+        assert!(v.abs_diff_eq(Vec3::X, 1e-5));
+    }
+
+    #[test]
+    fn hermite_uses_separate_in_and_out_tangents() {
+        let mut track = KeyframeTrack::new(KeyframeInterpolation::CubicHermite);
+        track.add_key_with_tangents(0.0, 0.0f32, 0.0, 1.0);
+        // the middle key arrives flat and leaves with slope 1
+        track.add_key_with_tangents(1.0, 1.0, 0.0, 1.0);
+        track.add_key_with_tangents(2.0, 2.0, 1.0, 0.0);
+        assert!((track.sample(0.5).unwrap() - 0.625).abs() < 1e-5);
+        assert!((track.sample(1.5).unwrap() - 1.5).abs() < 1e-5);
+    }
+}
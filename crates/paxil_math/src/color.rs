@@ -0,0 +1,552 @@
+use super::vec3::Vec3;
+use super::vec4::Vec4;
+
+// sRGB encoded components with straight alpha, the same values a hex code or a
+// color picker gives. Use to_linear() before lighting or blending math.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorSpace {
+    Srgb,
+    LinearSrgb,
+    Hsv,
+    Hsl,
+    #[default]
+    Oklab,
+    Oklch,
+    Lab,
+}
+
+impl Color {
+    pub const TRANSPARENT: Self = Self::new(0.0, 0.0, 0.0, 0.0);
+    pub const BLACK: Self = Self::rgb(0.0, 0.0, 0.0);
+    pub const WHITE: Self = Self::rgb(1.0, 1.0, 1.0);
+    pub const RED: Self = Self::rgb(1.0, 0.0, 0.0);
+    pub const GREEN: Self = Self::rgb(0.0, 1.0, 0.0);
+    pub const BLUE: Self = Self::rgb(0.0, 0.0, 1.0);
+    pub const YELLOW: Self = Self::rgb(1.0, 1.0, 0.0);
+    pub const CYAN: Self = Self::rgb(0.0, 1.0, 1.0);
+    pub const MAGENTA: Self = Self::rgb(1.0, 0.0, 1.0);
+
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Self::new(r, g, b, 1.0)
+    }
+
+    pub const fn gray(v: f32) -> Self {
+        Self::rgb(v, v, v)
+    }
+
+    pub fn from_rgba8(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self::new(
+            r as f32 / 255.0,
+            g as f32 / 255.0,
+            b as f32 / 255.0,
+            a as f32 / 255.0,
+        )
+    }
+
+    pub fn to_rgba8(self) -> [u8; 4] {
+        let c = self.clamp();
+        [c.r, c.g, c.b, c.a].map(|v| (v * 255.0).round() as u8)
+    }
+
+    // accepts rgb, rgba, rrggbb and rrggbbaa with an optional leading #
+    pub fn from_hex(hex: &str) -> Result<Self, String> {
+        let digits = hex.trim().trim_start_matches('#');
+        let nibble = |c: char| {
+            c.to_digit(16)
+                .map(|d| d as u8)
+                .ok_or_else(|| format!("Invalid hex color: {}", hex))
+        };
+        let chars = digits.chars().map(nibble).collect::<Result<Vec<_>, _>>()?;
+        let bytes: Vec<u8> = match chars.len() {
+            3 | 4 => chars.iter().map(|n| n * 17).collect(),
+            6 | 8 => chars.chunks(2).map(|p| p[0] * 16 + p[1]).collect(),
+            _ => return Err(format!("Invalid hex color length: {}", hex)),
+        };
+        let alpha = bytes.get(3).copied().unwrap_or(255);
+        Ok(Self::from_rgba8(bytes[0], bytes[1], bytes[2], alpha))
+    }
+
+    // #rrggbb, or #rrggbbaa when the color is not opaque
+    pub fn to_hex(self) -> String {
+        let [r, g, b, a] = self.to_rgba8();
+        if a == 255 {
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+        }
+    }
+
+    pub fn with_alpha(self, a: f32) -> Self {
+        Self { a, ..self }
+    }
+
+    pub fn clamp(self) -> Self {
+        Self::new(
+            self.r.clamp(0.0, 1.0),
+            self.g.clamp(0.0, 1.0),
+            self.b.clamp(0.0, 1.0),
+            self.a.clamp(0.0, 1.0),
+        )
+    }
+
+    pub fn premultiply(self) -> Self {
+        Self::new(self.r * self.a, self.g * self.a, self.b * self.a, self.a)
+    }
+
+    pub fn to_vec3(self) -> Vec3 {
+        Vec3::new(self.r, self.g, self.b)
+    }
+
+    pub fn to_vec4(self) -> Vec4 {
+        Vec4::new(self.r, self.g, self.b, self.a)
+    }
+
+    pub fn to_array(self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    fn from_vec3(v: Vec3, a: f32) -> Self {
+        Self::new(v.x, v.y, v.z, a)
+    }
+
+    // sRGB transfer function, alpha is left untouched
+
+    pub fn to_linear(self) -> Self {
+        Self::new(
+            srgb_to_linear(self.r),
+            srgb_to_linear(self.g),
+            srgb_to_linear(self.b),
+            self.a,
+        )
+    }
+
+    pub fn from_linear(linear: Self) -> Self {
+        Self::new(
+            linear_to_srgb(linear.r),
+            linear_to_srgb(linear.g),
+            linear_to_srgb(linear.b),
+            linear.a,
+        )
+    }
+
+    // hue in degrees, saturation and value in [0, 1]
+
+    pub fn from_hsv(h: f32, s: f32, v: f32, a: f32) -> Self {
+        let h = h.rem_euclid(360.0) / 60.0;
+        let c = v * s;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = hue_sector(h, c, x);
+        let m = v - c;
+        Self::new(r + m, g + m, b + m, a)
+    }
+
+    pub fn to_hsv(self) -> Vec3 {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let delta = max - min;
+        let s = if max > 0.0 { delta / max } else { 0.0 };
+        Vec3::new(self.hue(max, delta), s, max)
+    }
+
+    pub fn from_hsl(h: f32, s: f32, l: f32, a: f32) -> Self {
+        let h = h.rem_euclid(360.0) / 60.0;
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = hue_sector(h, c, x);
+        let m = l - c * 0.5;
+        Self::new(r + m, g + m, b + m, a)
+    }
+
+    pub fn to_hsl(self) -> Vec3 {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let delta = max - min;
+        let l = (max + min) * 0.5;
+        let s = if delta > 0.0 {
+            delta / (1.0 - (2.0 * l - 1.0).abs())
+        } else {
+            0.0
+        };
+        Vec3::new(self.hue(max, delta), s, l)
+    }
+
+    fn hue(self, max: f32, delta: f32) -> f32 {
+        if delta <= 0.0 {
+            return 0.0;
+        }
+        let h = if max == self.r {
+            ((self.g - self.b) / delta).rem_euclid(6.0)
+        } else if max == self.g {
+            (self.b - self.r) / delta + 2.0
+        } else {
+            (self.r - self.g) / delta + 4.0
+        };
+        h * 60.0
+    }
+
+    // Oklab (L, a, b) after Björn Ottosson, the published coefficients are kept as is
+
+    #[allow(clippy::excessive_precision)]
+    pub fn to_oklab(self) -> Vec3 {
+        let c = self.to_linear();
+        let l = 0.4122214708 * c.r + 0.5363325363 * c.g + 0.0514459929 * c.b;
+        let m = 0.2119034982 * c.r + 0.6806995451 * c.g + 0.1073969566 * c.b;
+        let s = 0.0883024619 * c.r + 0.2817188376 * c.g + 0.6299787005 * c.b;
+        let (l, m, s) = (l.cbrt(), m.cbrt(), s.cbrt());
+        Vec3::new(
+            0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+            1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+            0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+        )
+    }
+
+    #[allow(clippy::excessive_precision)]
+    pub fn from_oklab(lab: Vec3, a: f32) -> Self {
+        let l = lab.x + 0.3963377774 * lab.y + 0.2158037573 * lab.z;
+        let m = lab.x - 0.1055613458 * lab.y - 0.0638541728 * lab.z;
+        let s = lab.x - 0.0894841775 * lab.y - 1.2914855480 * lab.z;
+        let (l, m, s) = (l * l * l, m * m * m, s * s * s);
+        Self::from_linear(Self::new(
+            4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+            -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+            -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+            a,
+        ))
+    }
+
+    // (L, chroma, hue in degrees)
+    pub fn to_oklch(self) -> Vec3 {
+        lab_to_lch(self.to_oklab())
+    }
+
+    pub fn from_oklch(lch: Vec3, a: f32) -> Self {
+        Self::from_oklab(lch_to_lab(lch), a)
+    }
+
+    // CIE L*a*b* relative to the D65 white point, L in [0, 100]
+
+    pub fn to_lab(self) -> Vec3 {
+        let c = self.to_linear();
+        let x = (0.4124564 * c.r + 0.3575761 * c.g + 0.1804375 * c.b) / D65.x;
+        let y = (0.2126729 * c.r + 0.7151522 * c.g + 0.0721750 * c.b) / D65.y;
+        let z = (0.0193339 * c.r + 0.119192 * c.g + 0.9503041 * c.b) / D65.z;
+        let (fx, fy, fz) = (lab_f(x), lab_f(y), lab_f(z));
+        Vec3::new(116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz))
+    }
+
+    pub fn from_lab(lab: Vec3, a: f32) -> Self {
+        let fy = (lab.x + 16.0) / 116.0;
+        let fx = fy + lab.y / 500.0;
+        let fz = fy - lab.z / 200.0;
+        let x = lab_f_inverse(fx) * D65.x;
+        let y = lab_f_inverse(fy) * D65.y;
+        let z = lab_f_inverse(fz) * D65.z;
+        Self::from_linear(Self::new(
+            3.2404542 * x - 1.5371385 * y - 0.4985314 * z,
+            -0.969266 * x + 1.8760108 * y + 0.041556 * z,
+            0.0556434 * x - 0.2040259 * y + 1.0572252 * z,
+            a,
+        ))
+    }
+
+    pub fn to_space(self, space: ColorSpace) -> Vec3 {
+        match space {
+            ColorSpace::Srgb => self.to_vec3(),
+            ColorSpace::LinearSrgb => self.to_linear().to_vec3(),
+            ColorSpace::Hsv => self.to_hsv(),
+            ColorSpace::Hsl => self.to_hsl(),
+            ColorSpace::Oklab => self.to_oklab(),
+            ColorSpace::Oklch => self.to_oklch(),
+            ColorSpace::Lab => self.to_lab(),
+        }
+    }
+
+    pub fn from_space(v: Vec3, a: f32, space: ColorSpace) -> Self {
+        match space {
+            ColorSpace::Srgb => Self::from_vec3(v, a),
+            ColorSpace::LinearSrgb => Self::from_linear(Self::from_vec3(v, a)),
+            ColorSpace::Hsv => Self::from_hsv(v.x, v.y, v.z, a),
+            ColorSpace::Hsl => Self::from_hsl(v.x, v.y, v.z, a),
+            ColorSpace::Oklab => Self::from_oklab(v, a),
+            ColorSpace::Oklch => Self::from_oklch(v, a),
+            ColorSpace::Lab => Self::from_lab(v, a),
+        }
+    }
+
+    // component-wise in sRGB
+    pub fn lerp(self, rhs: Self, t: f32) -> Self {
+        Self::new(
+            self.r + (rhs.r - self.r) * t,
+            self.g + (rhs.g - self.g) * t,
+            self.b + (rhs.b - self.b) * t,
+            self.a + (rhs.a - self.a) * t,
+        )
+    }
+
+    // interpolates in the given space, hues take the shorter way around
+    pub fn mix(self, rhs: Self, t: f32, space: ColorSpace) -> Self {
+        let mut from = self.to_space(space);
+        let mut to = rhs.to_space(space);
+        let hue = match space {
+            ColorSpace::Hsv | ColorSpace::Hsl => Some(0),
+            ColorSpace::Oklch => Some(2),
+            _ => None,
+        };
+        if let Some(i) = hue {
+            // saturation or chroma is always the second component. An achromatic
+            // end has no meaningful hue, so it borrows the other one
+            if from[1] <= 1e-6 {
+                from[i] = to[i];
+            } else if to[1] <= 1e-6 {
+                to[i] = from[i];
+            }
+            let delta = (to[i] - from[i] + 180.0).rem_euclid(360.0) - 180.0;
+            to[i] = from[i] + delta;
+        }
+        let a = self.a + (rhs.a - self.a) * t;
+        Self::from_space(from.lerp(to, t), a, space)
+    }
+}
+
+impl From<Color> for Vec4 {
+    fn from(c: Color) -> Self {
+        c.to_vec4()
+    }
+}
+
+impl From<Color> for [f32; 4] {
+    fn from(c: Color) -> Self {
+        c.to_array()
+    }
+}
+
+impl From<Vec4> for Color {
+    fn from(v: Vec4) -> Self {
+        Self::new(v.x, v.y, v.z, v.w)
+    }
+}
+
+impl From<Vec3> for Color {
+    fn from(v: Vec3) -> Self {
+        Self::from_vec3(v, 1.0)
+    }
+}
+
+impl From<[f32; 4]> for Color {
+    fn from(a: [f32; 4]) -> Self {
+        Self::new(a[0], a[1], a[2], a[3])
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Gradient {
+    // (position, color), kept sorted by position
+    stops: Vec<(f32, Color)>,
+    pub space: ColorSpace,
+}
+
+impl Gradient {
+    pub fn new(space: ColorSpace) -> Self {
+        Self {
+            stops: Vec::new(),
+            space,
+        }
+    }
+
+    // evenly spaced stops over [0, 1]
+    pub fn from_colors(colors: &[Color], space: ColorSpace) -> Self {
+        let mut gradient = Self::new(space);
+        let last = colors.len().saturating_sub(1).max(1) as f32;
+        for (i, color) in colors.iter().enumerate() {
+            gradient.add_stop(i as f32 / last, *color);
+        }
+        gradient
+    }
+
+    pub fn from_hex(hexes: &[&str], space: ColorSpace) -> Result<Self, String> {
+        let colors = hexes
+            .iter()
+            .map(|h| Color::from_hex(h))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::from_colors(&colors, space))
+    }
+
+    pub fn add_stop(&mut self, position: f32, color: Color) {
+        let index = self.stops.partition_point(|(p, _)| *p <= position);
+        self.stops.insert(index, (position, color));
+    }
+
+    pub fn get_stops(&self) -> &[(f32, Color)] {
+        &self.stops
+    }
+
+    // clamps outside the first and last stops
+    pub fn sample(&self, t: f32) -> Color {
+        let Some(first) = self.stops.first() else {
+            return Color::TRANSPARENT;
+        };
+        if t <= first.0 {
+            return first.1;
+        }
+        let index = self.stops.partition_point(|(p, _)| *p <= t);
+        if index >= self.stops.len() {
+            return self.stops[self.stops.len() - 1].1;
+        }
+        let (p0, c0) = self.stops[index - 1];
+        let (p1, c1) = self.stops[index];
+        let span = p1 - p0;
+        let local = if span > 0.0 { (t - p0) / span } else { 1.0 };
+        c0.mix(c1, local, self.space)
+    }
+
+    // n colors sampled evenly over [0, 1], for building discrete palettes
+    pub fn sample_n(&self, n: usize) -> Vec<Color> {
+        let last = n.saturating_sub(1).max(1) as f32;
+        (0..n).map(|i| self.sample(i as f32 / last)).collect()
+    }
+}
+
+// Quilez cosine palette, a + b * cos(2pi * (c * t + d))
+pub fn cosine_palette(a: Vec3, b: Vec3, c: Vec3, d: Vec3, t: f32) -> Color {
+    let tau = std::f32::consts::TAU;
+    Color::rgb(
+        a.x + b.x * (tau * (c.x * t + d.x)).cos(),
+        a.y + b.y * (tau * (c.y * t + d.y)).cos(),
+        a.z + b.z * (tau * (c.z * t + d.z)).cos(),
+    )
+}
+
+pub fn srgb_to_linear(v: f32) -> f32 {
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(v: f32) -> f32 {
+    if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+const D65: Vec3 = Vec3::new(0.95047, 1.0, 1.08883);
+
+fn lab_f(t: f32) -> f32 {
+    const DELTA: f32 = 6.0 / 29.0;
+    if t > DELTA * DELTA * DELTA {
+        t.cbrt()
+    } else {
+        t / (3.0 * DELTA * DELTA) + 4.0 / 29.0
+    }
+}
+
+fn lab_f_inverse(t: f32) -> f32 {
+    const DELTA: f32 = 6.0 / 29.0;
+    if t > DELTA {
+        t * t * t
+    } else {
+        3.0 * DELTA * DELTA * (t - 4.0 / 29.0)
+    }
+}
+
+fn lab_to_lch(lab: Vec3) -> Vec3 {
+    let c = (lab.y * lab.y + lab.z * lab.z).sqrt();
+    let h = lab.z.atan2(lab.y).to_degrees().rem_euclid(360.0);
+    Vec3::new(lab.x, c, h)
+}
+
+fn lch_to_lab(lch: Vec3) -> Vec3 {
+    let h = lch.z.to_radians();
+    Vec3::new(lch.x, lch.y * h.cos(), lch.y * h.sin())
+}
+
+fn hue_sector(h: f32, c: f32, x: f32) -> (f32, f32, f32) {
+    match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Color, b: Color) -> bool {
+        a.to_vec4().abs_diff_eq(b.to_vec4(), 1e-4)
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        assert_eq!(
+            Color::from_hex("#ff8000").unwrap().to_rgba8(),
+            [255, 128, 0, 255]
+        );
+        assert_eq!(
+            Color::from_hex("f80").unwrap().to_rgba8(),
+            [255, 136, 0, 255]
+        );
+        assert_eq!(Color::from_hex("#11223344").unwrap().to_hex(), "#11223344");
+        assert!(Color::from_hex("#12345").is_err());
+        assert!(Color::from_hex("#gg0000").is_err());
+    }
+
+    #[test]
+    fn conversions_round_trip() {
+        let color = Color::new(0.8, 0.3, 0.1, 0.5);
+        assert!(close(Color::from_linear(color.to_linear()), color));
+        let hsv = color.to_hsv();
+        assert!(close(Color::from_hsv(hsv.x, hsv.y, hsv.z, 0.5), color));
+        let hsl = color.to_hsl();
+        assert!(close(Color::from_hsl(hsl.x, hsl.y, hsl.z, 0.5), color));
+        assert!(close(Color::from_oklab(color.to_oklab(), 0.5), color));
+        assert!(close(Color::from_oklch(color.to_oklch(), 0.5), color));
+        assert!(close(Color::from_lab(color.to_lab(), 0.5), color));
+    }
+
+    #[test]
+    fn known_values() {
+        assert_eq!(Color::RED.to_hsv(), Vec3::new(0.0, 1.0, 1.0));
+        assert!(Color::from_hsv(120.0, 1.0, 1.0, 1.0) == Color::GREEN);
+        assert!(Color::WHITE
+            .to_oklab()
+            .abs_diff_eq(Vec3::new(1.0, 0.0, 0.0), 1e-4));
+        assert!(Color::WHITE
+            .to_lab()
+            .abs_diff_eq(Vec3::new(100.0, 0.0, 0.0), 1e-2));
+        assert!((srgb_to_linear(0.5) - 0.214).abs() < 1e-3);
+    }
+
+    #[test]
+    fn gradient_sampling() {
+        let gradient = Gradient::from_colors(&[Color::BLACK, Color::WHITE], ColorSpace::Srgb);
+        assert_eq!(gradient.sample(-1.0), Color::BLACK);
+        assert_eq!(gradient.sample(2.0), Color::WHITE);
+        assert!(close(gradient.sample(0.5), Color::gray(0.5)));
+
+        // hue interpolation goes the short way from 350 to 10 degrees
+        let a = Color::from_hsv(350.0, 1.0, 1.0, 1.0);
+        let b = Color::from_hsv(10.0, 1.0, 1.0, 1.0);
+        assert!(close(a.mix(b, 0.5, ColorSpace::Hsv), Color::RED));
+        assert_eq!(gradient.sample_n(3).len(), 3);
+    }
+}
@@ -0,0 +1,136 @@
+use super::aabb::Aabb;
+use super::mat4::Mat4;
+use super::plane::Plane;
+use super::sphere::Sphere;
+use super::vec3::Vec3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Containment {
+    Outside,
+    Intersects,
+    Inside,
+}
+
+// plane normals point into the frustum
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frustum {
+    // left, right, bottom, top, near, far
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    // Gribb/Hartmann extraction for GL clip space (-w <= z <= w)
+    pub fn from_view_projection(m: &Mat4) -> Self {
+        let (r0, r1, r2, r3) = (m.row(0), m.row(1), m.row(2), m.row(3));
+        Self {
+            planes: [
+                Plane::from_vec4(r3 + r0),
+                Plane::from_vec4(r3 - r0),
+                Plane::from_vec4(r3 + r1),
+                Plane::from_vec4(r3 - r1),
+                Plane::from_vec4(r3 + r2),
+                Plane::from_vec4(r3 - r2),
+            ],
+        }
+    }
+
+    pub fn contains_point(&self, p: Vec3) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(p) >= 0.0)
+    }
+
+    pub fn classify_sphere(&self, sphere: &Sphere) -> Containment {
+        let mut result = Containment::Inside;
+        for plane in self.planes.iter() {
+            let distance = plane.signed_distance(sphere.center);
+            if distance < -sphere.radius {
+                return Containment::Outside;
+            }
+            if distance < sphere.radius {
+                result = Containment::Intersects;
+            }
+        }
+        result
+    }
+
+    // conservative, boxes near the frustum corners may be reported as intersecting
+    pub fn classify_aabb(&self, aabb: &Aabb) -> Containment {
+        let mut result = Containment::Inside;
+        for plane in self.planes.iter() {
+            let n = plane.normal;
+            let positive = Vec3::new(
+                if n.x >= 0.0 { aabb.max.x } else { aabb.min.x },
+                if n.y >= 0.0 { aabb.max.y } else { aabb.min.y },
+                if n.z >= 0.0 { aabb.max.z } else { aabb.min.z },
+            );
+            if plane.signed_distance(positive) < 0.0 {
+                return Containment::Outside;
+            }
+            let negative = Vec3::new(
+                if n.x >= 0.0 { aabb.min.x } else { aabb.max.x },
+                if n.y >= 0.0 { aabb.min.y } else { aabb.max.y },
+                if n.z >= 0.0 { aabb.min.z } else { aabb.max.z },
+            );
+            if plane.signed_distance(negative) < 0.0 {
+                result = Containment::Intersects;
+            }
+        }
+        result
+    }
+
+    pub fn intersects_sphere(&self, sphere: &Sphere) -> bool {
+        self.classify_sphere(sphere) != Containment::Outside
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.classify_aabb(aabb) != Containment::Outside
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frustum() -> Frustum {
+        let projection = Mat4::perspective(std::f32::consts::FRAC_PI_2, 1.0, 1.0, 100.0);
+        let view = Mat4::look_at(Vec3::ZERO, -Vec3::Z, Vec3::Y);
+        Frustum::from_view_projection(&(projection * view))
+    }
+
+    #[test]
+    fn frustum_planes_face_inward() {
+        let frustum = frustum();
+        assert!(frustum.contains_point(Vec3::new(0.0, 0.0, -10.0)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, 10.0)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -0.5)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -101.0)));
+        assert!(!frustum.contains_point(Vec3::new(20.0, 0.0, -10.0)));
+        // near plane sits at distance 1 from the eye
+        assert!((frustum.planes[4].signed_distance(Vec3::new(0.0, 0.0, -2.0)) - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn frustum_culls_spheres_and_boxes() {
+        let frustum = frustum();
+        let inside = Sphere::new(Vec3::new(0.0, 0.0, -10.0), 1.0);
+        let straddling = Sphere::new(Vec3::new(10.0, 0.0, -10.0), 1.0);
+        let outside = Sphere::new(Vec3::new(0.0, 0.0, 10.0), 1.0);
+        assert_eq!(frustum.classify_sphere(&inside), Containment::Inside);
+        assert_eq!(
+            frustum.classify_sphere(&straddling),
+            Containment::Intersects
+        );
+        assert_eq!(frustum.classify_sphere(&outside), Containment::Outside);
+
+        assert_eq!(
+            frustum.classify_aabb(&inside.get_aabb()),
+            Containment::Inside
+        );
+        assert_eq!(
+            frustum.classify_aabb(&straddling.get_aabb()),
+            Containment::Intersects
+        );
+        assert!(!frustum.intersects_aabb(&outside.get_aabb()));
+    }
+}
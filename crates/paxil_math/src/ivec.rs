@@ -0,0 +1,71 @@
+use super::macros::impl_vec_ops;
+use super::vec2::Vec2;
+use super::vec3::Vec3;
+use super::vec4::Vec4;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct IVec2 {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct IVec3 {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct IVec4 {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub w: i32,
+}
+
+impl_vec_ops!(IVec2, i32, x, y);
+impl_vec_ops!(IVec3, i32, x, y, z);
+impl_vec_ops!(IVec4, i32, x, y, z, w);
+
+impl IVec2 {
+    pub const ZERO: Self = Self::new(0, 0);
+    pub const ONE: Self = Self::new(1, 1);
+
+    pub const fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn as_vec2(self) -> Vec2 {
+        Vec2::new(self.x as f32, self.y as f32)
+    }
+}
+
+impl IVec3 {
+    pub const ZERO: Self = Self::new(0, 0, 0);
+    pub const ONE: Self = Self::new(1, 1, 1);
+
+    pub const fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn as_vec3(self) -> Vec3 {
+        Vec3::new(self.x as f32, self.y as f32, self.z as f32)
+    }
+}
+
+impl IVec4 {
+    pub const ZERO: Self = Self::new(0, 0, 0, 0);
+    pub const ONE: Self = Self::new(1, 1, 1, 1);
+
+    pub const fn new(x: i32, y: i32, z: i32, w: i32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    pub fn as_vec4(self) -> Vec4 {
+        Vec4::new(self.x as f32, self.y as f32, self.z as f32, self.w as f32)
+    }
+}
@@ -0,0 +1,64 @@
+mod macros;
+
+pub mod vec2;
+pub use vec2::*;
+
+pub mod vec3;
+pub use vec3::*;
+
+pub mod vec4;
+pub use vec4::*;
+
+pub mod ivec;
+pub use ivec::*;
+
+pub mod mat2;
+pub use mat2::*;
+
+pub mod mat3;
+pub use mat3::*;
+
+pub mod mat4;
+pub use mat4::*;
+
+pub mod quat;
+pub use quat::*;
+
+pub mod transform;
+pub use transform::*;
+
+pub mod ray;
+pub use ray::*;
+
+pub mod plane;
+pub use plane::*;
+
+pub mod aabb;
+pub use aabb::*;
+
+pub mod sphere;
+pub use sphere::*;
+
+pub mod obb;
+pub use obb::*;
+
+pub mod triangle;
+pub use triangle::*;
+
+pub mod frustum;
+pub use frustum::*;
+
+pub mod noise;
+pub use noise::*;
+
+pub mod color;
+pub use color::*;
+
+pub mod animation;
+pub use animation::*;
+
+pub mod spline;
+pub use spline::*;
+
+pub mod random;
+pub use random::*;
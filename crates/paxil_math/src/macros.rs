@@ -0,0 +1,219 @@
+// component-wise operators shared by the vector types
+macro_rules! impl_vec_ops {
+    ($t:ident, $s:ty, $($f:ident),+) => {
+        impl std::ops::Add for $t {
+            type Output = Self;
+            fn add(self, rhs: Self) -> Self {
+                Self { $($f: self.$f + rhs.$f),+ }
+            }
+        }
+
+        impl std::ops::Sub for $t {
+            type Output = Self;
+            fn sub(self, rhs: Self) -> Self {
+                Self { $($f: self.$f - rhs.$f),+ }
+            }
+        }
+
+        impl std::ops::Mul for $t {
+            type Output = Self;
+            fn mul(self, rhs: Self) -> Self {
+                Self { $($f: self.$f * rhs.$f),+ }
+            }
+        }
+
+        impl std::ops::Div for $t {
+            type Output = Self;
+            fn div(self, rhs: Self) -> Self {
+                Self { $($f: self.$f / rhs.$f),+ }
+            }
+        }
+
+        impl std::ops::Mul<$s> for $t {
+            type Output = Self;
+            fn mul(self, rhs: $s) -> Self {
+                Self { $($f: self.$f * rhs),+ }
+            }
+        }
+
+        impl std::ops::Mul<$t> for $s {
+            type Output = $t;
+            fn mul(self, rhs: $t) -> $t {
+                $t { $($f: self * rhs.$f),+ }
+            }
+        }
+
+        impl std::ops::Div<$s> for $t {
+            type Output = Self;
+            fn div(self, rhs: $s) -> Self {
+                Self { $($f: self.$f / rhs),+ }
+            }
+        }
+
+        impl std::ops::Neg for $t {
+            type Output = Self;
+            fn neg(self) -> Self {
+                Self { $($f: -self.$f),+ }
+            }
+        }
+
+        impl std::ops::AddAssign for $t {
+            fn add_assign(&mut self, rhs: Self) {
+                $(self.$f += rhs.$f;)+
+            }
+        }
+
+        impl std::ops::SubAssign for $t {
+            fn sub_assign(&mut self, rhs: Self) {
+                $(self.$f -= rhs.$f;)+
+            }
+        }
+
+        impl std::ops::MulAssign for $t {
+            fn mul_assign(&mut self, rhs: Self) {
+                $(self.$f *= rhs.$f;)+
+            }
+        }
+
+        impl std::ops::MulAssign<$s> for $t {
+            fn mul_assign(&mut self, rhs: $s) {
+                $(self.$f *= rhs;)+
+            }
+        }
+
+        impl std::ops::DivAssign<$s> for $t {
+            fn div_assign(&mut self, rhs: $s) {
+                $(self.$f /= rhs;)+
+            }
+        }
+
+        impl std::ops::Index<usize> for $t {
+            type Output = $s;
+            fn index(&self, index: usize) -> &$s {
+                &self.as_array()[index]
+            }
+        }
+
+        impl std::ops::IndexMut<usize> for $t {
+            fn index_mut(&mut self, index: usize) -> &mut $s {
+                &mut self.as_array_mut()[index]
+            }
+        }
+
+        impl $t {
+            pub fn splat(v: $s) -> Self {
+                Self { $($f: v),+ }
+            }
+
+            pub fn dot(self, rhs: Self) -> $s {
+                let mut sum = <$s>::default();
+                $(sum += self.$f * rhs.$f;)+
+                sum
+            }
+
+            pub fn min(self, rhs: Self) -> Self {
+                Self { $($f: self.$f.min(rhs.$f)),+ }
+            }
+
+            pub fn max(self, rhs: Self) -> Self {
+                Self { $($f: self.$f.max(rhs.$f)),+ }
+            }
+
+            pub fn abs(self) -> Self {
+                Self { $($f: self.$f.abs()),+ }
+            }
+
+            pub fn as_array(&self) -> &[$s; $crate::macros::impl_vec_ops!(@count $($f)+)] {
+                // repr(C) with only $s fields has the same layout as an array
+                unsafe { &*(self as *const Self as *const [$s; $crate::macros::impl_vec_ops!(@count $($f)+)]) }
+            }
+
+            pub fn as_array_mut(&mut self) -> &mut [$s; $crate::macros::impl_vec_ops!(@count $($f)+)] {
+                unsafe { &mut *(self as *mut Self as *mut [$s; $crate::macros::impl_vec_ops!(@count $($f)+)]) }
+            }
+
+            pub fn to_array(self) -> [$s; $crate::macros::impl_vec_ops!(@count $($f)+)] {
+                *self.as_array()
+            }
+        }
+
+        impl From<[$s; $crate::macros::impl_vec_ops!(@count $($f)+)]> for $t {
+            fn from(a: [$s; $crate::macros::impl_vec_ops!(@count $($f)+)]) -> Self {
+                let mut v = Self::default();
+                *v.as_array_mut() = a;
+                v
+            }
+        }
+
+        impl From<$t> for [$s; $crate::macros::impl_vec_ops!(@count $($f)+)] {
+            fn from(v: $t) -> Self {
+                v.to_array()
+            }
+        }
+    };
+
+    (@count $h:ident $($rest:ident)*) => { 1 + $crate::macros::impl_vec_ops!(@count $($rest)*) };
+    (@count) => { 0 };
+}
+
+// length and interpolation only make sense for the float vectors
+macro_rules! impl_float_vec {
+    ($t:ident, $($f:ident),+) => {
+        $crate::macros::impl_vec_ops!($t, f32, $($f),+);
+
+        impl $t {
+            pub fn length_squared(self) -> f32 {
+                self.dot(self)
+            }
+
+            pub fn length(self) -> f32 {
+                self.length_squared().sqrt()
+            }
+
+            pub fn distance(self, rhs: Self) -> f32 {
+                (self - rhs).length()
+            }
+
+            pub fn distance_squared(self, rhs: Self) -> f32 {
+                (self - rhs).length_squared()
+            }
+
+            pub fn is_finite(self) -> bool {
+                $(self.$f.is_finite())&&+
+            }
+
+            // returns zero for zero length vectors instead of NaN
+            pub fn normalize(self) -> Self {
+                let length = self.length();
+                if length > 0.0 {
+                    self / length
+                } else {
+                    Self::default()
+                }
+            }
+
+            pub fn lerp(self, rhs: Self, t: f32) -> Self {
+                self + (rhs - self) * t
+            }
+
+            pub fn floor(self) -> Self {
+                Self { $($f: self.$f.floor()),+ }
+            }
+
+            pub fn fract(self) -> Self {
+                Self { $($f: self.$f - self.$f.floor()),+ }
+            }
+
+            pub fn clamp(self, min: Self, max: Self) -> Self {
+                self.max(min).min(max)
+            }
+
+            pub fn abs_diff_eq(self, rhs: Self, epsilon: f32) -> bool {
+                true $(&& (self.$f - rhs.$f).abs() <= epsilon)+
+            }
+        }
+    };
+}
+
+pub(crate) use impl_float_vec;
+pub(crate) use impl_vec_ops;
@@ -0,0 +1,88 @@
+use super::vec2::Vec2;
+
+// column major
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mat2 {
+    pub cols: [Vec2; 2],
+}
+
+impl Default for Mat2 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mat2 {
+    pub const IDENTITY: Self = Self::from_cols(Vec2::X, Vec2::Y);
+    pub const ZERO: Self = Self::from_cols(Vec2::ZERO, Vec2::ZERO);
+
+    pub const fn from_cols(c0: Vec2, c1: Vec2) -> Self {
+        Self { cols: [c0, c1] }
+    }
+
+    pub fn from_angle(angle: f32) -> Self {
+        let (s, c) = angle.sin_cos();
+        Self::from_cols(Vec2::new(c, s), Vec2::new(-s, c))
+    }
+
+    pub fn from_scale(scale: Vec2) -> Self {
+        Self::from_cols(Vec2::new(scale.x, 0.0), Vec2::new(0.0, scale.y))
+    }
+
+    pub fn row(&self, i: usize) -> Vec2 {
+        Vec2::new(self.cols[0][i], self.cols[1][i])
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self::from_cols(self.row(0), self.row(1))
+    }
+
+    pub fn determinant(&self) -> f32 {
+        self.cols[0].x * self.cols[1].y - self.cols[1].x * self.cols[0].y
+    }
+
+    // None when the matrix is singular
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == 0.0 {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+        Some(Self::from_cols(
+            Vec2::new(self.cols[1].y, -self.cols[0].y) * inv_det,
+            Vec2::new(-self.cols[1].x, self.cols[0].x) * inv_det,
+        ))
+    }
+
+    pub fn as_slice(&self) -> &[f32] {
+        unsafe { std::slice::from_raw_parts(self as *const Self as *const f32, 4) }
+    }
+
+    pub fn to_cols_array(&self) -> [f32; 4] {
+        let mut a = [0.0; 4];
+        a.copy_from_slice(self.as_slice());
+        a
+    }
+}
+
+impl std::ops::Mul<Vec2> for Mat2 {
+    type Output = Vec2;
+    fn mul(self, v: Vec2) -> Vec2 {
+        self.cols[0] * v.x + self.cols[1] * v.y
+    }
+}
+
+impl std::ops::Mul for Mat2 {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self::from_cols(self * rhs.cols[0], self * rhs.cols[1])
+    }
+}
+
+impl std::ops::Mul<f32> for Mat2 {
+    type Output = Self;
+    fn mul(self, rhs: f32) -> Self {
+        Self::from_cols(self.cols[0] * rhs, self.cols[1] * rhs)
+    }
+}
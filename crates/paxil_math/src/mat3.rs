@@ -0,0 +1,151 @@
+use super::mat4::Mat4;
+use super::quat::Quat;
+use super::vec2::Vec2;
+use super::vec3::Vec3;
+
+// column major
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mat3 {
+    pub cols: [Vec3; 3],
+}
+
+impl Default for Mat3 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mat3 {
+    pub const IDENTITY: Self = Self::from_cols(Vec3::X, Vec3::Y, Vec3::Z);
+    pub const ZERO: Self = Self::from_cols(Vec3::ZERO, Vec3::ZERO, Vec3::ZERO);
+
+    pub const fn from_cols(c0: Vec3, c1: Vec3, c2: Vec3) -> Self {
+        Self { cols: [c0, c1, c2] }
+    }
+
+    // upper left 3x3 block
+    pub fn from_mat4(m: &Mat4) -> Self {
+        Self::from_cols(
+            m.cols[0].truncate(),
+            m.cols[1].truncate(),
+            m.cols[2].truncate(),
+        )
+    }
+
+    pub fn from_quat(q: Quat) -> Self {
+        let (x, y, z, w) = (q.x, q.y, q.z, q.w);
+        let (x2, y2, z2) = (x + x, y + y, z + z);
+        let (xx, xy, xz) = (x * x2, x * y2, x * z2);
+        let (yy, yz, zz) = (y * y2, y * z2, z * z2);
+        let (wx, wy, wz) = (w * x2, w * y2, w * z2);
+        Self::from_cols(
+            Vec3::new(1.0 - (yy + zz), xy + wz, xz - wy),
+            Vec3::new(xy - wz, 1.0 - (xx + zz), yz + wx),
+            Vec3::new(xz + wy, yz - wx, 1.0 - (xx + yy)),
+        )
+    }
+
+    pub fn from_axis_angle(axis: Vec3, angle: f32) -> Self {
+        Self::from_quat(Quat::from_axis_angle(axis, angle))
+    }
+
+    pub fn from_scale(scale: Vec3) -> Self {
+        Self::from_cols(
+            Vec3::new(scale.x, 0.0, 0.0),
+            Vec3::new(0.0, scale.y, 0.0),
+            Vec3::new(0.0, 0.0, scale.z),
+        )
+    }
+
+    // 2D affine transform in homogeneous coordinates
+    pub fn from_translation_2d(translation: Vec2) -> Self {
+        Self::from_cols(Vec3::X, Vec3::Y, translation.extend(1.0))
+    }
+
+    // inverse transpose of the upper 3x3, for transforming normals
+    pub fn normal_matrix(model: &Mat4) -> Self {
+        Self::from_mat4(model)
+            .inverse()
+            .unwrap_or(Self::IDENTITY)
+            .transpose()
+    }
+
+    pub fn row(&self, i: usize) -> Vec3 {
+        Vec3::new(self.cols[0][i], self.cols[1][i], self.cols[2][i])
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self::from_cols(self.row(0), self.row(1), self.row(2))
+    }
+
+    pub fn determinant(&self) -> f32 {
+        self.cols[2].dot(self.cols[0].cross(self.cols[1]))
+    }
+
+    // None when the matrix is singular
+    pub fn inverse(&self) -> Option<Self> {
+        let [c0, c1, c2] = self.cols;
+        let r0 = c1.cross(c2);
+        let r1 = c2.cross(c0);
+        let r2 = c0.cross(c1);
+        let det = c2.dot(r2);
+        if det == 0.0 {
+            return None;
+        }
+        Some(Self::from_cols(r0, r1, r2).transpose() * (1.0 / det))
+    }
+
+    pub fn as_slice(&self) -> &[f32] {
+        unsafe { std::slice::from_raw_parts(self as *const Self as *const f32, 9) }
+    }
+
+    pub fn to_cols_array(&self) -> [f32; 9] {
+        let mut a = [0.0; 9];
+        a.copy_from_slice(self.as_slice());
+        a
+    }
+}
+
+impl std::ops::Mul<Vec3> for Mat3 {
+    type Output = Vec3;
+    fn mul(self, v: Vec3) -> Vec3 {
+        self.cols[0] * v.x + self.cols[1] * v.y + self.cols[2] * v.z
+    }
+}
+
+impl std::ops::Mul for Mat3 {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self::from_cols(self * rhs.cols[0], self * rhs.cols[1], self * rhs.cols[2])
+    }
+}
+
+impl std::ops::Mul<f32> for Mat3 {
+    type Output = Self;
+    fn mul(self, rhs: f32) -> Self {
+        Self::from_cols(self.cols[0] * rhs, self.cols[1] * rhs, self.cols[2] * rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverse_and_normal_matrix() {
+        let m = Mat3::from_axis_angle(Vec3::Z, 0.5) * Mat3::from_scale(Vec3::new(2.0, 4.0, 1.0));
+        assert!((m.determinant() - 8.0).abs() < 1e-5);
+        let product = m.inverse().unwrap() * m;
+        for (col, expected) in product.cols.iter().zip(Mat3::IDENTITY.cols) {
+            assert!(col.abs_diff_eq(expected, 1e-5));
+        }
+        assert_eq!(Mat3::ZERO.inverse(), None);
+
+        // normals stay perpendicular to a surface under non uniform scale
+        let model = Mat4::from_scale(Vec3::new(4.0, 1.0, 1.0));
+        let tangent = model.transform_vector3(Vec3::new(1.0, 1.0, 0.0));
+        let normal = Mat3::normal_matrix(&model) * Vec3::new(1.0, -1.0, 0.0);
+        assert!(tangent.dot(normal).abs() < 1e-5);
+    }
+}
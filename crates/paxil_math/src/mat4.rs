@@ -0,0 +1,392 @@
+use super::mat3::Mat3;
+use super::quat::Quat;
+use super::vec3::Vec3;
+use super::vec4::Vec4;
+
+// column major, matching the layout GL expects for uniforms
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mat4 {
+    pub cols: [Vec4; 4],
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mat4 {
+    pub const IDENTITY: Self = Self::from_cols(Vec4::X, Vec4::Y, Vec4::Z, Vec4::W);
+    pub const ZERO: Self = Self::from_cols(Vec4::ZERO, Vec4::ZERO, Vec4::ZERO, Vec4::ZERO);
+
+    pub const fn from_cols(c0: Vec4, c1: Vec4, c2: Vec4, c3: Vec4) -> Self {
+        Self {
+            cols: [c0, c1, c2, c3],
+        }
+    }
+
+    pub fn from_mat3(m: &Mat3) -> Self {
+        Self::from_cols(
+            m.cols[0].extend(0.0),
+            m.cols[1].extend(0.0),
+            m.cols[2].extend(0.0),
+            Vec4::W,
+        )
+    }
+
+    pub fn from_translation(translation: Vec3) -> Self {
+        Self::from_cols(Vec4::X, Vec4::Y, Vec4::Z, translation.extend(1.0))
+    }
+
+    pub fn from_scale(scale: Vec3) -> Self {
+        Self::from_mat3(&Mat3::from_scale(scale))
+    }
+
+    pub fn from_quat(rotation: Quat) -> Self {
+        Self::from_mat3(&Mat3::from_quat(rotation))
+    }
+
+    pub fn from_axis_angle(axis: Vec3, angle: f32) -> Self {
+        Self::from_quat(Quat::from_axis_angle(axis, angle))
+    }
+
+    pub fn from_rotation_x(angle: f32) -> Self {
+        Self::from_axis_angle(Vec3::X, angle)
+    }
+
+    pub fn from_rotation_y(angle: f32) -> Self {
+        Self::from_axis_angle(Vec3::Y, angle)
+    }
+
+    pub fn from_rotation_z(angle: f32) -> Self {
+        Self::from_axis_angle(Vec3::Z, angle)
+    }
+
+    // translation * rotation * scale
+    pub fn from_trs(translation: Vec3, rotation: Quat, scale: Vec3) -> Self {
+        let r = Mat3::from_quat(rotation);
+        Self::from_cols(
+            (r.cols[0] * scale.x).extend(0.0),
+            (r.cols[1] * scale.y).extend(0.0),
+            (r.cols[2] * scale.z).extend(0.0),
+            translation.extend(1.0),
+        )
+    }
+
+    // right handed view matrix looking down -Z
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Self {
+        let f = (target - eye).normalize();
+        let s = f.cross(up).normalize();
+        let u = s.cross(f);
+        Self::from_cols(
+            Vec4::new(s.x, u.x, -f.x, 0.0),
+            Vec4::new(s.y, u.y, -f.y, 0.0),
+            Vec4::new(s.z, u.z, -f.z, 0.0),
+            Vec4::new(-s.dot(eye), -u.dot(eye), f.dot(eye), 1.0),
+        )
+    }
+
+    // right handed projection into GL clip space with depth in [-1, 1]
+    pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+        let f = 1.0 / (fov_y * 0.5).tan();
+        let range = 1.0 / (near - far);
+        Self::from_cols(
+            Vec4::new(f / aspect, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, f, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, (far + near) * range, -1.0),
+            Vec4::new(0.0, 0.0, 2.0 * far * near * range, 0.0),
+        )
+    }
+
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Self {
+        let rl = 1.0 / (right - left);
+        let tb = 1.0 / (top - bottom);
+        let fn_ = 1.0 / (far - near);
+        Self::from_cols(
+            Vec4::new(2.0 * rl, 0.0, 0.0, 0.0),
+            Vec4::new(0.0, 2.0 * tb, 0.0, 0.0),
+            Vec4::new(0.0, 0.0, -2.0 * fn_, 0.0),
+            Vec4::new(
+                -(right + left) * rl,
+                -(top + bottom) * tb,
+                -(far + near) * fn_,
+                1.0,
+            ),
+        )
+    }
+
+    pub fn row(&self, i: usize) -> Vec4 {
+        Vec4::new(
+            self.cols[0][i],
+            self.cols[1][i],
+            self.cols[2][i],
+            self.cols[3][i],
+        )
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self::from_cols(self.row(0), self.row(1), self.row(2), self.row(3))
+    }
+
+    pub fn determinant(&self) -> f32 {
+        let m = self.to_cols_array();
+        let cofactors = Self::cofactors(&m);
+        m[0] * cofactors[0] + m[1] * cofactors[4] + m[2] * cofactors[8] + m[3] * cofactors[12]
+    }
+
+    // None when the matrix is singular
+    pub fn inverse(&self) -> Option<Self> {
+        let m = self.to_cols_array();
+        let inv = Self::cofactors(&m);
+        let det = m[0] * inv[0] + m[1] * inv[4] + m[2] * inv[8] + m[3] * inv[12];
+        if det == 0.0 {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+        let mut out = [0.0; 16];
+        for (o, v) in out.iter_mut().zip(inv.iter()) {
+            *o = v * inv_det;
+        }
+        Some(Self::from_cols_array(&out))
+    }
+
+    // adjugate of a column major array, the classic MESA expansion
+    fn cofactors(m: &[f32; 16]) -> [f32; 16] {
+        let mut inv = [0.0; 16];
+        inv[0] = m[5] * m[10] * m[15] - m[5] * m[11] * m[14] - m[9] * m[6] * m[15]
+            + m[9] * m[7] * m[14]
+            + m[13] * m[6] * m[11]
+            - m[13] * m[7] * m[10];
+        inv[4] = -m[4] * m[10] * m[15] + m[4] * m[11] * m[14] + m[8] * m[6] * m[15]
+            - m[8] * m[7] * m[14]
+            - m[12] * m[6] * m[11]
+            + m[12] * m[7] * m[10];
+        inv[8] = m[4] * m[9] * m[15] - m[4] * m[11] * m[13] - m[8] * m[5] * m[15]
+            + m[8] * m[7] * m[13]
+            + m[12] * m[5] * m[11]
+            - m[12] * m[7] * m[9];
+        inv[12] = -m[4] * m[9] * m[14] + m[4] * m[10] * m[13] + m[8] * m[5] * m[14]
+            - m[8] * m[6] * m[13]
+            - m[12] * m[5] * m[10]
+            + m[12] * m[6] * m[9];
+        inv[1] = -m[1] * m[10] * m[15] + m[1] * m[11] * m[14] + m[9] * m[2] * m[15]
+            - m[9] * m[3] * m[14]
+            - m[13] * m[2] * m[11]
+            + m[13] * m[3] * m[10];
+        inv[5] = m[0] * m[10] * m[15] - m[0] * m[11] * m[14] - m[8] * m[2] * m[15]
+            + m[8] * m[3] * m[14]
+            + m[12] * m[2] * m[11]
+            - m[12] * m[3] * m[10];
+        inv[9] = -m[0] * m[9] * m[15] + m[0] * m[11] * m[13] + m[8] * m[1] * m[15]
+            - m[8] * m[3] * m[13]
+            - m[12] * m[1] * m[11]
+            + m[12] * m[3] * m[9];
+        inv[13] = m[0] * m[9] * m[14] - m[0] * m[10] * m[13] - m[8] * m[1] * m[14]
+            + m[8] * m[2] * m[13]
+            + m[12] * m[1] * m[10]
+            - m[12] * m[2] * m[9];
+        inv[2] = m[1] * m[6] * m[15] - m[1] * m[7] * m[14] - m[5] * m[2] * m[15]
+            + m[5] * m[3] * m[14]
+            + m[13] * m[2] * m[7]
+            - m[13] * m[3] * m[6];
+        inv[6] = -m[0] * m[6] * m[15] + m[0] * m[7] * m[14] + m[4] * m[2] * m[15]
+            - m[4] * m[3] * m[14]
+            - m[12] * m[2] * m[7]
+            + m[12] * m[3] * m[6];
+        inv[10] = m[0] * m[5] * m[15] - m[0] * m[7] * m[13] - m[4] * m[1] * m[15]
+            + m[4] * m[3] * m[13]
+            + m[12] * m[1] * m[7]
+            - m[12] * m[3] * m[5];
+        inv[14] = -m[0] * m[5] * m[14] + m[0] * m[6] * m[13] + m[4] * m[1] * m[14]
+            - m[4] * m[2] * m[13]
+            - m[12] * m[1] * m[6]
+            + m[12] * m[2] * m[5];
+        inv[3] = -m[1] * m[6] * m[11] + m[1] * m[7] * m[10] + m[5] * m[2] * m[11]
+            - m[5] * m[3] * m[10]
+            - m[9] * m[2] * m[7]
+            + m[9] * m[3] * m[6];
+        inv[7] = m[0] * m[6] * m[11] - m[0] * m[7] * m[10] - m[4] * m[2] * m[11]
+            + m[4] * m[3] * m[10]
+            + m[8] * m[2] * m[7]
+            - m[8] * m[3] * m[6];
+        inv[11] = -m[0] * m[5] * m[11] + m[0] * m[7] * m[9] + m[4] * m[1] * m[11]
+            - m[4] * m[3] * m[9]
+            - m[8] * m[1] * m[7]
+            + m[8] * m[3] * m[5];
+        inv[15] = m[0] * m[5] * m[10] - m[0] * m[6] * m[9] - m[4] * m[1] * m[10]
+            + m[4] * m[2] * m[9]
+            + m[8] * m[1] * m[6]
+            - m[8] * m[2] * m[5];
+        inv
+    }
+
+    pub fn transform_point3(&self, p: Vec3) -> Vec3 {
+        (*self * p.extend(1.0)).truncate()
+    }
+
+    // applies the perspective divide, for projection matrices
+    pub fn project_point3(&self, p: Vec3) -> Vec3 {
+        (*self * p.extend(1.0)).project()
+    }
+
+    pub fn transform_vector3(&self, v: Vec3) -> Vec3 {
+        (*self * v.extend(0.0)).truncate()
+    }
+
+    pub fn get_translation(&self) -> Vec3 {
+        self.cols[3].truncate()
+    }
+
+    // assumes no shear, negative scale is folded into the x axis
+    pub fn to_scale_rotation_translation(&self) -> (Vec3, Quat, Vec3) {
+        let det = Mat3::from_mat4(self).determinant();
+        let scale = Vec3::new(
+            self.cols[0].truncate().length() * det.signum(),
+            self.cols[1].truncate().length(),
+            self.cols[2].truncate().length(),
+        );
+        let rotation = Quat::from_mat3(&Mat3::from_cols(
+            self.cols[0].truncate() / scale.x,
+            self.cols[1].truncate() / scale.y,
+            self.cols[2].truncate() / scale.z,
+        ));
+        (scale, rotation, self.get_translation())
+    }
+
+    pub fn from_cols_array(a: &[f32; 16]) -> Self {
+        Self::from_cols(
+            Vec4::new(a[0], a[1], a[2], a[3]),
+            Vec4::new(a[4], a[5], a[6], a[7]),
+            Vec4::new(a[8], a[9], a[10], a[11]),
+            Vec4::new(a[12], a[13], a[14], a[15]),
+        )
+    }
+
+    pub fn as_slice(&self) -> &[f32] {
+        unsafe { std::slice::from_raw_parts(self as *const Self as *const f32, 16) }
+    }
+
+    pub fn to_cols_array(&self) -> [f32; 16] {
+        let mut a = [0.0; 16];
+        a.copy_from_slice(self.as_slice());
+        a
+    }
+}
+
+impl std::ops::Mul<Vec4> for Mat4 {
+    type Output = Vec4;
+    fn mul(self, v: Vec4) -> Vec4 {
+        self.cols[0] * v.x + self.cols[1] * v.y + self.cols[2] * v.z + self.cols[3] * v.w
+    }
+}
+
+impl std::ops::Mul for Mat4 {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self::from_cols(
+            self * rhs.cols[0],
+            self * rhs.cols[1],
+            self * rhs.cols[2],
+            self * rhs.cols[3],
+        )
+    }
+}
+
+impl std::ops::Mul<f32> for Mat4 {
+    type Output = Self;
+    fn mul(self, rhs: f32) -> Self {
+        Self::from_cols(
+            self.cols[0] * rhs,
+            self.cols[1] * rhs,
+            self.cols[2] * rhs,
+            self.cols[3] * rhs,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    fn assert_mat4_eq(a: &Mat4, b: &Mat4) {
+        for (ca, cb) in a.cols.iter().zip(&b.cols) {
+            assert!(ca.abs_diff_eq(*cb, 1e-5), "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn inverse_and_determinant() {
+        let m = Mat4::from_trs(
+            Vec3::new(1.0, -2.0, 3.0),
+            Quat::from_axis_angle(Vec3::new(1.0, 2.0, 2.0) / 3.0, 0.7),
+            Vec3::new(2.0, 0.5, 3.0),
+        );
+        assert!((m.determinant() - 3.0).abs() < 1e-5);
+        let inverse = m.inverse().unwrap();
+        assert_mat4_eq(&(inverse * m), &Mat4::IDENTITY);
+        assert_mat4_eq(&(m * inverse), &Mat4::IDENTITY);
+        assert!((inverse.determinant() - 1.0 / 3.0).abs() < 1e-5);
+
+        assert_eq!(Mat4::from_scale(Vec3::new(1.0, 0.0, 1.0)).inverse(), None);
+        assert_mat4_eq(&m.transpose().transpose(), &m);
+        assert_eq!(m.row(3), Vec4::W);
+    }
+
+    #[test]
+    fn points_and_vectors() {
+        let m = Mat4::from_translation(Vec3::new(1.0, 2.0, 3.0)) * Mat4::from_rotation_z(FRAC_PI_2);
+        let p = m.transform_point3(Vec3::X);
+        assert!(p.abs_diff_eq(Vec3::new(1.0, 3.0, 3.0), 1e-6));
+        // vectors ignore the translation
+        let v = m.transform_vector3(Vec3::X);
+        assert!(v.abs_diff_eq(Vec3::Y, 1e-6));
+
+        let (scale, rotation, translation) = Mat4::from_trs(
+            Vec3::new(4.0, 5.0, 6.0),
+            Quat::from_axis_angle(Vec3::Y, 0.5),
+            Vec3::new(-2.0, 1.0, 3.0),
+        )
+        .to_scale_rotation_translation();
+        assert!(scale.abs_diff_eq(Vec3::new(-2.0, 1.0, 3.0), 1e-5));
+        assert!(translation.abs_diff_eq(Vec3::new(4.0, 5.0, 6.0), 1e-6));
+        assert!(Mat4::from_quat(rotation)
+            .transform_vector3(Vec3::X)
+            .abs_diff_eq(Quat::from_axis_angle(Vec3::Y, 0.5) * Vec3::X, 1e-5));
+    }
+
+    #[test]
+    fn projections_map_to_clip_space() {
+        let (near, far) = (0.5, 50.0);
+        let projection = Mat4::perspective(FRAC_PI_2, 2.0, near, far);
+        // the near and far planes land on -1 and 1, the frustum corners on the unit cube
+        let p = projection.project_point3(Vec3::new(2.0 * near, near, -near));
+        assert!(p.abs_diff_eq(Vec3::new(1.0, 1.0, -1.0), 1e-5));
+        let p = projection.project_point3(Vec3::new(-2.0 * far, -far, -far));
+        assert!(p.abs_diff_eq(Vec3::new(-1.0, -1.0, 1.0), 1e-4));
+        let clip = projection * Vec4::new(0.0, 0.0, -10.0, 1.0);
+        assert_eq!(clip.w, 10.0);
+
+        let ortho = Mat4::orthographic(-2.0, 4.0, -1.0, 1.0, 1.0, 11.0);
+        let p = ortho.transform_point3(Vec3::new(-2.0, 1.0, -1.0));
+        assert!(p.abs_diff_eq(Vec3::new(-1.0, 1.0, -1.0), 1e-6));
+        let p = ortho.transform_point3(Vec3::new(1.0, 0.0, -6.0));
+        assert!(p.abs_diff_eq(Vec3::new(0.0, 0.0, 0.0), 1e-6));
+        let p = ortho.transform_point3(Vec3::new(4.0, -1.0, -11.0));
+        assert!(p.abs_diff_eq(Vec3::new(1.0, -1.0, 1.0), 1e-6));
+    }
+
+    #[test]
+    fn look_at_faces_negative_z() {
+        let eye = Vec3::new(3.0, 2.0, 5.0);
+        let target = Vec3::new(3.0, 2.0, -5.0) + Vec3::X;
+        let view = Mat4::look_at(eye, target, Vec3::Y);
+        assert!(view.transform_point3(eye).abs_diff_eq(Vec3::ZERO, 1e-5));
+        let forward = view.transform_point3(target).normalize();
+        assert!(forward.abs_diff_eq(-Vec3::Z, 1e-5));
+        // up stays up and the view keeps lengths
+        assert!(view.transform_vector3(Vec3::Y).abs_diff_eq(Vec3::Y, 1e-5));
+        assert!((view.determinant() - 1.0).abs() < 1e-5);
+    }
+}
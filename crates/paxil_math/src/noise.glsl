@@ -0,0 +1,446 @@
+// mirrors paxil_math::noise, PAXIL_PERM is emitted by Noise::glsl_source()
+
+int paxil_hash1(int i) {
+    return PAXIL_PERM[i & 255];
+}
+
+int paxil_hash2(int i, int j) {
+    return PAXIL_PERM[paxil_hash1(i) + (j & 255)];
+}
+
+int paxil_hash3(int i, int j, int k) {
+    return PAXIL_PERM[paxil_hash2(i, j) + (k & 255)];
+}
+
+int paxil_hash4(int i, int j, int k, int l) {
+    return PAXIL_PERM[paxil_hash3(i, j, k) + (l & 255)];
+}
+
+int paxil_wrap(int i, int period) {
+    // % is undefined for negative operands in GLSL
+    return period > 0 ? i - period * int(floor(float(i) / float(period))) : i;
+}
+
+float paxil_fade(float t) {
+    return t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+}
+
+float paxil_lerp(float a, float b, float t) {
+    return a + t * (b - a);
+}
+
+float paxil_lattice_value(int h) {
+    return float(h) / 255.0 * 2.0 - 1.0;
+}
+
+float paxil_grad1(int h, float x) {
+    float g = 1.0 + float(h & 7);
+    return (h & 8) != 0 ? -g * x : g * x;
+}
+
+float paxil_grad2(int h, float x, float y) {
+    h = h & 7;
+    float u = h < 4 ? x : y;
+    float v = h < 4 ? y : x;
+    u = (h & 1) != 0 ? -u : u;
+    v = (h & 2) != 0 ? -2.0 * v : 2.0 * v;
+    return u + v;
+}
+
+float paxil_grad3(int h, float x, float y, float z) {
+    h = h & 15;
+    float u = h < 8 ? x : y;
+    float v = h < 4 ? y : (h == 12 || h == 14 ? x : z);
+    u = (h & 1) != 0 ? -u : u;
+    v = (h & 2) != 0 ? -v : v;
+    return u + v;
+}
+
+float paxil_grad4(int h, float x, float y, float z, float w) {
+    h = h & 31;
+    float u = h < 24 ? x : y;
+    float v = h < 16 ? y : z;
+    float t = h < 8 ? z : w;
+    u = (h & 1) != 0 ? -u : u;
+    v = (h & 2) != 0 ? -v : v;
+    t = (h & 4) != 0 ? -t : t;
+    return u + v + t;
+}
+
+float paxil_reduce2(float n[4], vec2 t) {
+    float a = paxil_lerp(n[0], n[1], t.x);
+    float b = paxil_lerp(n[2], n[3], t.x);
+    return paxil_lerp(a, b, t.y);
+}
+
+float paxil_reduce3(float n[8], vec3 t) {
+    float m[4];
+    for (int i = 0; i < 4; i++) {
+        m[i] = paxil_lerp(n[2 * i], n[2 * i + 1], t.x);
+    }
+    float a = paxil_lerp(m[0], m[1], t.y);
+    float b = paxil_lerp(m[2], m[3], t.y);
+    return paxil_lerp(a, b, t.z);
+}
+
+float paxil_reduce4(float n[16], vec4 t) {
+    float m[8];
+    for (int i = 0; i < 8; i++) {
+        m[i] = paxil_lerp(n[2 * i], n[2 * i + 1], t.x);
+    }
+    return paxil_reduce3(m, t.yzw);
+}
+
+// Perlin noise
+
+float paxil_perlin1_tiled(float x, int period) {
+    float i = floor(x);
+    float f = x - i;
+    int ix = int(i);
+    float n0 = paxil_grad1(paxil_hash1(paxil_wrap(ix, period)), f);
+    float n1 = paxil_grad1(paxil_hash1(paxil_wrap(ix + 1, period)), f - 1.0);
+    return 0.188 * paxil_lerp(n0, n1, paxil_fade(f));
+}
+
+float paxil_perlin2_tiled(vec2 p, ivec2 period) {
+    vec2 i = floor(p);
+    vec2 f = p - i;
+    ivec2 c = ivec2(i);
+    float n[4];
+    for (int k = 0; k < 4; k++) {
+        ivec2 o = ivec2(k & 1, (k >> 1) & 1);
+        int h = paxil_hash2(paxil_wrap(c.x + o.x, period.x), paxil_wrap(c.y + o.y, period.y));
+        n[k] = paxil_grad2(h, f.x - float(o.x), f.y - float(o.y));
+    }
+    return 0.507 * paxil_reduce2(n, vec2(paxil_fade(f.x), paxil_fade(f.y)));
+}
+
+float paxil_perlin3_tiled(vec3 p, ivec3 period) {
+    vec3 i = floor(p);
+    vec3 f = p - i;
+    ivec3 c = ivec3(i);
+    float n[8];
+    for (int k = 0; k < 8; k++) {
+        ivec3 o = ivec3(k & 1, (k >> 1) & 1, (k >> 2) & 1);
+        int h = paxil_hash3(paxil_wrap(c.x + o.x, period.x), paxil_wrap(c.y + o.y, period.y), paxil_wrap(c.z + o.z, period.z));
+        n[k] = paxil_grad3(h, f.x - float(o.x), f.y - float(o.y), f.z - float(o.z));
+    }
+    return 0.936 * paxil_reduce3(n, vec3(paxil_fade(f.x), paxil_fade(f.y), paxil_fade(f.z)));
+}
+
+float paxil_perlin4_tiled(vec4 p, ivec4 period) {
+    vec4 i = floor(p);
+    vec4 f = p - i;
+    ivec4 c = ivec4(i);
+    float n[16];
+    for (int k = 0; k < 16; k++) {
+        ivec4 o = ivec4(k & 1, (k >> 1) & 1, (k >> 2) & 1, (k >> 3) & 1);
+        int h = paxil_hash4(paxil_wrap(c.x + o.x, period.x), paxil_wrap(c.y + o.y, period.y), paxil_wrap(c.z + o.z, period.z), paxil_wrap(c.w + o.w, period.w));
+        n[k] = paxil_grad4(h, f.x - float(o.x), f.y - float(o.y), f.z - float(o.z), f.w - float(o.w));
+    }
+    return 0.87 * paxil_reduce4(n, vec4(paxil_fade(f.x), paxil_fade(f.y), paxil_fade(f.z), paxil_fade(f.w)));
+}
+
+float paxil_perlin1(float x) { return paxil_perlin1_tiled(x, 0); }
+float paxil_perlin2(vec2 p) { return paxil_perlin2_tiled(p, ivec2(0)); }
+float paxil_perlin3(vec3 p) { return paxil_perlin3_tiled(p, ivec3(0)); }
+float paxil_perlin4(vec4 p) { return paxil_perlin4_tiled(p, ivec4(0)); }
+
+// value noise
+
+float paxil_value1_tiled(float x, int period) {
+    float i = floor(x);
+    float f = x - i;
+    int ix = int(i);
+    float n0 = paxil_lattice_value(paxil_hash1(paxil_wrap(ix, period)));
+    float n1 = paxil_lattice_value(paxil_hash1(paxil_wrap(ix + 1, period)));
+    return paxil_lerp(n0, n1, paxil_fade(f));
+}
+
+float paxil_value2_tiled(vec2 p, ivec2 period) {
+    vec2 i = floor(p);
+    vec2 f = p - i;
+    ivec2 c = ivec2(i);
+    float n[4];
+    for (int k = 0; k < 4; k++) {
+        ivec2 o = ivec2(k & 1, (k >> 1) & 1);
+        n[k] = paxil_lattice_value(paxil_hash2(paxil_wrap(c.x + o.x, period.x), paxil_wrap(c.y + o.y, period.y)));
+    }
+    return paxil_reduce2(n, vec2(paxil_fade(f.x), paxil_fade(f.y)));
+}
+
+float paxil_value3_tiled(vec3 p, ivec3 period) {
+    vec3 i = floor(p);
+    vec3 f = p - i;
+    ivec3 c = ivec3(i);
+    float n[8];
+    for (int k = 0; k < 8; k++) {
+        ivec3 o = ivec3(k & 1, (k >> 1) & 1, (k >> 2) & 1);
+        n[k] = paxil_lattice_value(paxil_hash3(paxil_wrap(c.x + o.x, period.x), paxil_wrap(c.y + o.y, period.y), paxil_wrap(c.z + o.z, period.z)));
+    }
+    return paxil_reduce3(n, vec3(paxil_fade(f.x), paxil_fade(f.y), paxil_fade(f.z)));
+}
+
+float paxil_value4_tiled(vec4 p, ivec4 period) {
+    vec4 i = floor(p);
+    vec4 f = p - i;
+    ivec4 c = ivec4(i);
+    float n[16];
+    for (int k = 0; k < 16; k++) {
+        ivec4 o = ivec4(k & 1, (k >> 1) & 1, (k >> 2) & 1, (k >> 3) & 1);
+        n[k] = paxil_lattice_value(paxil_hash4(paxil_wrap(c.x + o.x, period.x), paxil_wrap(c.y + o.y, period.y), paxil_wrap(c.z + o.z, period.z), paxil_wrap(c.w + o.w, period.w)));
+    }
+    return paxil_reduce4(n, vec4(paxil_fade(f.x), paxil_fade(f.y), paxil_fade(f.z), paxil_fade(f.w)));
+}
+
+float paxil_value1(float x) { return paxil_value1_tiled(x, 0); }
+float paxil_value2(vec2 p) { return paxil_value2_tiled(p, ivec2(0)); }
+float paxil_value3(vec3 p) { return paxil_value3_tiled(p, ivec3(0)); }
+float paxil_value4(vec4 p) { return paxil_value4_tiled(p, ivec4(0)); }
+
+// simplex noise
+
+float paxil_simplex_corner2(int h, float x, float y) {
+    float t = 0.5 - x * x - y * y;
+    if (t < 0.0) return 0.0;
+    t = t * t;
+    return t * t * paxil_grad2(h, x, y);
+}
+
+float paxil_simplex_corner3(int h, float x, float y, float z) {
+    float t = 0.6 - x * x - y * y - z * z;
+    if (t < 0.0) return 0.0;
+    t = t * t;
+    return t * t * paxil_grad3(h, x, y, z);
+}
+
+float paxil_simplex_corner4(int h, float x, float y, float z, float w) {
+    float t = 0.6 - x * x - y * y - z * z - w * w;
+    if (t < 0.0) return 0.0;
+    t = t * t;
+    return t * t * paxil_grad4(h, x, y, z, w);
+}
+
+float paxil_simplex1(float x) {
+    int i0 = int(floor(x));
+    float x0 = x - float(i0);
+    float x1 = x0 - 1.0;
+    float t0 = 1.0 - x0 * x0;
+    t0 *= t0;
+    float n0 = t0 * t0 * paxil_grad1(paxil_hash1(i0), x0);
+    float t1 = 1.0 - x1 * x1;
+    t1 *= t1;
+    float n1 = t1 * t1 * paxil_grad1(paxil_hash1(i0 + 1), x1);
+    return 0.395 * (n0 + n1);
+}
+
+float paxil_simplex2(vec2 p) {
+    const float F2 = 0.3660254;
+    const float G2 = 0.21132487;
+    float s = (p.x + p.y) * F2;
+    int i = int(floor(p.x + s));
+    int j = int(floor(p.y + s));
+    float t = float(i + j) * G2;
+    float x0 = p.x - (float(i) - t);
+    float y0 = p.y - (float(j) - t);
+    int i1 = x0 > y0 ? 1 : 0;
+    int j1 = x0 > y0 ? 0 : 1;
+    float x1 = x0 - float(i1) + G2;
+    float y1 = y0 - float(j1) + G2;
+    float x2 = x0 - 1.0 + 2.0 * G2;
+    float y2 = y0 - 1.0 + 2.0 * G2;
+    float n0 = paxil_simplex_corner2(paxil_hash2(i, j), x0, y0);
+    float n1 = paxil_simplex_corner2(paxil_hash2(i + i1, j + j1), x1, y1);
+    float n2 = paxil_simplex_corner2(paxil_hash2(i + 1, j + 1), x2, y2);
+    return 40.0 * (n0 + n1 + n2);
+}
+
+float paxil_simplex3(vec3 p) {
+    const float F3 = 0.33333334;
+    const float G3 = 0.16666667;
+    float s = (p.x + p.y + p.z) * F3;
+    int i = int(floor(p.x + s));
+    int j = int(floor(p.y + s));
+    int k = int(floor(p.z + s));
+    float t = float(i + j + k) * G3;
+    float x0 = p.x - (float(i) - t);
+    float y0 = p.y - (float(j) - t);
+    float z0 = p.z - (float(k) - t);
+    ivec3 o1;
+    ivec3 o2;
+    if (x0 >= y0) {
+        if (y0 >= z0) { o1 = ivec3(1, 0, 0); o2 = ivec3(1, 1, 0); }
+        else if (x0 >= z0) { o1 = ivec3(1, 0, 0); o2 = ivec3(1, 0, 1); }
+        else { o1 = ivec3(0, 0, 1); o2 = ivec3(1, 0, 1); }
+    } else {
+        if (y0 < z0) { o1 = ivec3(0, 0, 1); o2 = ivec3(0, 1, 1); }
+        else if (x0 < z0) { o1 = ivec3(0, 1, 0); o2 = ivec3(0, 1, 1); }
+        else { o1 = ivec3(0, 1, 0); o2 = ivec3(1, 1, 0); }
+    }
+    float x1 = x0 - float(o1.x) + G3;
+    float y1 = y0 - float(o1.y) + G3;
+    float z1 = z0 - float(o1.z) + G3;
+    float x2 = x0 - float(o2.x) + 2.0 * G3;
+    float y2 = y0 - float(o2.y) + 2.0 * G3;
+    float z2 = z0 - float(o2.z) + 2.0 * G3;
+    float x3 = x0 - 1.0 + 3.0 * G3;
+    float y3 = y0 - 1.0 + 3.0 * G3;
+    float z3 = z0 - 1.0 + 3.0 * G3;
+    float n0 = paxil_simplex_corner3(paxil_hash3(i, j, k), x0, y0, z0);
+    float n1 = paxil_simplex_corner3(paxil_hash3(i + o1.x, j + o1.y, k + o1.z), x1, y1, z1);
+    float n2 = paxil_simplex_corner3(paxil_hash3(i + o2.x, j + o2.y, k + o2.z), x2, y2, z2);
+    float n3 = paxil_simplex_corner3(paxil_hash3(i + 1, j + 1, k + 1), x3, y3, z3);
+    return 32.0 * (n0 + n1 + n2 + n3);
+}
+
+float paxil_simplex4(vec4 p) {
+    const float F4 = 0.309017;
+    const float G4 = 0.1381966;
+    float s = (p.x + p.y + p.z + p.w) * F4;
+    int i = int(floor(p.x + s));
+    int j = int(floor(p.y + s));
+    int k = int(floor(p.z + s));
+    int l = int(floor(p.w + s));
+    float t = float(i + j + k + l) * G4;
+    float d[4] = float[4](p.x - (float(i) - t), p.y - (float(j) - t), p.z - (float(k) - t), p.w - (float(l) - t));
+    int rank[4] = int[4](0, 0, 0, 0);
+    for (int a = 0; a < 4; a++) {
+        for (int b = a + 1; b < 4; b++) {
+            if (d[a] > d[b]) rank[a] += 1; else rank[b] += 1;
+        }
+    }
+    float n = 0.0;
+    ivec4 corner = ivec4(0);
+    for (int vertex = 0; vertex < 5; vertex++) {
+        if (vertex > 0) {
+            corner = ivec4(rank[0] >= 4 - vertex, rank[1] >= 4 - vertex, rank[2] >= 4 - vertex, rank[3] >= 4 - vertex);
+        }
+        float g = float(vertex) * G4;
+        n += paxil_simplex_corner4(
+            paxil_hash4(i + corner.x, j + corner.y, k + corner.z, l + corner.w),
+            d[0] - float(corner.x) + g,
+            d[1] - float(corner.y) + g,
+            d[2] - float(corner.z) + g,
+            d[3] - float(corner.w) + g);
+    }
+    return 27.0 * n;
+}
+
+// Worley noise, returns (F1, F2)
+
+vec2 paxil_worley2_tiled(vec2 p, ivec2 period) {
+    vec2 i = floor(p);
+    vec2 f = p - i;
+    ivec2 c = ivec2(i);
+    float f1 = 1e9;
+    float f2 = 1e9;
+    for (int oy = -1; oy <= 1; oy++) {
+        for (int ox = -1; ox <= 1; ox++) {
+            int h = paxil_hash2(paxil_wrap(c.x + ox, period.x), paxil_wrap(c.y + oy, period.y));
+            float dx = float(ox) + float(PAXIL_PERM[h]) / 255.0 - f.x;
+            float dy = float(oy) + float(PAXIL_PERM[h + 1]) / 255.0 - f.y;
+            float d = dx * dx + dy * dy;
+            if (d < f1) { f2 = f1; f1 = d; }
+            else if (d < f2) { f2 = d; }
+        }
+    }
+    return vec2(sqrt(f1), sqrt(f2));
+}
+
+vec2 paxil_worley3_tiled(vec3 p, ivec3 period) {
+    vec3 i = floor(p);
+    vec3 f = p - i;
+    ivec3 c = ivec3(i);
+    float f1 = 1e9;
+    float f2 = 1e9;
+    for (int oz = -1; oz <= 1; oz++) {
+        for (int oy = -1; oy <= 1; oy++) {
+            for (int ox = -1; ox <= 1; ox++) {
+                int h = paxil_hash3(paxil_wrap(c.x + ox, period.x), paxil_wrap(c.y + oy, period.y), paxil_wrap(c.z + oz, period.z));
+                float dx = float(ox) + float(PAXIL_PERM[h]) / 255.0 - f.x;
+                float dy = float(oy) + float(PAXIL_PERM[h + 1]) / 255.0 - f.y;
+                float dz = float(oz) + float(PAXIL_PERM[h + 2]) / 255.0 - f.z;
+                float d = dx * dx + dy * dy + dz * dz;
+                if (d < f1) { f2 = f1; f1 = d; }
+                else if (d < f2) { f2 = d; }
+            }
+        }
+    }
+    return vec2(sqrt(f1), sqrt(f2));
+}
+
+vec2 paxil_worley2(vec2 p) { return paxil_worley2_tiled(p, ivec2(0)); }
+vec2 paxil_worley3(vec3 p) { return paxil_worley3_tiled(p, ivec3(0)); }
+
+// fBm and domain warping
+
+float paxil_fbm1(float x, int octaves, float lacunarity, float gain) {
+    float sum = 0.0;
+    float norm = 0.0;
+    float amplitude = 1.0;
+    float frequency = 1.0;
+    for (int o = 0; o < octaves; o++) {
+        sum += amplitude * paxil_perlin1(x * frequency);
+        norm += amplitude;
+        amplitude *= gain;
+        frequency *= lacunarity;
+    }
+    return norm > 0.0 ? sum / norm : 0.0;
+}
+
+float paxil_fbm2(vec2 p, int octaves, float lacunarity, float gain) {
+    float sum = 0.0;
+    float norm = 0.0;
+    float amplitude = 1.0;
+    float frequency = 1.0;
+    for (int o = 0; o < octaves; o++) {
+        sum += amplitude * paxil_perlin2(p * frequency);
+        norm += amplitude;
+        amplitude *= gain;
+        frequency *= lacunarity;
+    }
+    return norm > 0.0 ? sum / norm : 0.0;
+}
+
+float paxil_fbm3(vec3 p, int octaves, float lacunarity, float gain) {
+    float sum = 0.0;
+    float norm = 0.0;
+    float amplitude = 1.0;
+    float frequency = 1.0;
+    for (int o = 0; o < octaves; o++) {
+        sum += amplitude * paxil_perlin3(p * frequency);
+        norm += amplitude;
+        amplitude *= gain;
+        frequency *= lacunarity;
+    }
+    return norm > 0.0 ? sum / norm : 0.0;
+}
+
+float paxil_fbm4(vec4 p, int octaves, float lacunarity, float gain) {
+    float sum = 0.0;
+    float norm = 0.0;
+    float amplitude = 1.0;
+    float frequency = 1.0;
+    for (int o = 0; o < octaves; o++) {
+        sum += amplitude * paxil_perlin4(p * frequency);
+        norm += amplitude;
+        amplitude *= gain;
+        frequency *= lacunarity;
+    }
+    return norm > 0.0 ? sum / norm : 0.0;
+}
+
+float paxil_domain_warp2(vec2 p, float strength, int octaves, float lacunarity, float gain) {
+    vec2 q = vec2(
+        paxil_fbm2(p, octaves, lacunarity, gain),
+        paxil_fbm2(p + vec2(5.2, 1.3), octaves, lacunarity, gain));
+    return paxil_fbm2(p + q * strength, octaves, lacunarity, gain);
+}
+
+float paxil_domain_warp3(vec3 p, float strength, int octaves, float lacunarity, float gain) {
+    vec3 q = vec3(
+        paxil_fbm3(p, octaves, lacunarity, gain),
+        paxil_fbm3(p + vec3(5.2, 1.3, 2.8), octaves, lacunarity, gain),
+        paxil_fbm3(p + vec3(1.7, 9.2, 4.1), octaves, lacunarity, gain));
+    return paxil_fbm3(p + q * strength, octaves, lacunarity, gain);
+}
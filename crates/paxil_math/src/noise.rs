@@ -0,0 +1,691 @@
+use super::ivec::{IVec2, IVec3, IVec4};
+use super::vec2::Vec2;
+use super::vec3::Vec3;
+use super::vec4::Vec4;
+
+// noise.glsl mirrors every function below with the same lattice hashing and the
+// same order of float operations, so the GPU agrees with the CPU for a given seed
+// up to the precision of the driver's float math
+const NOISE_GLSL: &str = include_str!("noise.glsl");
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FbmParams {
+    pub octaves: u32,
+    pub lacunarity: f32,
+    pub gain: f32,
+}
+
+impl Default for FbmParams {
+    fn default() -> Self {
+        Self {
+            octaves: 5,
+            lacunarity: 2.0,
+            gain: 0.5,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Noise {
+    seed: u64,
+    perm: [u8; 512],
+}
+
+impl Default for Noise {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Noise {
+    pub fn new(seed: u64) -> Self {
+        let mut table: [u8; 256] = std::array::from_fn(|i| i as u8);
+        let mut state = seed;
+        // Fisher-Yates shuffle driven by splitmix64
+        for i in (1..256).rev() {
+            state = state.wrapping_add(0x9e3779b97f4a7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^= z >> 31;
+            table.swap(i, (z % (i as u64 + 1)) as usize);
+        }
+        Self {
+            seed,
+            perm: std::array::from_fn(|i| table[i & 255]),
+        }
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn get_permutation(&self) -> &[u8; 512] {
+        &self.perm
+    }
+
+    // GLSL functions prefixed with paxil_ that use this noise's permutation table
+    pub fn glsl_source(&self) -> String {
+        let table = self
+            .perm
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            "const int PAXIL_PERM[512] = int[512]({});\n{}",
+            table, NOISE_GLSL
+        )
+    }
+
+    // inserts glsl_source() right after the #version line of a shader
+    pub fn inject_glsl(&self, shader_src: &str) -> String {
+        let glsl = self.glsl_source();
+        match shader_src.find("#version") {
+            Some(start) => {
+                let end = shader_src[start..]
+                    .find('\n')
+                    .map(|i| start + i + 1)
+                    .unwrap_or(shader_src.len());
+                format!("{}{}\n{}", &shader_src[..end], glsl, &shader_src[end..])
+            }
+            None => format!("{}\n{}", glsl, shader_src),
+        }
+    }
+
+    fn hash1(&self, i: i32) -> usize {
+        self.perm[(i & 255) as usize] as usize
+    }
+
+    fn hash2(&self, i: i32, j: i32) -> usize {
+        self.perm[self.hash1(i) + (j & 255) as usize] as usize
+    }
+
+    fn hash3(&self, i: i32, j: i32, k: i32) -> usize {
+        self.perm[self.hash2(i, j) + (k & 255) as usize] as usize
+    }
+
+    fn hash4(&self, i: i32, j: i32, k: i32, l: i32) -> usize {
+        self.perm[self.hash3(i, j, k) + (l & 255) as usize] as usize
+    }
+
+    // Perlin's improved gradient noise, roughly in [-1, 1]
+
+    pub fn perlin1(&self, x: f32) -> f32 {
+        self.perlin1_tiled(x, 0)
+    }
+
+    pub fn perlin2(&self, p: Vec2) -> f32 {
+        self.perlin2_tiled(p, IVec2::ZERO)
+    }
+
+    pub fn perlin3(&self, p: Vec3) -> f32 {
+        self.perlin3_tiled(p, IVec3::ZERO)
+    }
+
+    pub fn perlin4(&self, p: Vec4) -> f32 {
+        self.perlin4_tiled(p, IVec4::ZERO)
+    }
+
+    // repeats every period lattice cells, a period of 0 disables tiling on that axis
+    pub fn perlin1_tiled(&self, x: f32, period: i32) -> f32 {
+        let i = x.floor();
+        let f = x - i;
+        let i = i as i32;
+        let mut n = [0.0f32; 2];
+        for (c, v) in n.iter_mut().enumerate() {
+            let o = c as i32;
+            *v = grad1(self.hash1(wrap(i + o, period)), f - o as f32);
+        }
+        0.188 * reduce(&mut n, &[fade(f)])
+    }
+
+    pub fn perlin2_tiled(&self, p: Vec2, period: IVec2) -> f32 {
+        let i = p.floor();
+        let f = p - i;
+        let (ix, iy) = (i.x as i32, i.y as i32);
+        let mut n = [0.0f32; 4];
+        for (c, v) in n.iter_mut().enumerate() {
+            let (ox, oy) = ((c & 1) as i32, ((c >> 1) & 1) as i32);
+            let h = self.hash2(wrap(ix + ox, period.x), wrap(iy + oy, period.y));
+            *v = grad2(h, f.x - ox as f32, f.y - oy as f32);
+        }
+        0.507 * reduce(&mut n, &[fade(f.x), fade(f.y)])
+    }
+
+    pub fn perlin3_tiled(&self, p: Vec3, period: IVec3) -> f32 {
+        let i = p.floor();
+        let f = p - i;
+        let (ix, iy, iz) = (i.x as i32, i.y as i32, i.z as i32);
+        let mut n = [0.0f32; 8];
+        for (c, v) in n.iter_mut().enumerate() {
+            let (ox, oy, oz) = ((c & 1) as i32, ((c >> 1) & 1) as i32, ((c >> 2) & 1) as i32);
+            let h = self.hash3(
+                wrap(ix + ox, period.x),
+                wrap(iy + oy, period.y),
+                wrap(iz + oz, period.z),
+            );
+            *v = grad3(h, f.x - ox as f32, f.y - oy as f32, f.z - oz as f32);
+        }
+        0.936 * reduce(&mut n, &[fade(f.x), fade(f.y), fade(f.z)])
+    }
+
+    pub fn perlin4_tiled(&self, p: Vec4, period: IVec4) -> f32 {
+        let i = p.floor();
+        let f = p - i;
+        let (ix, iy, iz, iw) = (i.x as i32, i.y as i32, i.z as i32, i.w as i32);
+        let mut n = [0.0f32; 16];
+        for (c, v) in n.iter_mut().enumerate() {
+            let (ox, oy) = ((c & 1) as i32, ((c >> 1) & 1) as i32);
+            let (oz, ow) = (((c >> 2) & 1) as i32, ((c >> 3) & 1) as i32);
+            let h = self.hash4(
+                wrap(ix + ox, period.x),
+                wrap(iy + oy, period.y),
+                wrap(iz + oz, period.z),
+                wrap(iw + ow, period.w),
+            );
+            *v = grad4(
+                h,
+                f.x - ox as f32,
+                f.y - oy as f32,
+                f.z - oz as f32,
+                f.w - ow as f32,
+            );
+        }
+        0.87 * reduce(&mut n, &[fade(f.x), fade(f.y), fade(f.z), fade(f.w)])
+    }
+
+    // value noise in [-1, 1]
+
+    pub fn value1(&self, x: f32) -> f32 {
+        self.value1_tiled(x, 0)
+    }
+
+    pub fn value2(&self, p: Vec2) -> f32 {
+        self.value2_tiled(p, IVec2::ZERO)
+    }
+
+    pub fn value3(&self, p: Vec3) -> f32 {
+        self.value3_tiled(p, IVec3::ZERO)
+    }
+
+    pub fn value4(&self, p: Vec4) -> f32 {
+        self.value4_tiled(p, IVec4::ZERO)
+    }
+
+    pub fn value1_tiled(&self, x: f32, period: i32) -> f32 {
+        let i = x.floor();
+        let f = x - i;
+        let i = i as i32;
+        let mut n = [0.0f32; 2];
+        for (c, v) in n.iter_mut().enumerate() {
+            *v = lattice_value(self.hash1(wrap(i + c as i32, period)));
+        }
+        reduce(&mut n, &[fade(f)])
+    }
+
+    pub fn value2_tiled(&self, p: Vec2, period: IVec2) -> f32 {
+        let i = p.floor();
+        let f = p - i;
+        let (ix, iy) = (i.x as i32, i.y as i32);
+        let mut n = [0.0f32; 4];
+        for (c, v) in n.iter_mut().enumerate() {
+            let (ox, oy) = ((c & 1) as i32, ((c >> 1) & 1) as i32);
+            *v = lattice_value(self.hash2(wrap(ix + ox, period.x), wrap(iy + oy, period.y)));
+        }
+        reduce(&mut n, &[fade(f.x), fade(f.y)])
+    }
+
+    pub fn value3_tiled(&self, p: Vec3, period: IVec3) -> f32 {
+        let i = p.floor();
+        let f = p - i;
+        let (ix, iy, iz) = (i.x as i32, i.y as i32, i.z as i32);
+        let mut n = [0.0f32; 8];
+        for (c, v) in n.iter_mut().enumerate() {
+            let (ox, oy, oz) = ((c & 1) as i32, ((c >> 1) & 1) as i32, ((c >> 2) & 1) as i32);
+            *v = lattice_value(self.hash3(
+                wrap(ix + ox, period.x),
+                wrap(iy + oy, period.y),
+                wrap(iz + oz, period.z),
+            ));
+        }
+        reduce(&mut n, &[fade(f.x), fade(f.y), fade(f.z)])
+    }
+
+    pub fn value4_tiled(&self, p: Vec4, period: IVec4) -> f32 {
+        let i = p.floor();
+        let f = p - i;
+        let (ix, iy, iz, iw) = (i.x as i32, i.y as i32, i.z as i32, i.w as i32);
+        let mut n = [0.0f32; 16];
+        for (c, v) in n.iter_mut().enumerate() {
+            let (ox, oy) = ((c & 1) as i32, ((c >> 1) & 1) as i32);
+            let (oz, ow) = (((c >> 2) & 1) as i32, ((c >> 3) & 1) as i32);
+            *v = lattice_value(self.hash4(
+                wrap(ix + ox, period.x),
+                wrap(iy + oy, period.y),
+                wrap(iz + oz, period.z),
+                wrap(iw + ow, period.w),
+            ));
+        }
+        reduce(&mut n, &[fade(f.x), fade(f.y), fade(f.z), fade(f.w)])
+    }
+
+    // simplex noise after Gustavson's simplexnoise1234, roughly in [-1, 1]
+
+    pub fn simplex1(&self, x: f32) -> f32 {
+        let i0 = x.floor() as i32;
+        let x0 = x - i0 as f32;
+        let x1 = x0 - 1.0;
+        let mut t0 = 1.0 - x0 * x0;
+        t0 *= t0;
+        let n0 = t0 * t0 * grad1(self.hash1(i0), x0);
+        let mut t1 = 1.0 - x1 * x1;
+        t1 *= t1;
+        let n1 = t1 * t1 * grad1(self.hash1(i0 + 1), x1);
+        0.395 * (n0 + n1)
+    }
+
+    pub fn simplex2(&self, p: Vec2) -> f32 {
+        const F2: f32 = 0.3660254;
+        const G2: f32 = 0.21132487;
+        let s = (p.x + p.y) * F2;
+        let i = (p.x + s).floor() as i32;
+        let j = (p.y + s).floor() as i32;
+        let t = (i + j) as f32 * G2;
+        let x0 = p.x - (i as f32 - t);
+        let y0 = p.y - (j as f32 - t);
+        let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+        let x1 = x0 - i1 as f32 + G2;
+        let y1 = y0 - j1 as f32 + G2;
+        let x2 = x0 - 1.0 + 2.0 * G2;
+        let y2 = y0 - 1.0 + 2.0 * G2;
+        let n0 = simplex_corner2(self.hash2(i, j), x0, y0);
+        let n1 = simplex_corner2(self.hash2(i + i1, j + j1), x1, y1);
+        let n2 = simplex_corner2(self.hash2(i + 1, j + 1), x2, y2);
+        40.0 * (n0 + n1 + n2)
+    }
+
+    pub fn simplex3(&self, p: Vec3) -> f32 {
+        const F3: f32 = 0.33333334;
+        const G3: f32 = 0.16666667;
+        let s = (p.x + p.y + p.z) * F3;
+        let i = (p.x + s).floor() as i32;
+        let j = (p.y + s).floor() as i32;
+        let k = (p.z + s).floor() as i32;
+        let t = (i + j + k) as f32 * G3;
+        let x0 = p.x - (i as f32 - t);
+        let y0 = p.y - (j as f32 - t);
+        let z0 = p.z - (k as f32 - t);
+        let (i1, j1, k1, i2, j2, k2) = if x0 >= y0 {
+            if y0 >= z0 {
+                (1, 0, 0, 1, 1, 0)
+            } else if x0 >= z0 {
+                (1, 0, 0, 1, 0, 1)
+            } else {
+                (0, 0, 1, 1, 0, 1)
+            }
+        } else if y0 < z0 {
+            (0, 0, 1, 0, 1, 1)
+        } else if x0 < z0 {
+            (0, 1, 0, 0, 1, 1)
+        } else {
+            (0, 1, 0, 1, 1, 0)
+        };
+        let x1 = x0 - i1 as f32 + G3;
+        let y1 = y0 - j1 as f32 + G3;
+        let z1 = z0 - k1 as f32 + G3;
+        let x2 = x0 - i2 as f32 + 2.0 * G3;
+        let y2 = y0 - j2 as f32 + 2.0 * G3;
+        let z2 = z0 - k2 as f32 + 2.0 * G3;
+        let x3 = x0 - 1.0 + 3.0 * G3;
+        let y3 = y0 - 1.0 + 3.0 * G3;
+        let z3 = z0 - 1.0 + 3.0 * G3;
+        let n0 = simplex_corner3(self.hash3(i, j, k), x0, y0, z0);
+        let n1 = simplex_corner3(self.hash3(i + i1, j + j1, k + k1), x1, y1, z1);
+        let n2 = simplex_corner3(self.hash3(i + i2, j + j2, k + k2), x2, y2, z2);
+        let n3 = simplex_corner3(self.hash3(i + 1, j + 1, k + 1), x3, y3, z3);
+        32.0 * (n0 + n1 + n2 + n3)
+    }
+
+    pub fn simplex4(&self, p: Vec4) -> f32 {
+        const F4: f32 = 0.309017;
+        const G4: f32 = 0.1381966;
+        let s = (p.x + p.y + p.z + p.w) * F4;
+        let i = (p.x + s).floor() as i32;
+        let j = (p.y + s).floor() as i32;
+        let k = (p.z + s).floor() as i32;
+        let l = (p.w + s).floor() as i32;
+        let t = (i + j + k + l) as f32 * G4;
+        let x0 = p.x - (i as f32 - t);
+        let y0 = p.y - (j as f32 - t);
+        let z0 = p.z - (k as f32 - t);
+        let w0 = p.w - (l as f32 - t);
+
+        // rank the components to find which simplex we are in
+        let mut rank = [0i32; 4];
+        let d = [x0, y0, z0, w0];
+        for a in 0..4 {
+            for b in (a + 1)..4 {
+                if d[a] > d[b] {
+                    rank[a] += 1;
+                } else {
+                    rank[b] += 1;
+                }
+            }
+        }
+        let mut n = 0.0;
+        let mut corner = [0i32; 4];
+        for step in 0..5 {
+            if step > 0 {
+                // corners are entered from the largest component down
+                for (c, r) in corner.iter_mut().zip(rank.iter()) {
+                    *c = (*r >= 4 - step) as i32;
+                }
+            }
+            let g = step as f32 * G4;
+            n += simplex_corner4(
+                self.hash4(i + corner[0], j + corner[1], k + corner[2], l + corner[3]),
+                x0 - corner[0] as f32 + g,
+                y0 - corner[1] as f32 + g,
+                z0 - corner[2] as f32 + g,
+                w0 - corner[3] as f32 + g,
+            );
+        }
+        27.0 * n
+    }
+
+    // Worley noise, returns the distances to the nearest and second nearest feature points
+
+    pub fn worley2(&self, p: Vec2) -> Vec2 {
+        self.worley2_tiled(p, IVec2::ZERO)
+    }
+
+    pub fn worley3(&self, p: Vec3) -> Vec2 {
+        self.worley3_tiled(p, IVec3::ZERO)
+    }
+
+    pub fn worley2_tiled(&self, p: Vec2, period: IVec2) -> Vec2 {
+        let i = p.floor();
+        let f = p - i;
+        let (ix, iy) = (i.x as i32, i.y as i32);
+        let mut f1 = 1e9f32;
+        let mut f2 = 1e9f32;
+        for oy in -1..=1 {
+            for ox in -1..=1 {
+                let h = self.hash2(wrap(ix + ox, period.x), wrap(iy + oy, period.y));
+                let dx = ox as f32 + self.perm[h] as f32 / 255.0 - f.x;
+                let dy = oy as f32 + self.perm[h + 1] as f32 / 255.0 - f.y;
+                let d = dx * dx + dy * dy;
+                if d < f1 {
+                    f2 = f1;
+                    f1 = d;
+                } else if d < f2 {
+                    f2 = d;
+                }
+            }
+        }
+        Vec2::new(f1.sqrt(), f2.sqrt())
+    }
+
+    pub fn worley3_tiled(&self, p: Vec3, period: IVec3) -> Vec2 {
+        let i = p.floor();
+        let f = p - i;
+        let (ix, iy, iz) = (i.x as i32, i.y as i32, i.z as i32);
+        let mut f1 = 1e9f32;
+        let mut f2 = 1e9f32;
+        for oz in -1..=1 {
+            for oy in -1..=1 {
+                for ox in -1..=1 {
+                    let h = self.hash3(
+                        wrap(ix + ox, period.x),
+                        wrap(iy + oy, period.y),
+                        wrap(iz + oz, period.z),
+                    );
+                    let dx = ox as f32 + self.perm[h] as f32 / 255.0 - f.x;
+                    let dy = oy as f32 + self.perm[h + 1] as f32 / 255.0 - f.y;
+                    let dz = oz as f32 + self.perm[h + 2] as f32 / 255.0 - f.z;
+                    let d = dx * dx + dy * dy + dz * dz;
+                    if d < f1 {
+                        f2 = f1;
+                        f1 = d;
+                    } else if d < f2 {
+                        f2 = d;
+                    }
+                }
+            }
+        }
+        Vec2::new(f1.sqrt(), f2.sqrt())
+    }
+
+    // fractal sums of Perlin noise, normalized back to roughly [-1, 1]
+
+    pub fn fbm1(&self, x: f32, params: &FbmParams) -> f32 {
+        fbm(params, |frequency| self.perlin1(x * frequency))
+    }
+
+    pub fn fbm2(&self, p: Vec2, params: &FbmParams) -> f32 {
+        fbm(params, |frequency| self.perlin2(p * frequency))
+    }
+
+    pub fn fbm3(&self, p: Vec3, params: &FbmParams) -> f32 {
+        fbm(params, |frequency| self.perlin3(p * frequency))
+    }
+
+    pub fn fbm4(&self, p: Vec4, params: &FbmParams) -> f32 {
+        fbm(params, |frequency| self.perlin4(p * frequency))
+    }
+
+    // Quilez style domain warping, p is offset by fbm before being sampled again
+    pub fn domain_warp2(&self, p: Vec2, strength: f32, params: &FbmParams) -> f32 {
+        let q = Vec2::new(
+            self.fbm2(p, params),
+            self.fbm2(p + Vec2::new(5.2, 1.3), params),
+        );
+        self.fbm2(p + q * strength, params)
+    }
+
+    pub fn domain_warp3(&self, p: Vec3, strength: f32, params: &FbmParams) -> f32 {
+        let q = Vec3::new(
+            self.fbm3(p, params),
+            self.fbm3(p + Vec3::new(5.2, 1.3, 2.8), params),
+            self.fbm3(p + Vec3::new(1.7, 9.2, 4.1), params),
+        );
+        self.fbm3(p + q * strength, params)
+    }
+}
+
+fn wrap(i: i32, period: i32) -> i32 {
+    if period > 0 {
+        i.rem_euclid(period)
+    } else {
+        i
+    }
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + t * (b - a)
+}
+
+// collapses the 2^n corner values one axis at a time, x first
+fn reduce(values: &mut [f32], t: &[f32]) -> f32 {
+    let mut len = values.len();
+    for t in t.iter() {
+        len /= 2;
+        for m in 0..len {
+            values[m] = lerp(values[2 * m], values[2 * m + 1], *t);
+        }
+    }
+    values[0]
+}
+
+fn lattice_value(h: usize) -> f32 {
+    h as f32 / 255.0 * 2.0 - 1.0
+}
+
+fn grad1(h: usize, x: f32) -> f32 {
+    let g = 1.0 + (h & 7) as f32;
+    if h & 8 != 0 {
+        -g * x
+    } else {
+        g * x
+    }
+}
+
+fn grad2(h: usize, x: f32, y: f32) -> f32 {
+    let h = h & 7;
+    let (u, v) = if h < 4 { (x, y) } else { (y, x) };
+    let u = if h & 1 != 0 { -u } else { u };
+    let v = if h & 2 != 0 { -2.0 * v } else { 2.0 * v };
+    u + v
+}
+
+fn grad3(h: usize, x: f32, y: f32, z: f32) -> f32 {
+    let h = h & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    let u = if h & 1 != 0 { -u } else { u };
+    let v = if h & 2 != 0 { -v } else { v };
+    u + v
+}
+
+fn grad4(h: usize, x: f32, y: f32, z: f32, w: f32) -> f32 {
+    let h = h & 31;
+    let u = if h < 24 { x } else { y };
+    let v = if h < 16 { y } else { z };
+    let t = if h < 8 { z } else { w };
+    let u = if h & 1 != 0 { -u } else { u };
+    let v = if h & 2 != 0 { -v } else { v };
+    let t = if h & 4 != 0 { -t } else { t };
+    u + v + t
+}
+
+fn simplex_corner2(h: usize, x: f32, y: f32) -> f32 {
+    let t = 0.5 - x * x - y * y;
+    if t < 0.0 {
+        0.0
+    } else {
+        let t = t * t;
+        t * t * grad2(h, x, y)
+    }
+}
+
+fn simplex_corner3(h: usize, x: f32, y: f32, z: f32) -> f32 {
+    let t = 0.6 - x * x - y * y - z * z;
+    if t < 0.0 {
+        0.0
+    } else {
+        let t = t * t;
+        t * t * grad3(h, x, y, z)
+    }
+}
+
+fn simplex_corner4(h: usize, x: f32, y: f32, z: f32, w: f32) -> f32 {
+    let t = 0.6 - x * x - y * y - z * z - w * w;
+    if t < 0.0 {
+        0.0
+    } else {
+        let t = t * t;
+        t * t * grad4(h, x, y, z, w)
+    }
+}
+
+fn fbm<F: Fn(f32) -> f32>(params: &FbmParams, noise: F) -> f32 {
+    let mut sum = 0.0;
+    let mut norm = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    for _ in 0..params.octaves {
+        sum += amplitude * noise(frequency);
+        norm += amplitude;
+        amplitude *= params.gain;
+        frequency *= params.lacunarity;
+    }
+    if norm > 0.0 {
+        sum / norm
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_noise() {
+        let a = Noise::new(42);
+        let b = Noise::new(42);
+        let c = Noise::new(7);
+        let p = Vec3::new(1.3, -2.7, 0.4);
+        assert_eq!(a.perlin3(p), b.perlin3(p));
+        assert_ne!(a.get_permutation(), c.get_permutation());
+    }
+
+    #[test]
+    fn gradient_noise_is_zero_on_lattice() {
+        let noise = Noise::new(1);
+        assert_eq!(noise.perlin2(Vec2::new(3.0, -4.0)), 0.0);
+        assert_eq!(noise.perlin3(Vec3::new(3.0, -4.0, 5.0)), 0.0);
+    }
+
+    #[test]
+    fn noise_stays_in_range() {
+        let noise = Noise::new(3);
+        for i in 0..2000 {
+            let t = i as f32 * 0.173;
+            let p = Vec4::new(t, t * 0.71 - 3.0, t * 1.37 + 2.0, -t * 0.3);
+            for n in [
+                noise.perlin1(t),
+                noise.perlin2(p.truncate().truncate()),
+                noise.perlin3(p.truncate()),
+                noise.perlin4(p),
+                noise.simplex1(t),
+                noise.simplex2(p.truncate().truncate()),
+                noise.simplex3(p.truncate()),
+                noise.simplex4(p),
+                noise.value3(p.truncate()),
+            ] {
+                assert!((-1.1..=1.1).contains(&n), "{} out of range", n);
+            }
+            let w = noise.worley2(p.truncate().truncate());
+            assert!(w.x <= w.y);
+        }
+    }
+
+    #[test]
+    fn tiled_noise_repeats() {
+        let noise = Noise::new(9);
+        let period = IVec3::new(4, 5, 6);
+        let p = Vec3::new(0.3, 1.7, 2.2);
+        let shifted = p + Vec3::new(4.0, 5.0, 6.0);
+        assert!(
+            (noise.perlin3_tiled(p, period) - noise.perlin3_tiled(shifted, period)).abs() < 1e-5
+        );
+        assert!((noise.value3_tiled(p, period) - noise.value3_tiled(shifted, period)).abs() < 1e-5);
+        let w0 = noise.worley3_tiled(p, period);
+        let w1 = noise.worley3_tiled(shifted, period);
+        assert!(w0.abs_diff_eq(w1, 1e-5));
+    }
+
+    #[test]
+    fn glsl_source_embeds_permutation() {
+        let noise = Noise::new(5);
+        let src = noise.inject_glsl("#version 410\nvoid main() {}\n");
+        assert!(src.starts_with("#version 410\nconst int PAXIL_PERM[512]"));
+        assert!(src.contains("float paxil_perlin3(vec3 p)"));
+        assert!(src.contains("float paxil_fbm4(vec4 p, int octaves"));
+        // line continuations need GLSL 4.20
+        assert!(!src.lines().any(|line| line.ends_with('\\')));
+        assert!(src.ends_with("void main() {}\n"));
+    }
+}
@@ -0,0 +1,66 @@
+use super::aabb::Aabb;
+use super::mat4::Mat4;
+use super::quat::Quat;
+use super::vec3::Vec3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Obb {
+    pub center: Vec3,
+    pub half_extents: Vec3,
+    pub rotation: Quat,
+}
+
+impl Obb {
+    pub fn new(center: Vec3, half_extents: Vec3, rotation: Quat) -> Self {
+        Self {
+            center,
+            half_extents,
+            rotation,
+        }
+    }
+
+    // scale in the matrix ends up in the extents, shear is lost
+    pub fn from_aabb(aabb: &Aabb, m: &Mat4) -> Self {
+        let (scale, rotation, _) = m.to_scale_rotation_translation();
+        Self::new(
+            m.transform_point3(aabb.get_center()),
+            (aabb.get_half_extents() * scale).abs(),
+            rotation,
+        )
+    }
+
+    pub fn get_axes(&self) -> [Vec3; 3] {
+        [
+            self.rotation * Vec3::X,
+            self.rotation * Vec3::Y,
+            self.rotation * Vec3::Z,
+        ]
+    }
+
+    pub fn get_corners(&self) -> [Vec3; 8] {
+        let local = Aabb::new(-self.half_extents, self.half_extents).get_corners();
+        local.map(|p| self.center + self.rotation * p)
+    }
+
+    pub fn get_aabb(&self) -> Aabb {
+        Aabb::from_points(&self.get_corners())
+    }
+
+    pub fn to_local(&self, p: Vec3) -> Vec3 {
+        self.rotation.inverse() * (p - self.center)
+    }
+
+    pub fn contains_point(&self, p: Vec3) -> bool {
+        let local = self.to_local(p).abs();
+        local.x <= self.half_extents.x
+            && local.y <= self.half_extents.y
+            && local.z <= self.half_extents.z
+    }
+
+    pub fn closest_point(&self, p: Vec3) -> Vec3 {
+        let local = self
+            .to_local(p)
+            .clamp(-self.half_extents, self.half_extents);
+        self.center + self.rotation * local
+    }
+}
@@ -0,0 +1,52 @@
+use super::vec3::Vec3;
+use super::vec4::Vec4;
+
+// points p with normal.dot(p) + d == 0
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Plane {
+    pub normal: Vec3,
+    pub d: f32,
+}
+
+impl Plane {
+    pub fn new(normal: Vec3, d: f32) -> Self {
+        Self { normal, d }
+    }
+
+    pub fn from_point_normal(point: Vec3, normal: Vec3) -> Self {
+        let normal = normal.normalize();
+        Self::new(normal, -normal.dot(point))
+    }
+
+    // counter clockwise points face the normal
+    pub fn from_points(a: Vec3, b: Vec3, c: Vec3) -> Self {
+        Self::from_point_normal(a, (b - a).cross(c - a))
+    }
+
+    // (a, b, c, d) coefficients, rescaled so the normal has unit length
+    pub fn from_vec4(v: Vec4) -> Self {
+        Self::new(v.truncate(), v.w).normalize()
+    }
+
+    pub fn normalize(&self) -> Self {
+        let length = self.normal.length();
+        if length > 0.0 {
+            Self::new(self.normal / length, self.d / length)
+        } else {
+            *self
+        }
+    }
+
+    pub fn flip(&self) -> Self {
+        Self::new(-self.normal, -self.d)
+    }
+
+    // positive on the side the normal points to
+    pub fn signed_distance(&self, p: Vec3) -> f32 {
+        self.normal.dot(p) + self.d
+    }
+
+    pub fn closest_point(&self, p: Vec3) -> Vec3 {
+        p - self.normal * self.signed_distance(p)
+    }
+}
@@ -0,0 +1,245 @@
+use super::mat3::Mat3;
+use super::mat4::Mat4;
+use super::vec3::Vec3;
+use super::vec4::Vec4;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quat {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Default for Quat {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Quat {
+    pub const IDENTITY: Self = Self::from_xyzw(0.0, 0.0, 0.0, 1.0);
+
+    pub const fn from_xyzw(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    // axis must be normalized
+    pub fn from_axis_angle(axis: Vec3, angle: f32) -> Self {
+        let (s, c) = (angle * 0.5).sin_cos();
+        Self::from_xyzw(axis.x * s, axis.y * s, axis.z * s, c)
+    }
+
+    // rotates around X, then Y, then Z
+    pub fn from_euler(x: f32, y: f32, z: f32) -> Self {
+        Self::from_axis_angle(Vec3::Z, z)
+            * Self::from_axis_angle(Vec3::Y, y)
+            * Self::from_axis_angle(Vec3::X, x)
+    }
+
+    // shortest rotation taking the unit vector from onto the unit vector to
+    pub fn from_rotation_arc(from: Vec3, to: Vec3) -> Self {
+        let d = from.dot(to);
+        if d < -0.999_999 {
+            return Self::from_axis_angle(from.any_orthonormal(), std::f32::consts::PI);
+        }
+        let c = from.cross(to);
+        Self::from_xyzw(c.x, c.y, c.z, 1.0 + d).normalize()
+    }
+
+    pub fn from_mat3(m: &Mat3) -> Self {
+        let [c0, c1, c2] = m.cols;
+        let trace = c0.x + c1.y + c2.z;
+        if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Self::from_xyzw(
+                (c1.z - c2.y) / s,
+                (c2.x - c0.z) / s,
+                (c0.y - c1.x) / s,
+                0.25 * s,
+            )
+        } else if c0.x > c1.y && c0.x > c2.z {
+            let s = (1.0 + c0.x - c1.y - c2.z).sqrt() * 2.0;
+            Self::from_xyzw(
+                0.25 * s,
+                (c1.x + c0.y) / s,
+                (c2.x + c0.z) / s,
+                (c1.z - c2.y) / s,
+            )
+        } else if c1.y > c2.z {
+            let s = (1.0 + c1.y - c0.x - c2.z).sqrt() * 2.0;
+            Self::from_xyzw(
+                (c1.x + c0.y) / s,
+                0.25 * s,
+                (c2.y + c1.z) / s,
+                (c2.x - c0.z) / s,
+            )
+        } else {
+            let s = (1.0 + c2.z - c0.x - c1.y).sqrt() * 2.0;
+            Self::from_xyzw(
+                (c2.x + c0.z) / s,
+                (c2.y + c1.z) / s,
+                0.25 * s,
+                (c0.y - c1.x) / s,
+            )
+        }
+        .normalize()
+    }
+
+    pub fn to_mat3(self) -> Mat3 {
+        Mat3::from_quat(self)
+    }
+
+    pub fn to_mat4(self) -> Mat4 {
+        Mat4::from_quat(self)
+    }
+
+    pub fn to_axis_angle(self) -> (Vec3, f32) {
+        let q = self.normalize();
+        let s = (1.0 - q.w * q.w).max(0.0).sqrt();
+        let angle = 2.0 * q.w.clamp(-1.0, 1.0).acos();
+        if s < 1e-6 {
+            (Vec3::X, angle)
+        } else {
+            (Vec3::new(q.x, q.y, q.z) / s, angle)
+        }
+    }
+
+    pub fn to_vec4(self) -> Vec4 {
+        Vec4::new(self.x, self.y, self.z, self.w)
+    }
+
+    pub fn dot(self, rhs: Self) -> f32 {
+        self.to_vec4().dot(rhs.to_vec4())
+    }
+
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalize(self) -> Self {
+        let v = self.to_vec4().normalize();
+        Self::from_xyzw(v.x, v.y, v.z, v.w)
+    }
+
+    pub fn conjugate(self) -> Self {
+        Self::from_xyzw(-self.x, -self.y, -self.z, self.w)
+    }
+
+    pub fn inverse(self) -> Self {
+        let len2 = self.dot(self);
+        let c = self.conjugate();
+        Self::from_xyzw(c.x / len2, c.y / len2, c.z / len2, c.w / len2)
+    }
+
+    // takes the shortest path, falls back to nlerp when nearly parallel
+    pub fn slerp(self, end: Self, t: f32) -> Self {
+        let mut end = end;
+        let mut d = self.dot(end);
+        if d < 0.0 {
+            end = Self::from_xyzw(-end.x, -end.y, -end.z, -end.w);
+            d = -d;
+        }
+
+        if d > 0.9995 {
+            let v = self.to_vec4().lerp(end.to_vec4(), t);
+            return Self::from_xyzw(v.x, v.y, v.z, v.w).normalize();
+        }
+
+        let theta = d.acos();
+        let sin_theta = theta.sin();
+        let a = ((1.0 - t) * theta).sin() / sin_theta;
+        let b = (t * theta).sin() / sin_theta;
+        let v = self.to_vec4() * a + end.to_vec4() * b;
+        Self::from_xyzw(v.x, v.y, v.z, v.w)
+    }
+}
+
+impl std::ops::Mul for Quat {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self::from_xyzw(
+            self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+            self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+        )
+    }
+}
+
+impl std::ops::Mul<Vec3> for Quat {
+    type Output = Vec3;
+    fn mul(self, v: Vec3) -> Vec3 {
+        let u = Vec3::new(self.x, self.y, self.z);
+        let t = u.cross(v) * 2.0;
+        v + t * self.w + u.cross(t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    fn assert_same_rotation(a: Quat, b: Quat) {
+        assert!(a.dot(b).abs() > 1.0 - 1e-5, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn axis_angle() {
+        let q = Quat::from_axis_angle(Vec3::Z, FRAC_PI_2);
+        assert!((q * Vec3::X).abs_diff_eq(Vec3::Y, 1e-6));
+        assert!((q.inverse() * Vec3::Y).abs_diff_eq(Vec3::X, 1e-6));
+        assert!((q.length() - 1.0).abs() < 1e-6);
+
+        let axis = Vec3::new(1.0, -2.0, 2.0) / 3.0;
+        let (out_axis, angle) = Quat::from_axis_angle(axis, 1.2).to_axis_angle();
+        assert!(out_axis.abs_diff_eq(axis, 1e-5));
+        assert!((angle - 1.2).abs() < 1e-5);
+
+        let arc = Quat::from_rotation_arc(Vec3::X, Vec3::Z);
+        assert!((arc * Vec3::X).abs_diff_eq(Vec3::Z, 1e-6));
+        let flip = Quat::from_rotation_arc(Vec3::Y, -Vec3::Y);
+        assert!((flip * Vec3::Y).abs_diff_eq(-Vec3::Y, 1e-5));
+    }
+
+    #[test]
+    fn matrix_round_trip() {
+        // one rotation per branch of from_mat3
+        for q in [
+            Quat::from_axis_angle(Vec3::new(0.0, 0.6, 0.8), 0.4),
+            Quat::from_axis_angle(Vec3::X, PI * 0.9),
+            Quat::from_axis_angle(Vec3::Y, PI * 0.9),
+            Quat::from_axis_angle(Vec3::Z, PI * 0.9),
+            Quat::from_euler(0.3, -1.1, 2.5),
+        ] {
+            let m = q.to_mat3();
+            assert_same_rotation(Quat::from_mat3(&m), q);
+            for v in [Vec3::X, Vec3::Y, Vec3::new(0.3, -0.2, 0.9)] {
+                assert!((m * v).abs_diff_eq(q * v, 1e-5));
+            }
+        }
+        let q = Quat::from_euler(0.2, 0.0, 0.0);
+        assert_same_rotation(q, Quat::from_axis_angle(Vec3::X, 0.2));
+    }
+
+    #[test]
+    fn slerp() {
+        let a = Quat::from_axis_angle(Vec3::Y, 0.2);
+        let b = Quat::from_axis_angle(Vec3::Y, 1.8);
+        assert_same_rotation(a.slerp(b, 0.0), a);
+        assert_same_rotation(a.slerp(b, 1.0), b);
+        assert_same_rotation(a.slerp(b, 0.25), Quat::from_axis_angle(Vec3::Y, 0.6));
+        assert!((a.slerp(b, 0.5).length() - 1.0).abs() < 1e-5);
+
+        // the negated end is the same rotation, slerp still takes the short way round
+        let c = Quat::from_axis_angle(Vec3::Y, 0.6);
+        let negated = Quat::from_xyzw(-c.x, -c.y, -c.z, -c.w);
+        assert_same_rotation(a.slerp(negated, 0.5), Quat::from_axis_angle(Vec3::Y, 0.4));
+
+        // nearly parallel rotations fall back to nlerp
+        let d = Quat::from_axis_angle(Vec3::Y, 0.2001);
+        assert_same_rotation(a.slerp(d, 0.5), a);
+    }
+}
@@ -0,0 +1,557 @@
+use super::vec2::Vec2;
+use super::vec3::Vec3;
+
+// PCG32 (XSH RR), integer only so the same seed gives the same stream everywhere
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rng {
+    state: u64,
+    increment: u64,
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Rng {
+    const MULTIPLIER: u64 = 6364136223846793005;
+
+    pub fn new(seed: u64) -> Self {
+        Self::with_stream(seed, 0xda3e39cb94b95bdb)
+    }
+
+    // generators with the same seed but different streams are independent
+    pub fn with_stream(seed: u64, stream: u64) -> Self {
+        let mut rng = Self {
+            state: 0,
+            increment: (stream << 1) | 1,
+        };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old
+            .wrapping_mul(Self::MULTIPLIER)
+            .wrapping_add(self.increment);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rotation = (old >> 59) as u32;
+        xorshifted.rotate_right(rotation)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        ((self.next_u32() as u64) << 32) | self.next_u32() as u64
+    }
+
+    // uniform in [0, 1), 24 random bits
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 * (1.0 / (1u32 << 24) as f32)
+    }
+
+    // uniform in [0, bound), without modulo bias
+    pub fn below(&mut self, bound: u32) -> u32 {
+        if bound == 0 {
+            return 0;
+        }
+        let threshold = bound.wrapping_neg() % bound;
+        loop {
+            let r = self.next_u32();
+            if r >= threshold {
+                return r % bound;
+            }
+        }
+    }
+
+    // uniform in [min, max)
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    // uniform in [min, max)
+    pub fn range_i32(&mut self, min: i32, max: i32) -> i32 {
+        if max <= min {
+            return min;
+        }
+        min.wrapping_add(self.below(max.wrapping_sub(min) as u32) as i32)
+    }
+
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            return None;
+        }
+        items.get(self.below(items.len() as u32) as usize)
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i as u32 + 1) as usize);
+        }
+    }
+
+    // Box-Muller, one of the pair is discarded to keep the generator stateless
+    pub fn gaussian(&mut self, mean: f32, std_dev: f32) -> f32 {
+        let u1 = 1.0 - self.next_f32();
+        let u2 = self.next_f32();
+        let r = (-2.0 * u1.ln()).sqrt();
+        mean + std_dev * r * (std::f32::consts::TAU * u2).cos()
+    }
+
+    // rate is the inverse of the mean
+    pub fn exponential(&mut self, rate: f32) -> f32 {
+        -(1.0 - self.next_f32()).ln() / rate
+    }
+
+    pub fn on_circle(&mut self, radius: f32) -> Vec2 {
+        Vec2::from_angle(self.range(0.0, std::f32::consts::TAU)) * radius
+    }
+
+    // uniform over the area, not clustered at the center
+    pub fn in_circle(&mut self, radius: f32) -> Vec2 {
+        let r = radius * self.next_f32().sqrt();
+        self.on_circle(r)
+    }
+
+    pub fn on_sphere(&mut self, radius: f32) -> Vec3 {
+        let z = self.range(-1.0, 1.0);
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        let a = self.range(0.0, std::f32::consts::TAU);
+        Vec3::new(r * a.cos(), r * a.sin(), z) * radius
+    }
+
+    pub fn in_sphere(&mut self, radius: f32) -> Vec3 {
+        let r = radius * self.next_f32().cbrt();
+        self.on_sphere(r)
+    }
+
+    pub fn in_triangle(&mut self, a: Vec3, b: Vec3, c: Vec3) -> Vec3 {
+        let mut u = self.next_f32();
+        let mut v = self.next_f32();
+        // fold the far half of the parallelogram back into the triangle
+        if u + v > 1.0 {
+            u = 1.0 - u;
+            v = 1.0 - v;
+        }
+        a + (b - a) * u + (c - a) * v
+    }
+
+    pub fn in_rect(&mut self, min: Vec2, max: Vec2) -> Vec2 {
+        Vec2::new(self.range(min.x, max.x), self.range(min.y, max.y))
+    }
+
+    pub fn in_box(&mut self, min: Vec3, max: Vec3) -> Vec3 {
+        Vec3::new(
+            self.range(min.x, max.x),
+            self.range(min.y, max.y),
+            self.range(min.z, max.z),
+        )
+    }
+}
+
+// area weighted sampling over the surface of an indexed triangle mesh
+#[derive(Clone, Debug)]
+pub struct MeshSampler {
+    triangles: Vec<[Vec3; 3]>,
+    cumulative_areas: Vec<f32>,
+}
+
+impl MeshSampler {
+    pub fn new(positions: &[Vec3], indices: &[u32]) -> Result<Self, String> {
+        let mut triangles = Vec::with_capacity(indices.len() / 3);
+        let mut cumulative_areas = Vec::with_capacity(indices.len() / 3);
+        let mut total = 0.0;
+        for face in indices.chunks_exact(3) {
+            let mut corners = [Vec3::ZERO; 3];
+            for (corner, index) in corners.iter_mut().zip(face) {
+                *corner = *positions
+                    .get(*index as usize)
+                    .ok_or_else(|| format!("Index {} is out of range", index))?;
+            }
+            total += (corners[1] - corners[0])
+                .cross(corners[2] - corners[0])
+                .length()
+                * 0.5;
+            triangles.push(corners);
+            cumulative_areas.push(total);
+        }
+        if total <= 0.0 {
+            return Err("Mesh has no surface area".to_string());
+        }
+        Ok(Self {
+            triangles,
+            cumulative_areas,
+        })
+    }
+
+    pub fn get_area(&self) -> f32 {
+        self.cumulative_areas[self.cumulative_areas.len() - 1]
+    }
+
+    // a point on the surface and the index of the triangle it lies on
+    pub fn sample(&self, rng: &mut Rng) -> (Vec3, usize) {
+        let target = rng.next_f32() * self.get_area();
+        let index = self
+            .cumulative_areas
+            .partition_point(|a| *a <= target)
+            .min(self.triangles.len() - 1);
+        let [a, b, c] = self.triangles[index];
+        (rng.in_triangle(a, b, c), index)
+    }
+}
+
+// Bridson's algorithm, points are at least radius apart inside [0, size)
+pub fn poisson_disk_2d(rng: &mut Rng, size: Vec2, radius: f32, attempts: u32) -> Vec<Vec2> {
+    if radius <= 0.0 || radius.is_nan() {
+        return Vec::new();
+    }
+    let cell = radius / std::f32::consts::SQRT_2;
+    let columns = (size.x / cell).ceil().max(1.0) as usize;
+    let rows = (size.y / cell).ceil().max(1.0) as usize;
+    let mut grid: Vec<Option<usize>> = vec![None; columns * rows];
+    let cell_of = |p: Vec2| {
+        (
+            ((p.x / cell) as usize).min(columns - 1),
+            ((p.y / cell) as usize).min(rows - 1),
+        )
+    };
+
+    let mut points = Vec::new();
+    let mut active = Vec::new();
+    let first = rng.in_rect(Vec2::ZERO, size);
+    let (cx, cy) = cell_of(first);
+    grid[cy * columns + cx] = Some(0);
+    points.push(first);
+    active.push(0);
+
+    while !active.is_empty() {
+        let slot = rng.below(active.len() as u32) as usize;
+        let center = points[active[slot]];
+        let mut found = false;
+        for _ in 0..attempts {
+            let distance = radius * (1.0 + rng.next_f32());
+            let offset = rng.on_circle(distance);
+            let candidate = center + offset;
+            if candidate.x < 0.0
+                || candidate.y < 0.0
+                || candidate.x >= size.x
+                || candidate.y >= size.y
+            {
+                continue;
+            }
+            let (cx, cy) = cell_of(candidate);
+            let far_enough = (cy.saturating_sub(2)..(cy + 3).min(rows)).all(|y| {
+                (cx.saturating_sub(2)..(cx + 3).min(columns)).all(|x| match grid[y * columns + x] {
+                    Some(i) => points[i].distance_squared(candidate) >= radius * radius,
+                    None => true,
+                })
+            });
+            if far_enough {
+                grid[cy * columns + cx] = Some(points.len());
+                active.push(points.len());
+                points.push(candidate);
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            active.swap_remove(slot);
+        }
+    }
+    points
+}
+
+pub fn poisson_disk_3d(rng: &mut Rng, size: Vec3, radius: f32, attempts: u32) -> Vec<Vec3> {
+    if radius <= 0.0 || radius.is_nan() {
+        return Vec::new();
+    }
+    let cell = radius / 3f32.sqrt();
+    let dims = [
+        (size.x / cell).ceil().max(1.0) as usize,
+        (size.y / cell).ceil().max(1.0) as usize,
+        (size.z / cell).ceil().max(1.0) as usize,
+    ];
+    let mut grid: Vec<Option<usize>> = vec![None; dims[0] * dims[1] * dims[2]];
+    let cell_of = |p: Vec3| {
+        [
+            ((p.x / cell) as usize).min(dims[0] - 1),
+            ((p.y / cell) as usize).min(dims[1] - 1),
+            ((p.z / cell) as usize).min(dims[2] - 1),
+        ]
+    };
+    let index_of = |c: [usize; 3]| (c[2] * dims[1] + c[1]) * dims[0] + c[0];
+
+    let mut points = Vec::new();
+    let mut active = Vec::new();
+    let first = rng.in_box(Vec3::ZERO, size);
+    grid[index_of(cell_of(first))] = Some(0);
+    points.push(first);
+    active.push(0);
+
+    while !active.is_empty() {
+        let slot = rng.below(active.len() as u32) as usize;
+        let center = points[active[slot]];
+        let mut found = false;
+        for _ in 0..attempts {
+            let distance = radius * (1.0 + rng.next_f32());
+            let offset = rng.on_sphere(distance);
+            let candidate = center + offset;
+            if candidate.x < 0.0
+                || candidate.y < 0.0
+                || candidate.z < 0.0
+                || candidate.x >= size.x
+                || candidate.y >= size.y
+                || candidate.z >= size.z
+            {
+                continue;
+            }
+            let c = cell_of(candidate);
+            let range = |i: usize| c[i].saturating_sub(2)..(c[i] + 3).min(dims[i]);
+            let far_enough = range(2).all(|z| {
+                range(1).all(|y| {
+                    range(0).all(|x| match grid[index_of([x, y, z])] {
+                        Some(i) => points[i].distance_squared(candidate) >= radius * radius,
+                        None => true,
+                    })
+                })
+            });
+            if far_enough {
+                grid[index_of(c)] = Some(points.len());
+                active.push(points.len());
+                points.push(candidate);
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            active.swap_remove(slot);
+        }
+    }
+    points
+}
+
+// radical inverse of index in the given base, in [0, 1), base must be at least 2
+pub fn halton(index: u32, base: u32) -> f32 {
+    assert!(base >= 2, "Halton base must be at least 2, got {}", base);
+    let mut result = 0.0f64;
+    let mut fraction = 1.0f64;
+    let mut i = index;
+    while i > 0 {
+        fraction /= base as f64;
+        result += fraction * (i % base) as f64;
+        i /= base;
+    }
+    result as f32
+}
+
+pub fn halton_2d(index: u32) -> Vec2 {
+    Vec2::new(halton(index, 2), halton(index, 3))
+}
+
+pub fn halton_3d(index: u32) -> Vec3 {
+    Vec3::new(halton(index, 2), halton(index, 3), halton(index, 5))
+}
+
+// Roberts' additive recurrence from the plastic number
+pub fn r2(index: u32) -> Vec2 {
+    const G: f64 = 1.324_717_957_244_746;
+    let a1 = 1.0 / G;
+    let a2 = 1.0 / (G * G);
+    let n = index as f64;
+    Vec2::new((0.5 + a1 * n).fract() as f32, (0.5 + a2 * n).fract() as f32)
+}
+
+// direction numbers for the first three Sobol dimensions (Joe and Kuo)
+const SOBOL_DIRECTIONS: [[u32; 32]; 3] = sobol_directions();
+
+const fn sobol_directions() -> [[u32; 32]; 3] {
+    let mut v = [[0u32; 32]; 3];
+    let mut k = 0;
+    while k < 32 {
+        // van der Corput
+        v[0][k] = 1 << (31 - k);
+        k += 1;
+    }
+    // x + 1, m = 1
+    let mut m = [0u32; 32];
+    m[0] = 1;
+    k = 1;
+    while k < 32 {
+        m[k] = (m[k - 1] << 1) ^ m[k - 1];
+        k += 1;
+    }
+    k = 0;
+    while k < 32 {
+        v[1][k] = m[k] << (31 - k);
+        k += 1;
+    }
+    // x^2 + x + 1, m = 1, 3
+    let mut m = [0u32; 32];
+    m[0] = 1;
+    m[1] = 3;
+    k = 2;
+    while k < 32 {
+        m[k] = (m[k - 1] << 1) ^ (m[k - 2] << 2) ^ m[k - 2];
+        k += 1;
+    }
+    k = 0;
+    while k < 32 {
+        v[2][k] = m[k] << (31 - k);
+        k += 1;
+    }
+    v
+}
+
+// dimension in 0..3, only three dimensions have direction numbers so higher ones panic
+// points are taken in Gray code order
+pub fn sobol(index: u32, dimension: usize) -> f32 {
+    assert!(
+        dimension < SOBOL_DIRECTIONS.len(),
+        "Sobol dimension must be below {}, got {}",
+        SOBOL_DIRECTIONS.len(),
+        dimension
+    );
+    let directions = &SOBOL_DIRECTIONS[dimension];
+    let gray = index ^ (index >> 1);
+    let mut x = 0u32;
+    for (bit, direction) in directions.iter().enumerate() {
+        if gray & (1 << bit) != 0 {
+            x ^= direction;
+        }
+    }
+    (x as f64 / 4294967296.0) as f32
+}
+
+pub fn sobol_2d(index: u32) -> Vec2 {
+    Vec2::new(sobol(index, 0), sobol(index, 1))
+}
+
+pub fn sobol_3d(index: u32) -> Vec3 {
+    Vec3::new(sobol(index, 0), sobol(index, 1), sobol(index, 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_streams_are_reproducible() {
+        let mut a = Rng::new(1234);
+        let mut b = Rng::new(1234);
+        let mut c = Rng::new(1235);
+        let xs: Vec<u32> = (0..8).map(|_| a.next_u32()).collect();
+        let ys: Vec<u32> = (0..8).map(|_| b.next_u32()).collect();
+        let zs: Vec<u32> = (0..8).map(|_| c.next_u32()).collect();
+        assert_eq!(xs, ys);
+        assert_ne!(xs, zs);
+    }
+
+    #[test]
+    fn distributions_have_expected_moments() {
+        let mut rng = Rng::new(7);
+        let n = 20000;
+        let uniform: f32 = (0..n).map(|_| rng.next_f32()).sum::<f32>() / n as f32;
+        assert!((uniform - 0.5).abs() < 0.02);
+        let samples: Vec<f32> = (0..n).map(|_| rng.gaussian(2.0, 3.0)).collect();
+        let mean = samples.iter().sum::<f32>() / n as f32;
+        let variance = samples.iter().map(|s| (s - mean) * (s - mean)).sum::<f32>() / n as f32;
+        assert!((mean - 2.0).abs() < 0.1);
+        assert!((variance.sqrt() - 3.0).abs() < 0.1);
+        let exponential: f32 = (0..n).map(|_| rng.exponential(4.0)).sum::<f32>() / n as f32;
+        assert!((exponential - 0.25).abs() < 0.02);
+        for _ in 0..1000 {
+            let i = rng.range_i32(-3, 3);
+            assert!((-3..3).contains(&i));
+        }
+    }
+
+    #[test]
+    fn geometric_samples_stay_in_shape() {
+        let mut rng = Rng::new(99);
+        for _ in 0..1000 {
+            assert!((rng.on_sphere(2.0).length() - 2.0).abs() < 1e-4);
+            assert!(rng.in_sphere(2.0).length() <= 2.0);
+            assert!((rng.on_circle(1.5).length() - 1.5).abs() < 1e-4);
+            assert!(rng.in_circle(1.5).length() <= 1.5);
+            let p = rng.in_triangle(Vec3::ZERO, Vec3::X, Vec3::Y);
+            assert!(p.x >= 0.0 && p.y >= 0.0 && p.x + p.y <= 1.0 + 1e-6);
+        }
+
+        // the big triangle holds 3/4 of the area
+        let positions = [
+            Vec3::ZERO,
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(10.0, 0.0, 0.0),
+            Vec3::new(10.0, 3.0, 0.0),
+        ];
+        let sampler = MeshSampler::new(&positions, &[0, 1, 2, 1, 3, 4]).unwrap();
+        assert_eq!(sampler.get_area(), 0.5 + 13.5);
+        let big = (0..4000)
+            .filter(|_| sampler.sample(&mut rng).1 == 1)
+            .count();
+        assert!((big as f32 / 4000.0 - 13.5 / 14.0).abs() < 0.02);
+        assert!(MeshSampler::new(&positions, &[0, 1, 9]).is_err());
+    }
+
+    #[test]
+    fn poisson_disk_respects_radius() {
+        let mut rng = Rng::new(5);
+        let points = poisson_disk_2d(&mut rng, Vec2::new(10.0, 10.0), 0.5, 30);
+        assert!(points.len() > 200);
+        for (i, a) in points.iter().enumerate() {
+            for b in points.iter().skip(i + 1) {
+                assert!(a.distance(*b) >= 0.5 - 1e-5);
+            }
+        }
+        let points = poisson_disk_3d(&mut rng, Vec3::splat(3.0), 0.5, 30);
+        assert!(points.len() > 50);
+        for (i, a) in points.iter().enumerate() {
+            for b in points.iter().skip(i + 1) {
+                assert!(a.distance(*b) >= 0.5 - 1e-5);
+            }
+        }
+        assert!(poisson_disk_2d(&mut rng, Vec2::ONE, 0.0, 30).is_empty());
+        assert!(poisson_disk_3d(&mut rng, Vec3::ONE, -1.0, 30).is_empty());
+    }
+
+    #[test]
+    fn low_discrepancy_sequences() {
+        assert_eq!(halton(1, 2), 0.5);
+        assert_eq!(halton(2, 2), 0.25);
+        assert_eq!(halton(3, 2), 0.75);
+        assert!((halton(1, 3) - 1.0 / 3.0).abs() < 1e-7);
+        assert_eq!(sobol_2d(0), Vec2::ZERO);
+        assert_eq!(sobol_2d(1), Vec2::new(0.5, 0.5));
+        assert_eq!(sobol_2d(2), Vec2::new(0.75, 0.25));
+        assert_eq!(sobol_2d(3), Vec2::new(0.25, 0.75));
+
+        // every 4x4 stratum of the first 16 Sobol points holds exactly one point
+        let mut cells = [0; 16];
+        for i in 0..16 {
+            let p = sobol_2d(i);
+            cells[(p.y * 4.0) as usize * 4 + (p.x * 4.0) as usize] += 1;
+        }
+        assert!(cells.iter().all(|c| *c == 1));
+        let p = r2(10);
+        assert!((0.0..1.0).contains(&p.x) && (0.0..1.0).contains(&p.y));
+    }
+
+    #[test]
+    #[should_panic]
+    fn halton_rejects_base_one() {
+        halton(3, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn sobol_rejects_higher_dimensions() {
+        sobol(3, 3);
+    }
+}
@@ -0,0 +1,206 @@
+use super::aabb::Aabb;
+use super::mat4::Mat4;
+use super::obb::Obb;
+use super::plane::Plane;
+use super::sphere::Sphere;
+use super::triangle::Triangle;
+use super::vec2::Vec2;
+use super::vec3::Vec3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    // kept normalized so hit distances are in world units
+    pub direction: Vec3,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TriangleHit {
+    pub distance: f32,
+    // barycentric weights of b and c, the weight of a is 1 - u - v
+    pub uv: Vec2,
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    pub fn from_points(from: Vec3, to: Vec3) -> Self {
+        Self::new(from, to - from)
+    }
+
+    pub fn at(&self, t: f32) -> Vec3 {
+        self.origin + self.direction * t
+    }
+
+    pub fn transform(&self, m: &Mat4) -> Self {
+        Self::new(
+            m.transform_point3(self.origin),
+            m.transform_vector3(self.direction),
+        )
+    }
+
+    // closest point on the ray, never behind the origin
+    pub fn closest_point(&self, p: Vec3) -> Vec3 {
+        self.at((p - self.origin).dot(self.direction).max(0.0))
+    }
+
+    pub fn distance_to_point(&self, p: Vec3) -> f32 {
+        self.closest_point(p).distance(p)
+    }
+
+    pub fn intersect_plane(&self, plane: &Plane) -> Option<f32> {
+        let denom = plane.normal.dot(self.direction);
+        if denom.abs() < f32::EPSILON {
+            return None;
+        }
+        let t = -plane.signed_distance(self.origin) / denom;
+        (t >= 0.0).then_some(t)
+    }
+
+    // slab test, returns the entry and exit distances clamped to the ray start
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<(f32, f32)> {
+        let mut t_min = 0.0f32;
+        let mut t_max = f32::INFINITY;
+        for i in 0..3 {
+            let inv = 1.0 / self.direction[i];
+            let mut t0 = (aabb.min[i] - self.origin[i]) * inv;
+            let mut t1 = (aabb.max[i] - self.origin[i]) * inv;
+            if inv < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            // NaN from 0 * inf compares false, which keeps the previous bounds
+            if t0 > t_min {
+                t_min = t0;
+            }
+            if t1 < t_max {
+                t_max = t1;
+            }
+            if t_max < t_min {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+
+    pub fn intersect_obb(&self, obb: &Obb) -> Option<(f32, f32)> {
+        let inverse = obb.rotation.inverse();
+        let local = Ray {
+            origin: inverse * (self.origin - obb.center),
+            direction: inverse * self.direction,
+        };
+        local.intersect_aabb(&Aabb::new(-obb.half_extents, obb.half_extents))
+    }
+
+    pub fn intersect_sphere(&self, sphere: &Sphere) -> Option<f32> {
+        let oc = self.origin - sphere.center;
+        let b = oc.dot(self.direction);
+        let c = oc.length_squared() - sphere.radius * sphere.radius;
+        let discriminant = b * b - c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let sqrt_d = discriminant.sqrt();
+        let t0 = -b - sqrt_d;
+        let t1 = -b + sqrt_d;
+        if t0 >= 0.0 {
+            Some(t0)
+        } else if t1 >= 0.0 {
+            // the origin is inside the sphere
+            Some(t1)
+        } else {
+            None
+        }
+    }
+
+    // Möller–Trumbore, hits both faces
+    pub fn intersect_triangle(&self, triangle: &Triangle) -> Option<TriangleHit> {
+        const EPSILON: f32 = 1e-7;
+        let edge1 = triangle.b - triangle.a;
+        let edge2 = triangle.c - triangle.a;
+        let p = self.direction.cross(edge2);
+        let det = edge1.dot(p);
+        if det.abs() < EPSILON {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+        let s = self.origin - triangle.a;
+        let u = s.dot(p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = s.cross(edge1);
+        let v = self.direction.dot(q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = edge2.dot(q) * inv_det;
+        (t >= 0.0).then_some(TriangleHit {
+            distance: t,
+            uv: Vec2::new(u, v),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ray_hits_triangle() {
+        let triangle = Triangle::new(
+            Vec3::new(-1.0, -1.0, 0.0),
+            Vec3::new(1.0, -1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 5.0), -Vec3::Z);
+        let hit = ray.intersect_triangle(&triangle).unwrap();
+        assert!((hit.distance - 5.0).abs() < 1e-5);
+        assert!(ray.at(hit.distance).abs_diff_eq(Vec3::ZERO, 1e-5));
+
+        let miss = Ray::new(Vec3::new(2.0, 0.0, 5.0), -Vec3::Z);
+        assert!(miss.intersect_triangle(&triangle).is_none());
+        let behind = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::Z);
+        assert!(behind.intersect_triangle(&triangle).is_none());
+    }
+
+    #[test]
+    fn ray_hits_aabb() {
+        let aabb = Aabb::new(-Vec3::ONE, Vec3::ONE);
+        let ray = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::X);
+        assert_eq!(ray.intersect_aabb(&aabb), Some((4.0, 6.0)));
+
+        // axis aligned direction with zero components
+        let inside = Ray::new(Vec3::ZERO, Vec3::Y);
+        assert_eq!(inside.intersect_aabb(&aabb), Some((0.0, 1.0)));
+
+        let miss = Ray::new(Vec3::new(-5.0, 2.0, 0.0), Vec3::X);
+        assert!(miss.intersect_aabb(&aabb).is_none());
+    }
+
+    #[test]
+    fn ray_hits_sphere_and_plane() {
+        let sphere = Sphere::new(Vec3::new(0.0, 0.0, -10.0), 2.0);
+        let ray = Ray::new(Vec3::ZERO, -Vec3::Z);
+        assert!((ray.intersect_sphere(&sphere).unwrap() - 8.0).abs() < 1e-5);
+
+        let plane = Plane::from_point_normal(Vec3::new(0.0, -1.0, 0.0), Vec3::Y);
+        let down = Ray::new(Vec3::ZERO, Vec3::new(0.0, -1.0, 1.0));
+        let t = down.intersect_plane(&plane).unwrap();
+        assert!(down.at(t).abs_diff_eq(Vec3::new(0.0, -1.0, 1.0), 1e-5));
+        assert!(ray.intersect_plane(&plane).is_none());
+    }
+
+    #[test]
+    fn ray_hits_rotated_obb() {
+        let rotation = crate::Quat::from_axis_angle(Vec3::Z, std::f32::consts::FRAC_PI_4);
+        let obb = Obb::new(Vec3::ZERO, Vec3::new(1.0, 1.0, 1.0), rotation);
+        let ray = Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::X);
+        let (t0, _) = ray.intersect_obb(&obb).unwrap();
+        assert!((t0 - (5.0 - std::f32::consts::SQRT_2)).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,50 @@
+use super::aabb::Aabb;
+use super::vec3::Vec3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl Sphere {
+    pub fn new(center: Vec3, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    // centered on the bounding box, not the minimal sphere
+    pub fn from_points(points: &[Vec3]) -> Self {
+        let center = Aabb::from_points(points).get_center();
+        let radius = points
+            .iter()
+            .map(|p| p.distance(center))
+            .fold(0.0, f32::max);
+        Self::new(center, radius)
+    }
+
+    pub fn contains_point(&self, p: Vec3) -> bool {
+        p.distance_squared(self.center) <= self.radius * self.radius
+    }
+
+    pub fn intersects_sphere(&self, other: &Sphere) -> bool {
+        let r = self.radius + other.radius;
+        self.center.distance_squared(other.center) <= r * r
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        aabb.intersects_sphere(self)
+    }
+
+    pub fn closest_point(&self, p: Vec3) -> Vec3 {
+        let offset = p - self.center;
+        if offset.length_squared() <= self.radius * self.radius {
+            p
+        } else {
+            self.center + offset.normalize() * self.radius
+        }
+    }
+
+    pub fn get_aabb(&self) -> Aabb {
+        Aabb::from_center_half_extents(self.center, Vec3::splat(self.radius))
+    }
+}
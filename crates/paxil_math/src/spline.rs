@@ -0,0 +1,786 @@
+use std::ops::{Add, Mul, Sub};
+
+use super::mat4::Mat4;
+use super::quat::Quat;
+use super::vec3::Vec3;
+use super::vec4::Vec4;
+
+pub trait Curve {
+    // parameter range the curve is defined on
+    fn get_domain(&self) -> (f32, f32);
+
+    fn evaluate(&self, t: f32) -> Vec3;
+
+    // central differences unless the curve knows its analytic derivative
+    fn derivative(&self, t: f32) -> Vec3 {
+        let (start, end) = self.get_domain();
+        let h = (end - start) * 1e-3;
+        let t0 = (t - h).max(start);
+        let t1 = (t + h).min(end);
+        (self.evaluate(t1) - self.evaluate(t0)) / (t1 - t0)
+    }
+
+    fn second_derivative(&self, t: f32) -> Vec3 {
+        let (start, end) = self.get_domain();
+        let h = (end - start) * 1e-3;
+        let t0 = (t - h).max(start);
+        let t1 = (t + h).min(end);
+        (self.derivative(t1) - self.derivative(t0)) / (t1 - t0)
+    }
+
+    fn length(&self) -> f32 {
+        ArcLengthTable::new(self, 64).get_length()
+    }
+
+    // parameters of a polyline that stays within tolerance of the curve
+    fn flatten_params(&self, tolerance: f32) -> Vec<f32> {
+        let (start, end) = self.get_domain();
+        let mut params = vec![start];
+        // a fixed first split keeps closed or symmetric curves from looking flat
+        let segments = 8;
+        for i in 0..segments {
+            let t0 = start + (end - start) * i as f32 / segments as f32;
+            let t1 = start + (end - start) * (i + 1) as f32 / segments as f32;
+            flatten_segment(self, t0, t1, tolerance.max(1e-6), 16, &mut params);
+        }
+        params
+    }
+
+    fn flatten(&self, tolerance: f32) -> Vec<Vec3> {
+        self.flatten_params(tolerance)
+            .into_iter()
+            .map(|t| self.evaluate(t))
+            .collect()
+    }
+
+    // n evenly spaced parameters, n >= 2
+    fn sample_params(&self, n: usize) -> Vec<f32> {
+        let (start, end) = self.get_domain();
+        let last = n.saturating_sub(1).max(1) as f32;
+        (0..n)
+            .map(|i| start + (end - start) * i as f32 / last)
+            .collect()
+    }
+}
+
+fn flatten_segment<C: Curve + ?Sized>(
+    curve: &C,
+    t0: f32,
+    t1: f32,
+    tolerance: f32,
+    depth: u32,
+    params: &mut Vec<f32>,
+) {
+    let a = curve.evaluate(t0);
+    let b = curve.evaluate(t1);
+    let flat = depth == 0
+        || [0.25, 0.5, 0.75].iter().all(|f| {
+            let p = curve.evaluate(t0 + (t1 - t0) * f);
+            distance_to_segment(p, a, b) <= tolerance
+        });
+    if flat {
+        params.push(t1);
+    } else {
+        let tm = (t0 + t1) * 0.5;
+        flatten_segment(curve, t0, tm, tolerance, depth - 1, params);
+        flatten_segment(curve, tm, t1, tolerance, depth - 1, params);
+    }
+}
+
+fn distance_to_segment(p: Vec3, a: Vec3, b: Vec3) -> f32 {
+    let ab = b - a;
+    let length_squared = ab.length_squared();
+    let t = if length_squared > 0.0 {
+        ((p - a).dot(ab) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    p.distance(a + ab * t)
+}
+
+// cumulative arc length at evenly spaced parameters, for constant speed motion
+#[derive(Clone, Debug)]
+pub struct ArcLengthTable {
+    params: Vec<f32>,
+    lengths: Vec<f32>,
+}
+
+impl ArcLengthTable {
+    pub fn new<C: Curve + ?Sized>(curve: &C, segments: usize) -> Self {
+        // 5 point Gauss-Legendre quadrature of |C'(t)| per segment
+        const NODES: [(f32, f32); 5] = [
+            (0.0, 0.568_888_9),
+            (-0.538_469_3, 0.478_628_67),
+            (0.538_469_3, 0.478_628_67),
+            (-0.906_179_85, 0.236_926_88),
+            (0.906_179_85, 0.236_926_88),
+        ];
+        let params = curve.sample_params(segments.max(1) + 1);
+        let mut lengths = vec![0.0];
+        for pair in params.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            let half = (b - a) * 0.5;
+            let mid = (a + b) * 0.5;
+            let length: f32 = NODES
+                .iter()
+                .map(|(x, w)| w * curve.derivative(mid + half * x).length())
+                .sum();
+            lengths.push(lengths[lengths.len() - 1] + length * half);
+        }
+        Self { params, lengths }
+    }
+
+    pub fn get_length(&self) -> f32 {
+        self.lengths[self.lengths.len() - 1]
+    }
+
+    // inverse of the table, linear between samples
+    pub fn get_param(&self, length: f32) -> f32 {
+        let length = length.clamp(0.0, self.get_length());
+        let index = self.lengths.partition_point(|l| *l < length).max(1);
+        if index >= self.lengths.len() {
+            return self.params[self.params.len() - 1];
+        }
+        let (l0, l1) = (self.lengths[index - 1], self.lengths[index]);
+        let (t0, t1) = (self.params[index - 1], self.params[index]);
+        if l1 > l0 {
+            t0 + (t1 - t0) * (length - l0) / (l1 - l0)
+        } else {
+            t0
+        }
+    }
+
+    // u in [0, 1] as a fraction of the total length
+    pub fn get_param_normalized(&self, u: f32) -> f32 {
+        self.get_param(u * self.get_length())
+    }
+}
+
+// Bezier curve of any degree, evaluated with de Casteljau
+#[derive(Clone, Debug, PartialEq)]
+pub struct BezierCurve {
+    pub points: Vec<Vec3>,
+}
+
+impl BezierCurve {
+    pub fn new(points: Vec<Vec3>) -> Self {
+        Self { points }
+    }
+
+    pub fn quadratic(p0: Vec3, p1: Vec3, p2: Vec3) -> Self {
+        Self::new(vec![p0, p1, p2])
+    }
+
+    pub fn cubic(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3) -> Self {
+        Self::new(vec![p0, p1, p2, p3])
+    }
+
+    pub fn get_degree(&self) -> usize {
+        self.points.len().saturating_sub(1)
+    }
+
+    // the hodograph, a Bezier of one degree less
+    pub fn derivative_curve(&self) -> Self {
+        let n = self.get_degree() as f32;
+        Self::new(self.points.windows(2).map(|w| (w[1] - w[0]) * n).collect())
+    }
+
+    pub fn split(&self, t: f32) -> (Self, Self) {
+        let mut left = Vec::with_capacity(self.points.len());
+        let mut right = Vec::with_capacity(self.points.len());
+        let mut points = self.points.clone();
+        while !points.is_empty() {
+            left.push(points[0]);
+            right.push(points[points.len() - 1]);
+            points = points.windows(2).map(|w| w[0].lerp(w[1], t)).collect();
+        }
+        right.reverse();
+        (Self::new(left), Self::new(right))
+    }
+}
+
+impl Curve for BezierCurve {
+    fn get_domain(&self) -> (f32, f32) {
+        (0.0, 1.0)
+    }
+
+    fn evaluate(&self, t: f32) -> Vec3 {
+        let mut points = self.points.clone();
+        if points.is_empty() {
+            return Vec3::ZERO;
+        }
+        for k in (1..points.len()).rev() {
+            for i in 0..k {
+                points[i] = points[i].lerp(points[i + 1], t);
+            }
+        }
+        points[0]
+    }
+
+    fn derivative(&self, t: f32) -> Vec3 {
+        if self.points.len() < 2 {
+            return Vec3::ZERO;
+        }
+        self.derivative_curve().evaluate(t)
+    }
+
+    fn second_derivative(&self, t: f32) -> Vec3 {
+        if self.points.len() < 3 {
+            return Vec3::ZERO;
+        }
+        self.derivative_curve().derivative(t)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CatmullRomType {
+    Uniform,
+    #[default]
+    Centripetal,
+    Chordal,
+}
+
+impl CatmullRomType {
+    fn get_alpha(&self) -> f32 {
+        match self {
+            CatmullRomType::Uniform => 0.0,
+            CatmullRomType::Centripetal => 0.5,
+            CatmullRomType::Chordal => 1.0,
+        }
+    }
+}
+
+// passes through every point, the parameter runs from 0 to the number of segments
+#[derive(Clone, Debug, PartialEq)]
+pub struct CatmullRomSpline {
+    pub points: Vec<Vec3>,
+    pub kind: CatmullRomType,
+    pub closed: bool,
+}
+
+impl CatmullRomSpline {
+    pub fn new(points: Vec<Vec3>, kind: CatmullRomType) -> Self {
+        Self {
+            points,
+            kind,
+            closed: false,
+        }
+    }
+
+    pub fn new_closed(points: Vec<Vec3>, kind: CatmullRomType) -> Self {
+        Self {
+            points,
+            kind,
+            closed: true,
+        }
+    }
+
+    pub fn get_segment_count(&self) -> usize {
+        match (self.points.len(), self.closed) {
+            (0 | 1, _) => 0,
+            (n, true) => n,
+            (n, false) => n - 1,
+        }
+    }
+
+    // open ends are extended by mirroring the neighbouring point
+    fn get_point(&self, i: isize) -> Vec3 {
+        let n = self.points.len() as isize;
+        if self.closed {
+            return self.points[i.rem_euclid(n) as usize];
+        }
+        if i < 0 {
+            self.points[0] * 2.0 - self.points[1]
+        } else if i >= n {
+            self.points[(n - 1) as usize] * 2.0 - self.points[(n - 2) as usize]
+        } else {
+            self.points[i as usize]
+        }
+    }
+}
+
+impl Curve for CatmullRomSpline {
+    fn get_domain(&self) -> (f32, f32) {
+        (0.0, self.get_segment_count() as f32)
+    }
+
+    // Barry and Goldman's pyramidal formulation
+    fn evaluate(&self, t: f32) -> Vec3 {
+        match self.points.len() {
+            0 => return Vec3::ZERO,
+            1 => return self.points[0],
+            _ => (),
+        }
+        let segments = self.get_segment_count();
+        let t = t.clamp(0.0, segments as f32);
+        let segment = (t.floor() as usize).min(segments - 1);
+        let local = t - segment as f32;
+        let i = segment as isize;
+        let (p0, p1, p2, p3) = (
+            self.get_point(i - 1),
+            self.get_point(i),
+            self.get_point(i + 1),
+            self.get_point(i + 2),
+        );
+
+        let alpha = self.kind.get_alpha();
+        let knot = |a: Vec3, b: Vec3| a.distance(b).powf(alpha).max(1e-4);
+        let t0 = 0.0;
+        let t1 = t0 + knot(p0, p1);
+        let t2 = t1 + knot(p1, p2);
+        let t3 = t2 + knot(p2, p3);
+        let u = t1 + (t2 - t1) * local;
+
+        let blend = |a: Vec3, b: Vec3, ta: f32, tb: f32| a.lerp(b, (u - ta) / (tb - ta));
+        let a1 = blend(p0, p1, t0, t1);
+        let a2 = blend(p1, p2, t1, t2);
+        let a3 = blend(p2, p3, t2, t3);
+        let b1 = blend(a1, a2, t0, t2);
+        let b2 = blend(a2, a3, t1, t3);
+        blend(b1, b2, t1, t2)
+    }
+}
+
+// de Boor for anything that can be blended linearly, shared with the NURBS homogeneous points
+fn de_boor<T>(points: &[T], knots: &[f32], degree: usize, t: f32) -> T
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>,
+{
+    let n = points.len();
+    let t = t.clamp(knots[degree], knots[n]);
+    // the span k with knots[k] <= t < knots[k + 1], the last span includes the end
+    let mut k = degree;
+    while k + 1 < n && knots[k + 1] <= t {
+        k += 1;
+    }
+    let mut d: Vec<T> = (0..=degree).map(|j| points[j + k - degree]).collect();
+    for r in 1..=degree {
+        for j in (r..=degree).rev() {
+            let i = j + k - degree;
+            let denom = knots[i + degree + 1 - r] - knots[i];
+            let alpha = if denom > 0.0 {
+                (t - knots[i]) / denom
+            } else {
+                0.0
+            };
+            d[j] = d[j - 1] + (d[j] - d[j - 1]) * alpha;
+        }
+    }
+    d[degree]
+}
+
+fn derivative_points<T>(points: &[T], knots: &[f32], degree: usize) -> Vec<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>,
+{
+    (0..points.len() - 1)
+        .map(|i| {
+            let denom = knots[i + degree + 1] - knots[i + 1];
+            let scale = if denom > 0.0 {
+                degree as f32 / denom
+            } else {
+                0.0
+            };
+            (points[i + 1] - points[i]) * scale
+        })
+        .collect()
+}
+
+// knots that make the curve start and end on its first and last control points
+pub fn clamped_uniform_knots(point_count: usize, degree: usize) -> Vec<f32> {
+    let spans = point_count.saturating_sub(degree).max(1);
+    let mut knots = vec![0.0; degree + 1];
+    for i in 1..spans {
+        knots.push(i as f32 / spans as f32);
+    }
+    knots.extend(std::iter::repeat_n(1.0, degree + 1));
+    knots
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BSpline {
+    pub points: Vec<Vec3>,
+    pub degree: usize,
+    // points.len() + degree + 1 non-decreasing values
+    pub knots: Vec<f32>,
+}
+
+impl BSpline {
+    // clamped uniform knots over [0, 1]
+    pub fn new(points: Vec<Vec3>, degree: usize) -> Result<Self, String> {
+        let knots = clamped_uniform_knots(points.len(), degree);
+        Self::with_knots(points, degree, knots)
+    }
+
+    pub fn with_knots(points: Vec<Vec3>, degree: usize, knots: Vec<f32>) -> Result<Self, String> {
+        validate_knots(points.len(), degree, &knots)?;
+        Ok(Self {
+            points,
+            degree,
+            knots,
+        })
+    }
+
+    // a B-spline of one degree less, None for degree 0
+    pub fn derivative_curve(&self) -> Option<Self> {
+        if self.degree == 0 {
+            return None;
+        }
+        Some(Self {
+            points: derivative_points(&self.points, &self.knots, self.degree),
+            degree: self.degree - 1,
+            knots: self.knots[1..self.knots.len() - 1].to_vec(),
+        })
+    }
+}
+
+fn validate_knots(point_count: usize, degree: usize, knots: &[f32]) -> Result<(), String> {
+    if point_count <= degree {
+        return Err(format!(
+            "A degree {} spline needs at least {} control points, got {}",
+            degree,
+            degree + 1,
+            point_count
+        ));
+    }
+    if knots.len() != point_count + degree + 1 {
+        return Err(format!(
+            "Expected {} knots, got {}",
+            point_count + degree + 1,
+            knots.len()
+        ));
+    }
+    if knots.windows(2).any(|w| w[1] < w[0]) {
+        return Err("Knots must be non-decreasing".to_string());
+    }
+    Ok(())
+}
+
+impl Curve for BSpline {
+    fn get_domain(&self) -> (f32, f32) {
+        (self.knots[self.degree], self.knots[self.points.len()])
+    }
+
+    fn evaluate(&self, t: f32) -> Vec3 {
+        de_boor(&self.points, &self.knots, self.degree, t)
+    }
+
+    fn derivative(&self, t: f32) -> Vec3 {
+        match self.derivative_curve() {
+            Some(d) => d.evaluate(t),
+            None => Vec3::ZERO,
+        }
+    }
+
+    fn second_derivative(&self, t: f32) -> Vec3 {
+        match self.derivative_curve() {
+            Some(d) => d.derivative(t),
+            None => Vec3::ZERO,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NurbsCurve {
+    pub points: Vec<Vec3>,
+    pub weights: Vec<f32>,
+    pub degree: usize,
+    pub knots: Vec<f32>,
+}
+
+impl NurbsCurve {
+    pub fn new(
+        points: Vec<Vec3>,
+        weights: Vec<f32>,
+        degree: usize,
+        knots: Vec<f32>,
+    ) -> Result<Self, String> {
+        validate_knots(points.len(), degree, &knots)?;
+        if weights.len() != points.len() {
+            return Err(format!(
+                "Expected {} weights, got {}",
+                points.len(),
+                weights.len()
+            ));
+        }
+        Ok(Self {
+            points,
+            weights,
+            degree,
+            knots,
+        })
+    }
+
+    // exact circle in the XY plane from nine rational quadratic control points
+    pub fn circle(center: Vec3, radius: f32) -> Self {
+        let w = std::f32::consts::FRAC_1_SQRT_2;
+        let corners = [
+            (1.0, 0.0, 1.0),
+            (1.0, 1.0, w),
+            (0.0, 1.0, 1.0),
+            (-1.0, 1.0, w),
+            (-1.0, 0.0, 1.0),
+            (-1.0, -1.0, w),
+            (0.0, -1.0, 1.0),
+            (1.0, -1.0, w),
+            (1.0, 0.0, 1.0),
+        ];
+        Self {
+            points: corners
+                .iter()
+                .map(|(x, y, _)| center + Vec3::new(*x, *y, 0.0) * radius)
+                .collect(),
+            weights: corners.iter().map(|(_, _, w)| *w).collect(),
+            degree: 2,
+            knots: vec![
+                0.0, 0.0, 0.0, 0.25, 0.25, 0.5, 0.5, 0.75, 0.75, 1.0, 1.0, 1.0,
+            ],
+        }
+    }
+
+    fn get_homogeneous(&self) -> Vec<Vec4> {
+        self.points
+            .iter()
+            .zip(self.weights.iter())
+            .map(|(p, w)| (*p * *w).extend(*w))
+            .collect()
+    }
+}
+
+impl Curve for NurbsCurve {
+    fn get_domain(&self) -> (f32, f32) {
+        (self.knots[self.degree], self.knots[self.points.len()])
+    }
+
+    fn evaluate(&self, t: f32) -> Vec3 {
+        de_boor(&self.get_homogeneous(), &self.knots, self.degree, t).project()
+    }
+
+    // quotient rule on the homogeneous curve, C' = (A' - w' C) / w
+    fn derivative(&self, t: f32) -> Vec3 {
+        if self.degree == 0 {
+            return Vec3::ZERO;
+        }
+        let homogeneous = self.get_homogeneous();
+        let h = de_boor(&homogeneous, &self.knots, self.degree, t);
+        let d_points = derivative_points(&homogeneous, &self.knots, self.degree);
+        let d = de_boor(
+            &d_points,
+            &self.knots[1..self.knots.len() - 1],
+            self.degree - 1,
+            t,
+        );
+        (d.truncate() - h.project() * d.w) / h.w
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CurveFrame {
+    pub position: Vec3,
+    pub tangent: Vec3,
+    pub normal: Vec3,
+    pub binormal: Vec3,
+}
+
+impl CurveFrame {
+    // columns are normal, binormal and tangent, so local z runs along the curve
+    pub fn to_mat4(&self) -> Mat4 {
+        Mat4::from_cols(
+            self.normal.extend(0.0),
+            self.binormal.extend(0.0),
+            self.tangent.extend(0.0),
+            self.position.extend(1.0),
+        )
+    }
+}
+
+// Frenet frames follow the curvature and flip at inflection points. Straight
+// stretches have no curvature, there the previous normal is carried along.
+pub fn frenet_frames<C: Curve + ?Sized>(curve: &C, params: &[f32]) -> Vec<CurveFrame> {
+    let mut frames: Vec<CurveFrame> = Vec::with_capacity(params.len());
+    for &t in params {
+        let d1 = curve.derivative(t);
+        let tangent = d1.normalize();
+        let b = d1.cross(curve.second_derivative(t));
+        let binormal = if b.length_squared() > 1e-12 {
+            b.normalize()
+        } else {
+            let previous = frames
+                .last()
+                .map(|f| f.normal)
+                .unwrap_or_else(|| tangent.any_orthonormal());
+            tangent.cross(previous).normalize()
+        };
+        frames.push(CurveFrame {
+            position: curve.evaluate(t),
+            tangent,
+            normal: binormal.cross(tangent),
+            binormal,
+        });
+    }
+    frames
+}
+
+// rotation minimizing frames, the normal is rotated from one tangent to the next
+// so tubes extruded along the curve do not twist
+pub fn parallel_transport_frames<C: Curve + ?Sized>(curve: &C, params: &[f32]) -> Vec<CurveFrame> {
+    let mut frames: Vec<CurveFrame> = Vec::with_capacity(params.len());
+    for &t in params {
+        let tangent = curve.derivative(t).normalize();
+        let normal = match frames.last() {
+            Some(previous) => {
+                let rotation = Quat::from_rotation_arc(previous.tangent, tangent);
+                let n = rotation * previous.normal;
+                // remove drift so the frame stays orthonormal
+                (n - tangent * n.dot(tangent)).normalize()
+            }
+            None => tangent.any_orthonormal(),
+        };
+        frames.push(CurveFrame {
+            position: curve.evaluate(t),
+            tangent,
+            normal,
+            binormal: tangent.cross(normal),
+        });
+    }
+    frames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bezier_evaluation_and_derivative() {
+        let p = [
+            Vec3::ZERO,
+            Vec3::new(1.0, 2.0, 0.0),
+            Vec3::new(3.0, 2.0, 0.0),
+            Vec3::new(4.0, 0.0, 0.0),
+        ];
+        let curve = BezierCurve::cubic(p[0], p[1], p[2], p[3]);
+        assert_eq!(curve.evaluate(0.0), p[0]);
+        assert_eq!(curve.evaluate(1.0), p[3]);
+        assert_eq!(curve.derivative(0.0), (p[1] - p[0]) * 3.0);
+        assert!(curve
+            .evaluate(0.5)
+            .abs_diff_eq(Vec3::new(2.0, 1.5, 0.0), 1e-6));
+
+        let (left, right) = curve.split(0.3);
+        assert!(left.evaluate(1.0).abs_diff_eq(curve.evaluate(0.3), 1e-6));
+        assert!(right.evaluate(0.5).abs_diff_eq(curve.evaluate(0.65), 1e-5));
+    }
+
+    #[test]
+    fn catmull_rom_interpolates_points() {
+        let points = vec![
+            Vec3::ZERO,
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(5.0, 1.0, 0.0),
+        ];
+        for kind in [
+            CatmullRomType::Uniform,
+            CatmullRomType::Centripetal,
+            CatmullRomType::Chordal,
+        ] {
+            let spline = CatmullRomSpline::new(points.clone(), kind);
+            for (i, p) in points.iter().enumerate() {
+                assert!(spline.evaluate(i as f32).abs_diff_eq(*p, 1e-5));
+            }
+        }
+        let closed = CatmullRomSpline::new_closed(points.clone(), CatmullRomType::Centripetal);
+        assert_eq!(closed.get_domain(), (0.0, 4.0));
+        assert!(closed.evaluate(4.0).abs_diff_eq(points[0], 1e-5));
+    }
+
+    #[test]
+    fn bspline_is_clamped_and_degree_one_is_a_polyline() {
+        let points = vec![
+            Vec3::ZERO,
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(2.0, -1.0, 0.0),
+            Vec3::new(3.0, 0.0, 0.0),
+        ];
+        let cubic = BSpline::new(points.clone(), 3).unwrap();
+        assert!(cubic.evaluate(0.0).abs_diff_eq(points[0], 1e-6));
+        assert!(cubic.evaluate(1.0).abs_diff_eq(points[3], 1e-6));
+        // a single span cubic B-spline is the Bezier with the same points
+        let bezier = BezierCurve::new(points.clone());
+        assert!(cubic.evaluate(0.4).abs_diff_eq(bezier.evaluate(0.4), 1e-5));
+        assert!(cubic
+            .derivative(0.4)
+            .abs_diff_eq(bezier.derivative(0.4), 1e-4));
+
+        let linear = BSpline::new(points.clone(), 1).unwrap();
+        assert!(linear
+            .evaluate(0.5)
+            .abs_diff_eq(Vec3::new(1.5, 0.0, 0.0), 1e-6));
+        assert!(BSpline::new(points, 4).is_err());
+    }
+
+    #[test]
+    fn nurbs_circle_is_exact() {
+        let circle = NurbsCurve::circle(Vec3::new(1.0, 2.0, 0.0), 3.0);
+        for i in 0..=20 {
+            let p = circle.evaluate(i as f32 / 20.0);
+            assert!((p.distance(Vec3::new(1.0, 2.0, 0.0)) - 3.0).abs() < 1e-4);
+            // the tangent of a circle is perpendicular to the radius
+            let d = circle.derivative(i as f32 / 20.0);
+            assert!(
+                d.normalize()
+                    .dot((p - Vec3::new(1.0, 2.0, 0.0)).normalize())
+                    .abs()
+                    < 1e-3
+            );
+        }
+        let length = circle.length();
+        assert!((length - std::f32::consts::TAU * 3.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn arc_length_and_flattening() {
+        let line = BezierCurve::quadratic(
+            Vec3::ZERO,
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(4.0, 0.0, 0.0),
+        );
+        let table = ArcLengthTable::new(&line, 32);
+        assert!((table.get_length() - 4.0).abs() < 1e-4);
+        let t = table.get_param(2.0);
+        assert!((line.evaluate(t).x - 2.0).abs() < 1e-2);
+
+        let circle = NurbsCurve::circle(Vec3::ZERO, 1.0);
+        let coarse = circle.flatten(0.05);
+        let fine = circle.flatten(0.001);
+        assert!(fine.len() > coarse.len());
+        for pair in fine.windows(2) {
+            let mid = (pair[0] + pair[1]) * 0.5;
+            assert!(1.0 - mid.length() <= 0.0011);
+        }
+    }
+
+    #[test]
+    fn frames_are_orthonormal() {
+        let helix = CatmullRomSpline::new(
+            (0..12)
+                .map(|i| {
+                    let a = i as f32 * 0.8;
+                    Vec3::new(a.cos(), a.sin(), i as f32 * 0.3)
+                })
+                .collect(),
+            CatmullRomType::Centripetal,
+        );
+        let params = helix.sample_params(50);
+        for frames in [
+            frenet_frames(&helix, &params),
+            parallel_transport_frames(&helix, &params),
+        ] {
+            for f in frames {
+                assert!((f.tangent.length() - 1.0).abs() < 1e-3);
+                assert!(f.tangent.dot(f.normal).abs() < 1e-3);
+                assert!(f.tangent.cross(f.normal).abs_diff_eq(f.binormal, 1e-3));
+            }
+        }
+    }
+}
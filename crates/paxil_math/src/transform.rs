@@ -0,0 +1,135 @@
+use super::mat3::Mat3;
+use super::mat4::Mat4;
+use super::quat::Quat;
+use super::vec3::Vec3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self {
+        translation: Vec3::ZERO,
+        rotation: Quat::IDENTITY,
+        scale: Vec3::ONE,
+    };
+
+    pub fn new(translation: Vec3, rotation: Quat, scale: Vec3) -> Self {
+        Self {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    pub fn from_translation(translation: Vec3) -> Self {
+        Self {
+            translation,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn from_rotation(rotation: Quat) -> Self {
+        Self {
+            rotation,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn from_scale(scale: Vec3) -> Self {
+        Self {
+            scale,
+            ..Self::IDENTITY
+        }
+    }
+
+    // shear in the matrix is lost
+    pub fn from_matrix(m: &Mat4) -> Self {
+        let (scale, rotation, translation) = m.to_scale_rotation_translation();
+        Self {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    pub fn to_matrix(&self) -> Mat4 {
+        Mat4::from_trs(self.translation, self.rotation, self.scale)
+    }
+
+    // self applied after child, exact unless non-uniform scale meets rotation
+    pub fn mul_transform(&self, child: &Transform) -> Self {
+        Self {
+            translation: self.transform_point(child.translation),
+            rotation: self.rotation * child.rotation,
+            scale: self.scale * child.scale,
+        }
+    }
+
+    pub fn transform_point(&self, p: Vec3) -> Vec3 {
+        self.rotation * (p * self.scale) + self.translation
+    }
+
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        self.rotation * (v * self.scale)
+    }
+
+    pub fn translate(&mut self, offset: Vec3) {
+        self.translation += offset;
+    }
+
+    // rotation applied on top of the current one, in parent space
+    pub fn rotate(&mut self, rotation: Quat) {
+        self.rotation = (rotation * self.rotation).normalize();
+    }
+
+    pub fn rotate_local(&mut self, rotation: Quat) {
+        self.rotation = (self.rotation * rotation).normalize();
+    }
+
+    // -Z is forward, matching Mat4::look_at
+    pub fn forward(&self) -> Vec3 {
+        self.rotation * -Vec3::Z
+    }
+
+    pub fn right(&self) -> Vec3 {
+        self.rotation * Vec3::X
+    }
+
+    pub fn up(&self) -> Vec3 {
+        self.rotation * Vec3::Y
+    }
+
+    pub fn look_at(&mut self, target: Vec3, up: Vec3) {
+        let f = (target - self.translation).normalize();
+        let s = f.cross(up).normalize();
+        if f == Vec3::ZERO || s == Vec3::ZERO {
+            return;
+        }
+        let u = s.cross(f);
+        self.rotation = Quat::from_mat3(&Mat3::from_cols(s, u, -f));
+    }
+
+    pub fn lerp(&self, rhs: &Transform, t: f32) -> Self {
+        Self {
+            translation: self.translation.lerp(rhs.translation, t),
+            rotation: self.rotation.slerp(rhs.rotation, t),
+            scale: self.scale.lerp(rhs.scale, t),
+        }
+    }
+}
+
+impl From<Transform> for Mat4 {
+    fn from(t: Transform) -> Self {
+        t.to_matrix()
+    }
+}
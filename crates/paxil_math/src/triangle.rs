@@ -0,0 +1,139 @@
+use super::plane::Plane;
+use super::vec3::Vec3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Triangle {
+    pub a: Vec3,
+    pub b: Vec3,
+    pub c: Vec3,
+}
+
+impl Triangle {
+    pub fn new(a: Vec3, b: Vec3, c: Vec3) -> Self {
+        Self { a, b, c }
+    }
+
+    // counter clockwise winding faces the normal
+    pub fn get_normal(&self) -> Vec3 {
+        (self.b - self.a).cross(self.c - self.a).normalize()
+    }
+
+    pub fn get_area(&self) -> f32 {
+        (self.b - self.a).cross(self.c - self.a).length() * 0.5
+    }
+
+    pub fn get_centroid(&self) -> Vec3 {
+        (self.a + self.b + self.c) / 3.0
+    }
+
+    pub fn get_plane(&self) -> Plane {
+        Plane::from_points(self.a, self.b, self.c)
+    }
+
+    // weights of a, b and c for p projected onto the triangle plane
+    pub fn barycentric(&self, p: Vec3) -> Vec3 {
+        let v0 = self.b - self.a;
+        let v1 = self.c - self.a;
+        let v2 = p - self.a;
+        let d00 = v0.dot(v0);
+        let d01 = v0.dot(v1);
+        let d11 = v1.dot(v1);
+        let d20 = v2.dot(v0);
+        let d21 = v2.dot(v1);
+        let denom = d00 * d11 - d01 * d01;
+        if denom.abs() < f32::EPSILON {
+            return Vec3::new(1.0, 0.0, 0.0);
+        }
+        let v = (d11 * d20 - d01 * d21) / denom;
+        let w = (d00 * d21 - d01 * d20) / denom;
+        Vec3::new(1.0 - v - w, v, w)
+    }
+
+    pub fn contains_point(&self, p: Vec3) -> bool {
+        let w = self.barycentric(p);
+        w.x >= 0.0 && w.y >= 0.0 && w.z >= 0.0
+    }
+
+    // Voronoi region test from Real-Time Collision Detection
+    pub fn closest_point(&self, p: Vec3) -> Vec3 {
+        let (a, b, c) = (self.a, self.b, self.c);
+        let ab = b - a;
+        let ac = c - a;
+        let ap = p - a;
+        let d1 = ab.dot(ap);
+        let d2 = ac.dot(ap);
+        if d1 <= 0.0 && d2 <= 0.0 {
+            return a;
+        }
+
+        let bp = p - b;
+        let d3 = ab.dot(bp);
+        let d4 = ac.dot(bp);
+        if d3 >= 0.0 && d4 <= d3 {
+            return b;
+        }
+
+        let vc = d1 * d4 - d3 * d2;
+        if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+            return a + ab * (d1 / (d1 - d3));
+        }
+
+        let cp = p - c;
+        let d5 = ab.dot(cp);
+        let d6 = ac.dot(cp);
+        if d6 >= 0.0 && d5 <= d6 {
+            return c;
+        }
+
+        let vb = d5 * d2 - d1 * d6;
+        if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+            return a + ac * (d2 / (d2 - d6));
+        }
+
+        let va = d3 * d6 - d5 * d4;
+        if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+            return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+        }
+
+        let denom = 1.0 / (va + vb + vc);
+        a + ab * (vb * denom) + ac * (vc * denom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closest_point_regions() {
+        let triangle = Triangle::new(Vec3::ZERO, Vec3::X, Vec3::Y);
+        // vertex, edge and face regions
+        assert_eq!(
+            triangle.closest_point(Vec3::new(-1.0, -1.0, 0.0)),
+            Vec3::ZERO
+        );
+        assert_eq!(
+            triangle.closest_point(Vec3::new(0.5, -1.0, 0.0)),
+            Vec3::new(0.5, 0.0, 0.0)
+        );
+        assert!(triangle
+            .closest_point(Vec3::new(1.0, 1.0, 0.0))
+            .abs_diff_eq(Vec3::new(0.5, 0.5, 0.0), 1e-6));
+        assert_eq!(
+            triangle.closest_point(Vec3::new(0.25, 0.25, 3.0)),
+            Vec3::new(0.25, 0.25, 0.0)
+        );
+    }
+
+    #[test]
+    fn barycentric_weights() {
+        let triangle = Triangle::new(Vec3::ZERO, Vec3::X, Vec3::Y);
+        assert!(triangle
+            .barycentric(triangle.get_centroid())
+            .abs_diff_eq(Vec3::splat(1.0 / 3.0), 1e-6));
+        assert!(triangle.contains_point(Vec3::new(0.2, 0.2, 0.0)));
+        assert!(!triangle.contains_point(Vec3::new(0.8, 0.8, 0.0)));
+        assert_eq!(triangle.get_area(), 0.5);
+        assert_eq!(triangle.get_normal(), Vec3::Z);
+    }
+}
@@ -0,0 +1,49 @@
+use super::macros::impl_float_vec;
+use super::vec3::Vec3;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl_float_vec!(Vec2, x, y);
+
+impl Vec2 {
+    pub const ZERO: Self = Self::new(0.0, 0.0);
+    pub const ONE: Self = Self::new(1.0, 1.0);
+    pub const X: Self = Self::new(1.0, 0.0);
+    pub const Y: Self = Self::new(0.0, 1.0);
+
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn extend(self, z: f32) -> Vec3 {
+        Vec3::new(self.x, self.y, z)
+    }
+
+    // counter-clockwise perpendicular
+    pub fn perp(self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+
+    // z component of the 3D cross product
+    pub fn perp_dot(self, rhs: Self) -> f32 {
+        self.x * rhs.y - self.y * rhs.x
+    }
+
+    pub fn from_angle(angle: f32) -> Self {
+        Self::new(angle.cos(), angle.sin())
+    }
+
+    pub fn angle(self) -> f32 {
+        self.y.atan2(self.x)
+    }
+
+    pub fn rotate(self, angle: f32) -> Self {
+        let (s, c) = angle.sin_cos();
+        Self::new(self.x * c - self.y * s, self.x * s + self.y * c)
+    }
+}
@@ -0,0 +1,83 @@
+use super::macros::impl_float_vec;
+use super::vec2::Vec2;
+use super::vec4::Vec4;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl_float_vec!(Vec3, x, y, z);
+
+impl Vec3 {
+    pub const ZERO: Self = Self::new(0.0, 0.0, 0.0);
+    pub const ONE: Self = Self::new(1.0, 1.0, 1.0);
+    pub const X: Self = Self::new(1.0, 0.0, 0.0);
+    pub const Y: Self = Self::new(0.0, 1.0, 0.0);
+    pub const Z: Self = Self::new(0.0, 0.0, 1.0);
+
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn cross(self, rhs: Self) -> Self {
+        Self::new(
+            self.y * rhs.z - self.z * rhs.y,
+            self.z * rhs.x - self.x * rhs.z,
+            self.x * rhs.y - self.y * rhs.x,
+        )
+    }
+
+    pub fn extend(self, w: f32) -> Vec4 {
+        Vec4::new(self.x, self.y, self.z, w)
+    }
+
+    pub fn truncate(self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
+
+    // any unit vector perpendicular to self, self must be normalized
+    pub fn any_orthonormal(self) -> Self {
+        let other = if self.x.abs() < 0.9 { Self::X } else { Self::Y };
+        self.cross(other).normalize()
+    }
+
+    pub fn reflect(self, normal: Self) -> Self {
+        self - normal * (2.0 * self.dot(normal))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vector_ops() {
+        let a = Vec3::new(1.0, 2.0, 3.0);
+        let b = Vec3::new(-2.0, 0.5, 4.0);
+        assert_eq!(a + b, Vec3::new(-1.0, 2.5, 7.0));
+        assert_eq!(a - b, Vec3::new(3.0, 1.5, -1.0));
+        assert_eq!(a * b, Vec3::new(-2.0, 1.0, 12.0));
+        assert_eq!(a * 2.0, 2.0 * a);
+        assert_eq!(-a / 2.0, Vec3::new(-0.5, -1.0, -1.5));
+        assert_eq!(a.dot(b), 11.0);
+        assert_eq!(a.min(b), Vec3::new(-2.0, 0.5, 3.0));
+        assert_eq!(a[2], 3.0);
+        assert_eq!(Vec3::from([1.0, 2.0, 3.0]), a);
+
+        assert_eq!(Vec3::X.cross(Vec3::Y), Vec3::Z);
+        assert_eq!(a.cross(b).dot(a), 0.0);
+        assert_eq!(Vec3::ZERO.normalize(), Vec3::ZERO);
+        assert!((a.normalize().length() - 1.0).abs() < 1e-6);
+        assert!(a.any_orthonormal().dot(a).abs() < 1e-6);
+        assert_eq!(
+            Vec3::new(1.0, -1.0, 0.0).reflect(Vec3::Y),
+            Vec3::new(1.0, 1.0, 0.0)
+        );
+        assert_eq!(crate::IVec3::new(1, 2, 3) * 2, crate::IVec3::new(2, 4, 6));
+        assert_eq!(crate::IVec3::new(1, 2, 3).as_vec3(), a);
+    }
+}
@@ -0,0 +1,410 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::f32::consts::PI;
+
+use super::half_edge::HalfEdgeMesh;
+use super::mesh::Mesh;
+use paxil_math::*;
+
+// boundary and uv seam edges get this much extra weight against sliding off their line
+const BOUNDARY_WEIGHT: f64 = 1000.0;
+
+#[derive(Clone, Debug)]
+pub struct DecimateOptions {
+    // stops once the mesh has at most this many triangles
+    pub target_triangles: usize,
+    // stops before any collapse whose quadric error, roughly a squared distance, exceeds this
+    pub max_error: f32,
+    pub preserve_boundaries: bool,
+}
+
+impl Default for DecimateOptions {
+    fn default() -> Self {
+        Self {
+            target_triangles: 0,
+            max_error: f32::INFINITY,
+            preserve_boundaries: true,
+        }
+    }
+}
+
+// symmetric 4x4 error quadric stored as its upper triangle
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    fn from_plane(normal: Vec3, point: Vec3, weight: f64) -> Self {
+        let [a, b, c] = [normal.x, normal.y, normal.z].map(|v| v as f64);
+        let d = -(a * point.x as f64 + b * point.y as f64 + c * point.z as f64);
+        Self(
+            [
+                a * a,
+                a * b,
+                a * c,
+                a * d,
+                b * b,
+                b * c,
+                b * d,
+                c * c,
+                c * d,
+                d * d,
+            ]
+            .map(|v| v * weight),
+        )
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        let mut sum = self.0;
+        for (s, o) in sum.iter_mut().zip(other.0) {
+            *s += o;
+        }
+        Self(sum)
+    }
+
+    fn evaluate(&self, p: Vec3) -> f64 {
+        let q = &self.0;
+        let (x, y, z) = (p.x as f64, p.y as f64, p.z as f64);
+        q[0] * x * x
+            + 2.0 * q[1] * x * y
+            + 2.0 * q[2] * x * z
+            + 2.0 * q[3] * x
+            + q[4] * y * y
+            + 2.0 * q[5] * y * z
+            + 2.0 * q[6] * y
+            + q[7] * z * z
+            + 2.0 * q[8] * z
+            + q[9]
+    }
+
+    // the point of least error, None when the quadric is close to singular
+    fn get_optimal(&self) -> Option<Vec3> {
+        let q = &self.0;
+        let m = [[q[0], q[1], q[2]], [q[1], q[4], q[5]], [q[2], q[5], q[7]]];
+        let det = m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0]);
+        let scale = m.iter().flatten().fold(0.0f64, |s, v| s.max(v.abs()));
+        if det.abs() <= 1e-9 * scale.powi(3) {
+            return None;
+        }
+        let r = [-q[3], -q[6], -q[8]];
+        let solve = |col: usize| {
+            let mut a = m;
+            for (row, value) in a.iter_mut().zip(r) {
+                row[col] = value;
+            }
+            (a[0][0] * (a[1][1] * a[2][2] - a[1][2] * a[2][1])
+                - a[0][1] * (a[1][0] * a[2][2] - a[1][2] * a[2][0])
+                + a[0][2] * (a[1][0] * a[2][1] - a[1][1] * a[2][0]))
+                / det
+        };
+        Some(Vec3::new(solve(0) as f32, solve(1) as f32, solve(2) as f32))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Candidate {
+    cost: f64,
+    // collapses the end of this half-edge into its origin
+    halfedge: usize,
+    target: Vec3,
+    versions: [u64; 2],
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// reversed so the binary heap pops the cheapest collapse first
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+struct Decimator {
+    mesh: HalfEdgeMesh,
+    quadrics: Vec<Quadric>,
+    // each collapse hands the surviving vertex a version no vertex has held before
+    versions: Vec<u64>,
+    next_version: u64,
+    heap: BinaryHeap<Candidate>,
+}
+
+impl Decimator {
+    fn new(mesh: HalfEdgeMesh, options: &DecimateOptions) -> Self {
+        let mut quadrics = vec![Quadric::default(); mesh.vertices.len()];
+        for f in mesh.get_faces() {
+            let vertices = mesh.get_face_vertices(f);
+            let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[vertices[i]].position);
+            let cross = (b - a).cross(c - a);
+            let area = cross.length() as f64 * 0.5;
+            let plane = Quadric::from_plane(cross.normalize(), a, area);
+            for v in vertices {
+                quadrics[v] = quadrics[v].add(&plane);
+            }
+        }
+
+        let has_uvs = !mesh.uvs.is_empty();
+        for h in mesh.get_edges() {
+            let twin = mesh.halfedges[h].twin;
+            let seam = has_uvs && mesh.halfedges[h].face.is_some() && {
+                // the corner uvs on either side of the edge disagree
+                let other = mesh.halfedges[twin].face.is_some();
+                other
+                    && (mesh.uvs[h] != mesh.uvs[mesh.halfedges[twin].next]
+                        || mesh.uvs[twin] != mesh.uvs[mesh.halfedges[h].next])
+            };
+            let boundary = options.preserve_boundaries && mesh.is_boundary_edge(h);
+            if !seam && !boundary {
+                continue;
+            }
+            let side = if mesh.halfedges[h].face.is_some() {
+                h
+            } else {
+                twin
+            };
+            let f = mesh.halfedges[side].face.unwrap();
+            let (a, b) = (mesh.halfedges[h].origin, mesh.halfedges[twin].origin);
+            let (pa, pb) = (mesh.vertices[a].position, mesh.vertices[b].position);
+            let normal = (pb - pa).cross(mesh.get_face_normal(f)).normalize();
+            let plane =
+                Quadric::from_plane(normal, pa, BOUNDARY_WEIGHT * pa.distance_squared(pb) as f64);
+            quadrics[a] = quadrics[a].add(&plane);
+            quadrics[b] = quadrics[b].add(&plane);
+        }
+
+        let versions = vec![0; mesh.vertices.len()];
+        let mut decimator = Self {
+            mesh,
+            quadrics,
+            versions,
+            next_version: 1,
+            heap: BinaryHeap::new(),
+        };
+        let edges: Vec<usize> = decimator.mesh.get_edges().collect();
+        for h in edges {
+            decimator.push_edge(h);
+        }
+        decimator
+    }
+
+    // a vertex can only be merged away when all its corners share one uv
+    fn has_single_uv(&self, v: usize) -> bool {
+        let mut uvs = self
+            .mesh
+            .get_outgoing(v)
+            .into_iter()
+            .filter(|h| self.mesh.halfedges[*h].face.is_some())
+            .map(|h| self.mesh.uvs[h]);
+        let first = uvs.next();
+        uvs.all(|uv| Some(uv) == first)
+    }
+
+    fn push_edge(&mut self, h: usize) {
+        let twin = self.mesh.halfedges[h].twin;
+        let (a, b) = (
+            self.mesh.halfedges[h].origin,
+            self.mesh.halfedges[twin].origin,
+        );
+        let quadric = self.quadrics[a].add(&self.quadrics[b]);
+        let (pa, pb) = (
+            self.mesh.vertices[a].position,
+            self.mesh.vertices[b].position,
+        );
+
+        // with uvs the kept vertex stays where it is so its uvs stay valid
+        let mut options: Vec<(usize, Vec3)> = Vec::new();
+        if self.mesh.uvs.is_empty() {
+            let mut targets = vec![pa, pb, pa.lerp(pb, 0.5)];
+            targets.extend(quadric.get_optimal());
+            let best = targets
+                .into_iter()
+                .min_by(|x, y| quadric.evaluate(*x).total_cmp(&quadric.evaluate(*y)))
+                .unwrap();
+            options.push((h, best));
+        } else {
+            if self.has_single_uv(b) {
+                options.push((h, pa));
+            }
+            if self.has_single_uv(a) {
+                options.push((twin, pb));
+            }
+        }
+
+        for (halfedge, target) in options {
+            self.heap.push(Candidate {
+                cost: quadric.evaluate(target).max(0.0),
+                halfedge,
+                target,
+                versions: [self.versions[a], self.versions[b]],
+            });
+        }
+    }
+
+    // rejects collapses that would flip or crush a surrounding triangle
+    fn flips_faces(&self, a: usize, b: usize, target: Vec3) -> bool {
+        for v in [a, b] {
+            for f in self.mesh.get_vertex_faces(v) {
+                let vertices = self.mesh.get_face_vertices(f);
+                if vertices.contains(&a) && vertices.contains(&b) {
+                    continue;
+                }
+                let old: Vec<Vec3> = vertices
+                    .iter()
+                    .map(|u| self.mesh.vertices[*u].position)
+                    .collect();
+                let new: Vec<Vec3> = vertices
+                    .iter()
+                    .zip(&old)
+                    .map(|(u, p)| if *u == v { target } else { *p })
+                    .collect();
+                let old_normal = (old[1] - old[0]).cross(old[2] - old[0]).normalize();
+                let new_normal = (new[1] - new[0]).cross(new[2] - new[0]).normalize();
+                if old_normal.dot(new_normal) < (PI / 3.0).cos() {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    fn run(&mut self, options: &DecimateOptions) {
+        let mut triangles = self.mesh.get_face_count();
+        while triangles > options.target_triangles {
+            let Some(candidate) = self.heap.pop() else {
+                break;
+            };
+            let h = candidate.halfedge;
+            if self.mesh.halfedges[h].removed {
+                continue;
+            }
+            let twin = self.mesh.halfedges[h].twin;
+            let (a, b) = (
+                self.mesh.halfedges[h].origin,
+                self.mesh.halfedges[twin].origin,
+            );
+            let versions = [self.versions[a], self.versions[b]];
+            // stale entries carry the versions of the other direction or an older quadric
+            if candidate.versions != versions && candidate.versions != [versions[1], versions[0]] {
+                continue;
+            }
+            if candidate.cost > options.max_error as f64 {
+                break;
+            }
+            if self.flips_faces(a, b, candidate.target) {
+                continue;
+            }
+
+            // uvs of the corners moving from b to a, taken from the face the edge borders
+            let uv = if self.mesh.uvs.is_empty() {
+                None
+            } else if self.mesh.halfedges[h].face.is_some() {
+                Some(self.mesh.uvs[h])
+            } else {
+                Some(self.mesh.uvs[self.mesh.halfedges[twin].next])
+            };
+            let moved = self.mesh.get_outgoing(b);
+            let removed = [h, twin]
+                .iter()
+                .filter(|e| self.mesh.halfedges[**e].face.is_some())
+                .count();
+            if self.mesh.collapse_edge(h, 0.0).is_err() {
+                continue;
+            }
+            triangles -= removed;
+            self.mesh.vertices[a].position = candidate.target;
+            if let Some(uv) = uv {
+                for e in moved {
+                    if !self.mesh.halfedges[e].removed && self.mesh.halfedges[e].face.is_some() {
+                        self.mesh.uvs[e] = uv;
+                    }
+                }
+            }
+            self.quadrics[a] = self.quadrics[a].add(&self.quadrics[b]);
+            self.versions[a] = self.next_version;
+            self.next_version += 1;
+            for e in self.mesh.get_outgoing(a) {
+                self.push_edge(e);
+            }
+        }
+    }
+}
+
+impl Mesh {
+    // Garland-Heckbert quadric error decimation by edge collapses
+    pub fn decimate(&self, options: &DecimateOptions) -> Result<Mesh, String> {
+        let mut decimator = Decimator::new(HalfEdgeMesh::from_mesh(self)?, options);
+        decimator.run(options);
+        let mut result = decimator.mesh.to_mesh();
+        if self.has_normals() {
+            result.compute_normals(PI / 3.0)?;
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quadric_finds_plane_intersection() {
+        let planes = [Vec3::X, Vec3::Y, Vec3::Z]
+            .map(|n| Quadric::from_plane(n, Vec3::new(1.0, 2.0, 3.0), 1.0));
+        let sum = planes[0].add(&planes[1]).add(&planes[2]);
+        let optimal = sum.get_optimal().unwrap();
+        assert!(optimal.distance(Vec3::new(1.0, 2.0, 3.0)) < 1e-5);
+        assert!(sum.evaluate(optimal) < 1e-9);
+        assert!(planes[0].get_optimal().is_none());
+    }
+
+    #[test]
+    fn flat_regions_collapse_without_error() {
+        let mut plane = Mesh::plane(2.0, 2.0, 8, 8);
+        plane.uvs.clear();
+        let options = DecimateOptions {
+            max_error: 1e-6,
+            ..Default::default()
+        };
+        let result = plane.decimate(&options).unwrap();
+        assert!(result.get_primitive_count() <= 4);
+        assert!(result.validate().is_ok());
+        // the outline survives
+        let aabb = result.get_aabb();
+        assert_eq!(aabb.min, Vec3::new(-1.0, 0.0, -1.0));
+        assert_eq!(aabb.max, Vec3::new(1.0, 0.0, 1.0));
+        for p in &result.positions {
+            assert_eq!(p.y, 0.0);
+        }
+    }
+
+    #[test]
+    fn sphere_reaches_target_count() {
+        let sphere = Mesh::icosphere(1.0, 3);
+        let options = DecimateOptions {
+            target_triangles: 200,
+            ..Default::default()
+        };
+        let result = sphere.decimate(&options).unwrap();
+        assert!(result.get_primitive_count() <= 200);
+        assert!(result.get_primitive_count() > 150);
+        let half_edge = HalfEdgeMesh::from_mesh(&result).unwrap();
+        assert!(half_edge.is_closed() && half_edge.is_manifold());
+        assert_eq!(half_edge.get_euler_characteristic(), 2);
+        for p in &result.positions {
+            assert!((p.length() - 1.0).abs() < 0.1);
+        }
+        assert!(result.has_normals() && result.has_uvs());
+    }
+}
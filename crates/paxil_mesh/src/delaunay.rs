@@ -0,0 +1,362 @@
+use std::collections::{HashMap, HashSet};
+
+use super::mesh::{Mesh, PrimitiveMode};
+use paxil_math::*;
+
+// the vertex at infinity that closes the hull
+const GHOST: usize = usize::MAX;
+
+// directed edges of the triangles without a twin, they have the triangles on their left
+fn get_boundary(triangles: impl Iterator<Item = [usize; 3]>) -> Vec<[usize; 2]> {
+    let edges: Vec<[usize; 2]> = triangles
+        .flat_map(|[a, b, c]| [[a, b], [b, c], [c, a]])
+        .collect();
+    let lookup: HashSet<[usize; 2]> = edges.iter().copied().collect();
+    edges
+        .into_iter()
+        .filter(|[a, b]| !lookup.contains(&[*b, *a]))
+        .collect()
+}
+
+// twice the signed area of abc, positive when counter-clockwise
+fn orient(a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> f64 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+// positive when p is strictly inside the circle through the counter-clockwise abc
+fn incircle(a: [f64; 2], b: [f64; 2], c: [f64; 2], p: [f64; 2]) -> f64 {
+    let [adx, ady] = [a[0] - p[0], a[1] - p[1]];
+    let [bdx, bdy] = [b[0] - p[0], b[1] - p[1]];
+    let [cdx, cdy] = [c[0] - p[0], c[1] - p[1]];
+    (adx * adx + ady * ady) * (bdx * cdy - cdx * bdy)
+        + (bdx * bdx + bdy * bdy) * (cdx * ady - adx * cdy)
+        + (cdx * cdx + cdy * cdy) * (adx * bdy - bdx * ady)
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Circumcircle {
+    center: [f64; 2],
+    radius_squared: f64,
+}
+
+impl Circumcircle {
+    fn new(a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> Self {
+        let (bx, by) = (b[0] - a[0], b[1] - a[1]);
+        let (cx, cy) = (c[0] - a[0], c[1] - a[1]);
+        let d = 2.0 * (bx * cy - by * cx);
+        let (b2, c2) = (bx * bx + by * by, cx * cx + cy * cy);
+        let x = (cy * b2 - by * c2) / d;
+        let y = (bx * c2 - cx * b2) / d;
+        Self {
+            center: [a[0] + x, a[1] + y],
+            radius_squared: x * x + y * y,
+        }
+    }
+
+    // true once the sweep has passed the circle, with some slack for rounding
+    fn is_behind(&self, p: [f64; 2]) -> bool {
+        let dx = p[0] - self.center[0];
+        dx > 0.0 && dx * dx > self.radius_squared * (1.0 + 1e-9)
+    }
+}
+
+// triangles are counter-clockwise and index into points, duplicate points are left out
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Delaunay {
+    pub points: Vec<Vec2>,
+    pub triangles: Vec<[usize; 3]>,
+}
+
+impl Delaunay {
+    // Bowyer-Watson insertion in x order, circles left behind by the sweep are retired early
+    // hull edges form ghost triangles with a vertex at infinity, so no super triangle can
+    // bend the circles near the points, predicates run in f64 relative to the centre
+    pub fn new(points: &[Vec2]) -> Result<Self, String> {
+        if let Some(p) = points.iter().find(|p| !p.is_finite()) {
+            return Err(format!("Cannot triangulate non finite point {:?}", p));
+        }
+        let mut delaunay = Self {
+            points: points.to_vec(),
+            triangles: Vec::new(),
+        };
+        let n = points.len();
+        if n < 3 {
+            return Ok(delaunay);
+        }
+
+        let (min, max) = points.iter().fold(
+            (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+            |(min, max), p| (min.min(*p), max.max(*p)),
+        );
+        let center = (min + max) * 0.5;
+        let coords: Vec<[f64; 2]> = points
+            .iter()
+            .map(|p| [p.x as f64 - center.x as f64, p.y as f64 - center.y as f64])
+            .collect();
+
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|a, b| {
+            let (a, b) = (coords[*a], coords[*b]);
+            a[0].total_cmp(&b[0]).then(a[1].total_cmp(&b[1]))
+        });
+        order.dedup_by(|a, b| coords[*a] == coords[*b]);
+
+        // the leading points may all lie on one line, the first point off it fans over them
+        let Some(k) = (2..order.len())
+            .find(|k| orient(coords[order[0]], coords[order[1]], coords[order[*k]]) != 0.0)
+        else {
+            return Ok(delaunay);
+        };
+        let apex = order[k];
+        let left = orient(coords[order[0]], coords[order[1]], coords[apex]) > 0.0;
+        let mut open: Vec<([usize; 3], Option<Circumcircle>)> = order[..k]
+            .windows(2)
+            .map(|w| {
+                let triangle = if left {
+                    [w[0], w[1], apex]
+                } else {
+                    [w[1], w[0], apex]
+                };
+                let [a, b, c] = triangle.map(|i| coords[i]);
+                (triangle, Some(Circumcircle::new(a, b, c)))
+            })
+            .collect();
+        let ghosts = get_boundary(open.iter().map(|(t, _)| *t))
+            .into_iter()
+            .map(|[a, b]| ([b, a, GHOST], None))
+            .collect::<Vec<_>>();
+        open.extend(ghosts);
+
+        let mut closed = Vec::new();
+        for &i in &order[k + 1..] {
+            let p = coords[i];
+            let mut cavity = Vec::new();
+            let mut t = 0;
+            while t < open.len() {
+                let (triangle, circle) = open[t];
+                let inside = match circle {
+                    Some(circle) if circle.is_behind(p) => {
+                        // no later point can reach this circle
+                        closed.push(triangle);
+                        open.swap_remove(t);
+                        continue;
+                    }
+                    Some(_) => {
+                        let [a, b, c] = triangle.map(|v| coords[v]);
+                        incircle(a, b, c, p) > 0.0
+                    }
+                    None => {
+                        // the open half plane beyond the hull edge, or the edge itself
+                        let [a, b] = [coords[triangle[0]], coords[triangle[1]]];
+                        let side = orient(a, b, p);
+                        side > 0.0
+                            || (side == 0.0
+                                && (p[0] - a[0]) * (p[0] - b[0]) + (p[1] - a[1]) * (p[1] - b[1])
+                                    < 0.0)
+                    }
+                };
+                if inside {
+                    cavity.push(triangle);
+                    open.swap_remove(t);
+                } else {
+                    t += 1;
+                }
+            }
+
+            for [a, b] in get_boundary(cavity.into_iter()) {
+                let triangle = match (a, b) {
+                    (GHOST, b) => [b, i, GHOST],
+                    (a, GHOST) => [i, a, GHOST],
+                    (a, b) => [a, b, i],
+                };
+                let circle = (triangle[2] != GHOST).then(|| {
+                    let [a, b, c] = triangle.map(|v| coords[v]);
+                    Circumcircle::new(a, b, c)
+                });
+                open.push((triangle, circle));
+            }
+        }
+
+        closed.extend(open.into_iter().map(|(triangle, _)| triangle));
+        delaunay.triangles = closed.into_iter().filter(|t| !t.contains(&GHOST)).collect();
+        Ok(delaunay)
+    }
+
+    // unique edges with the smaller index first
+    pub fn get_edges(&self) -> Vec<[usize; 2]> {
+        let mut edges: Vec<[usize; 2]> = self
+            .triangles
+            .iter()
+            .flat_map(|[a, b, c]| [[*a, *b], [*b, *c], [*c, *a]])
+            .map(|[a, b]| [a.min(b), a.max(b)])
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+        edges
+    }
+
+    pub fn get_neighbours(&self) -> Vec<Vec<usize>> {
+        let mut neighbours = vec![Vec::new(); self.points.len()];
+        for [a, b] in self.get_edges() {
+            neighbours[a].push(b);
+            neighbours[b].push(a);
+        }
+        neighbours
+    }
+
+    // hull edges belong to a single triangle, walked counter-clockwise from the lowest index
+    pub fn get_hull_edges(&self) -> Vec<[usize; 2]> {
+        let next: HashMap<usize, usize> = get_boundary(self.triangles.iter().copied())
+            .into_iter()
+            .map(|[a, b]| (a, b))
+            .collect();
+        let Some(start) = next.keys().min().copied() else {
+            return Vec::new();
+        };
+        let mut hull = Vec::with_capacity(next.len());
+        let mut a = start;
+        while let Some(b) = next.get(&a).copied() {
+            hull.push([a, b]);
+            a = b;
+            if a == start || hull.len() == next.len() {
+                break;
+            }
+        }
+        hull
+    }
+
+    pub fn get_circumcenter(&self, triangle: usize) -> Vec2 {
+        let [a, b, c] = self.triangles[triangle].map(|i| {
+            let p = self.points[i];
+            [p.x as f64, p.y as f64]
+        });
+        let circle = Circumcircle::new(a, b, c);
+        Vec2::new(circle.center[0] as f32, circle.center[1] as f32)
+    }
+
+    pub fn to_mesh(&self) -> Mesh {
+        let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+        mesh.positions = self.points.iter().map(|p| p.extend(0.0)).collect();
+        mesh.indices = self.triangles.iter().flatten().map(|i| *i as u32).collect();
+        mesh
+    }
+
+    pub fn to_edge_mesh(&self) -> Mesh {
+        let mut mesh = Mesh::new(PrimitiveMode::Lines);
+        mesh.positions = self.points.iter().map(|p| p.extend(0.0)).collect();
+        mesh.indices = self
+            .get_edges()
+            .iter()
+            .flatten()
+            .map(|i| *i as u32)
+            .collect();
+        mesh
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_circumcircles() {
+        let mut rng = Rng::new(7);
+        let mut points = vec![Vec2::ZERO, Vec2::X, Vec2::ONE, Vec2::Y];
+        points.extend((0..300).map(|_| rng.in_rect(Vec2::splat(0.01), Vec2::splat(0.99))));
+        // duplicates are skipped
+        points.push(points[10]);
+        let delaunay = Delaunay::new(&points).unwrap();
+
+        // every other point is a vertex, the hull is the unit square
+        assert_eq!(delaunay.triangles.len(), 2 * (points.len() - 1) - 4 - 2);
+        assert_eq!(delaunay.get_hull_edges().len(), 4);
+        assert_delaunay(&delaunay);
+        let area: f32 = delaunay
+            .triangles
+            .iter()
+            .map(|t| {
+                let [a, b, c] = t.map(|i| points[i]);
+                (b - a).perp_dot(c - a) * 0.5
+            })
+            .sum();
+        assert!((area - 1.0).abs() < 1e-4);
+
+        let mesh = delaunay.to_mesh();
+        assert!(mesh.validate().is_ok());
+        assert_eq!(
+            delaunay.to_edge_mesh().get_primitive_count(),
+            delaunay.get_edges().len()
+        );
+    }
+
+    fn assert_delaunay(delaunay: &Delaunay) {
+        for (t, triangle) in delaunay.triangles.iter().enumerate() {
+            let [a, b, c] = triangle.map(|i| delaunay.points[i]);
+            assert!((b - a).perp_dot(c - a) > 0.0);
+            let center = delaunay.get_circumcenter(t);
+            let radius = center.distance(a);
+            for p in &delaunay.points {
+                assert!(center.distance(*p) > radius - 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn grids_use_every_point() {
+        for n in [3, 4, 5, 6, 8, 10, 20] {
+            let grid: Vec<Vec2> = (0..n * n)
+                .map(|i| Vec2::new((i % n) as f32, (i / n) as f32))
+                .collect();
+            let delaunay = Delaunay::new(&grid).unwrap();
+            assert_eq!(
+                delaunay.triangles.len(),
+                2 * (n - 1) * (n - 1),
+                "{0}x{0} grid",
+                n
+            );
+            let mut used = vec![false; grid.len()];
+            for i in delaunay.triangles.iter().flatten() {
+                used[*i] = true;
+            }
+            assert!(used.iter().all(|u| *u), "{0}x{0} grid", n);
+            assert_delaunay(&delaunay);
+
+            // the hull walks around the square, including the points along its sides
+            let hull = delaunay.get_hull_edges();
+            assert_eq!(hull.len(), 4 * (n - 1));
+            assert_eq!(hull[0][0], 0);
+            assert!(hull.windows(2).all(|w| w[0][1] == w[1][0]));
+            assert_eq!(hull[hull.len() - 1][1], 0);
+            assert_eq!(hull[0][1], 1);
+        }
+    }
+
+    #[test]
+    fn degenerate_input() {
+        let line: Vec<Vec2> = (0..5)
+            .map(|i| Vec2::new(i as f32, i as f32 * 2.0))
+            .collect();
+        assert!(Delaunay::new(&line).unwrap().triangles.is_empty());
+        assert!(Delaunay::new(&line[..2]).unwrap().triangles.is_empty());
+        assert!(Delaunay::new(&[Vec2::ZERO, Vec2::X, Vec2::new(f32::NAN, 0.0)]).is_err());
+
+        // cocircular points still cover the square
+        let grid: Vec<Vec2> = (0..16)
+            .map(|i| Vec2::new((i % 4) as f32, (i / 4) as f32))
+            .collect();
+        let delaunay = Delaunay::new(&grid).unwrap();
+        assert_eq!(delaunay.triangles.len(), 18);
+
+        // a long collinear run before the first point off the line
+        let mut fan: Vec<Vec2> = (0..6).map(|i| Vec2::new(0.0, i as f32)).collect();
+        fan.extend([
+            Vec2::new(3.0, 2.5),
+            Vec2::new(-1.0, 7.0),
+            Vec2::new(0.0, -3.0),
+        ]);
+        let delaunay = Delaunay::new(&fan).unwrap();
+        assert_delaunay(&delaunay);
+        let used: HashSet<usize> = delaunay.triangles.iter().flatten().copied().collect();
+        assert_eq!(used.len(), fan.len());
+    }
+}
@@ -0,0 +1,928 @@
+use std::path::Path;
+
+use gltf::animation::util::ReadOutputs;
+
+use super::mesh::{Mesh, PrimitiveMode};
+use paxil_math::*;
+
+// morph target offsets, each list is empty or one per vertex
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GltfMorphTarget {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub tangents: Vec<Vec3>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GltfPrimitive {
+    pub mesh: Mesh,
+    pub material: Option<usize>,
+    // TEXCOORD_1, commonly used for light and occlusion maps
+    pub uvs1: Vec<Vec2>,
+    pub joints: Vec<[u16; 4]>,
+    pub weights: Vec<Vec4>,
+    pub targets: Vec<GltfMorphTarget>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GltfMesh {
+    pub name: String,
+    pub primitives: Vec<GltfPrimitive>,
+    pub weights: Vec<f32>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GltfNode {
+    pub name: String,
+    pub transform: Transform,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+    pub mesh: Option<usize>,
+    pub camera: Option<usize>,
+    pub skin: Option<usize>,
+    pub weights: Vec<f32>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AlphaMode {
+    #[default]
+    Opaque,
+    Mask,
+    Blend,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GltfTextureRef {
+    pub texture: usize,
+    pub tex_coord: u32,
+    // normal scale or occlusion strength, 1 for the other slots
+    pub scale: f32,
+}
+
+// metallic-roughness PBR, factors are linear as in the file except base_color which is a Color
+#[derive(Clone, Debug, PartialEq)]
+pub struct GltfMaterial {
+    pub name: String,
+    pub base_color: Color,
+    pub base_color_texture: Option<GltfTextureRef>,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub metallic_roughness_texture: Option<GltfTextureRef>,
+    pub normal_texture: Option<GltfTextureRef>,
+    pub occlusion_texture: Option<GltfTextureRef>,
+    pub emissive: Vec3,
+    pub emissive_texture: Option<GltfTextureRef>,
+    pub alpha_mode: AlphaMode,
+    pub alpha_cutoff: f32,
+    pub double_sided: bool,
+}
+
+impl Default for GltfMaterial {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            base_color: Color::WHITE,
+            base_color_texture: None,
+            metallic: 1.0,
+            roughness: 1.0,
+            metallic_roughness_texture: None,
+            normal_texture: None,
+            occlusion_texture: None,
+            emissive: Vec3::ZERO,
+            emissive_texture: None,
+            alpha_mode: AlphaMode::Opaque,
+            alpha_cutoff: 0.5,
+            double_sided: false,
+        }
+    }
+}
+
+// GL enums as stored in the file, None leaves the filter up to the renderer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GltfSampler {
+    pub mag_filter: Option<u32>,
+    pub min_filter: Option<u32>,
+    pub wrap_s: u32,
+    pub wrap_t: u32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GltfTexture {
+    pub name: String,
+    pub image: usize,
+    pub sampler: GltfSampler,
+}
+
+// encoded image file contents, decoding is left to the caller
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GltfImage {
+    pub name: String,
+    pub mime_type: Option<String>,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GltfProjection {
+    Perspective {
+        y_fov: f32,
+        aspect_ratio: Option<f32>,
+        z_near: f32,
+        // None is an infinite projection
+        z_far: Option<f32>,
+    },
+    Orthographic {
+        x_mag: f32,
+        y_mag: f32,
+        z_near: f32,
+        z_far: f32,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GltfCamera {
+    pub name: String,
+    pub projection: GltfProjection,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GltfSkin {
+    pub name: String,
+    pub joints: Vec<usize>,
+    pub inverse_bind_matrices: Vec<Mat4>,
+    pub skeleton: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GltfChannelTarget {
+    Translation,
+    Rotation,
+    Scale,
+    Weights,
+}
+
+// cubic spline channels keep the glTF layout of in tangent, value, out tangent per key
+#[derive(Clone, Debug, PartialEq)]
+pub enum GltfChannelValues {
+    Vec3(Vec<Vec3>),
+    Quat(Vec<Quat>),
+    Weights(Vec<f32>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GltfChannel {
+    pub node: usize,
+    pub target: GltfChannelTarget,
+    pub interpolation: KeyframeInterpolation,
+    pub times: Vec<f32>,
+    pub values: GltfChannelValues,
+}
+
+impl GltfChannel {
+    fn to_track<T: Interpolate>(&self, values: &[T]) -> KeyframeTrack<T> {
+        let mut track = KeyframeTrack::new(self.interpolation);
+        let cubic = self.interpolation == KeyframeInterpolation::CubicHermite;
+        for (i, time) in self.times.iter().enumerate() {
+            if cubic {
+                track.add_key_with_tangents(
+                    *time,
+                    values[i * 3 + 1],
+                    values[i * 3],
+                    values[i * 3 + 2],
+                );
+            } else {
+                track.add_key(*time, values[i]);
+            }
+        }
+        track
+    }
+
+    pub fn to_vec3_track(&self) -> Option<KeyframeTrack<Vec3>> {
+        match &self.values {
+            GltfChannelValues::Vec3(values) => Some(self.to_track(values)),
+            _ => None,
+        }
+    }
+
+    pub fn to_quat_track(&self) -> Option<KeyframeTrack<Quat>> {
+        match &self.values {
+            GltfChannelValues::Quat(values) => Some(self.to_track(values)),
+            _ => None,
+        }
+    }
+
+    pub fn get_duration(&self) -> f32 {
+        self.times.last().copied().unwrap_or(0.0)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GltfAnimation {
+    pub name: String,
+    pub channels: Vec<GltfChannel>,
+}
+
+impl GltfAnimation {
+    pub fn get_duration(&self) -> f32 {
+        self.channels
+            .iter()
+            .map(|c| c.get_duration())
+            .fold(0.0, f32::max)
+    }
+
+    // writes translation, rotation and scale at time into the node transforms
+    pub fn apply(&self, time: f32, nodes: &mut [GltfNode]) {
+        for channel in &self.channels {
+            let Some(node) = nodes.get_mut(channel.node) else {
+                continue;
+            };
+            match channel.target {
+                GltfChannelTarget::Translation => {
+                    if let Some(v) = channel.to_vec3_track().and_then(|t| t.sample(time)) {
+                        node.transform.translation = v;
+                    }
+                }
+                GltfChannelTarget::Rotation => {
+                    if let Some(q) = channel.to_quat_track().and_then(|t| t.sample(time)) {
+                        node.transform.rotation = q.normalize();
+                    }
+                }
+                GltfChannelTarget::Scale => {
+                    if let Some(v) = channel.to_vec3_track().and_then(|t| t.sample(time)) {
+                        node.transform.scale = v;
+                    }
+                }
+                GltfChannelTarget::Weights => {}
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GltfScene {
+    pub meshes: Vec<GltfMesh>,
+    pub materials: Vec<GltfMaterial>,
+    pub textures: Vec<GltfTexture>,
+    pub images: Vec<GltfImage>,
+    pub nodes: Vec<GltfNode>,
+    // root nodes of the default scene
+    pub roots: Vec<usize>,
+    pub cameras: Vec<GltfCamera>,
+    pub skins: Vec<GltfSkin>,
+    pub animations: Vec<GltfAnimation>,
+}
+
+impl GltfScene {
+    pub fn get_world_matrix(&self, node: usize) -> Mat4 {
+        let mut matrix = self.nodes[node].transform.to_matrix();
+        let mut parent = self.nodes[node].parent;
+        while let Some(index) = parent {
+            matrix = self.nodes[index].transform.to_matrix() * matrix;
+            parent = self.nodes[index].parent;
+        }
+        matrix
+    }
+
+    pub fn get_world_matrices(&self) -> Vec<Mat4> {
+        let mut matrices = vec![Mat4::IDENTITY; self.nodes.len()];
+        let mut stack: Vec<(usize, Mat4)> =
+            self.roots.iter().map(|r| (*r, Mat4::IDENTITY)).collect();
+        while let Some((node, parent)) = stack.pop() {
+            matrices[node] = parent * self.nodes[node].transform.to_matrix();
+            stack.extend(
+                self.nodes[node]
+                    .children
+                    .iter()
+                    .map(|c| (*c, matrices[node])),
+            );
+        }
+        matrices
+    }
+
+    // joint matrices for skinning, in the space of the skinned mesh node
+    pub fn get_joint_matrices(&self, skin: usize, mesh_node: usize) -> Vec<Mat4> {
+        let world = self.get_world_matrices();
+        let inverse_mesh = world[mesh_node].inverse().unwrap_or(Mat4::IDENTITY);
+        let skin = &self.skins[skin];
+        skin.joints
+            .iter()
+            .enumerate()
+            .map(|(i, joint)| {
+                let inverse_bind = skin
+                    .inverse_bind_matrices
+                    .get(i)
+                    .copied()
+                    .unwrap_or(Mat4::IDENTITY);
+                inverse_mesh * world[*joint] * inverse_bind
+            })
+            .collect()
+    }
+}
+
+fn decode_base64(src: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(src.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in src.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' | b'\n' | b'\r' | b' ' => continue,
+            _ => return Err(format!("Invalid base64 character '{}'", c as char)),
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Ok(out)
+}
+
+fn decode_percent(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(value)) => {
+                out.push(value);
+                i += 3;
+            }
+            (c, _) => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+// data URIs are decoded in place, anything else is a path relative to the glTF file
+fn read_uri(uri: &str, base_dir: Option<&Path>) -> Result<(Vec<u8>, Option<String>), String> {
+    if let Some(data) = uri.strip_prefix("data:") {
+        let (header, payload) = data
+            .split_once(',')
+            .ok_or_else(|| "Malformed data URI".to_string())?;
+        let mime_type = header.split(';').next().filter(|m| !m.is_empty());
+        let bytes = if header.ends_with(";base64") {
+            decode_base64(payload)?
+        } else {
+            decode_percent(payload).into_bytes()
+        };
+        return Ok((bytes, mime_type.map(|m| m.to_string())));
+    }
+    if uri.contains("://") {
+        return Err(format!("Only local files are supported, got '{}'", uri));
+    }
+    let base_dir = base_dir.ok_or_else(|| format!("No directory to resolve '{}' against", uri))?;
+    let path = base_dir.join(decode_percent(uri));
+    let bytes =
+        std::fs::read(&path).map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    Ok((bytes, None))
+}
+
+fn get_texture_ref(info: Option<gltf::texture::Info>) -> Option<GltfTextureRef> {
+    info.map(|info| GltfTextureRef {
+        texture: info.texture().index(),
+        tex_coord: info.tex_coord(),
+        scale: 1.0,
+    })
+}
+
+fn get_primitive_mode(mode: gltf::mesh::Mode) -> PrimitiveMode {
+    match mode {
+        gltf::mesh::Mode::Points => PrimitiveMode::Points,
+        gltf::mesh::Mode::Lines => PrimitiveMode::Lines,
+        gltf::mesh::Mode::LineLoop => PrimitiveMode::LineLoop,
+        gltf::mesh::Mode::LineStrip => PrimitiveMode::LineStrip,
+        gltf::mesh::Mode::Triangles => PrimitiveMode::Triangles,
+        gltf::mesh::Mode::TriangleStrip => PrimitiveMode::TriangleStrip,
+        gltf::mesh::Mode::TriangleFan => PrimitiveMode::TriangleFan,
+    }
+}
+
+fn read_primitive(
+    primitive: &gltf::Primitive,
+    buffers: &[Vec<u8>],
+) -> Result<GltfPrimitive, String> {
+    let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|b| b.as_slice()));
+    let to_vec3 = |v: [f32; 3]| Vec3::new(v[0], v[1], v[2]);
+
+    let mut mesh = Mesh::new(get_primitive_mode(primitive.mode()));
+    mesh.positions = reader
+        .read_positions()
+        .ok_or_else(|| "Primitive has no POSITION attribute".to_string())?
+        .map(to_vec3)
+        .collect();
+    if let Some(normals) = reader.read_normals() {
+        mesh.normals = normals.map(to_vec3).collect();
+    }
+    if let Some(tangents) = reader.read_tangents() {
+        mesh.tangents = tangents
+            .map(|t| Vec4::new(t[0], t[1], t[2], t[3]))
+            .collect();
+    }
+    if let Some(uvs) = reader.read_tex_coords(0) {
+        mesh.uvs = uvs.into_f32().map(|t| Vec2::new(t[0], t[1])).collect();
+    }
+    // vertex colors are linear in glTF
+    if let Some(colors) = reader.read_colors(0) {
+        mesh.colors = colors
+            .into_rgba_f32()
+            .map(|c| Color::from_linear(Color::new(c[0], c[1], c[2], c[3])))
+            .collect();
+    }
+    if let Some(indices) = reader.read_indices() {
+        mesh.indices = indices.into_u32().collect();
+    }
+    mesh.validate()?;
+
+    let mut result = GltfPrimitive {
+        mesh,
+        material: primitive.material().index(),
+        ..Default::default()
+    };
+    if let Some(uvs) = reader.read_tex_coords(1) {
+        result.uvs1 = uvs.into_f32().map(|t| Vec2::new(t[0], t[1])).collect();
+    }
+    if let Some(joints) = reader.read_joints(0) {
+        result.joints = joints.into_u16().collect();
+    }
+    if let Some(weights) = reader.read_weights(0) {
+        result.weights = weights
+            .into_f32()
+            .map(|w| Vec4::new(w[0], w[1], w[2], w[3]))
+            .collect();
+    }
+    for (positions, normals, tangents) in reader.read_morph_targets() {
+        result.targets.push(GltfMorphTarget {
+            positions: positions
+                .map(|p| p.map(to_vec3).collect())
+                .unwrap_or_default(),
+            normals: normals
+                .map(|n| n.map(to_vec3).collect())
+                .unwrap_or_default(),
+            tangents: tangents
+                .map(|t| t.map(to_vec3).collect())
+                .unwrap_or_default(),
+        });
+    }
+    Ok(result)
+}
+
+fn read_material(material: &gltf::Material) -> GltfMaterial {
+    let pbr = material.pbr_metallic_roughness();
+    let [r, g, b, a] = pbr.base_color_factor();
+    GltfMaterial {
+        name: material.name().unwrap_or_default().to_string(),
+        base_color: Color::from_linear(Color::new(r, g, b, a)),
+        base_color_texture: get_texture_ref(pbr.base_color_texture()),
+        metallic: pbr.metallic_factor(),
+        roughness: pbr.roughness_factor(),
+        metallic_roughness_texture: get_texture_ref(pbr.metallic_roughness_texture()),
+        normal_texture: material.normal_texture().map(|t| GltfTextureRef {
+            texture: t.texture().index(),
+            tex_coord: t.tex_coord(),
+            scale: t.scale(),
+        }),
+        occlusion_texture: material.occlusion_texture().map(|t| GltfTextureRef {
+            texture: t.texture().index(),
+            tex_coord: t.tex_coord(),
+            scale: t.strength(),
+        }),
+        emissive: Vec3::from(material.emissive_factor()),
+        emissive_texture: get_texture_ref(material.emissive_texture()),
+        alpha_mode: match material.alpha_mode() {
+            gltf::material::AlphaMode::Opaque => AlphaMode::Opaque,
+            gltf::material::AlphaMode::Mask => AlphaMode::Mask,
+            gltf::material::AlphaMode::Blend => AlphaMode::Blend,
+        },
+        alpha_cutoff: material.alpha_cutoff().unwrap_or(0.5),
+        double_sided: material.double_sided(),
+    }
+}
+
+fn read_animation(
+    animation: &gltf::Animation,
+    buffers: &[Vec<u8>],
+) -> Result<GltfAnimation, String> {
+    let name = animation.name().unwrap_or_default().to_string();
+    let mut channels = Vec::new();
+    for channel in animation.channels() {
+        let reader = channel.reader(|buffer| buffers.get(buffer.index()).map(|b| b.as_slice()));
+        let times: Vec<f32> = reader
+            .read_inputs()
+            .ok_or_else(|| format!("Animation '{}' has a channel without times", name))?
+            .collect();
+        let outputs = reader
+            .read_outputs()
+            .ok_or_else(|| format!("Animation '{}' has a channel without values", name))?;
+        let (target, values) = match outputs {
+            ReadOutputs::Translations(v) => (
+                GltfChannelTarget::Translation,
+                GltfChannelValues::Vec3(v.map(Vec3::from).collect()),
+            ),
+            ReadOutputs::Rotations(v) => (
+                GltfChannelTarget::Rotation,
+                GltfChannelValues::Quat(
+                    v.into_f32()
+                        .map(|q| Quat::from_xyzw(q[0], q[1], q[2], q[3]))
+                        .collect(),
+                ),
+            ),
+            ReadOutputs::Scales(v) => (
+                GltfChannelTarget::Scale,
+                GltfChannelValues::Vec3(v.map(Vec3::from).collect()),
+            ),
+            ReadOutputs::MorphTargetWeights(v) => (
+                GltfChannelTarget::Weights,
+                GltfChannelValues::Weights(v.into_f32().collect()),
+            ),
+        };
+        let interpolation = match channel.sampler().interpolation() {
+            gltf::animation::Interpolation::Step => KeyframeInterpolation::Step,
+            gltf::animation::Interpolation::Linear => KeyframeInterpolation::Linear,
+            gltf::animation::Interpolation::CubicSpline => KeyframeInterpolation::CubicHermite,
+        };
+
+        let per_key = if interpolation == KeyframeInterpolation::CubicHermite {
+            3
+        } else {
+            1
+        };
+        let count = match &values {
+            GltfChannelValues::Vec3(v) => v.len(),
+            GltfChannelValues::Quat(v) => v.len(),
+            // one weight per morph target per key
+            GltfChannelValues::Weights(_) => times.len() * per_key,
+        };
+        if count != times.len() * per_key {
+            return Err(format!(
+                "Animation '{}' has {} values for {} keys",
+                name,
+                count,
+                times.len()
+            ));
+        }
+
+        channels.push(GltfChannel {
+            node: channel.target().node().index(),
+            target,
+            interpolation,
+            times,
+            values,
+        });
+    }
+    Ok(GltfAnimation { name, channels })
+}
+
+// base_dir resolves external buffers and images, None only allows embedded data
+pub fn parse_gltf(bytes: &[u8], base_dir: Option<&Path>) -> Result<GltfScene, String> {
+    let gltf::Gltf { document, mut blob } =
+        gltf::Gltf::from_slice(bytes).map_err(|e| e.to_string())?;
+
+    let mut buffers = Vec::new();
+    for buffer in document.buffers() {
+        let mut data = match buffer.source() {
+            gltf::buffer::Source::Bin => blob
+                .take()
+                .ok_or_else(|| "GLB binary chunk is missing".to_string())?,
+            gltf::buffer::Source::Uri(uri) => read_uri(uri, base_dir)?.0,
+        };
+        if data.len() < buffer.length() {
+            return Err(format!(
+                "Buffer {} has {} bytes, expected {}",
+                buffer.index(),
+                data.len(),
+                buffer.length()
+            ));
+        }
+        // the GLB chunk may carry up to 3 bytes of padding
+        data.truncate(buffer.length());
+        buffers.push(data);
+    }
+
+    let mut scene = GltfScene::default();
+    for image in document.images() {
+        let (data, mime_type) = match image.source() {
+            gltf::image::Source::View { view, mime_type } => {
+                let buffer = &buffers[view.buffer().index()];
+                let data = buffer
+                    .get(view.offset()..view.offset() + view.length())
+                    .ok_or_else(|| format!("Image {} view is out of range", image.index()))?;
+                (data.to_vec(), Some(mime_type.to_string()))
+            }
+            gltf::image::Source::Uri { uri, mime_type } => {
+                let (data, data_mime) = read_uri(uri, base_dir)?;
+                (data, mime_type.map(|m| m.to_string()).or(data_mime))
+            }
+        };
+        scene.images.push(GltfImage {
+            name: image.name().unwrap_or_default().to_string(),
+            mime_type,
+            data,
+        });
+    }
+
+    for texture in document.textures() {
+        let sampler = texture.sampler();
+        scene.textures.push(GltfTexture {
+            name: texture.name().unwrap_or_default().to_string(),
+            image: texture.source().index(),
+            sampler: GltfSampler {
+                mag_filter: sampler.mag_filter().map(|f| f.as_gl_enum()),
+                min_filter: sampler.min_filter().map(|f| f.as_gl_enum()),
+                wrap_s: sampler.wrap_s().as_gl_enum(),
+                wrap_t: sampler.wrap_t().as_gl_enum(),
+            },
+        });
+    }
+
+    scene.materials = document.materials().map(|m| read_material(&m)).collect();
+
+    for mesh in document.meshes() {
+        let name = mesh.name().unwrap_or_default().to_string();
+        let primitives = mesh
+            .primitives()
+            .map(|p| read_primitive(&p, &buffers))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Mesh '{}': {}", name, e))?;
+        scene.meshes.push(GltfMesh {
+            name,
+            primitives,
+            weights: mesh.weights().map(|w| w.to_vec()).unwrap_or_default(),
+        });
+    }
+
+    for node in document.nodes() {
+        let (translation, rotation, scale) = node.transform().decomposed();
+        let [x, y, z, w] = rotation;
+        scene.nodes.push(GltfNode {
+            name: node.name().unwrap_or_default().to_string(),
+            transform: Transform::new(
+                Vec3::from(translation),
+                Quat::from_xyzw(x, y, z, w),
+                Vec3::from(scale),
+            ),
+            parent: None,
+            children: node.children().map(|c| c.index()).collect(),
+            mesh: node.mesh().map(|m| m.index()),
+            camera: node.camera().map(|c| c.index()),
+            skin: node.skin().map(|s| s.index()),
+            weights: node.weights().map(|w| w.to_vec()).unwrap_or_default(),
+        });
+    }
+    for parent in 0..scene.nodes.len() {
+        for child in scene.nodes[parent].children.clone() {
+            scene.nodes[child].parent = Some(parent);
+        }
+    }
+    scene.roots = match document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+    {
+        Some(s) => s.nodes().map(|n| n.index()).collect(),
+        None => (0..scene.nodes.len())
+            .filter(|n| scene.nodes[*n].parent.is_none())
+            .collect(),
+    };
+
+    for camera in document.cameras() {
+        let projection = match camera.projection() {
+            gltf::camera::Projection::Perspective(p) => GltfProjection::Perspective {
+                y_fov: p.yfov(),
+                aspect_ratio: p.aspect_ratio(),
+                z_near: p.znear(),
+                z_far: p.zfar(),
+            },
+            gltf::camera::Projection::Orthographic(o) => GltfProjection::Orthographic {
+                x_mag: o.xmag(),
+                y_mag: o.ymag(),
+                z_near: o.znear(),
+                z_far: o.zfar(),
+            },
+        };
+        scene.cameras.push(GltfCamera {
+            name: camera.name().unwrap_or_default().to_string(),
+            projection,
+        });
+    }
+
+    for skin in document.skins() {
+        let reader = skin.reader(|buffer| buffers.get(buffer.index()).map(|b| b.as_slice()));
+        scene.skins.push(GltfSkin {
+            name: skin.name().unwrap_or_default().to_string(),
+            joints: skin.joints().map(|j| j.index()).collect(),
+            inverse_bind_matrices: reader
+                .read_inverse_bind_matrices()
+                .map(|m| {
+                    m.map(|m| Mat4::from_cols_array(&flatten_matrix(m)))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            skeleton: skin.skeleton().map(|s| s.index()),
+        });
+    }
+
+    scene.animations = document
+        .animations()
+        .map(|a| read_animation(&a, &buffers))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(scene)
+}
+
+fn flatten_matrix(m: [[f32; 4]; 4]) -> [f32; 16] {
+    let mut out = [0.0; 16];
+    for (i, v) in m.iter().flatten().enumerate() {
+        out[i] = *v;
+    }
+    out
+}
+
+// .gltf with embedded, data URI or sibling buffers, or binary .glb
+pub fn load_gltf<P: AsRef<Path>>(path: P) -> Result<GltfScene, String> {
+    let path = path.as_ref();
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    parse_gltf(&bytes, Some(path.parent().unwrap_or(Path::new(""))))
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_base64(bytes: &[u8]) -> String {
+        const TABLE: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let mut out = String::new();
+        for chunk in bytes.chunks(3) {
+            let b = [
+                chunk[0],
+                *chunk.get(1).unwrap_or(&0),
+                *chunk.get(2).unwrap_or(&0),
+            ];
+            let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    out.push(TABLE[(n >> (18 - i * 6)) as usize & 63] as char);
+                } else {
+                    out.push('=');
+                }
+            }
+        }
+        out
+    }
+
+    // one triangle, a two node hierarchy and a translation animation
+    fn triangle_gltf() -> String {
+        let mut bin: Vec<u8> = Vec::new();
+        for v in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            bin.extend_from_slice(&v.to_le_bytes());
+        }
+        for i in [0u16, 1, 2, 0] {
+            bin.extend_from_slice(&i.to_le_bytes());
+        }
+        for v in [0.0f32, 1.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0] {
+            bin.extend_from_slice(&v.to_le_bytes());
+        }
+        format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "scene": 0,
+                "scenes": [{{ "nodes": [0] }}],
+                "nodes": [
+                    {{ "name": "root", "translation": [0, 1, 0], "children": [1] }},
+                    {{ "name": "child", "mesh": 0, "camera": 0, "scale": [2, 2, 2] }}
+                ],
+                "meshes": [{{ "name": "tri", "primitives": [{{
+                    "attributes": {{ "POSITION": 0 }}, "indices": 1, "material": 0
+                }}] }}],
+                "materials": [{{
+                    "name": "red",
+                    "pbrMetallicRoughness": {{ "baseColorFactor": [1, 0, 0, 1], "metallicFactor": 0.25 }},
+                    "alphaMode": "MASK",
+                    "doubleSided": true
+                }}],
+                "cameras": [{{ "type": "perspective", "perspective": {{ "yfov": 1.0, "znear": 0.1 }} }}],
+                "animations": [{{
+                    "channels": [{{ "sampler": 0, "target": {{ "node": 1, "path": "translation" }} }}],
+                    "samplers": [{{ "input": 2, "output": 3 }}]
+                }}],
+                "accessors": [
+                    {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                       "min": [0, 0, 0], "max": [1, 1, 0] }},
+                    {{ "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }},
+                    {{ "bufferView": 2, "componentType": 5126, "count": 2, "type": "SCALAR",
+                       "min": [0], "max": [1] }},
+                    {{ "bufferView": 3, "componentType": 5126, "count": 2, "type": "VEC3" }}
+                ],
+                "bufferViews": [
+                    {{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }},
+                    {{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }},
+                    {{ "buffer": 0, "byteOffset": 44, "byteLength": 8 }},
+                    {{ "buffer": 0, "byteOffset": 52, "byteLength": 24 }}
+                ],
+                "buffers": [{{
+                    "byteLength": {},
+                    "uri": "data:application/octet-stream;base64,{}"
+                }}]
+            }}"#,
+            bin.len() + 24,
+            encode_base64(&[bin, vec![0; 24]].concat())
+        )
+    }
+
+    #[test]
+    fn base64_round_trip() {
+        for len in 0..8 {
+            let bytes: Vec<u8> = (0..len).map(|i| i * 37 + 5).collect();
+            assert_eq!(decode_base64(&encode_base64(&bytes)).unwrap(), bytes);
+        }
+        assert!(decode_base64("ab*c").is_err());
+        assert_eq!(decode_percent("a%20b%2"), "a b%2");
+    }
+
+    #[test]
+    fn loads_embedded_scene() {
+        let scene = parse_gltf(triangle_gltf().as_bytes(), None).unwrap();
+        let mesh = &scene.meshes[0].primitives[0].mesh;
+        assert_eq!(mesh.positions[1], Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(mesh.indices, vec![0, 1, 2]);
+        assert_eq!(scene.meshes[0].primitives[0].material, Some(0));
+
+        let material = &scene.materials[0];
+        assert!((material.base_color.r - 1.0).abs() < 1e-5 && material.base_color.g == 0.0);
+        assert_eq!(material.metallic, 0.25);
+        assert_eq!(material.alpha_mode, AlphaMode::Mask);
+        assert!(material.double_sided);
+
+        assert_eq!(scene.roots, vec![0]);
+        assert_eq!(scene.nodes[1].parent, Some(0));
+        let world = scene.get_world_matrix(1);
+        assert_eq!(world.transform_point3(Vec3::X), Vec3::new(2.0, 1.0, 0.0));
+        assert_eq!(scene.get_world_matrices()[1], world);
+        assert!(matches!(
+            scene.cameras[0].projection,
+            GltfProjection::Perspective { z_far: None, .. }
+        ));
+    }
+
+    #[test]
+    fn animation_drives_nodes() {
+        let mut scene = parse_gltf(triangle_gltf().as_bytes(), None).unwrap();
+        let animation = &scene.animations[0];
+        assert_eq!(animation.get_duration(), 1.0);
+        assert_eq!(animation.channels[0].target, GltfChannelTarget::Translation);
+
+        let animation = animation.clone();
+        animation.apply(0.5, &mut scene.nodes);
+        assert_eq!(
+            scene.nodes[1].transform.translation,
+            Vec3::new(1.0, 0.0, 0.0)
+        );
+        // translation channels keep the node's scale
+        assert_eq!(scene.nodes[1].transform.scale, Vec3::splat(2.0));
+    }
+
+    #[test]
+    fn cubic_splines_keep_in_and_out_tangents() {
+        // in tangent, value, out tangent per key, the second key arrives flat
+        let channel = GltfChannel {
+            node: 0,
+            target: GltfChannelTarget::Translation,
+            interpolation: KeyframeInterpolation::CubicHermite,
+            times: vec![0.0, 1.0],
+            values: GltfChannelValues::Vec3(vec![
+                Vec3::X,
+                Vec3::ZERO,
+                Vec3::X,
+                Vec3::ZERO,
+                Vec3::X,
+                Vec3::X,
+            ]),
+        };
+        let track = channel.to_vec3_track().unwrap();
+        let v = track.sample(0.5).unwrap();
+        assert!(v.abs_diff_eq(Vec3::new(0.625, 0.0, 0.0), 1e-5));
+    }
+
+    #[test]
+    fn external_buffers_need_a_directory() {
+        let src = triangle_gltf();
+        let start = src.find("data:").unwrap();
+        let end = src[start..].find('"').unwrap() + start;
+        let external = format!("{}missing.bin{}", &src[..start], &src[end..]);
+        assert!(parse_gltf(external.as_bytes(), None).is_err());
+
+        let dir = std::env::temp_dir().join("paxil_gltf_missing");
+        assert!(parse_gltf(external.as_bytes(), Some(&dir)).is_err());
+    }
+}
@@ -0,0 +1,960 @@
+use std::collections::{HashMap, HashSet};
+
+use super::mesh::{Mesh, PrimitiveMode};
+use super::obj::{get_polygon_normal, triangulate_face};
+use paxil_math::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HeVertex {
+    pub position: Vec3,
+    // outgoing half-edge, a boundary one when the vertex is on a boundary
+    pub halfedge: Option<usize>,
+    pub removed: bool,
+}
+
+// every half-edge has a twin, boundary half-edges have no face and form loops around holes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HalfEdge {
+    pub origin: usize,
+    pub twin: usize,
+    pub next: usize,
+    pub prev: usize,
+    pub face: Option<usize>,
+    pub removed: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HeFace {
+    pub halfedge: usize,
+    pub removed: bool,
+}
+
+// edits mark elements as removed instead of shifting indices, compact() drops them
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HalfEdgeMesh {
+    pub vertices: Vec<HeVertex>,
+    pub halfedges: Vec<HalfEdge>,
+    pub faces: Vec<HeFace>,
+    // uv of the face corner at each half-edge's origin, empty when there are none
+    pub uvs: Vec<Vec2>,
+}
+
+fn position_key(p: Vec3) -> [u32; 3] {
+    [p.x, p.y, p.z].map(|c| (c + 0.0).to_bits())
+}
+
+// maps every mesh vertex to a vertex of equal position
+fn weld_positions(mesh: &Mesh) -> (Vec<Vec3>, Vec<usize>) {
+    let mut positions = Vec::new();
+    let mut keys: HashMap<[u32; 3], usize> = HashMap::new();
+    let remap = mesh
+        .positions
+        .iter()
+        .map(|p| {
+            *keys.entry(position_key(*p)).or_insert_with(|| {
+                positions.push(*p);
+                positions.len() - 1
+            })
+        })
+        .collect();
+    (positions, remap)
+}
+
+fn get_welded_triangles(mesh: &Mesh, remap: &[usize]) -> Result<Vec<[usize; 3]>, String> {
+    mesh.validate()?;
+    if !mesh.mode.is_triangles() {
+        return Err(format!(
+            "Cannot build a half-edge mesh from {:?} primitives",
+            mesh.mode
+        ));
+    }
+    Ok(mesh
+        .get_triangles()
+        .iter()
+        .map(|t| t.map(|i| remap[i as usize]))
+        .collect())
+}
+
+impl Mesh {
+    // edges, by welded position, used by more than two faces or twice in the same direction
+    pub fn get_non_manifold_edges(&self) -> Result<Vec<[Vec3; 2]>, String> {
+        let (positions, remap) = weld_positions(self);
+        let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+        for t in get_welded_triangles(self, &remap)? {
+            for k in 0..3 {
+                *edges.entry((t[k], t[(k + 1) % 3])).or_default() += 1;
+            }
+        }
+        let mut result = Vec::new();
+        let mut reported = HashSet::new();
+        for (&(a, b), &count) in &edges {
+            let reverse = edges.get(&(b, a)).copied().unwrap_or(0);
+            if (count > 1 || reverse > 1) && reported.insert((a.min(b), a.max(b))) {
+                result.push([positions[a], positions[b]]);
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl HalfEdgeMesh {
+    // faces are vertex loops in counter clockwise order
+    pub fn from_polygons(positions: &[Vec3], faces: &[Vec<usize>]) -> Result<Self, String> {
+        let mut mesh = Self {
+            vertices: positions
+                .iter()
+                .map(|p| HeVertex {
+                    position: *p,
+                    halfedge: None,
+                    removed: false,
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+        for (f, face) in faces.iter().enumerate() {
+            if face.len() < 3 {
+                return Err(format!("Face {} has fewer than 3 vertices", f));
+            }
+            if let Some(v) = face.iter().find(|v| **v >= positions.len()) {
+                return Err(format!(
+                    "Face {} uses vertex {} which is out of range",
+                    f, v
+                ));
+            }
+            let first = mesh.halfedges.len();
+            for (k, a) in face.iter().enumerate() {
+                let b = face[(k + 1) % face.len()];
+                if *a == b {
+                    return Err(format!("Face {} repeats vertex {}", f, a));
+                }
+                let h = first + k;
+                if edges.insert((*a, b), h).is_some() {
+                    return Err(format!(
+                        "Edge {}-{} is shared by more than two faces or has inconsistent winding",
+                        a, b
+                    ));
+                }
+                mesh.halfedges.push(HalfEdge {
+                    origin: *a,
+                    twin: usize::MAX,
+                    next: first + (k + 1) % face.len(),
+                    prev: first + (k + face.len() - 1) % face.len(),
+                    face: Some(f),
+                    removed: false,
+                });
+                mesh.vertices[*a].halfedge = Some(h);
+            }
+            mesh.faces.push(HeFace {
+                halfedge: first,
+                removed: false,
+            });
+        }
+
+        // pair up twins, edges without one get a boundary half-edge
+        let interior = mesh.halfedges.len();
+        for h in 0..interior {
+            if mesh.halfedges[h].twin != usize::MAX {
+                continue;
+            }
+            let a = mesh.halfedges[h].origin;
+            let b = mesh.halfedges[mesh.halfedges[h].next].origin;
+            match edges.get(&(b, a)) {
+                Some(&t) => {
+                    mesh.halfedges[h].twin = t;
+                    mesh.halfedges[t].twin = h;
+                }
+                None => {
+                    let t = mesh.halfedges.len();
+                    mesh.halfedges.push(HalfEdge {
+                        origin: b,
+                        twin: h,
+                        next: usize::MAX,
+                        prev: usize::MAX,
+                        face: None,
+                        removed: false,
+                    });
+                    mesh.halfedges[h].twin = t;
+                }
+            }
+        }
+
+        // the next boundary half-edge is found by turning around the end vertex within its fan
+        for b in interior..mesh.halfedges.len() {
+            let mut h = mesh.halfedges[b].twin;
+            while mesh.halfedges[h].face.is_some() {
+                h = mesh.halfedges[mesh.halfedges[h].prev].twin;
+            }
+            mesh.link(b, h);
+            mesh.vertices[mesh.halfedges[h].origin].halfedge = Some(h);
+        }
+        Ok(mesh)
+    }
+
+    // positions are welded so uv seams stay connected, uvs are kept per face corner
+    pub fn from_mesh(mesh: &Mesh) -> Result<Self, String> {
+        let (positions, remap) = weld_positions(mesh);
+        let source = mesh.get_triangles();
+        let mut faces = Vec::with_capacity(source.len());
+        let mut corners = Vec::with_capacity(source.len());
+        for (t, welded) in source.iter().zip(get_welded_triangles(mesh, &remap)?) {
+            // triangles that collapse when welded carry no area
+            if welded[0] != welded[1] && welded[1] != welded[2] && welded[2] != welded[0] {
+                faces.push(welded.to_vec());
+                corners.push(*t);
+            }
+        }
+
+        let mut result = Self::from_polygons(&positions, &faces)?;
+        if mesh.has_uvs() {
+            result.uvs = vec![Vec2::ZERO; result.halfedges.len()];
+            for (f, t) in corners.iter().enumerate() {
+                let first = result.faces[f].halfedge;
+                for (k, vertex) in t.iter().enumerate() {
+                    result.uvs[first + k] = mesh.uvs[*vertex as usize];
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    // indexed triangle list, polygons are ear clipped and vertices split along uv seams
+    pub fn to_mesh(&self) -> Mesh {
+        let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+        let mut vertices: HashMap<(usize, [u32; 2]), u32> = HashMap::new();
+        for f in self.get_faces() {
+            let loop_ = self.get_face_halfedges(f);
+            let mut indices = Vec::with_capacity(loop_.len());
+            for h in &loop_ {
+                let v = self.halfedges[*h].origin;
+                let uv = self.uvs.get(*h).copied();
+                let key = uv.map_or([0; 2], |t| [t.x.to_bits(), t.y.to_bits()]);
+                let index = *vertices.entry((v, key)).or_insert_with(|| {
+                    mesh.positions.push(self.vertices[v].position);
+                    if let Some(uv) = uv {
+                        mesh.uvs.push(uv);
+                    }
+                    mesh.positions.len() as u32 - 1
+                });
+                indices.push(index);
+            }
+            let points: Vec<Vec3> = indices
+                .iter()
+                .map(|i| mesh.positions[*i as usize])
+                .collect();
+            for [a, b, c] in triangulate_face(&points) {
+                mesh.indices
+                    .extend_from_slice(&[indices[a], indices[b], indices[c]]);
+            }
+        }
+        mesh
+    }
+
+    fn link(&mut self, a: usize, b: usize) {
+        self.halfedges[a].next = b;
+        self.halfedges[b].prev = a;
+    }
+
+    fn add_vertex(&mut self, position: Vec3) -> usize {
+        self.vertices.push(HeVertex {
+            position,
+            halfedge: None,
+            removed: false,
+        });
+        self.vertices.len() - 1
+    }
+
+    // a twin pair a -> b and b -> a, left for the caller to link
+    fn add_edge(&mut self, a: usize, b: usize) -> (usize, usize) {
+        let h = self.halfedges.len();
+        for (origin, twin) in [(a, h + 1), (b, h)] {
+            self.halfedges.push(HalfEdge {
+                origin,
+                twin,
+                next: usize::MAX,
+                prev: usize::MAX,
+                face: None,
+                removed: false,
+            });
+            if !self.uvs.is_empty() {
+                self.uvs.push(Vec2::ZERO);
+            }
+        }
+        (h, h + 1)
+    }
+
+    fn add_face(&mut self, halfedge: usize) -> usize {
+        self.faces.push(HeFace {
+            halfedge,
+            removed: false,
+        });
+        let f = self.faces.len() - 1;
+        for h in self.get_loop(halfedge) {
+            self.halfedges[h].face = Some(f);
+        }
+        f
+    }
+
+    pub fn get_destination(&self, h: usize) -> usize {
+        self.halfedges[self.halfedges[h].twin].origin
+    }
+
+    pub fn get_vertices(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.vertices.len()).filter(|v| !self.vertices[*v].removed)
+    }
+
+    pub fn get_faces(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.faces.len()).filter(|f| !self.faces[*f].removed)
+    }
+
+    // one half-edge per edge
+    pub fn get_edges(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.halfedges.len())
+            .filter(|h| !self.halfedges[*h].removed && *h < self.halfedges[*h].twin)
+    }
+
+    pub fn get_vertex_count(&self) -> usize {
+        self.get_vertices().count()
+    }
+
+    pub fn get_face_count(&self) -> usize {
+        self.get_faces().count()
+    }
+
+    pub fn get_edge_count(&self) -> usize {
+        self.get_edges().count()
+    }
+
+    // follows next pointers from h until it returns to h
+    pub fn get_loop(&self, h: usize) -> Vec<usize> {
+        let mut result = vec![h];
+        let mut current = self.halfedges[h].next;
+        while current != h {
+            result.push(current);
+            current = self.halfedges[current].next;
+        }
+        result
+    }
+
+    pub fn get_face_halfedges(&self, f: usize) -> Vec<usize> {
+        self.get_loop(self.faces[f].halfedge)
+    }
+
+    pub fn get_face_vertices(&self, f: usize) -> Vec<usize> {
+        self.get_face_halfedges(f)
+            .iter()
+            .map(|h| self.halfedges[*h].origin)
+            .collect()
+    }
+
+    pub fn get_face_normal(&self, f: usize) -> Vec3 {
+        let points: Vec<Vec3> = self
+            .get_face_vertices(f)
+            .iter()
+            .map(|v| self.vertices[*v].position)
+            .collect();
+        get_polygon_normal(&points).normalize()
+    }
+
+    pub fn get_face_center(&self, f: usize) -> Vec3 {
+        let vertices = self.get_face_vertices(f);
+        let sum = vertices
+            .iter()
+            .fold(Vec3::ZERO, |sum, v| sum + self.vertices[*v].position);
+        sum / vertices.len() as f32
+    }
+
+    // outgoing half-edges in order around the vertex, one fan for non-manifold vertices
+    pub fn get_outgoing(&self, v: usize) -> Vec<usize> {
+        let Some(start) = self.vertices[v].halfedge else {
+            return Vec::new();
+        };
+        let mut result = vec![start];
+        let mut h = self.halfedges[self.halfedges[start].prev].twin;
+        while h != start {
+            result.push(h);
+            h = self.halfedges[self.halfedges[h].prev].twin;
+        }
+        result
+    }
+
+    pub fn get_one_ring(&self, v: usize) -> Vec<usize> {
+        self.get_outgoing(v)
+            .iter()
+            .map(|h| self.get_destination(*h))
+            .collect()
+    }
+
+    pub fn get_vertex_faces(&self, v: usize) -> Vec<usize> {
+        self.get_outgoing(v)
+            .iter()
+            .filter_map(|h| self.halfedges[*h].face)
+            .collect()
+    }
+
+    pub fn get_valence(&self, v: usize) -> usize {
+        self.get_outgoing(v).len()
+    }
+
+    pub fn is_boundary_halfedge(&self, h: usize) -> bool {
+        self.halfedges[h].face.is_none()
+    }
+
+    pub fn is_boundary_edge(&self, h: usize) -> bool {
+        self.is_boundary_halfedge(h) || self.is_boundary_halfedge(self.halfedges[h].twin)
+    }
+
+    pub fn is_boundary_vertex(&self, v: usize) -> bool {
+        self.vertices[v]
+            .halfedge
+            .is_some_and(|h| self.is_boundary_halfedge(h))
+    }
+
+    // vertex loops around each hole, in the order of their boundary half-edges
+    pub fn get_boundary_loops(&self) -> Vec<Vec<usize>> {
+        let mut visited = HashSet::new();
+        let mut loops = Vec::new();
+        for h in 0..self.halfedges.len() {
+            let he = &self.halfedges[h];
+            if he.removed || he.face.is_some() || visited.contains(&h) {
+                continue;
+            }
+            let loop_ = self.get_loop(h);
+            visited.extend(loop_.iter().copied());
+            loops.push(loop_.iter().map(|h| self.halfedges[*h].origin).collect());
+        }
+        loops
+    }
+
+    // vertices where several fans meet, their one-ring only covers one of them
+    pub fn get_non_manifold_vertices(&self) -> Vec<usize> {
+        let mut counts = vec![0; self.vertices.len()];
+        for h in self.halfedges.iter().filter(|h| !h.removed) {
+            counts[h.origin] += 1;
+        }
+        self.get_vertices()
+            .filter(|v| self.get_valence(*v) != counts[*v])
+            .collect()
+    }
+
+    pub fn is_manifold(&self) -> bool {
+        self.get_non_manifold_vertices().is_empty()
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.halfedges.iter().all(|h| h.removed || h.face.is_some())
+    }
+
+    pub fn get_euler_characteristic(&self) -> i64 {
+        self.get_vertex_count() as i64 - self.get_edge_count() as i64 + self.get_face_count() as i64
+    }
+
+    // checks that every pointer agrees with the ones it points to
+    pub fn validate(&self) -> Result<(), String> {
+        for (h, he) in self.halfedges.iter().enumerate() {
+            if he.removed {
+                continue;
+            }
+            let twin = &self.halfedges[he.twin];
+            let next = &self.halfedges[he.next];
+            if twin.removed || twin.twin != h || twin.origin == he.origin {
+                return Err(format!("Half-edge {} has an inconsistent twin", h));
+            }
+            if next.removed || next.prev != h || next.face != he.face {
+                return Err(format!("Half-edge {} has an inconsistent next", h));
+            }
+            if next.origin != twin.origin {
+                return Err(format!(
+                    "Half-edge {} does not end where its next starts",
+                    h
+                ));
+            }
+            if self.vertices[he.origin].removed {
+                return Err(format!("Half-edge {} starts at a removed vertex", h));
+            }
+            if let Some(f) = he.face {
+                if self.faces[f].removed {
+                    return Err(format!("Half-edge {} belongs to a removed face", h));
+                }
+            }
+        }
+        for v in self.get_vertices() {
+            if let Some(h) = self.vertices[v].halfedge {
+                if self.halfedges[h].removed || self.halfedges[h].origin != v {
+                    return Err(format!("Vertex {} points to a foreign half-edge", v));
+                }
+            }
+        }
+        for f in self.get_faces() {
+            let h = self.faces[f].halfedge;
+            if self.halfedges[h].removed || self.halfedges[h].face != Some(f) {
+                return Err(format!("Face {} points to a foreign half-edge", f));
+            }
+        }
+        Ok(())
+    }
+
+    // points the vertex at an outgoing boundary half-edge if it has one
+    fn update_vertex(&mut self, v: usize) {
+        let outgoing = self.get_outgoing(v);
+        if let Some(h) = outgoing.iter().find(|h| self.is_boundary_halfedge(**h)) {
+            self.vertices[v].halfedge = Some(*h);
+        }
+    }
+
+    // connects the origins of two non adjacent half-edges of a face, returns the new face
+    pub fn split_face(&mut self, ha: usize, hb: usize) -> Result<usize, String> {
+        let f = self.halfedges[ha].face;
+        if f.is_none() || f != self.halfedges[hb].face {
+            return Err("Half-edges do not belong to the same face".to_string());
+        }
+        if ha == hb || self.halfedges[ha].next == hb || self.halfedges[hb].next == ha {
+            return Err("Cannot split a face between adjacent vertices".to_string());
+        }
+        let (a, b) = (self.halfedges[ha].origin, self.halfedges[hb].origin);
+        let (pa, pb) = (self.halfedges[ha].prev, self.halfedges[hb].prev);
+        let (ab, ba) = self.add_edge(a, b);
+        self.link(pb, ba);
+        self.link(ba, ha);
+        self.link(pa, ab);
+        self.link(ab, hb);
+        if !self.uvs.is_empty() {
+            self.uvs[ba] = self.uvs[hb];
+            self.uvs[ab] = self.uvs[ha];
+        }
+        let f = f.unwrap();
+        self.halfedges[ba].face = Some(f);
+        self.faces[f].halfedge = ha;
+        Ok(self.add_face(hb))
+    }
+
+    // inserts a vertex at t along the edge and splits adjacent triangles in two
+    pub fn split_edge(&mut self, h: usize, t: f32) -> usize {
+        let twin = self.halfedges[h].twin;
+        let (a, b) = (self.halfedges[h].origin, self.halfedges[twin].origin);
+        let m = self.add_vertex(self.vertices[a].position.lerp(self.vertices[b].position, t));
+        let triangles =
+            [h, twin].map(|e| self.halfedges[e].face.is_some() && self.get_loop(e).len() == 3);
+
+        let (h2, twin2) = self.add_edge(m, m);
+        let (next_h, next_twin) = (self.halfedges[h].next, self.halfedges[twin].next);
+        if !self.uvs.is_empty() {
+            self.uvs[h2] = self.uvs[h].lerp(self.uvs[next_h], t);
+            self.uvs[twin2] = self.uvs[twin].lerp(self.uvs[next_twin], 1.0 - t);
+        }
+        self.halfedges[h2].face = self.halfedges[h].face;
+        self.halfedges[twin2].face = self.halfedges[twin].face;
+        self.link(h2, next_h);
+        self.link(h, h2);
+        self.link(twin2, next_twin);
+        self.link(twin, twin2);
+        self.halfedges[h].twin = twin2;
+        self.halfedges[twin2].twin = h;
+        self.halfedges[h2].twin = twin;
+        self.halfedges[twin].twin = h2;
+        self.vertices[m].halfedge = Some(h2);
+        self.update_vertex(m);
+
+        for (e, triangle) in [(h2, triangles[0]), (twin2, triangles[1])] {
+            if triangle {
+                let opposite = self.halfedges[self.halfedges[e].next].next;
+                let _ = self.split_face(e, opposite);
+            }
+        }
+        m
+    }
+
+    // replaces the diagonal shared by two triangles with the other one
+    pub fn flip_edge(&mut self, h: usize) -> Result<(), String> {
+        let t = self.halfedges[h].twin;
+        let (Some(f1), Some(f2)) = (self.halfedges[h].face, self.halfedges[t].face) else {
+            return Err("Cannot flip a boundary edge".to_string());
+        };
+        if self.get_loop(h).len() != 3 || self.get_loop(t).len() != 3 {
+            return Err("Can only flip edges between two triangles".to_string());
+        }
+        let (h_bc, h_ca) = (self.halfedges[h].next, self.halfedges[h].prev);
+        let (t_ad, t_db) = (self.halfedges[t].next, self.halfedges[t].prev);
+        let (a, b) = (self.halfedges[h].origin, self.halfedges[t].origin);
+        let (c, d) = (self.halfedges[h_ca].origin, self.halfedges[t_db].origin);
+        if c == d || self.get_one_ring(c).contains(&d) {
+            return Err("Flipped edge would already exist".to_string());
+        }
+
+        self.halfedges[h].origin = d;
+        self.halfedges[t].origin = c;
+        self.link(h_ca, t_ad);
+        self.link(t_ad, h);
+        self.link(h, h_ca);
+        self.link(t_db, h_bc);
+        self.link(h_bc, t);
+        self.link(t, t_db);
+        self.halfedges[t_ad].face = Some(f1);
+        self.halfedges[h_bc].face = Some(f2);
+        self.faces[f1].halfedge = h;
+        self.faces[f2].halfedge = t;
+        if !self.uvs.is_empty() {
+            self.uvs[h] = self.uvs[t_db];
+            self.uvs[t] = self.uvs[h_ca];
+        }
+        if self.vertices[a].halfedge == Some(h) {
+            self.vertices[a].halfedge = Some(t_ad);
+        }
+        if self.vertices[b].halfedge == Some(t) {
+            self.vertices[b].halfedge = Some(h_bc);
+        }
+        Ok(())
+    }
+
+    // merges the end of h into its origin at t along the edge, returns the kept vertex.
+    // adjacent faces must be triangles and the collapse must keep the mesh manifold
+    pub fn collapse_edge(&mut self, h: usize, t: f32) -> Result<usize, String> {
+        let twin = self.halfedges[h].twin;
+        let (a, b) = (self.halfedges[h].origin, self.halfedges[twin].origin);
+        let mut opposite = HashSet::new();
+        for e in [h, twin] {
+            if self.halfedges[e].face.is_some() {
+                if self.get_loop(e).len() != 3 {
+                    return Err("Can only collapse edges between triangles".to_string());
+                }
+                let (next, prev) = (self.halfedges[e].next, self.halfedges[e].prev);
+                if self.is_boundary_halfedge(self.halfedges[next].twin)
+                    && self.is_boundary_halfedge(self.halfedges[prev].twin)
+                {
+                    return Err("Collapse would leave a dangling edge".to_string());
+                }
+                opposite.insert(self.halfedges[prev].origin);
+            }
+        }
+        // the link condition, shared neighbours may only be the opposite corners
+        let ring_a: HashSet<usize> = self.get_one_ring(a).into_iter().collect();
+        let ring_b: HashSet<usize> = self.get_one_ring(b).into_iter().collect();
+        if ring_a
+            .intersection(&ring_b)
+            .copied()
+            .collect::<HashSet<_>>()
+            != opposite
+        {
+            return Err("Collapse would make the mesh non-manifold".to_string());
+        }
+        if !self.is_boundary_edge(h) && self.is_boundary_vertex(a) && self.is_boundary_vertex(b) {
+            return Err("Collapse would pinch two boundaries together".to_string());
+        }
+
+        let outgoing: Vec<usize> = self
+            .get_outgoing(a)
+            .into_iter()
+            .chain(self.get_outgoing(b))
+            .collect();
+        for e in [h, twin] {
+            let (next, prev) = (self.halfedges[e].next, self.halfedges[e].prev);
+            match self.halfedges[e].face {
+                Some(f) => {
+                    let (o1, o2) = (self.halfedges[next].twin, self.halfedges[prev].twin);
+                    self.halfedges[o1].twin = o2;
+                    self.halfedges[o2].twin = o1;
+                    let c = self.halfedges[prev].origin;
+                    if self.vertices[c].halfedge == Some(prev) {
+                        self.vertices[c].halfedge = Some(o1);
+                    }
+                    for r in [next, prev] {
+                        self.halfedges[r].removed = true;
+                    }
+                    self.faces[f].removed = true;
+                }
+                None => self.link(prev, next),
+            }
+            self.halfedges[e].removed = true;
+        }
+
+        let position = self.vertices[a].position.lerp(self.vertices[b].position, t);
+        self.vertices[a].position = position;
+        self.vertices[b].removed = true;
+        self.vertices[b].halfedge = None;
+        self.vertices[a].halfedge = None;
+        for e in outgoing {
+            if !self.halfedges[e].removed {
+                self.halfedges[e].origin = a;
+                self.vertices[a].halfedge = Some(e);
+            }
+        }
+        self.update_vertex(a);
+        Ok(a)
+    }
+
+    // moves the face along its normal and bridges the gap with quads, returns the side faces
+    pub fn extrude_face(&mut self, f: usize, distance: f32) -> Vec<usize> {
+        let offset = self.get_face_normal(f) * distance;
+        let loop_ = self.get_face_halfedges(f);
+        let n = loop_.len();
+        let top: Vec<usize> = loop_
+            .iter()
+            .map(|h| {
+                let p = self.vertices[self.halfedges[*h].origin].position;
+                self.add_vertex(p + offset)
+            })
+            .collect();
+
+        // up[i] runs from the old vertex i to the new one, cap[i] along the new face
+        let up: Vec<(usize, usize)> = (0..n)
+            .map(|i| self.add_edge(self.halfedges[loop_[i]].origin, top[i]))
+            .collect();
+        let cap: Vec<(usize, usize)> = (0..n)
+            .map(|i| self.add_edge(top[i], top[(i + 1) % n]))
+            .collect();
+
+        for i in 0..n {
+            let j = (i + 1) % n;
+            self.link(cap[i].0, cap[j].0);
+            self.halfedges[cap[i].0].face = Some(f);
+            self.vertices[top[i]].halfedge = Some(cap[i].0);
+            if !self.uvs.is_empty() {
+                self.uvs[cap[i].0] = self.uvs[loop_[i]];
+                self.uvs[up[j].0] = self.uvs[loop_[j]];
+                self.uvs[cap[i].1] = self.uvs[loop_[j]];
+                self.uvs[up[i].1] = self.uvs[loop_[i]];
+            }
+        }
+        self.faces[f].halfedge = cap[0].0;
+
+        let mut sides = Vec::with_capacity(n);
+        for i in 0..n {
+            let j = (i + 1) % n;
+            let side = [loop_[i], up[j].0, cap[i].1, up[i].1];
+            for k in 0..4 {
+                self.link(side[k], side[(k + 1) % 4]);
+            }
+            sides.push(self.add_face(loop_[i]));
+        }
+        sides
+    }
+
+    // turns the boundary loop through h into a face
+    pub fn fill_hole(&mut self, h: usize) -> Result<usize, String> {
+        if !self.is_boundary_halfedge(h) {
+            return Err(format!("Half-edge {} is not on a boundary", h));
+        }
+        let f = self.add_face(h);
+        for e in self.get_loop(h) {
+            let v = self.halfedges[e].origin;
+            self.update_vertex(v);
+            if !self.uvs.is_empty() {
+                self.uvs[e] = self.uvs[self.halfedges[self.halfedges[e].prev].twin];
+            }
+        }
+        Ok(f)
+    }
+
+    pub fn fill_holes(&mut self) -> Vec<usize> {
+        let boundaries: Vec<usize> = (0..self.halfedges.len())
+            .filter(|h| !self.halfedges[*h].removed && self.is_boundary_halfedge(*h))
+            .collect();
+        let mut faces = Vec::new();
+        for h in boundaries {
+            if self.is_boundary_halfedge(h) {
+                faces.extend(self.fill_hole(h));
+            }
+        }
+        faces
+    }
+
+    // drops removed elements and renumbers the rest
+    pub fn compact(&mut self) {
+        fn remap<T>(items: &mut Vec<T>, removed: impl Fn(&T) -> bool) -> Vec<usize> {
+            let mut map = vec![usize::MAX; items.len()];
+            let mut next = 0;
+            for (i, item) in items.iter().enumerate() {
+                if !removed(item) {
+                    map[i] = next;
+                    next += 1;
+                }
+            }
+            let mut i = 0;
+            items.retain(|_| {
+                i += 1;
+                map[i - 1] != usize::MAX
+            });
+            map
+        }
+        if !self.uvs.is_empty() {
+            let mut i = 0;
+            let halfedges = &self.halfedges;
+            self.uvs.retain(|_| {
+                i += 1;
+                !halfedges[i - 1].removed
+            });
+        }
+        let vertices = remap(&mut self.vertices, |v| v.removed);
+        let halfedges = remap(&mut self.halfedges, |h| h.removed);
+        let faces = remap(&mut self.faces, |f| f.removed);
+        for v in &mut self.vertices {
+            v.halfedge = v.halfedge.map(|h| halfedges[h]);
+        }
+        for h in &mut self.halfedges {
+            h.origin = vertices[h.origin];
+            h.twin = halfedges[h.twin];
+            h.next = halfedges[h.next];
+            h.prev = halfedges[h.prev];
+            h.face = h.face.map(|f| faces[f]);
+        }
+        for f in &mut self.faces {
+            f.halfedge = halfedges[f.halfedge];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quad_grid() -> HalfEdgeMesh {
+        HalfEdgeMesh::from_mesh(&Mesh::plane(2.0, 2.0, 2, 2)).unwrap()
+    }
+
+    #[test]
+    fn cube_topology() {
+        let mut mesh = HalfEdgeMesh::from_mesh(&Mesh::cuboid(Vec3::ONE, [1, 1, 1])).unwrap();
+        assert!(mesh.validate().is_ok());
+        assert_eq!(mesh.get_vertex_count(), 8);
+        assert_eq!(mesh.get_edge_count(), 18);
+        assert_eq!(mesh.get_euler_characteristic(), 2);
+        assert!(mesh.is_closed() && mesh.is_manifold());
+        assert!(mesh.get_boundary_loops().is_empty());
+        for v in mesh.get_vertices() {
+            let ring = mesh.get_one_ring(v);
+            assert!((3..=6).contains(&ring.len()));
+        }
+
+        // uv seams survive the round trip even though positions are welded
+        let triangles = mesh.to_mesh();
+        assert_eq!(triangles.get_primitive_count(), 12);
+        assert!(triangles.get_vertex_count() > 8);
+        assert_eq!(triangles.uvs.len(), triangles.get_vertex_count());
+
+        mesh.faces[0].removed = true;
+        assert!(mesh.validate().is_err());
+    }
+
+    #[test]
+    fn boundaries_and_holes() {
+        let mut mesh = quad_grid();
+        assert_eq!(mesh.get_vertex_count(), 9);
+        let loops = mesh.get_boundary_loops();
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].len(), 8);
+        assert_eq!(mesh.get_non_manifold_vertices(), Vec::<usize>::new());
+
+        let center = mesh
+            .get_vertices()
+            .find(|v| !mesh.is_boundary_vertex(*v))
+            .unwrap();
+        assert_eq!(
+            mesh.get_valence(center),
+            mesh.get_vertex_faces(center).len()
+        );
+
+        let faces = mesh.fill_holes();
+        assert_eq!(faces.len(), 1);
+        assert!(mesh.is_closed());
+        assert!(mesh.validate().is_ok());
+        assert_eq!(mesh.get_euler_characteristic(), 2);
+    }
+
+    #[test]
+    fn edge_operations_keep_consistency() {
+        let mut mesh = quad_grid();
+        let faces = mesh.get_face_count();
+        let interior = mesh
+            .get_edges()
+            .find(|h| !mesh.is_boundary_edge(*h))
+            .unwrap();
+
+        mesh.flip_edge(interior).unwrap();
+        assert!(mesh.validate().is_ok());
+        let boundary = mesh
+            .get_edges()
+            .find(|h| mesh.is_boundary_edge(*h))
+            .unwrap();
+        assert!(mesh.flip_edge(boundary).is_err());
+
+        let m = mesh.split_edge(interior, 0.5);
+        assert!(mesh.validate().is_ok());
+        assert_eq!(mesh.get_face_count(), faces + 2);
+        assert_eq!(mesh.get_valence(m), 4);
+
+        let boundary = mesh
+            .get_edges()
+            .find(|h| mesh.is_boundary_edge(*h))
+            .unwrap();
+        let m = mesh.split_edge(boundary, 0.25);
+        assert!(mesh.validate().is_ok());
+        assert!(mesh.is_boundary_vertex(m));
+        assert_eq!(mesh.get_face_count(), faces + 3);
+
+        let outgoing = mesh.get_outgoing(m);
+        let h = *outgoing
+            .iter()
+            .find(|h| !mesh.is_boundary_edge(**h))
+            .unwrap();
+        mesh.collapse_edge(h, 0.0).unwrap();
+        assert!(mesh.validate().is_ok());
+        assert_eq!(mesh.get_face_count(), faces + 1);
+        assert!(mesh.is_manifold());
+        assert_eq!(mesh.get_euler_characteristic(), 1);
+
+        mesh.compact();
+        assert!(mesh.validate().is_ok());
+        assert_eq!(mesh.faces.len(), faces + 1);
+        assert_eq!(mesh.to_mesh().get_primitive_count(), faces + 1);
+    }
+
+    #[test]
+    fn extrusion_closes_a_box() {
+        let square = vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        ];
+        let mut mesh = HalfEdgeMesh::from_polygons(&square, &[vec![0, 1, 2, 3]]).unwrap();
+        let sides = mesh.extrude_face(0, 2.0);
+        assert_eq!(sides.len(), 4);
+        assert!(mesh.validate().is_ok());
+        assert_eq!(mesh.get_vertex_count(), 8);
+        assert_eq!(mesh.get_face_center(0), Vec3::new(0.5, 0.5, 2.0));
+        assert_eq!(mesh.get_boundary_loops()[0].len(), 4);
+        mesh.fill_holes();
+        assert!(mesh.is_closed());
+        assert_eq!(mesh.get_euler_characteristic(), 2);
+    }
+
+    #[test]
+    fn detects_non_manifold_input() {
+        // three triangles on one edge
+        let mut fin = Mesh::from_positions(
+            vec![Vec3::ZERO, Vec3::X, Vec3::Y, -Vec3::Y, Vec3::Z],
+            vec![0, 1, 2, 1, 0, 3, 0, 1, 4],
+        );
+        assert!(HalfEdgeMesh::from_mesh(&fin).is_err());
+        assert_eq!(fin.get_non_manifold_edges().unwrap().len(), 1);
+        fin.indices.truncate(6);
+        assert!(fin.get_non_manifold_edges().unwrap().is_empty());
+
+        // two triangles touching at a single vertex
+        let bowtie = HalfEdgeMesh::from_polygons(
+            &[
+                Vec3::ZERO,
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec3::new(1.0, 1.0, 0.0),
+                Vec3::new(-1.0, 0.0, 0.0),
+                Vec3::new(-1.0, -1.0, 0.0),
+            ],
+            &[vec![0, 1, 2], vec![0, 3, 4]],
+        )
+        .unwrap();
+        assert!(bowtie.validate().is_ok());
+        assert_eq!(bowtie.get_non_manifold_vertices(), vec![0]);
+        assert_eq!(bowtie.get_boundary_loops().len(), 2);
+    }
+}
@@ -0,0 +1,435 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use super::mesh::{Mesh, PrimitiveMode};
+use paxil_math::*;
+
+// scalar samples on a regular lattice spanning min..max, x varies fastest
+// values below the iso level are inside, generated normals point towards larger values
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScalarGrid {
+    pub dims: [usize; 3],
+    pub min: Vec3,
+    pub max: Vec3,
+    pub values: Vec<f32>,
+}
+
+impl ScalarGrid {
+    pub fn new(dims: [usize; 3], min: Vec3, max: Vec3) -> Result<Self, String> {
+        Self::from_values(dims, min, max, vec![0.0; dims.iter().product()])
+    }
+
+    pub fn from_values(
+        dims: [usize; 3],
+        min: Vec3,
+        max: Vec3,
+        values: Vec<f32>,
+    ) -> Result<Self, String> {
+        if dims.iter().any(|d| *d < 2) {
+            return Err(format!(
+                "Grid needs at least 2 samples per axis, got {:?}",
+                dims
+            ));
+        }
+        if values.len() != dims.iter().product::<usize>() {
+            return Err(format!(
+                "Grid of {:?} needs {} values, got {}",
+                dims,
+                dims.iter().product::<usize>(),
+                values.len()
+            ));
+        }
+        Ok(Self {
+            dims,
+            min,
+            max,
+            values,
+        })
+    }
+
+    pub fn from_fn<F: Fn(Vec3) -> f32>(
+        dims: [usize; 3],
+        min: Vec3,
+        max: Vec3,
+        f: F,
+    ) -> Result<Self, String> {
+        let mut grid = Self::new(dims, min, max)?;
+        for z in 0..dims[2] {
+            for y in 0..dims[1] {
+                for x in 0..dims[0] {
+                    let i = grid.get_index(x, y, z);
+                    grid.values[i] = f(grid.get_position(x, y, z));
+                }
+            }
+        }
+        Ok(grid)
+    }
+
+    pub fn get_index(&self, x: usize, y: usize, z: usize) -> usize {
+        (z * self.dims[1] + y) * self.dims[0] + x
+    }
+
+    pub fn get_value(&self, x: usize, y: usize, z: usize) -> f32 {
+        self.values[self.get_index(x, y, z)]
+    }
+
+    pub fn set_value(&mut self, x: usize, y: usize, z: usize, value: f32) {
+        let i = self.get_index(x, y, z);
+        self.values[i] = value;
+    }
+
+    pub fn get_cell_size(&self) -> Vec3 {
+        let [x, y, z] = self.dims.map(|d| (d - 1) as f32);
+        (self.max - self.min) / Vec3::new(x, y, z)
+    }
+
+    pub fn get_position(&self, x: usize, y: usize, z: usize) -> Vec3 {
+        self.min + self.get_cell_size() * Vec3::new(x as f32, y as f32, z as f32)
+    }
+
+    // central differences, one sided on the faces of the grid
+    pub fn get_gradient(&self, x: usize, y: usize, z: usize) -> Vec3 {
+        let p = [x, y, z];
+        let cell = self.get_cell_size();
+        let mut gradient = [0.0; 3];
+        for (axis, g) in gradient.iter_mut().enumerate() {
+            let (mut lo, mut hi) = (p, p);
+            lo[axis] = p[axis].saturating_sub(1);
+            hi[axis] = (p[axis] + 1).min(self.dims[axis] - 1);
+            let spacing = (hi[axis] - lo[axis]) as f32 * cell[axis];
+            *g = (self.get_value(hi[0], hi[1], hi[2]) - self.get_value(lo[0], lo[1], lo[2]))
+                / spacing;
+        }
+        Vec3::from(gradient)
+    }
+
+    // trilinear interpolation, clamped to the grid
+    pub fn sample(&self, p: Vec3) -> f32 {
+        self.interpolate(p, |x, y, z| self.get_value(x, y, z))
+    }
+
+    pub fn sample_gradient(&self, p: Vec3) -> Vec3 {
+        self.interpolate(p, |x, y, z| self.get_gradient(x, y, z))
+    }
+
+    fn interpolate<T, F>(&self, p: Vec3, f: F) -> T
+    where
+        T: std::ops::Mul<f32, Output = T> + std::ops::Add<Output = T>,
+        F: Fn(usize, usize, usize) -> T,
+    {
+        let local = (p - self.min) / self.get_cell_size();
+        let mut base = [0; 3];
+        let mut t = [0.0; 3];
+        for axis in 0..3 {
+            let max = (self.dims[axis] - 1) as f32;
+            let c = local[axis].clamp(0.0, max);
+            base[axis] = (c.floor() as usize).min(self.dims[axis] - 2);
+            t[axis] = c - base[axis] as f32;
+        }
+        let corner = |i: usize| {
+            let [x, y, z] = get_corner_offset(i);
+            let weight = (0..3)
+                .map(|axis| {
+                    if [x, y, z][axis] == 1 {
+                        t[axis]
+                    } else {
+                        1.0 - t[axis]
+                    }
+                })
+                .product::<f32>();
+            f(base[0] + x, base[1] + y, base[2] + z) * weight
+        };
+        (1..8).fold(corner(0), |sum, i| sum + corner(i))
+    }
+}
+
+// corner i of a cell sits at offset (i & 1, (i >> 1) & 1, (i >> 2) & 1)
+fn get_corner_offset(i: usize) -> [usize; 3] {
+    [i & 1, (i >> 1) & 1, (i >> 2) & 1]
+}
+
+// edges 0..4 run along x, 4..8 along y and 8..12 along z, each from the corner with that bit clear
+pub fn get_cube_edge(edge: usize) -> [usize; 2] {
+    let (axis, k) = (edge / 4, edge % 4);
+    let low = k & ((1 << axis) - 1);
+    let start = ((k >> axis) << (axis + 1)) | low;
+    [start, start | (1 << axis)]
+}
+
+fn get_edge_between(a: usize, b: usize) -> usize {
+    (0..12)
+        .find(|e| {
+            let [s, t] = get_cube_edge(*e);
+            (s, t) == (a.min(b), a.max(b))
+        })
+        .unwrap()
+}
+
+// the corners of each cube face, counter-clockwise seen from outside
+const CUBE_FACES: [[usize; 4]; 6] = [
+    [0, 4, 6, 2],
+    [1, 3, 7, 5],
+    [0, 1, 5, 4],
+    [2, 6, 7, 3],
+    [0, 2, 3, 1],
+    [4, 5, 7, 6],
+];
+
+// triangles for each of the 256 inside/outside corner cases as edge triples, terminated by -1
+// generated by tracing the contour over the cube faces, ambiguous faces keep inside corners
+// apart so neighbouring cells always agree on the shared face
+pub fn get_marching_cubes_table() -> &'static [[i8; 16]; 256] {
+    static TABLE: OnceLock<[[i8; 16]; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = [[-1; 16]; 256];
+        for (case, row) in table.iter_mut().enumerate() {
+            let inside = |c: usize| case & (1 << c) != 0;
+            // next[e] is the edge the contour reaches after crossing edge e
+            let mut next = [usize::MAX; 12];
+            for face in CUBE_FACES {
+                let crossings: Vec<(bool, usize)> = (0..4)
+                    .map(|i| (face[i], face[(i + 1) % 4]))
+                    .filter(|(a, b)| inside(*a) != inside(*b))
+                    .map(|(a, b)| (inside(b), get_edge_between(a, b)))
+                    .collect();
+                // crossings alternate, each entry is followed by the exit closing the same inside run
+                for (k, (entering, e)) in crossings.iter().enumerate() {
+                    if *entering {
+                        next[*e] = crossings[(k + 1) % crossings.len()].1;
+                    }
+                }
+            }
+
+            let mut triangles = Vec::new();
+            let mut visited = [false; 12];
+            for start in 0..12 {
+                if next[start] == usize::MAX || visited[start] {
+                    continue;
+                }
+                let mut contour = vec![start];
+                visited[start] = true;
+                let mut e = next[start];
+                while e != start {
+                    visited[e] = true;
+                    contour.push(e);
+                    e = next[e];
+                }
+                for i in 1..contour.len() - 1 {
+                    triangles.extend([contour[0], contour[i], contour[i + 1]]);
+                }
+            }
+            for (slot, e) in row.iter_mut().zip(triangles) {
+                *slot = e as i8;
+            }
+        }
+        table
+    })
+}
+
+fn get_crossing(a: (Vec3, f32), b: (Vec3, f32), iso: f32) -> f32 {
+    let t = (iso - a.1) / (b.1 - a.1);
+    if t.is_finite() {
+        t.clamp(0.0, 1.0)
+    } else {
+        0.5
+    }
+}
+
+pub fn marching_cubes(grid: &ScalarGrid, iso: f32) -> Mesh {
+    let table = get_marching_cubes_table();
+    let [nx, ny, nz] = grid.dims;
+    let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+    // vertices are shared between cells through the grid edge they lie on
+    let mut vertices: HashMap<(usize, usize), u32> = HashMap::new();
+
+    for z in 0..nz - 1 {
+        for y in 0..ny - 1 {
+            for x in 0..nx - 1 {
+                let corners: [[usize; 3]; 8] = std::array::from_fn(|i| {
+                    let [dx, dy, dz] = get_corner_offset(i);
+                    [x + dx, y + dy, z + dz]
+                });
+                let case = corners
+                    .iter()
+                    .enumerate()
+                    .fold(0, |case, (i, [cx, cy, cz])| {
+                        if grid.get_value(*cx, *cy, *cz) < iso {
+                            case | (1 << i)
+                        } else {
+                            case
+                        }
+                    });
+                for e in table[case].iter().take_while(|e| **e >= 0) {
+                    let [a, b] = get_cube_edge(*e as usize).map(|c| corners[c]);
+                    let key = (grid.get_index(a[0], a[1], a[2]), *e as usize / 4);
+                    let index = *vertices.entry(key).or_insert_with(|| {
+                        let sample = |[x, y, z]: [usize; 3]| {
+                            (grid.get_position(x, y, z), grid.get_value(x, y, z))
+                        };
+                        let t = get_crossing(sample(a), sample(b), iso);
+                        let ga = grid.get_gradient(a[0], a[1], a[2]);
+                        let gb = grid.get_gradient(b[0], b[1], b[2]);
+                        mesh.positions.push(sample(a).0.lerp(sample(b).0, t));
+                        mesh.normals.push(ga.lerp(gb, t).normalize());
+                        mesh.positions.len() as u32 - 1
+                    });
+                    mesh.indices.push(index);
+                }
+            }
+        }
+    }
+    mesh
+}
+
+// one vertex per cell at the mean of its edge crossings, joined by a quad across every crossed edge
+pub fn surface_nets(grid: &ScalarGrid, iso: f32) -> Mesh {
+    let [nx, ny, nz] = grid.dims;
+    let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+    let mut cells: HashMap<[usize; 3], u32> = HashMap::new();
+
+    for z in 0..nz - 1 {
+        for y in 0..ny - 1 {
+            for x in 0..nx - 1 {
+                let corners: [[usize; 3]; 8] = std::array::from_fn(|i| {
+                    let [dx, dy, dz] = get_corner_offset(i);
+                    [x + dx, y + dy, z + dz]
+                });
+                let samples =
+                    corners.map(|[x, y, z]| (grid.get_position(x, y, z), grid.get_value(x, y, z)));
+                let mut sum = Vec3::ZERO;
+                let mut count = 0;
+                for e in 0..12 {
+                    let [a, b] = get_cube_edge(e).map(|c| samples[c]);
+                    if (a.1 < iso) != (b.1 < iso) {
+                        sum += a.0.lerp(b.0, get_crossing(a, b, iso));
+                        count += 1;
+                    }
+                }
+                if count > 0 {
+                    let p = sum / count as f32;
+                    mesh.positions.push(p);
+                    mesh.normals.push(grid.sample_gradient(p).normalize());
+                    cells.insert([x, y, z], mesh.positions.len() as u32 - 1);
+                }
+            }
+        }
+    }
+
+    for z in 0..nz {
+        for y in 0..ny {
+            for x in 0..nx {
+                let p = [x, y, z];
+                for axis in 0..3 {
+                    let (b, c) = ((axis + 1) % 3, (axis + 2) % 3);
+                    if p[axis] + 1 >= grid.dims[axis]
+                        || p[b] == 0
+                        || p[c] == 0
+                        || p[b] + 1 >= grid.dims[b]
+                        || p[c] + 1 >= grid.dims[c]
+                    {
+                        continue;
+                    }
+                    let mut q = p;
+                    q[axis] += 1;
+                    let inside = grid.get_value(x, y, z) < iso;
+                    if inside == (grid.get_value(q[0], q[1], q[2]) < iso) {
+                        continue;
+                    }
+                    // the four cells around the edge, counter-clockwise about the axis
+                    let cell = |db: usize, dc: usize| {
+                        let mut cell = p;
+                        cell[b] -= 1 - db;
+                        cell[c] -= 1 - dc;
+                        cells[&cell]
+                    };
+                    let mut quad = [cell(0, 0), cell(1, 0), cell(1, 1), cell(0, 1)];
+                    if !inside {
+                        quad.reverse();
+                    }
+                    mesh.indices
+                        .extend([quad[0], quad[1], quad[2], quad[0], quad[2], quad[3]]);
+                }
+            }
+        }
+    }
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::half_edge::HalfEdgeMesh;
+
+    fn sphere_grid(resolution: usize) -> ScalarGrid {
+        let dims = [resolution; 3];
+        ScalarGrid::from_fn(dims, Vec3::splat(-1.0), Vec3::splat(1.0), |p| {
+            p.length() - 0.7
+        })
+        .unwrap()
+    }
+
+    fn check_sphere(mesh: &Mesh, tolerance: f32) {
+        assert!(mesh.validate().is_ok());
+        assert!(mesh.get_primitive_count() > 100);
+        for (p, n) in mesh.positions.iter().zip(&mesh.normals) {
+            assert!((p.length() - 0.7).abs() < tolerance);
+            assert!(n.dot(p.normalize()) > 0.95);
+        }
+        for t in mesh.get_triangles() {
+            let [a, b, c] = t.map(|i| mesh.positions[i as usize]);
+            let center = (a + b + c) / 3.0;
+            assert!((b - a).cross(c - a).dot(center) > 0.0);
+        }
+        let half_edge = HalfEdgeMesh::from_mesh(mesh).unwrap();
+        assert!(half_edge.is_closed() && half_edge.is_manifold());
+        assert_eq!(half_edge.get_euler_characteristic(), 2);
+    }
+
+    #[test]
+    fn table_is_complete() {
+        let table = get_marching_cubes_table();
+        assert!(table[0][0] == -1 && table[255][0] == -1);
+        // a single inside corner is cut off by one triangle
+        for corner in 0..8 {
+            assert_eq!(table[1 << corner].iter().filter(|e| **e >= 0).count(), 3);
+        }
+        for (case, row) in table.iter().enumerate() {
+            let complement = &table[255 - case];
+            let count = |row: &[i8; 16]| row.iter().filter(|e| **e >= 0).count();
+            assert_eq!(count(row) % 3, 0);
+            assert!(row[15] == -1);
+            // every crossed edge gets a vertex
+            let crossed = (0..12)
+                .filter(|e| {
+                    let [a, b] = get_cube_edge(*e);
+                    (case >> a & 1) != (case >> b & 1)
+                })
+                .count();
+            let used: std::collections::HashSet<i8> =
+                row.iter().copied().filter(|e| *e >= 0).collect();
+            assert_eq!(used.len(), crossed);
+            assert_eq!(count(complement) > 0, crossed > 0);
+        }
+    }
+
+    #[test]
+    fn marching_cubes_sphere() {
+        check_sphere(&marching_cubes(&sphere_grid(24), 0.0), 0.01);
+    }
+
+    #[test]
+    fn surface_nets_sphere() {
+        check_sphere(&surface_nets(&sphere_grid(24), 0.0), 0.03);
+    }
+
+    #[test]
+    fn grid_sampling() {
+        let grid = ScalarGrid::from_fn([5, 4, 3], Vec3::ZERO, Vec3::new(4.0, 3.0, 2.0), |p| {
+            p.x * 2.0 + p.y - p.z
+        })
+        .unwrap();
+        assert_eq!(grid.sample(Vec3::new(1.5, 2.25, 0.5)), 4.75);
+        assert_eq!(grid.get_gradient(0, 3, 2), Vec3::new(2.0, 1.0, -1.0));
+        assert!(ScalarGrid::from_values([2, 2, 2], Vec3::ZERO, Vec3::ONE, vec![0.0; 7]).is_err());
+    }
+}
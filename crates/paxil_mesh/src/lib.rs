@@ -0,0 +1,38 @@
+pub mod mesh;
+pub use mesh::*;
+
+pub mod primitives;
+
+pub mod normals;
+
+pub mod half_edge;
+pub use half_edge::*;
+
+pub mod subdivision;
+
+pub mod decimate;
+pub use decimate::*;
+
+pub mod isosurface;
+pub use isosurface::*;
+
+pub mod tessellate;
+pub use tessellate::*;
+
+pub mod delaunay;
+pub use delaunay::*;
+
+pub mod voronoi;
+pub use voronoi::*;
+
+pub mod obj;
+pub use obj::*;
+
+pub mod gltf_import;
+pub use gltf_import::*;
+
+pub mod ply;
+pub use ply::*;
+
+pub mod stl;
+pub use stl::*;
@@ -0,0 +1,389 @@
+use paxil_math::*;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PrimitiveMode {
+    Points,
+    Lines,
+    LineStrip,
+    LineLoop,
+    #[default]
+    Triangles,
+    TriangleStrip,
+    TriangleFan,
+}
+
+impl PrimitiveMode {
+    pub fn get_primitive_count(&self, element_count: usize) -> usize {
+        match self {
+            PrimitiveMode::Points => element_count,
+            PrimitiveMode::Lines => element_count / 2,
+            PrimitiveMode::LineStrip => element_count.saturating_sub(1),
+            PrimitiveMode::LineLoop if element_count > 1 => element_count,
+            PrimitiveMode::LineLoop => 0,
+            PrimitiveMode::Triangles => element_count / 3,
+            PrimitiveMode::TriangleStrip | PrimitiveMode::TriangleFan => {
+                element_count.saturating_sub(2)
+            }
+        }
+    }
+
+    pub fn is_triangles(&self) -> bool {
+        matches!(
+            self,
+            PrimitiveMode::Triangles | PrimitiveMode::TriangleStrip | PrimitiveMode::TriangleFan
+        )
+    }
+}
+
+// attributes other than positions are either empty or one per vertex
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mesh {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub uvs: Vec<Vec2>,
+    pub colors: Vec<Color>,
+    // xyz tangent, w handedness of the bitangent
+    pub tangents: Vec<Vec4>,
+    pub indices: Vec<u32>,
+    pub mode: PrimitiveMode,
+}
+
+impl Mesh {
+    pub fn new(mode: PrimitiveMode) -> Self {
+        Self {
+            mode,
+            ..Default::default()
+        }
+    }
+
+    pub fn from_positions(positions: Vec<Vec3>, indices: Vec<u32>) -> Self {
+        Self {
+            positions,
+            indices,
+            ..Default::default()
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.positions.clear();
+        self.normals.clear();
+        self.uvs.clear();
+        self.colors.clear();
+        self.tangents.clear();
+        self.indices.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn is_indexed(&self) -> bool {
+        !self.indices.is_empty()
+    }
+
+    pub fn has_normals(&self) -> bool {
+        !self.normals.is_empty()
+    }
+
+    pub fn has_uvs(&self) -> bool {
+        !self.uvs.is_empty()
+    }
+
+    pub fn has_colors(&self) -> bool {
+        !self.colors.is_empty()
+    }
+
+    pub fn has_tangents(&self) -> bool {
+        !self.tangents.is_empty()
+    }
+
+    pub fn get_vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    // number of vertices the draw call walks, indices if present
+    pub fn get_element_count(&self) -> usize {
+        if self.is_indexed() {
+            self.indices.len()
+        } else {
+            self.positions.len()
+        }
+    }
+
+    pub fn get_primitive_count(&self) -> usize {
+        self.mode.get_primitive_count(self.get_element_count())
+    }
+
+    pub fn get_aabb(&self) -> Aabb {
+        Aabb::from_points(&self.positions)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let count = self.positions.len();
+        let check = |name: &str, len: usize| {
+            if len != 0 && len != count {
+                Err(format!("Mesh has {} {} for {} positions", len, name, count))
+            } else {
+                Ok(())
+            }
+        };
+        check("normals", self.normals.len())?;
+        check("uvs", self.uvs.len())?;
+        check("colors", self.colors.len())?;
+        check("tangents", self.tangents.len())?;
+
+        if let Some(index) = self.indices.iter().find(|i| **i as usize >= count) {
+            return Err(format!(
+                "Index {} is out of range for {} positions",
+                index, count
+            ));
+        }
+
+        let elements = self.get_element_count();
+        let incomplete = match self.mode {
+            PrimitiveMode::Lines => !elements.is_multiple_of(2),
+            PrimitiveMode::Triangles => !elements.is_multiple_of(3),
+            _ => false,
+        };
+        if incomplete {
+            return Err(format!(
+                "{} elements do not form whole {:?} primitives",
+                elements, self.mode
+            ));
+        }
+
+        if let Some(p) = self.positions.iter().find(|p| !p.is_finite()) {
+            return Err(format!("Position {:?} is not finite", p));
+        }
+        Ok(())
+    }
+
+    // triangle vertex indices for any triangle mode, strips keep a consistent winding
+    pub fn get_triangles(&self) -> Vec<[u32; 3]> {
+        let elements: Vec<u32> = if self.is_indexed() {
+            self.indices.clone()
+        } else {
+            (0..self.positions.len() as u32).collect()
+        };
+        match self.mode {
+            PrimitiveMode::Triangles => elements
+                .chunks_exact(3)
+                .map(|t| [t[0], t[1], t[2]])
+                .collect(),
+            PrimitiveMode::TriangleStrip => elements
+                .windows(3)
+                .enumerate()
+                .map(|(i, t)| {
+                    if i.is_multiple_of(2) {
+                        [t[0], t[1], t[2]]
+                    } else {
+                        [t[1], t[0], t[2]]
+                    }
+                })
+                .collect(),
+            PrimitiveMode::TriangleFan if elements.len() >= 3 => elements[1..]
+                .windows(2)
+                .map(|t| [elements[0], t[0], t[1]])
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    // converts any triangle mode to an indexed triangle list
+    pub fn to_triangle_list(&self) -> Result<Self, String> {
+        if !self.mode.is_triangles() {
+            return Err(format!("Cannot triangulate {:?} primitives", self.mode));
+        }
+        let mut mesh = self.clone();
+        mesh.indices = self.get_triangles().into_iter().flatten().collect();
+        mesh.mode = PrimitiveMode::Triangles;
+        Ok(mesh)
+    }
+
+    pub fn transform(&mut self, matrix: &Mat4) {
+        let normal_matrix = Mat3::from_mat4(matrix)
+            .inverse()
+            .map(|m| m.transpose())
+            .unwrap_or(Mat3::from_mat4(matrix));
+        // mirroring transforms flip the winding, and with it the bitangent sign
+        let mirrored = Mat3::from_mat4(matrix).determinant() < 0.0;
+
+        for p in &mut self.positions {
+            *p = matrix.transform_point3(*p);
+        }
+        for n in &mut self.normals {
+            *n = (normal_matrix * *n).normalize();
+        }
+        for t in &mut self.tangents {
+            let xyz = matrix.transform_vector3(t.truncate()).normalize();
+            let w = if mirrored { -t.w } else { t.w };
+            *t = xyz.extend(w);
+        }
+        if mirrored && self.mode.is_triangles() {
+            self.flip_winding();
+        }
+    }
+
+    // strips and fans tie their winding to the element order, so they become a triangle list
+    pub fn flip_winding(&mut self) {
+        if matches!(
+            self.mode,
+            PrimitiveMode::TriangleStrip | PrimitiveMode::TriangleFan
+        ) {
+            if let Ok(list) = self.to_triangle_list() {
+                *self = list;
+            }
+        }
+        if self.is_indexed() {
+            for t in self.indices.chunks_exact_mut(3) {
+                t.swap(1, 2);
+            }
+        } else {
+            for i in (0..self.positions.len() / 3).map(|t| t * 3) {
+                self.positions.swap(i + 1, i + 2);
+                if self.normals.len() > i + 2 {
+                    self.normals.swap(i + 1, i + 2);
+                }
+                if self.uvs.len() > i + 2 {
+                    self.uvs.swap(i + 1, i + 2);
+                }
+                if self.colors.len() > i + 2 {
+                    self.colors.swap(i + 1, i + 2);
+                }
+                if self.tangents.len() > i + 2 {
+                    self.tangents.swap(i + 1, i + 2);
+                }
+            }
+        }
+    }
+
+    // appends another mesh of the same mode, attributes missing on either side are filled with defaults
+    pub fn append(&mut self, other: &Mesh) -> Result<(), String> {
+        if self.mode != other.mode {
+            return Err(format!(
+                "Cannot append {:?} mesh to {:?} mesh",
+                other.mode, self.mode
+            ));
+        }
+        let offset = self.positions.len() as u32;
+        let count = self.positions.len();
+
+        if self.is_indexed() || other.is_indexed() {
+            if !self.is_indexed() {
+                self.indices = (0..offset).collect();
+            }
+            if other.is_indexed() {
+                self.indices
+                    .extend(other.indices.iter().map(|i| i + offset));
+            } else {
+                self.indices
+                    .extend((0..other.positions.len() as u32).map(|i| i + offset));
+            }
+        }
+
+        fn merge<T: Copy>(ours: &mut Vec<T>, theirs: &[T], count: usize, extra: usize, fill: T) {
+            if ours.is_empty() && theirs.is_empty() {
+                return;
+            }
+            ours.resize(count, fill);
+            if theirs.is_empty() {
+                ours.resize(count + extra, fill);
+            } else {
+                ours.extend_from_slice(theirs);
+            }
+        }
+        let extra = other.positions.len();
+        merge(&mut self.normals, &other.normals, count, extra, Vec3::ZERO);
+        merge(&mut self.uvs, &other.uvs, count, extra, Vec2::ZERO);
+        merge(&mut self.colors, &other.colors, count, extra, Color::WHITE);
+        merge(
+            &mut self.tangents,
+            &other.tangents,
+            count,
+            extra,
+            Vec4::new(1.0, 0.0, 0.0, 1.0),
+        );
+        self.positions.extend_from_slice(&other.positions);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quad() -> Mesh {
+        let mut mesh = Mesh::from_positions(
+            vec![
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec3::new(1.0, 1.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0),
+            ],
+            vec![0, 1, 2, 0, 2, 3],
+        );
+        mesh.normals = vec![Vec3::Z; 4];
+        mesh
+    }
+
+    #[test]
+    fn validation_catches_bad_meshes() {
+        let mut mesh = quad();
+        assert!(mesh.validate().is_ok());
+        assert_eq!(mesh.get_primitive_count(), 2);
+
+        mesh.uvs = vec![Vec2::ZERO; 3];
+        assert!(mesh.validate().is_err());
+        mesh.uvs.clear();
+
+        mesh.indices.push(7);
+        assert!(mesh.validate().is_err());
+        mesh.indices.pop();
+        mesh.indices.pop();
+        assert!(mesh.validate().is_err());
+    }
+
+    #[test]
+    fn strips_and_fans_become_lists() {
+        let mut mesh = quad();
+        mesh.indices = vec![0, 1, 3, 2];
+        mesh.mode = PrimitiveMode::TriangleStrip;
+        assert_eq!(mesh.get_triangles(), vec![[0, 1, 3], [3, 1, 2]]);
+
+        mesh.indices.clear();
+        mesh.mode = PrimitiveMode::TriangleFan;
+        let list = mesh.to_triangle_list().unwrap();
+        assert_eq!(list.indices, vec![0, 1, 2, 0, 2, 3]);
+        assert!(Mesh::new(PrimitiveMode::Lines).to_triangle_list().is_err());
+    }
+
+    #[test]
+    fn append_and_mirror() {
+        let mut mesh = quad();
+        let mut other = quad();
+        other.normals.clear();
+        other.uvs = vec![Vec2::ONE; 4];
+        mesh.append(&other).unwrap();
+        assert!(mesh.validate().is_ok());
+        assert_eq!(mesh.get_vertex_count(), 8);
+        assert_eq!(mesh.indices[6..], [4, 5, 6, 4, 6, 7]);
+        assert_eq!(mesh.uvs[0], Vec2::ZERO);
+
+        let mirror = Mat4::from_scale(Vec3::new(-1.0, 1.0, 1.0));
+        mesh.transform(&mirror);
+        assert_eq!(mesh.indices[..3], [0, 2, 1]);
+        assert_eq!(mesh.normals[0], Vec3::Z);
+
+        // a strip keeps facing its normals once mirrored
+        let mut strip = quad();
+        strip.mode = PrimitiveMode::TriangleStrip;
+        strip.indices = vec![0, 1, 3, 2];
+        strip.transform(&mirror);
+        assert_eq!(strip.mode, PrimitiveMode::Triangles);
+        assert_eq!(strip.get_primitive_count(), 2);
+        for [a, b, c] in strip.get_triangles() {
+            let [a, b, c] = [a, b, c].map(|i| strip.positions[i as usize]);
+            assert!((b - a).cross(c - a).dot(strip.normals[0]) > 0.0);
+        }
+    }
+}
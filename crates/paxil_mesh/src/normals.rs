@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+use super::mesh::{Mesh, PrimitiveMode};
+use paxil_math::*;
+
+// adding zero folds -0.0 into 0.0 so both hash alike
+fn vec3_bits(v: Vec3) -> [u32; 3] {
+    [v.x, v.y, v.z].map(|c| (c + 0.0).to_bits())
+}
+
+// directions that differ only by rounding share a vertex
+fn quantize(v: Vec3, w: f32) -> [u32; 4] {
+    [v.x, v.y, v.z, w].map(|c| (c * 65536.0).round() as i32 as u32)
+}
+
+// position, normal and uv bits plus whether the uv mapping preserves orientation
+type TangentKey = ([u32; 3], [u32; 3], [u32; 2], bool);
+
+// angle of the triangle at corner k, measured in the plane perpendicular to normal
+fn get_corner_angle(points: [Vec3; 3], k: usize, normal: Vec3) -> f32 {
+    let project = |v: Vec3| (v - normal * normal.dot(v)).normalize();
+    let p = points[k];
+    let a = project(points[(k + 1) % 3] - p);
+    let b = project(points[(k + 2) % 3] - p);
+    if a == Vec3::ZERO || b == Vec3::ZERO {
+        return 0.0;
+    }
+    a.dot(b).clamp(-1.0, 1.0).acos()
+}
+
+impl Mesh {
+    fn get_corner_triangles(&self) -> Result<Vec<[u32; 3]>, String> {
+        self.validate()?;
+        if !self.mode.is_triangles() {
+            return Err(format!(
+                "Cannot compute normals for {:?} primitives",
+                self.mode
+            ));
+        }
+        Ok(self.get_triangles())
+    }
+
+    // rebuilds the mesh as an indexed triangle list where every corner can carry its own value,
+    // corners of the same source vertex with equal keys keep sharing a vertex
+    fn split_corners(&mut self, triangles: &[[u32; 3]], keys: &[[u32; 4]]) -> Vec<usize> {
+        let corners: Vec<u32> = triangles.iter().flatten().copied().collect();
+        let mut vertices: HashMap<(u32, [u32; 4]), u32> = HashMap::new();
+        let mut sources: Vec<usize> = Vec::new();
+        let mut indices = Vec::with_capacity(corners.len());
+        for (c, vertex) in corners.iter().enumerate() {
+            let index = *vertices.entry((*vertex, keys[c])).or_insert_with(|| {
+                sources.push(c);
+                sources.len() as u32 - 1
+            });
+            indices.push(index);
+        }
+
+        fn remap<T: Copy>(values: &mut Vec<T>, sources: &[usize], corners: &[u32]) {
+            if !values.is_empty() {
+                *values = sources
+                    .iter()
+                    .map(|c| values[corners[*c] as usize])
+                    .collect();
+            }
+        }
+        remap(&mut self.positions, &sources, &corners);
+        remap(&mut self.normals, &sources, &corners);
+        remap(&mut self.uvs, &sources, &corners);
+        remap(&mut self.colors, &sources, &corners);
+        remap(&mut self.tangents, &sources, &corners);
+        self.indices = indices;
+        self.mode = PrimitiveMode::Triangles;
+        sources
+    }
+
+    fn set_corner_normals(&mut self, triangles: &[[u32; 3]], normals: &[Vec3]) {
+        let keys: Vec<[u32; 4]> = normals.iter().map(|n| quantize(*n, 0.0)).collect();
+        let sources = self.split_corners(triangles, &keys);
+        self.normals = sources.iter().map(|c| normals[*c]).collect();
+        // tangents were built against the old normals
+        self.tangents.clear();
+    }
+
+    fn get_face_normals(&self, triangles: &[[u32; 3]]) -> Vec<Vec3> {
+        triangles
+            .iter()
+            .map(|t| {
+                let [a, b, c] = t.map(|i| self.positions[i as usize]);
+                (b - a).cross(c - a).normalize()
+            })
+            .collect()
+    }
+
+    // every triangle gets its own normal, vertices are split where faces meet at an angle
+    pub fn compute_flat_normals(&mut self) -> Result<(), String> {
+        let triangles = self.get_corner_triangles()?;
+        let face_normals = self.get_face_normals(&triangles);
+        let normals: Vec<Vec3> = face_normals.iter().flat_map(|n| [*n; 3]).collect();
+        self.set_corner_normals(&triangles, &normals);
+        Ok(())
+    }
+
+    pub fn compute_smooth_normals(&mut self) -> Result<(), String> {
+        self.compute_normals(PI)
+    }
+
+    // angle weighted normals averaged over faces sharing a position, faces more than
+    // crease_angle radians apart do not smooth into each other and split the vertex instead
+    pub fn compute_normals(&mut self, crease_angle: f32) -> Result<(), String> {
+        let triangles = self.get_corner_triangles()?;
+        let face_normals = self.get_face_normals(&triangles);
+
+        // corners are grouped by position so uv seams do not show up in the shading
+        let mut groups: HashMap<[u32; 3], Vec<usize>> = HashMap::new();
+        let mut weights = Vec::with_capacity(triangles.len() * 3);
+        for (f, t) in triangles.iter().enumerate() {
+            let points = t.map(|i| self.positions[i as usize]);
+            for (k, p) in points.iter().enumerate() {
+                groups.entry(vec3_bits(*p)).or_default().push(f * 3 + k);
+                weights.push(get_corner_angle(points, k, face_normals[f]));
+            }
+        }
+
+        let min_dot = crease_angle.min(PI).cos() - 1e-6;
+        let mut normals = vec![Vec3::ZERO; triangles.len() * 3];
+        for corners in groups.values() {
+            for c in corners {
+                let face_normal = face_normals[c / 3];
+                let mut sum = Vec3::ZERO;
+                for other in corners {
+                    let other_normal = face_normals[other / 3];
+                    if other / 3 == c / 3 || face_normal.dot(other_normal) >= min_dot {
+                        sum += other_normal * weights[*other];
+                    }
+                }
+                normals[*c] = if sum == Vec3::ZERO {
+                    face_normal
+                } else {
+                    sum.normalize()
+                };
+            }
+        }
+        self.set_corner_normals(&triangles, &normals);
+        Ok(())
+    }
+
+    // per face uv derivatives projected onto the normal plane, angle weighted over vertices
+    // with equal position, normal and uv, and split where the uv mapping changes orientation.
+    // close to MikkTSpace on well behaved meshes but not bit exact, so baked normal maps can
+    // show small seams. w is the bitangent sign, bitangent = cross(n, t) * w
+    pub fn compute_tangents(&mut self) -> Result<(), String> {
+        let triangles = self.get_corner_triangles()?;
+        if !self.has_normals() || !self.has_uvs() {
+            return Err("Tangents need normals and uvs".to_string());
+        }
+
+        let mut groups: HashMap<TangentKey, Vec3> = HashMap::new();
+        let mut corner_groups = Vec::with_capacity(triangles.len() * 3);
+        let mut contributions = Vec::with_capacity(triangles.len() * 3);
+        for t in &triangles {
+            let points = t.map(|i| self.positions[i as usize]);
+            let uvs = t.map(|i| self.uvs[i as usize]);
+            let (d1, d2) = (points[1] - points[0], points[2] - points[0]);
+            let (t1, t2) = (uvs[1] - uvs[0], uvs[2] - uvs[0]);
+            let signed_area = t1.x * t2.y - t1.y * t2.x;
+            // dP/du scaled by the uv area, the sign keeps it pointing along increasing u
+            let face_tangent = (d1 * t2.y - d2 * t1.y) * signed_area.signum();
+            let preserves_orientation = signed_area > 0.0;
+
+            for (k, vertex) in t.iter().enumerate() {
+                let normal = self.normals[*vertex as usize];
+                let key = (
+                    vec3_bits(points[k]),
+                    vec3_bits(normal),
+                    [uvs[k].x.to_bits(), uvs[k].y.to_bits()],
+                    preserves_orientation,
+                );
+                let tangent = (face_tangent - normal * normal.dot(face_tangent)).normalize();
+                let weight = if signed_area == 0.0 {
+                    0.0
+                } else {
+                    get_corner_angle(points, k, normal)
+                };
+                corner_groups.push(key);
+                contributions.push(tangent * weight);
+            }
+        }
+        for (key, contribution) in corner_groups.iter().zip(&contributions) {
+            *groups.entry(*key).or_insert(Vec3::ZERO) += *contribution;
+        }
+
+        let tangents: Vec<Vec4> = corner_groups
+            .iter()
+            .map(|key| {
+                let normal = Vec3::new(
+                    f32::from_bits(key.1[0]),
+                    f32::from_bits(key.1[1]),
+                    f32::from_bits(key.1[2]),
+                );
+                let sum = groups[key];
+                let sum = (sum - normal * normal.dot(sum)).normalize();
+                let tangent = if sum == Vec3::ZERO {
+                    normal.any_orthonormal()
+                } else {
+                    sum
+                };
+                tangent.extend(if key.3 { 1.0 } else { -1.0 })
+            })
+            .collect();
+
+        let keys: Vec<[u32; 4]> = tangents
+            .iter()
+            .map(|t| quantize(t.truncate(), t.w))
+            .collect();
+        let sources = self.split_corners(&triangles, &keys);
+        self.tangents = sources.iter().map(|c| tangents[*c]).collect();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn welded_cube() -> Mesh {
+        let cube = Mesh::cuboid(Vec3::splat(2.0), [1, 1, 1]);
+        let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+        let mut vertices: HashMap<[u32; 3], u32> = HashMap::new();
+        for i in &cube.indices {
+            let p = cube.positions[*i as usize];
+            let index = *vertices.entry(vec3_bits(p)).or_insert_with(|| {
+                mesh.positions.push(p);
+                mesh.positions.len() as u32 - 1
+            });
+            mesh.indices.push(index);
+        }
+        mesh
+    }
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!(a.distance(b) < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn cube_normals() {
+        let mut mesh = welded_cube();
+        assert_eq!(mesh.get_vertex_count(), 8);
+
+        // the two triangles of a face meet at a corner with 45 + 45 or 90 degrees,
+        // so angle weighting gives every face the same share
+        mesh.compute_smooth_normals().unwrap();
+        assert_eq!(mesh.get_vertex_count(), 8);
+        for (p, n) in mesh.positions.iter().zip(&mesh.normals) {
+            assert_close(*n, p.normalize());
+        }
+
+        let mut creased = welded_cube();
+        creased.compute_normals(30f32.to_radians()).unwrap();
+        let mut flat = welded_cube();
+        flat.compute_flat_normals().unwrap();
+        for mesh in [creased, flat] {
+            assert_eq!(mesh.get_vertex_count(), 24);
+            for [a, b, c] in mesh.get_triangles() {
+                let [pa, pb, pc] = [a, b, c].map(|i| mesh.positions[i as usize]);
+                let face = (pb - pa).cross(pc - pa).normalize();
+                for i in [a, b, c] {
+                    assert_close(mesh.normals[i as usize], face);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sphere_normals_ignore_uv_seams() {
+        let mut mesh = Mesh::uv_sphere(1.0, 16, 8);
+        let vertex_count = mesh.get_vertex_count();
+        mesh.normals.clear();
+        mesh.compute_normals(60f32.to_radians()).unwrap();
+        assert!(mesh.get_vertex_count() <= vertex_count);
+        for (p, n) in mesh.positions.iter().zip(&mesh.normals) {
+            assert!(n.dot(p.normalize()) > 0.99);
+        }
+        assert!(Mesh::grid(1.0, 2).compute_smooth_normals().is_err());
+    }
+
+    #[test]
+    fn tangents_follow_uvs() {
+        let mut mesh = Mesh::plane(2.0, 2.0, 2, 2);
+        let vertex_count = mesh.get_vertex_count();
+        mesh.compute_tangents().unwrap();
+        assert_eq!(mesh.get_vertex_count(), vertex_count);
+        for t in &mesh.tangents {
+            assert_close(t.truncate(), Vec3::X);
+            assert_eq!(t.w, 1.0);
+        }
+
+        // mirroring u flips both the tangent and its handedness
+        for uv in &mut mesh.uvs {
+            uv.x = 1.0 - uv.x;
+        }
+        mesh.compute_tangents().unwrap();
+        for t in &mesh.tangents {
+            assert_close(t.truncate(), -Vec3::X);
+            assert_eq!(t.w, -1.0);
+        }
+
+        mesh.uvs.clear();
+        assert!(mesh.compute_tangents().is_err());
+    }
+
+    #[test]
+    fn torus_tangents_match_analytic_frames() {
+        let mut mesh = Mesh::torus(1.0, 0.25, 32, 16);
+        let key = |mesh: &Mesh, i: usize| (vec3_bits(mesh.positions[i]), mesh.uvs[i].x.to_bits());
+        let expected: HashMap<_, Vec4> = (0..mesh.get_vertex_count())
+            .map(|i| (key(&mesh, i), mesh.tangents[i]))
+            .collect();
+        mesh.compute_tangents().unwrap();
+        for (i, t) in mesh.tangents.iter().enumerate() {
+            let n = mesh.normals[i];
+            assert!(t.truncate().dot(n).abs() < 1e-4);
+            assert!((t.truncate().length() - 1.0).abs() < 1e-4);
+            assert!(t.truncate().dot(expected[&key(&mesh, i)].truncate()) > 0.98);
+            assert_eq!(t.w, 1.0);
+        }
+    }
+}
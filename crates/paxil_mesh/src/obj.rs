@@ -0,0 +1,872 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use super::mesh::{Mesh, PrimitiveMode};
+use paxil_math::*;
+
+// Wavefront material, texture paths are kept as written in the library
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjMaterial {
+    pub name: String,
+    pub ambient: Color,
+    pub diffuse: Color,
+    pub specular: Color,
+    pub emissive: Color,
+    pub shininess: f32,
+    pub opacity: f32,
+    pub optical_density: f32,
+    pub illumination: u32,
+    pub ambient_map: Option<String>,
+    pub diffuse_map: Option<String>,
+    pub specular_map: Option<String>,
+    pub emissive_map: Option<String>,
+    pub alpha_map: Option<String>,
+    pub bump_map: Option<String>,
+    pub normal_map: Option<String>,
+}
+
+impl ObjMaterial {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ambient: Color::BLACK,
+            diffuse: Color::gray(0.8),
+            specular: Color::BLACK,
+            emissive: Color::BLACK,
+            shininess: 0.0,
+            opacity: 1.0,
+            optical_density: 1.0,
+            illumination: 2,
+            ambient_map: None,
+            diffuse_map: None,
+            specular_map: None,
+            emissive_map: None,
+            alpha_map: None,
+            bump_map: None,
+            normal_map: None,
+        }
+    }
+}
+
+// one mesh per object, group and material run in the file
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ObjMesh {
+    pub object: String,
+    pub group: String,
+    pub material: Option<String>,
+    pub mesh: Mesh,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ObjScene {
+    pub meshes: Vec<ObjMesh>,
+    pub materials: Vec<ObjMaterial>,
+    pub material_libraries: Vec<String>,
+}
+
+impl ObjScene {
+    pub fn get_material(&self, name: &str) -> Option<&ObjMaterial> {
+        self.materials.iter().find(|m| m.name == name)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Corner {
+    position: usize,
+    uv: Option<usize>,
+    normal: Option<usize>,
+}
+
+struct Face {
+    corners: Vec<Corner>,
+    smoothing: u32,
+}
+
+#[derive(Default)]
+struct Run {
+    object: String,
+    group: String,
+    material: Option<String>,
+    faces: Vec<Face>,
+    // position indices of each l polyline and p point
+    lines: Vec<Vec<usize>>,
+    points: Vec<usize>,
+}
+
+impl Run {
+    fn is_empty(&self) -> bool {
+        self.faces.is_empty() && self.lines.is_empty() && self.points.is_empty()
+    }
+}
+
+fn parse_floats<const N: usize>(tokens: &[&str], line: usize) -> Result<[f32; N], String> {
+    if tokens.len() < N {
+        return Err(format!(
+            "Line {}: expected {} numbers, found {}",
+            line,
+            N,
+            tokens.len()
+        ));
+    }
+    let mut values = [0.0; N];
+    for (value, token) in values.iter_mut().zip(tokens) {
+        *value = token
+            .parse()
+            .map_err(|_| format!("Line {}: invalid number '{}'", line, token))?;
+    }
+    Ok(values)
+}
+
+// resolves 1-based and negative relative indices
+fn parse_index(token: &str, count: usize, line: usize) -> Result<usize, String> {
+    let index: i64 = token
+        .parse()
+        .map_err(|_| format!("Line {}: invalid index '{}'", line, token))?;
+    let resolved = if index < 0 {
+        count as i64 + index
+    } else {
+        index - 1
+    };
+    if resolved < 0 || resolved >= count as i64 {
+        return Err(format!(
+            "Line {}: index {} is out of range for {} elements",
+            line, index, count
+        ));
+    }
+    Ok(resolved as usize)
+}
+
+pub fn parse_obj(src: &str) -> Result<ObjScene, String> {
+    let mut positions: Vec<Vec3> = Vec::new();
+    let mut colors: Vec<Option<Color>> = Vec::new();
+    let mut uvs: Vec<Vec2> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut runs: Vec<Run> = vec![Run::default()];
+    let mut smoothing = 0;
+    let mut material_libraries = Vec::new();
+
+    for (number, line) in src.lines().enumerate() {
+        let number = number + 1;
+        let line = line.split('#').next().unwrap_or("").trim();
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let Some((keyword, args)) = tokens.split_first() else {
+            continue;
+        };
+
+        match *keyword {
+            "v" => {
+                let [x, y, z] = parse_floats(args, number)?;
+                positions.push(Vec3::new(x, y, z));
+                // vertex colors are a common extension, v x y z r g b
+                colors.push(if args.len() >= 6 {
+                    let [r, g, b] = parse_floats(&args[3..], number)?;
+                    Some(Color::rgb(r, g, b))
+                } else {
+                    None
+                });
+            }
+            "vt" => {
+                let [u] = parse_floats(args, number)?;
+                let v = if args.len() > 1 {
+                    parse_floats::<1>(&args[1..], number)?[0]
+                } else {
+                    0.0
+                };
+                uvs.push(Vec2::new(u, v));
+            }
+            "vn" => {
+                let [x, y, z] = parse_floats(args, number)?;
+                normals.push(Vec3::new(x, y, z));
+            }
+            "f" => {
+                if args.len() < 3 {
+                    return Err(format!("Line {}: face needs at least 3 vertices", number));
+                }
+                let mut corners = Vec::with_capacity(args.len());
+                for arg in args {
+                    let mut parts = arg.split('/');
+                    let position =
+                        parse_index(parts.next().unwrap_or(""), positions.len(), number)?;
+                    let uv = match parts.next() {
+                        Some(t) if !t.is_empty() => Some(parse_index(t, uvs.len(), number)?),
+                        _ => None,
+                    };
+                    let normal = match parts.next() {
+                        Some(t) if !t.is_empty() => Some(parse_index(t, normals.len(), number)?),
+                        _ => None,
+                    };
+                    corners.push(Corner {
+                        position,
+                        uv,
+                        normal,
+                    });
+                }
+                let run = runs.last_mut().unwrap();
+                run.faces.push(Face { corners, smoothing });
+            }
+            "l" | "p" => {
+                // texture coordinates on line vertices are ignored
+                let indices = args
+                    .iter()
+                    .map(|arg| {
+                        parse_index(arg.split('/').next().unwrap_or(""), positions.len(), number)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let run = runs.last_mut().unwrap();
+                if *keyword == "p" {
+                    run.points.extend(indices);
+                } else if indices.len() < 2 {
+                    return Err(format!("Line {}: line needs at least 2 vertices", number));
+                } else {
+                    run.lines.push(indices);
+                }
+            }
+            "o" | "g" | "usemtl" => {
+                let name = args.join(" ");
+                let last = runs.last().unwrap();
+                let mut run = Run {
+                    object: last.object.clone(),
+                    group: last.group.clone(),
+                    material: last.material.clone(),
+                    ..Default::default()
+                };
+                match *keyword {
+                    "o" => {
+                        run.object = name;
+                        run.group.clear();
+                    }
+                    "g" => run.group = name,
+                    _ => run.material = Some(name),
+                }
+                if last.is_empty() {
+                    runs.pop();
+                }
+                runs.push(run);
+            }
+            "s" => {
+                smoothing = match args.first() {
+                    None | Some(&"off") => 0,
+                    Some(s) => s
+                        .parse()
+                        .map_err(|_| format!("Line {}: invalid smoothing group '{}'", number, s))?,
+                };
+            }
+            "mtllib" => material_libraries.extend(args.iter().map(|s| s.to_string())),
+            // curves and free form surfaces are not supported
+            _ => {}
+        }
+    }
+
+    // vertex colors are only kept when at least one position has one
+    let colors = colors
+        .iter()
+        .any(|c| c.is_some())
+        .then_some(colors.as_slice());
+    // faces, lines and points of a run become separate meshes
+    let mut meshes = Vec::new();
+    for run in &runs {
+        let segments: Vec<usize> = run
+            .lines
+            .iter()
+            .flat_map(|line| line.windows(2).flatten().copied())
+            .collect();
+        for (mesh, empty) in [
+            (
+                build_mesh(run, &positions, colors, &uvs, &normals),
+                run.faces.is_empty(),
+            ),
+            (
+                build_element_mesh(PrimitiveMode::Lines, &segments, &positions, colors),
+                segments.is_empty(),
+            ),
+            (
+                build_element_mesh(PrimitiveMode::Points, &run.points, &positions, colors),
+                run.points.is_empty(),
+            ),
+        ] {
+            if !empty {
+                meshes.push(ObjMesh {
+                    object: run.object.clone(),
+                    group: run.group.clone(),
+                    material: run.material.clone(),
+                    mesh,
+                });
+            }
+        }
+    }
+
+    Ok(ObjScene {
+        meshes,
+        materials: Vec::new(),
+        material_libraries,
+    })
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum NormalKey {
+    Index(usize),
+    Smooth(u32),
+    Flat(usize),
+}
+
+type VertexKey = (usize, Option<usize>, NormalKey);
+
+fn get_vertex_key(face: &Face, face_index: usize, corner: &Corner) -> VertexKey {
+    let normal = match (corner.normal, face.smoothing) {
+        (Some(n), _) => NormalKey::Index(n),
+        (None, 0) => NormalKey::Flat(face_index),
+        (None, s) => NormalKey::Smooth(s),
+    };
+    (corner.position, corner.uv, normal)
+}
+
+// Newell's method, robust for non planar polygons
+pub(crate) fn get_polygon_normal(points: &[Vec3]) -> Vec3 {
+    let mut normal = Vec3::ZERO;
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        normal.x += (a.y - b.y) * (a.z + b.z);
+        normal.y += (a.z - b.z) * (a.x + b.x);
+        normal.z += (a.x - b.x) * (a.y + b.y);
+    }
+    normal
+}
+
+// ear clipping in the plane of the polygon, falls back to a fan for degenerate input
+pub(crate) fn triangulate_face(points: &[Vec3]) -> Vec<[usize; 3]> {
+    let n = points.len();
+    if n == 3 {
+        return vec![[0, 1, 2]];
+    }
+    let fan = || (1..n - 1).map(|i| [0, i, i + 1]).collect();
+    let normal = get_polygon_normal(points).normalize();
+    if normal == Vec3::ZERO {
+        return fan();
+    }
+    let axis = if normal.x.abs() < 0.9 {
+        Vec3::X
+    } else {
+        Vec3::Y
+    };
+    let u = normal.cross(axis).normalize();
+    let v = normal.cross(u);
+    let flat: Vec<Vec2> = points
+        .iter()
+        .map(|p| Vec2::new(p.dot(u), p.dot(v)))
+        .collect();
+    let cross = |a: Vec2, b: Vec2, c: Vec2| (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x);
+
+    let mut remaining: Vec<usize> = (0..n).collect();
+    let mut triangles = Vec::with_capacity(n - 2);
+    while remaining.len() > 3 {
+        let m = remaining.len();
+        let ear = (0..m).find(|&i| {
+            let (a, b, c) = (
+                remaining[(i + m - 1) % m],
+                remaining[i],
+                remaining[(i + 1) % m],
+            );
+            let (pa, pb, pc) = (flat[a], flat[b], flat[c]);
+            if cross(pa, pb, pc) <= 0.0 {
+                return false;
+            }
+            remaining.iter().all(|&k| {
+                if k == a || k == b || k == c {
+                    return true;
+                }
+                let p = flat[k];
+                cross(pa, pb, p) < 0.0 || cross(pb, pc, p) < 0.0 || cross(pc, pa, p) < 0.0
+            })
+        });
+        let Some(i) = ear else {
+            return fan();
+        };
+        triangles.push([
+            remaining[(i + m - 1) % m],
+            remaining[i],
+            remaining[(i + 1) % m],
+        ]);
+        remaining.remove(i);
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+    triangles
+}
+
+fn build_mesh(
+    run: &Run,
+    positions: &[Vec3],
+    colors: Option<&[Option<Color>]>,
+    uvs: &[Vec2],
+    normals: &[Vec3],
+) -> Mesh {
+    let face_points =
+        |face: &Face| -> Vec<Vec3> { face.corners.iter().map(|c| positions[c.position]).collect() };
+
+    // area weighted normals for faces in a smoothing group without explicit normals
+    let mut smooth_normals: HashMap<(usize, u32), Vec3> = HashMap::new();
+    let mut flat_normals = Vec::with_capacity(run.faces.len());
+    for face in &run.faces {
+        let normal = get_polygon_normal(&face_points(face));
+        flat_normals.push(normal.normalize());
+        if face.smoothing != 0 {
+            for corner in face.corners.iter().filter(|c| c.normal.is_none()) {
+                *smooth_normals
+                    .entry((corner.position, face.smoothing))
+                    .or_insert(Vec3::ZERO) += normal;
+            }
+        }
+    }
+
+    let has_uvs = run
+        .faces
+        .iter()
+        .flat_map(|f| &f.corners)
+        .any(|c| c.uv.is_some());
+    let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+    let mut vertices: HashMap<VertexKey, u32> = HashMap::new();
+    for (face_index, face) in run.faces.iter().enumerate() {
+        let mut indices = Vec::with_capacity(face.corners.len());
+        for corner in &face.corners {
+            let key = get_vertex_key(face, face_index, corner);
+            let index = *vertices.entry(key).or_insert_with(|| {
+                mesh.positions.push(positions[corner.position]);
+                mesh.normals.push(match key.2 {
+                    NormalKey::Index(n) => normals[n],
+                    NormalKey::Smooth(s) => smooth_normals[&(corner.position, s)].normalize(),
+                    NormalKey::Flat(f) => flat_normals[f],
+                });
+                if has_uvs {
+                    mesh.uvs.push(corner.uv.map_or(Vec2::ZERO, |t| uvs[t]));
+                }
+                if let Some(colors) = colors {
+                    mesh.colors
+                        .push(colors[corner.position].unwrap_or(Color::WHITE));
+                }
+                mesh.positions.len() as u32 - 1
+            });
+            indices.push(index);
+        }
+        for [a, b, c] in triangulate_face(&face_points(face)) {
+            mesh.indices
+                .extend_from_slice(&[indices[a], indices[b], indices[c]]);
+        }
+    }
+    mesh
+}
+
+fn build_element_mesh(
+    mode: PrimitiveMode,
+    elements: &[usize],
+    positions: &[Vec3],
+    colors: Option<&[Option<Color>]>,
+) -> Mesh {
+    let mut mesh = Mesh::new(mode);
+    let mut vertices: HashMap<usize, u32> = HashMap::new();
+    for position in elements {
+        let index = *vertices.entry(*position).or_insert_with(|| {
+            mesh.positions.push(positions[*position]);
+            if let Some(colors) = colors {
+                mesh.colors.push(colors[*position].unwrap_or(Color::WHITE));
+            }
+            mesh.positions.len() as u32 - 1
+        });
+        mesh.indices.push(index);
+    }
+    mesh
+}
+
+pub fn parse_mtl(src: &str) -> Result<Vec<ObjMaterial>, String> {
+    let mut materials: Vec<ObjMaterial> = Vec::new();
+    for (number, line) in src.lines().enumerate() {
+        let number = number + 1;
+        let line = line.split('#').next().unwrap_or("").trim();
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let Some((keyword, args)) = tokens.split_first() else {
+            continue;
+        };
+        if *keyword == "newmtl" {
+            materials.push(ObjMaterial::new(&args.join(" ")));
+            continue;
+        }
+        let material = materials
+            .last_mut()
+            .ok_or_else(|| format!("Line {}: '{}' before newmtl", number, keyword))?;
+        let color = |args: &[&str]| -> Result<Color, String> {
+            let [r, g, b] = parse_floats(args, number)?;
+            Ok(Color::rgb(r, g, b))
+        };
+        // map options like -s or -bm come first, the file name is last
+        let map = || -> Result<Option<String>, String> {
+            args.last()
+                .map(|s| Some(s.to_string()))
+                .ok_or_else(|| format!("Line {}: '{}' needs a file name", number, keyword))
+        };
+        match *keyword {
+            "Ka" => material.ambient = color(args)?,
+            "Kd" => material.diffuse = color(args)?,
+            "Ks" => material.specular = color(args)?,
+            "Ke" => material.emissive = color(args)?,
+            "Ns" => material.shininess = parse_floats::<1>(args, number)?[0],
+            "d" => material.opacity = parse_floats::<1>(args, number)?[0],
+            "Tr" => material.opacity = 1.0 - parse_floats::<1>(args, number)?[0],
+            "Ni" => material.optical_density = parse_floats::<1>(args, number)?[0],
+            "illum" => {
+                material.illumination = args
+                    .first()
+                    .and_then(|s| s.parse().ok())
+                    .ok_or_else(|| format!("Line {}: invalid illumination model", number))?
+            }
+            "map_Ka" => material.ambient_map = map()?,
+            "map_Kd" => material.diffuse_map = map()?,
+            "map_Ks" => material.specular_map = map()?,
+            "map_Ke" => material.emissive_map = map()?,
+            "map_d" => material.alpha_map = map()?,
+            "map_bump" | "bump" => material.bump_map = map()?,
+            "norm" | "map_Kn" => material.normal_map = map()?,
+            _ => {}
+        }
+    }
+    Ok(materials)
+}
+
+// material libraries are looked up next to the obj file, missing ones are skipped
+pub fn load_obj<P: AsRef<Path>>(path: P) -> Result<ObjScene, String> {
+    let path = path.as_ref();
+    let src = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    let mut scene = parse_obj(&src).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    for library in &scene.material_libraries {
+        let library_path = dir.join(library);
+        if let Ok(src) = std::fs::read_to_string(&library_path) {
+            let materials =
+                parse_mtl(&src).map_err(|e| format!("{}: {}", library_path.display(), e))?;
+            scene.materials.extend(materials);
+        }
+    }
+    Ok(scene)
+}
+
+pub fn write_obj(meshes: &[ObjMesh], material_library: Option<&str>) -> Result<String, String> {
+    let mut out = String::new();
+    if let Some(library) = material_library {
+        writeln!(out, "mtllib {}", library).unwrap();
+    }
+    // indices in obj files are global, offset each mesh by what came before
+    // meshes can differ in their attributes, so every kind of index counts on its own
+    let (mut offset, mut uv_offset, mut normal_offset) = (1, 1, 1);
+    for obj_mesh in meshes {
+        let mesh = &obj_mesh.mesh;
+        mesh.validate()?;
+        if !obj_mesh.object.is_empty() {
+            writeln!(out, "o {}", obj_mesh.object).unwrap();
+        }
+        if !obj_mesh.group.is_empty() {
+            writeln!(out, "g {}", obj_mesh.group).unwrap();
+        }
+        if let Some(material) = &obj_mesh.material {
+            writeln!(out, "usemtl {}", material).unwrap();
+        }
+        for (i, p) in mesh.positions.iter().enumerate() {
+            match mesh.colors.get(i) {
+                Some(c) => writeln!(out, "v {} {} {} {} {} {}", p.x, p.y, p.z, c.r, c.g, c.b),
+                None => writeln!(out, "v {} {} {}", p.x, p.y, p.z),
+            }
+            .unwrap();
+        }
+        for t in &mesh.uvs {
+            writeln!(out, "vt {} {}", t.x, t.y).unwrap();
+        }
+        for n in &mesh.normals {
+            writeln!(out, "vn {} {} {}", n.x, n.y, n.z).unwrap();
+        }
+
+        let corner = |i: u32| {
+            let i = i as usize;
+            let (v, vt, vn) = (i + offset, i + uv_offset, i + normal_offset);
+            match (mesh.has_uvs(), mesh.has_normals()) {
+                (true, true) => format!("{}/{}/{}", v, vt, vn),
+                (true, false) => format!("{}/{}", v, vt),
+                (false, true) => format!("{}//{}", v, vn),
+                (false, false) => format!("{}", v),
+            }
+        };
+        let elements: Vec<u32> = if mesh.is_indexed() {
+            mesh.indices.clone()
+        } else {
+            (0..mesh.positions.len() as u32).collect()
+        };
+        match mesh.mode {
+            PrimitiveMode::Points => {
+                for i in &elements {
+                    writeln!(out, "p {}", *i as usize + offset).unwrap();
+                }
+            }
+            PrimitiveMode::Lines => {
+                for l in elements.chunks_exact(2) {
+                    writeln!(
+                        out,
+                        "l {} {}",
+                        l[0] as usize + offset,
+                        l[1] as usize + offset
+                    )
+                    .unwrap();
+                }
+            }
+            PrimitiveMode::LineStrip | PrimitiveMode::LineLoop => {
+                let mut line: Vec<String> = elements
+                    .iter()
+                    .map(|i| (*i as usize + offset).to_string())
+                    .collect();
+                if mesh.mode == PrimitiveMode::LineLoop && !line.is_empty() {
+                    line.push(line[0].clone());
+                }
+                writeln!(out, "l {}", line.join(" ")).unwrap();
+            }
+            _ => {
+                for [a, b, c] in mesh.get_triangles() {
+                    writeln!(out, "f {} {} {}", corner(a), corner(b), corner(c)).unwrap();
+                }
+            }
+        }
+        offset += mesh.positions.len();
+        uv_offset += mesh.uvs.len();
+        normal_offset += mesh.normals.len();
+    }
+    Ok(out)
+}
+
+pub fn write_mtl(materials: &[ObjMaterial]) -> String {
+    let mut out = String::new();
+    for m in materials {
+        writeln!(out, "newmtl {}", m.name).unwrap();
+        for (keyword, c) in [
+            ("Ka", m.ambient),
+            ("Kd", m.diffuse),
+            ("Ks", m.specular),
+            ("Ke", m.emissive),
+        ] {
+            writeln!(out, "{} {} {} {}", keyword, c.r, c.g, c.b).unwrap();
+        }
+        writeln!(out, "Ns {}", m.shininess).unwrap();
+        writeln!(out, "d {}", m.opacity).unwrap();
+        writeln!(out, "Ni {}", m.optical_density).unwrap();
+        writeln!(out, "illum {}", m.illumination).unwrap();
+        for (keyword, map) in [
+            ("map_Ka", &m.ambient_map),
+            ("map_Kd", &m.diffuse_map),
+            ("map_Ks", &m.specular_map),
+            ("map_Ke", &m.emissive_map),
+            ("map_d", &m.alpha_map),
+            ("map_bump", &m.bump_map),
+            ("norm", &m.normal_map),
+        ] {
+            if let Some(map) = map {
+                writeln!(out, "{} {}", keyword, map).unwrap();
+            }
+        }
+        writeln!(out).unwrap();
+    }
+    out
+}
+
+// writes the materials to a .mtl next to the obj when there are any
+pub fn save_obj<P: AsRef<Path>>(path: P, scene: &ObjScene) -> Result<(), String> {
+    let path = path.as_ref();
+    let library = if scene.materials.is_empty() {
+        None
+    } else {
+        let library_path = path.with_extension("mtl");
+        std::fs::write(&library_path, write_mtl(&scene.materials))
+            .map_err(|e| format!("Failed to write '{}': {}", library_path.display(), e))?;
+        library_path
+            .file_name()
+            .map(|s| s.to_string_lossy().into_owned())
+    };
+    let src = write_obj(&scene.meshes, library.as_deref())?;
+    std::fs::write(path, src).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+}
+
+impl Mesh {
+    pub fn to_obj(&self) -> Result<String, String> {
+        write_obj(
+            &[ObjMesh {
+                mesh: self.clone(),
+                ..Default::default()
+            }],
+            None,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CUBE: &str = "
+        mtllib cube.mtl
+        o cube
+        v -1 -1 -1
+        v 1 -1 -1
+        v 1 1 -1
+        v -1 1 -1
+        v -1 -1 1
+        v 1 -1 1
+        v 1 1 1
+        v -1 1 1
+        usemtl red
+        s 1
+        f 1 4 3 2
+        f 5 6 7 8
+        f 1 2 6 5
+        usemtl blue
+        s off
+        f -7 -6 -2 -3 # relative indices
+        f 3 4 8 7
+        f 4 1 5 8
+    ";
+
+    #[test]
+    fn parses_groups_and_dedupes_vertices() {
+        let scene = parse_obj(CUBE).unwrap();
+        assert_eq!(scene.material_libraries, vec!["cube.mtl"]);
+        assert_eq!(scene.meshes.len(), 2);
+        let red = &scene.meshes[0];
+        assert_eq!(red.object, "cube");
+        assert_eq!(red.material.as_deref(), Some("red"));
+        // smoothed faces share the 8 corners they touch
+        assert_eq!(red.mesh.get_vertex_count(), 8);
+        assert_eq!(red.mesh.get_primitive_count(), 6);
+        // flat faces get their own vertices
+        let blue = &scene.meshes[1];
+        assert_eq!(blue.mesh.get_vertex_count(), 12);
+        assert!(blue.mesh.validate().is_ok());
+        assert!((blue.mesh.normals[0] - Vec3::X).length() < 1e-6);
+    }
+
+    #[test]
+    fn triangulates_concave_polygons() {
+        let src = "
+            v 0 0 0
+            v 2 0 0
+            v 2 2 0
+            v 1 1 0
+            v 0 2 0
+            vt 0 0
+            vn 0 0 1
+            f 1/1/1 2/1/1 3/1/1 4/1/1 5/1/1
+        ";
+        let mesh = &parse_obj(src).unwrap().meshes[0].mesh;
+        assert_eq!(mesh.get_primitive_count(), 3);
+        // no triangle may cover the notch at (1, 1.5)
+        for [a, b, c] in mesh.get_triangles() {
+            let t = Triangle::new(
+                mesh.positions[a as usize],
+                mesh.positions[b as usize],
+                mesh.positions[c as usize],
+            );
+            assert!(!t.contains_point(Vec3::new(1.0, 1.5, 0.0)));
+        }
+    }
+
+    #[test]
+    fn reports_line_numbers() {
+        let error = parse_obj("v 0 0 0\nv 1 0 0\nf 1 2 3\n").unwrap_err();
+        assert!(error.starts_with("Line 3"), "{}", error);
+        let error = parse_obj("v 0 zero 0\n").unwrap_err();
+        assert!(error.starts_with("Line 1"), "{}", error);
+        let error = parse_mtl("Kd 1 1 1\n").unwrap_err();
+        assert!(error.starts_with("Line 1"), "{}", error);
+    }
+
+    #[test]
+    fn parses_materials() {
+        let src = "
+            newmtl red
+            Kd 1 0 0
+            Ns 32
+            d 0.5
+            map_Kd -s 2 2 1 textures/red.png
+            norm red_normal.png
+        ";
+        let materials = parse_mtl(src).unwrap();
+        assert_eq!(materials.len(), 1);
+        assert_eq!(materials[0].diffuse, Color::RED);
+        assert_eq!(materials[0].opacity, 0.5);
+        assert_eq!(
+            materials[0].diffuse_map.as_deref(),
+            Some("textures/red.png")
+        );
+        assert_eq!(materials[0].normal_map.as_deref(), Some("red_normal.png"));
+        assert_eq!(parse_mtl(&write_mtl(&materials)).unwrap(), materials);
+    }
+
+    #[test]
+    fn round_trips_meshes() {
+        let mut mesh = Mesh::cuboid(Vec3::ONE, [1, 1, 1]);
+        mesh.tangents.clear();
+        mesh.colors = vec![Color::RED; mesh.get_vertex_count()];
+        let scene = parse_obj(&mesh.to_obj().unwrap()).unwrap();
+        let parsed = &scene.meshes[0].mesh;
+        assert_eq!(parsed.get_vertex_count(), mesh.get_vertex_count());
+        assert_eq!(parsed.colors, mesh.colors);
+        // vertices are renumbered in order of first use, compare what the triangles see
+        let corners = |m: &Mesh| -> Vec<(Vec3, Vec3, Vec2)> {
+            m.indices
+                .iter()
+                .map(|i| {
+                    let i = *i as usize;
+                    (m.positions[i], m.normals[i], m.uvs[i])
+                })
+                .collect()
+        };
+        assert_eq!(corners(parsed), corners(&mesh));
+    }
+
+    #[test]
+    fn round_trips_mixed_meshes() {
+        let bare = Mesh::from_positions(vec![Vec3::ZERO, Vec3::X, Vec3::Y], vec![0, 1, 2]);
+        let mut cube = Mesh::cuboid(Vec3::ONE, [1, 1, 1]);
+        cube.tangents.clear();
+        let mut lines = Mesh::new(PrimitiveMode::LineStrip);
+        lines.positions = vec![Vec3::ZERO, Vec3::Y, Vec3::ONE];
+        let mut points = Mesh::new(PrimitiveMode::Points);
+        points.positions = vec![Vec3::Z, Vec3::ONE];
+        let meshes: Vec<ObjMesh> = [bare, cube.clone(), lines, points]
+            .into_iter()
+            .enumerate()
+            .map(|(i, mesh)| ObjMesh {
+                object: format!("mesh{}", i),
+                mesh,
+                ..Default::default()
+            })
+            .collect();
+        let scene = parse_obj(&write_obj(&meshes, None).unwrap()).unwrap();
+        assert_eq!(scene.meshes.len(), 4);
+
+        // the cube still finds its own uvs and normals after a mesh without any
+        let parsed = &scene.meshes[1].mesh;
+        assert_eq!(scene.meshes[1].object, "mesh1");
+        for (a, b) in parsed.get_triangles().iter().zip(cube.get_triangles()) {
+            for (i, j) in a.iter().zip(b) {
+                let (i, j) = (*i as usize, j as usize);
+                assert_eq!(parsed.positions[i], cube.positions[j]);
+                assert_eq!(parsed.normals[i], cube.normals[j]);
+                assert_eq!(parsed.uvs[i], cube.uvs[j]);
+            }
+        }
+
+        let lines = &scene.meshes[2].mesh;
+        assert_eq!(lines.mode, PrimitiveMode::Lines);
+        let segments: Vec<Vec3> = lines
+            .indices
+            .iter()
+            .map(|i| lines.positions[*i as usize])
+            .collect();
+        assert_eq!(segments, [Vec3::ZERO, Vec3::Y, Vec3::Y, Vec3::ONE]);
+        let points = &scene.meshes[3].mesh;
+        assert_eq!(points.mode, PrimitiveMode::Points);
+        assert_eq!(points.positions, [Vec3::Z, Vec3::ONE]);
+    }
+}
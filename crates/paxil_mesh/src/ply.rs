@@ -0,0 +1,773 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use super::mesh::{Mesh, PrimitiveMode};
+use super::obj::triangulate_face;
+use paxil_math::*;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PlyFormat {
+    #[default]
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+impl PlyFormat {
+    fn get_name(&self) -> &'static str {
+        match self {
+            PlyFormat::Ascii => "ascii",
+            PlyFormat::BinaryLittleEndian => "binary_little_endian",
+            PlyFormat::BinaryBigEndian => "binary_big_endian",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlyType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64,
+}
+
+impl PlyType {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "char" | "int8" => PlyType::Int8,
+            "uchar" | "uint8" => PlyType::UInt8,
+            "short" | "int16" => PlyType::Int16,
+            "ushort" | "uint16" => PlyType::UInt16,
+            "int" | "int32" => PlyType::Int32,
+            "uint" | "uint32" => PlyType::UInt32,
+            "float" | "float32" => PlyType::Float32,
+            "double" | "float64" => PlyType::Float64,
+            _ => return None,
+        })
+    }
+
+    fn get_name(&self) -> &'static str {
+        match self {
+            PlyType::Int8 => "char",
+            PlyType::UInt8 => "uchar",
+            PlyType::Int16 => "short",
+            PlyType::UInt16 => "ushort",
+            PlyType::Int32 => "int",
+            PlyType::UInt32 => "uint",
+            PlyType::Float32 => "float",
+            PlyType::Float64 => "double",
+        }
+    }
+
+    pub fn get_size(&self) -> usize {
+        match self {
+            PlyType::Int8 | PlyType::UInt8 => 1,
+            PlyType::Int16 | PlyType::UInt16 => 2,
+            PlyType::Int32 | PlyType::UInt32 | PlyType::Float32 => 4,
+            PlyType::Float64 => 8,
+        }
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, PlyType::Float32 | PlyType::Float64)
+    }
+
+    // value that maps to 1.0 when the type holds a normalized colour
+    fn get_max(&self) -> f64 {
+        match self {
+            PlyType::Int8 => i8::MAX as f64,
+            PlyType::UInt8 => u8::MAX as f64,
+            PlyType::Int16 => i16::MAX as f64,
+            PlyType::UInt16 => u16::MAX as f64,
+            PlyType::Int32 => i32::MAX as f64,
+            PlyType::UInt32 => u32::MAX as f64,
+            PlyType::Float32 | PlyType::Float64 => 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlyValues {
+    Scalar(Vec<f64>),
+    // list properties keep the type of their length prefix for writing
+    List(PlyType, Vec<Vec<f64>>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlyProperty {
+    pub name: String,
+    pub data_type: PlyType,
+    pub values: PlyValues,
+}
+
+impl PlyProperty {
+    pub fn new_scalar(name: &str, data_type: PlyType, values: Vec<f64>) -> Self {
+        Self {
+            name: name.to_string(),
+            data_type,
+            values: PlyValues::Scalar(values),
+        }
+    }
+
+    pub fn new_list(
+        name: &str,
+        count_type: PlyType,
+        data_type: PlyType,
+        lists: Vec<Vec<f64>>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            data_type,
+            values: PlyValues::List(count_type, lists),
+        }
+    }
+
+    pub fn get_scalars(&self) -> Option<&[f64]> {
+        match &self.values {
+            PlyValues::Scalar(values) => Some(values),
+            PlyValues::List(..) => None,
+        }
+    }
+
+    pub fn get_lists(&self) -> Option<&[Vec<f64>]> {
+        match &self.values {
+            PlyValues::Scalar(_) => None,
+            PlyValues::List(_, lists) => Some(lists),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match &self.values {
+            PlyValues::Scalar(values) => values.len(),
+            PlyValues::List(_, lists) => lists.len(),
+        }
+    }
+}
+
+// properties are stored by column, every property of an element has count values
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlyElement {
+    pub name: String,
+    pub count: usize,
+    pub properties: Vec<PlyProperty>,
+}
+
+impl PlyElement {
+    pub fn new(name: &str, count: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            count,
+            properties: Vec::new(),
+        }
+    }
+
+    pub fn get_property(&self, name: &str) -> Option<&PlyProperty> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+
+    fn find_scalars(&self, names: &[&str]) -> Option<&PlyProperty> {
+        names
+            .iter()
+            .find_map(|n| self.get_property(n))
+            .filter(|p| p.get_scalars().is_some())
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlyData {
+    pub comments: Vec<String>,
+    pub elements: Vec<PlyElement>,
+}
+
+impl PlyData {
+    pub fn get_element(&self, name: &str) -> Option<&PlyElement> {
+        self.elements.iter().find(|e| e.name == name)
+    }
+
+    // vertex positions, normals, uvs and colours plus triangulated faces,
+    // files without faces become point clouds
+    pub fn to_mesh(&self) -> Result<Mesh, String> {
+        let vertex = self
+            .get_element("vertex")
+            .ok_or_else(|| "PLY has no vertex element".to_string())?;
+        let get_vec3 = |names: [&[&str]; 3]| -> Option<Vec<Vec3>> {
+            let [x, y, z] = names.map(|n| vertex.find_scalars(n).and_then(|p| p.get_scalars()));
+            let (x, y, z) = (x?, y?, z?);
+            Some(
+                (0..vertex.count)
+                    .map(|i| Vec3::new(x[i] as f32, y[i] as f32, z[i] as f32))
+                    .collect(),
+            )
+        };
+
+        let faces = self
+            .get_element("face")
+            .and_then(|f| {
+                f.get_property("vertex_indices")
+                    .or(f.get_property("vertex_index"))
+            })
+            .and_then(|p| p.get_lists())
+            .filter(|f| !f.is_empty());
+        let mut mesh = Mesh::new(if faces.is_some() {
+            PrimitiveMode::Triangles
+        } else {
+            PrimitiveMode::Points
+        });
+        mesh.positions = get_vec3([&["x"], &["y"], &["z"]])
+            .ok_or_else(|| "PLY vertices have no x, y and z properties".to_string())?;
+        mesh.normals = get_vec3([&["nx"], &["ny"], &["nz"]]).unwrap_or_default();
+
+        let u = vertex.find_scalars(&["u", "s", "texture_u", "texture_s"]);
+        let v = vertex.find_scalars(&["v", "t", "texture_v", "texture_t"]);
+        if let (Some(u), Some(v)) = (
+            u.and_then(|p| p.get_scalars()),
+            v.and_then(|p| p.get_scalars()),
+        ) {
+            mesh.uvs = (0..vertex.count)
+                .map(|i| Vec2::new(u[i] as f32, v[i] as f32))
+                .collect();
+        }
+
+        // integer colours are normalized by the range of their type
+        let channels = [
+            vertex.find_scalars(&["red", "diffuse_red", "r"]),
+            vertex.find_scalars(&["green", "diffuse_green", "g"]),
+            vertex.find_scalars(&["blue", "diffuse_blue", "b"]),
+        ];
+        if let [Some(r), Some(g), Some(b)] = channels {
+            let alpha = vertex.find_scalars(&["alpha", "diffuse_alpha", "a"]);
+            let channel = |p: &PlyProperty, i: usize| {
+                (p.get_scalars().unwrap()[i] / p.data_type.get_max()) as f32
+            };
+            mesh.colors = (0..vertex.count)
+                .map(|i| {
+                    Color::new(
+                        channel(r, i),
+                        channel(g, i),
+                        channel(b, i),
+                        alpha.map_or(1.0, |a| channel(a, i)),
+                    )
+                })
+                .collect();
+        }
+
+        for face in faces.unwrap_or_default() {
+            // list values are read as f64, so negative, fractional and NaN indices get here too
+            let indices = face
+                .iter()
+                .map(|i| {
+                    if *i >= 0.0
+                        && i.fract() == 0.0
+                        && *i < vertex.count.min(u32::MAX as usize) as f64
+                    {
+                        Ok(*i as u32)
+                    } else {
+                        Err(format!(
+                            "Face index {} is out of range for {} vertices",
+                            i, vertex.count
+                        ))
+                    }
+                })
+                .collect::<Result<Vec<u32>, String>>()?;
+            if indices.len() < 3 {
+                continue;
+            }
+            let points: Vec<Vec3> = indices
+                .iter()
+                .map(|i| mesh.positions[*i as usize])
+                .collect();
+            for [a, b, c] in triangulate_face(&points) {
+                mesh.indices
+                    .extend_from_slice(&[indices[a], indices[b], indices[c]]);
+            }
+        }
+        mesh.validate()?;
+        Ok(mesh)
+    }
+
+    // point meshes are written without faces, triangle modes as triangle faces
+    pub fn from_mesh(mesh: &Mesh) -> Result<Self, String> {
+        mesh.validate()?;
+        if !mesh.mode.is_triangles() && mesh.mode != PrimitiveMode::Points {
+            return Err(format!("Cannot write {:?} primitives to PLY", mesh.mode));
+        }
+
+        let mut vertex = PlyElement::new("vertex", mesh.get_vertex_count());
+        let mut add = |name: &str, data_type: PlyType, values: Vec<f64>| {
+            vertex
+                .properties
+                .push(PlyProperty::new_scalar(name, data_type, values));
+        };
+        let column = |values: &[f32]| values.iter().map(|v| *v as f64).collect();
+        let positions: Vec<[f32; 3]> = mesh.positions.iter().map(|p| (*p).into()).collect();
+        for (axis, name) in ["x", "y", "z"].iter().enumerate() {
+            let values: Vec<f32> = positions.iter().map(|p| p[axis]).collect();
+            add(name, PlyType::Float32, column(&values));
+        }
+        if mesh.has_normals() {
+            let normals: Vec<[f32; 3]> = mesh.normals.iter().map(|n| (*n).into()).collect();
+            for (axis, name) in ["nx", "ny", "nz"].iter().enumerate() {
+                let values: Vec<f32> = normals.iter().map(|n| n[axis]).collect();
+                add(name, PlyType::Float32, column(&values));
+            }
+        }
+        if mesh.has_uvs() {
+            let u: Vec<f32> = mesh.uvs.iter().map(|t| t.x).collect();
+            let v: Vec<f32> = mesh.uvs.iter().map(|t| t.y).collect();
+            add("s", PlyType::Float32, column(&u));
+            add("t", PlyType::Float32, column(&v));
+        }
+        if mesh.has_colors() {
+            let channel = |f: fn(&Color) -> f32| -> Vec<f64> {
+                mesh.colors
+                    .iter()
+                    .map(|c| (f(c).clamp(0.0, 1.0) * 255.0).round() as f64)
+                    .collect()
+            };
+            add("red", PlyType::UInt8, channel(|c| c.r));
+            add("green", PlyType::UInt8, channel(|c| c.g));
+            add("blue", PlyType::UInt8, channel(|c| c.b));
+            add("alpha", PlyType::UInt8, channel(|c| c.a));
+        }
+
+        let mut data = PlyData {
+            comments: Vec::new(),
+            elements: vec![vertex],
+        };
+        if mesh.mode.is_triangles() {
+            let faces: Vec<Vec<f64>> = mesh
+                .get_triangles()
+                .iter()
+                .map(|t| t.iter().map(|i| *i as f64).collect())
+                .collect();
+            let mut face = PlyElement::new("face", faces.len());
+            face.properties.push(PlyProperty::new_list(
+                "vertex_indices",
+                PlyType::UInt8,
+                PlyType::Int32,
+                faces,
+            ));
+            data.elements.push(face);
+        }
+        Ok(data)
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    format: PlyFormat,
+    tokens: std::str::SplitAsciiWhitespace<'a>,
+}
+
+impl Reader<'_> {
+    fn read(&mut self, data_type: PlyType) -> Result<f64, String> {
+        if self.format == PlyFormat::Ascii {
+            let token = self
+                .tokens
+                .next()
+                .ok_or_else(|| "Unexpected end of PLY data".to_string())?;
+            return token
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid PLY value '{}'", token));
+        }
+
+        let size = data_type.get_size();
+        let bytes = self
+            .bytes
+            .get(self.offset..self.offset + size)
+            .ok_or_else(|| "Unexpected end of PLY data".to_string())?;
+        self.offset += size;
+        let mut buffer = [0u8; 8];
+        buffer[..size].copy_from_slice(bytes);
+        if self.format == PlyFormat::BinaryBigEndian {
+            buffer[..size].reverse();
+        }
+        let [b0, b1, b2, b3, ..] = buffer;
+        Ok(match data_type {
+            PlyType::Int8 => b0 as i8 as f64,
+            PlyType::UInt8 => b0 as f64,
+            PlyType::Int16 => i16::from_le_bytes([b0, b1]) as f64,
+            PlyType::UInt16 => u16::from_le_bytes([b0, b1]) as f64,
+            PlyType::Int32 => i32::from_le_bytes([b0, b1, b2, b3]) as f64,
+            PlyType::UInt32 => u32::from_le_bytes([b0, b1, b2, b3]) as f64,
+            PlyType::Float32 => f32::from_le_bytes([b0, b1, b2, b3]) as f64,
+            PlyType::Float64 => f64::from_le_bytes(buffer),
+        })
+    }
+}
+
+// end_header only counts as a whole line, not inside a comment
+fn find_header_end(bytes: &[u8]) -> Option<usize> {
+    let mut start = 0;
+    while start < bytes.len() {
+        let length = bytes[start..].iter().position(|b| *b == b'\n')?;
+        let line = &bytes[start..start + length];
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.trim_ascii() == b"end_header" {
+            return Some(start + length + 1);
+        }
+        start += length + 1;
+    }
+    None
+}
+
+pub fn parse_ply(bytes: &[u8]) -> Result<PlyData, String> {
+    if !bytes.starts_with(b"ply") {
+        return Err("Missing 'ply' magic".to_string());
+    }
+    let header_end = find_header_end(bytes).ok_or_else(|| "Missing 'end_header'".to_string())?;
+    let header = std::str::from_utf8(&bytes[..header_end])
+        .map_err(|_| "PLY header is not valid text".to_string())?;
+
+    let mut data = PlyData::default();
+    let mut format = None;
+    for (number, line) in header.lines().enumerate().skip(1) {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let error = |message: &str| format!("Line {}: {}", number + 1, message);
+        match tokens.as_slice() {
+            ["format", name, _] => {
+                format = Some(match *name {
+                    "ascii" => PlyFormat::Ascii,
+                    "binary_little_endian" => PlyFormat::BinaryLittleEndian,
+                    "binary_big_endian" => PlyFormat::BinaryBigEndian,
+                    _ => return Err(error(&format!("Unknown format '{}'", name))),
+                })
+            }
+            ["comment", ..] | ["obj_info", ..] => {
+                let text = line.trim_start();
+                let text = text[tokens[0].len()..].trim();
+                data.comments.push(text.to_string());
+            }
+            ["element", name, count] => {
+                let count = count
+                    .parse()
+                    .map_err(|_| error(&format!("Invalid element count '{}'", count)))?;
+                data.elements.push(PlyElement::new(name, count));
+            }
+            ["property", "list", count_type, data_type, name] => {
+                let element = data
+                    .elements
+                    .last_mut()
+                    .ok_or_else(|| error("Property before any element"))?;
+                let (Some(count_type), Some(data_type)) =
+                    (PlyType::parse(count_type), PlyType::parse(data_type))
+                else {
+                    return Err(error("Unknown list property type"));
+                };
+                if count_type.is_float() {
+                    return Err(error("List counts must be integers"));
+                }
+                element.properties.push(PlyProperty::new_list(
+                    name,
+                    count_type,
+                    data_type,
+                    Vec::new(),
+                ));
+            }
+            ["property", data_type, name] => {
+                let element = data
+                    .elements
+                    .last_mut()
+                    .ok_or_else(|| error("Property before any element"))?;
+                let data_type = PlyType::parse(data_type)
+                    .ok_or_else(|| error(&format!("Unknown property type '{}'", data_type)))?;
+                element
+                    .properties
+                    .push(PlyProperty::new_scalar(name, data_type, Vec::new()));
+            }
+            ["end_header"] | [] => {}
+            _ => return Err(error(&format!("Unexpected header line '{}'", line.trim()))),
+        }
+    }
+    let format = format.ok_or_else(|| "PLY header has no format line".to_string())?;
+
+    let body = &bytes[header_end..];
+    let text = if format == PlyFormat::Ascii {
+        std::str::from_utf8(body).map_err(|_| "PLY ascii data is not valid text".to_string())?
+    } else {
+        ""
+    };
+    let mut reader = Reader {
+        bytes: body,
+        offset: 0,
+        format,
+        tokens: text.split_ascii_whitespace(),
+    };
+    for element in &mut data.elements {
+        if element.properties.is_empty() {
+            continue;
+        }
+        // every row takes at least a byte, so the count is never trusted beyond the body size
+        if element.count > body.len() {
+            return Err(format!(
+                "PLY element '{}' has {} rows but only {} bytes of data",
+                element.name,
+                element.count,
+                body.len()
+            ));
+        }
+        for property in &mut element.properties {
+            match &mut property.values {
+                PlyValues::Scalar(values) => values.reserve(element.count),
+                PlyValues::List(_, lists) => lists.reserve(element.count),
+            }
+        }
+        for _ in 0..element.count {
+            for property in &mut element.properties {
+                match &mut property.values {
+                    PlyValues::Scalar(values) => values.push(reader.read(property.data_type)?),
+                    PlyValues::List(count_type, lists) => {
+                        let count = reader.read(*count_type)? as usize;
+                        let list = (0..count)
+                            .map(|_| reader.read(property.data_type))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        lists.push(list);
+                    }
+                }
+            }
+        }
+    }
+    Ok(data)
+}
+
+fn write_value(out: &mut Vec<u8>, value: f64, data_type: PlyType, format: PlyFormat) {
+    if format == PlyFormat::Ascii {
+        if data_type == PlyType::Float32 {
+            out.extend_from_slice((value as f32).to_string().as_bytes());
+        } else if data_type == PlyType::Float64 {
+            out.extend_from_slice(value.to_string().as_bytes());
+        } else {
+            out.extend_from_slice((value as i64).to_string().as_bytes());
+        }
+        return;
+    }
+    let mut bytes: Vec<u8> = match data_type {
+        PlyType::Int8 => (value as i8).to_le_bytes().to_vec(),
+        PlyType::UInt8 => (value as u8).to_le_bytes().to_vec(),
+        PlyType::Int16 => (value as i16).to_le_bytes().to_vec(),
+        PlyType::UInt16 => (value as u16).to_le_bytes().to_vec(),
+        PlyType::Int32 => (value as i32).to_le_bytes().to_vec(),
+        PlyType::UInt32 => (value as u32).to_le_bytes().to_vec(),
+        PlyType::Float32 => (value as f32).to_le_bytes().to_vec(),
+        PlyType::Float64 => value.to_le_bytes().to_vec(),
+    };
+    if format == PlyFormat::BinaryBigEndian {
+        bytes.reverse();
+    }
+    out.extend_from_slice(&bytes);
+}
+
+pub fn write_ply(data: &PlyData, format: PlyFormat) -> Result<Vec<u8>, String> {
+    let mut header = String::new();
+    let _ = writeln!(header, "ply\nformat {} 1.0", format.get_name());
+    for comment in &data.comments {
+        let _ = writeln!(header, "comment {}", comment);
+    }
+    for element in &data.elements {
+        let _ = writeln!(header, "element {} {}", element.name, element.count);
+        for property in &element.properties {
+            if property.len() != element.count {
+                return Err(format!(
+                    "Property '{}' has {} values for {} {} elements",
+                    property.name,
+                    property.len(),
+                    element.count,
+                    element.name
+                ));
+            }
+            match &property.values {
+                PlyValues::Scalar(_) => {
+                    let _ = writeln!(
+                        header,
+                        "property {} {}",
+                        property.data_type.get_name(),
+                        property.name
+                    );
+                }
+                PlyValues::List(count_type, _) => {
+                    let _ = writeln!(
+                        header,
+                        "property list {} {} {}",
+                        count_type.get_name(),
+                        property.data_type.get_name(),
+                        property.name
+                    );
+                }
+            }
+        }
+    }
+    header.push_str("end_header\n");
+
+    let mut out = header.into_bytes();
+    for element in &data.elements {
+        for i in 0..element.count {
+            for (p, property) in element.properties.iter().enumerate() {
+                if format == PlyFormat::Ascii && p > 0 {
+                    out.push(b' ');
+                }
+                match &property.values {
+                    PlyValues::Scalar(values) => {
+                        write_value(&mut out, values[i], property.data_type, format)
+                    }
+                    PlyValues::List(count_type, lists) => {
+                        let list = &lists[i];
+                        if list.len() as f64 > count_type.get_max() {
+                            return Err(format!(
+                                "List of {} values does not fit a {} count",
+                                list.len(),
+                                count_type.get_name()
+                            ));
+                        }
+                        write_value(&mut out, list.len() as f64, *count_type, format);
+                        for value in list {
+                            if format == PlyFormat::Ascii {
+                                out.push(b' ');
+                            }
+                            write_value(&mut out, *value, property.data_type, format);
+                        }
+                    }
+                }
+            }
+            if format == PlyFormat::Ascii {
+                out.push(b'\n');
+            }
+        }
+    }
+    Ok(out)
+}
+
+pub fn load_ply<P: AsRef<Path>>(path: P) -> Result<PlyData, String> {
+    let path = path.as_ref();
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    parse_ply(&bytes).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+pub fn save_ply<P: AsRef<Path>>(path: P, data: &PlyData, format: PlyFormat) -> Result<(), String> {
+    let path = path.as_ref();
+    let bytes = write_ply(data, format)?;
+    std::fs::write(path, bytes).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+}
+
+impl Mesh {
+    pub fn to_ply(&self, format: PlyFormat) -> Result<Vec<u8>, String> {
+        write_ply(&PlyData::from_mesh(self)?, format)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POINT_CLOUD: &str = "ply
+format ascii 1.0
+comment scanned
+element vertex 3
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+property float confidence
+end_header
+0 0 0 255 0 0 0.5
+1 0 0 0 255 0 0.25
+0 1 0 0 0 255 1
+";
+
+    #[test]
+    fn point_clouds_keep_colors_and_extra_properties() {
+        let data = parse_ply(POINT_CLOUD.as_bytes()).unwrap();
+        assert_eq!(data.comments, vec!["scanned"]);
+        let vertex = data.get_element("vertex").unwrap();
+        let confidence = vertex.get_property("confidence").unwrap();
+        assert_eq!(confidence.get_scalars().unwrap(), &[0.5, 0.25, 1.0]);
+
+        let mesh = data.to_mesh().unwrap();
+        assert_eq!(mesh.mode, PrimitiveMode::Points);
+        assert_eq!(mesh.get_primitive_count(), 3);
+        assert_eq!(mesh.colors[1], Color::new(0.0, 1.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn faces_are_triangulated() {
+        let src = "ply
+format ascii 1.0
+element vertex 4
+property double x
+property double y
+property double z
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0
+1 0 0
+1 1 0
+0 1 0
+4 0 1 2 3
+";
+        let mesh = parse_ply(src.as_bytes()).unwrap().to_mesh().unwrap();
+        assert_eq!(mesh.mode, PrimitiveMode::Triangles);
+        assert_eq!(mesh.get_primitive_count(), 2);
+
+        let bad = src.replace("4 0 1 2 3", "3 0 1 9");
+        assert!(parse_ply(bad.as_bytes()).unwrap().to_mesh().is_err());
+        let negative = src.replace("4 0 1 2 3", "3 0 1 -1");
+        assert!(parse_ply(negative.as_bytes()).unwrap().to_mesh().is_err());
+        let commented = src.replace("end_header", "comment end_header\nend_header");
+        let mesh = parse_ply(commented.as_bytes()).unwrap().to_mesh().unwrap();
+        assert_eq!(mesh.get_primitive_count(), 2);
+        let short = src.replace("4 0 1 2 3", "4 0 1");
+        assert!(parse_ply(short.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let mut mesh = Mesh::cuboid(Vec3::ONE, [1, 1, 1]);
+        mesh.tangents.clear();
+        mesh.colors = vec![Color::new(1.0, 0.0, 0.0, 1.0); mesh.get_vertex_count()];
+        for format in [
+            PlyFormat::Ascii,
+            PlyFormat::BinaryLittleEndian,
+            PlyFormat::BinaryBigEndian,
+        ] {
+            let bytes = mesh.to_ply(format).unwrap();
+            let data = parse_ply(&bytes).unwrap();
+            assert_eq!(write_ply(&data, format).unwrap(), bytes);
+
+            let loaded = data.to_mesh().unwrap();
+            assert_eq!(loaded.positions, mesh.positions);
+            assert_eq!(loaded.normals, mesh.normals);
+            assert_eq!(loaded.uvs, mesh.uvs);
+            assert_eq!(loaded.colors, mesh.colors);
+            assert_eq!(loaded.get_triangles(), mesh.get_triangles());
+        }
+    }
+
+    #[test]
+    fn oversized_counts_are_rejected() {
+        for format in ["ascii", "binary_little_endian"] {
+            let src = format!(
+                "ply\nformat {} 1.0\nelement vertex 999999999999\nproperty float x\nend_header\n1 2 3\n",
+                format
+            );
+            assert!(parse_ply(src.as_bytes()).is_err());
+        }
+        // a list count past the end of the data fails instead of allocating
+        let src = "ply
+format binary_little_endian 1.0
+element face 1
+property list uint int vertex_indices
+end_header
+";
+        let mut bytes = src.as_bytes().to_vec();
+        bytes.extend(u32::MAX.to_le_bytes());
+        assert!(parse_ply(&bytes).is_err());
+    }
+}
@@ -0,0 +1,726 @@
+use std::collections::HashMap;
+use std::f32::consts::{PI, TAU};
+
+use super::mesh::{Mesh, PrimitiveMode};
+use paxil_math::*;
+
+// all generators build indexed triangle lists with counter clockwise outward faces,
+// tangents follow increasing u and the bitangent increasing v, so w is always 1
+
+fn push_vertex(mesh: &mut Mesh, position: Vec3, normal: Vec3, uv: Vec2, tangent: Vec3) -> u32 {
+    mesh.positions.push(position);
+    mesh.normals.push(normal);
+    mesh.uvs.push(uv);
+    mesh.tangents.push(tangent.extend(1.0));
+    mesh.positions.len() as u32 - 1
+}
+
+fn push_triangle(mesh: &mut Mesh, a: u32, b: u32, c: u32) {
+    let (pa, pb, pc) = (
+        mesh.positions[a as usize],
+        mesh.positions[b as usize],
+        mesh.positions[c as usize],
+    );
+    // collapsed rings at poles and tips produce zero area triangles
+    if pa == pb || pb == pc || pc == pa {
+        return;
+    }
+    mesh.indices.extend_from_slice(&[a, b, c]);
+}
+
+// a (u + 1) x (v + 1) vertex grid where cross(dP/du, dP/dv) points outward
+fn add_grid(
+    mesh: &mut Mesh,
+    u_segments: usize,
+    v_segments: usize,
+    vertex: impl Fn(usize, usize) -> (Vec3, Vec3, Vec2, Vec3),
+) {
+    let base = mesh.positions.len() as u32;
+    for j in 0..=v_segments {
+        for i in 0..=u_segments {
+            let (position, normal, uv, tangent) = vertex(i, j);
+            push_vertex(mesh, position, normal, uv, tangent);
+        }
+    }
+    let row = u_segments as u32 + 1;
+    for j in 0..v_segments as u32 {
+        for i in 0..u_segments as u32 {
+            let a = base + j * row + i;
+            let b = a + 1;
+            let c = b + row;
+            let d = a + row;
+            push_triangle(mesh, a, b, c);
+            push_triangle(mesh, a, c, d);
+        }
+    }
+}
+
+fn add_face(
+    mesh: &mut Mesh,
+    origin: Vec3,
+    u_axis: Vec3,
+    v_axis: Vec3,
+    u_segments: usize,
+    v_segments: usize,
+) {
+    let normal = u_axis.cross(v_axis).normalize();
+    let tangent = u_axis.normalize();
+    add_grid(mesh, u_segments, v_segments, |i, j| {
+        let uv = Vec2::new(i as f32 / u_segments as f32, j as f32 / v_segments as f32);
+        (origin + u_axis * uv.x + v_axis * uv.y, normal, uv, tangent)
+    });
+}
+
+// profile point in the (radius, y) plane with its normal and v coordinate
+#[derive(Clone, Copy, Debug)]
+struct ProfilePoint {
+    radius: f32,
+    y: f32,
+    normal: Vec2,
+    v: f32,
+}
+
+impl ProfilePoint {
+    fn new(radius: f32, y: f32, normal: Vec2, v: f32) -> Self {
+        Self {
+            radius,
+            y,
+            normal: normal.normalize(),
+            v,
+        }
+    }
+}
+
+// revolves the profile around +Y, the outward side is to the right of the profile direction
+fn add_lathe(mesh: &mut Mesh, profile: &[ProfilePoint], segments: usize) {
+    add_grid(mesh, segments, profile.len() - 1, |i, j| {
+        let p = profile[j];
+        // the seam column reuses angle zero so both sides match exactly
+        let (sin, cos) = (TAU * (i % segments) as f32 / segments as f32).sin_cos();
+        (
+            Vec3::new(p.radius * sin, p.y, p.radius * cos),
+            Vec3::new(p.normal.x * sin, p.normal.y, p.normal.x * cos),
+            Vec2::new(i as f32 / segments as f32, p.v),
+            Vec3::new(cos, 0.0, -sin),
+        )
+    });
+}
+
+// flat disc in the XZ plane with planar uvs
+fn add_disc(
+    mesh: &mut Mesh,
+    y: f32,
+    inner_radius: f32,
+    outer_radius: f32,
+    segments: usize,
+    up: bool,
+) {
+    let normal = if up { Vec3::Y } else { -Vec3::Y };
+    let v_sign = if up { -1.0 } else { 1.0 };
+    let vertex = |mesh: &mut Mesh, radius: f32, k: usize| {
+        let (sin, cos) = (TAU * (k % segments) as f32 / segments as f32).sin_cos();
+        let (x, z) = (radius * sin, radius * cos);
+        let uv = Vec2::new(
+            0.5 + x / (2.0 * outer_radius),
+            0.5 + v_sign * z / (2.0 * outer_radius),
+        );
+        push_vertex(mesh, Vec3::new(x, y, z), normal, uv, Vec3::X)
+    };
+
+    if inner_radius <= 0.0 {
+        let center = push_vertex(
+            mesh,
+            Vec3::new(0.0, y, 0.0),
+            normal,
+            Vec2::splat(0.5),
+            Vec3::X,
+        );
+        let rim: Vec<u32> = (0..segments)
+            .map(|k| vertex(mesh, outer_radius, k))
+            .collect();
+        for k in 0..segments {
+            let (a, b) = (rim[k], rim[(k + 1) % segments]);
+            if up {
+                push_triangle(mesh, center, a, b);
+            } else {
+                push_triangle(mesh, center, b, a);
+            }
+        }
+    } else {
+        let inner: Vec<u32> = (0..segments)
+            .map(|k| vertex(mesh, inner_radius, k))
+            .collect();
+        let outer: Vec<u32> = (0..segments)
+            .map(|k| vertex(mesh, outer_radius, k))
+            .collect();
+        for k in 0..segments {
+            let n = (k + 1) % segments;
+            let (i0, i1, o0, o1) = (inner[k], inner[n], outer[k], outer[n]);
+            if up {
+                push_triangle(mesh, i0, o0, o1);
+                push_triangle(mesh, i0, o1, i1);
+            } else {
+                push_triangle(mesh, i0, o1, o0);
+                push_triangle(mesh, i0, i1, o1);
+            }
+        }
+    }
+}
+
+impl Mesh {
+    // XZ plane facing +Y, centered on the origin
+    pub fn plane(width: f32, depth: f32, width_segments: u32, depth_segments: u32) -> Self {
+        let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+        add_face(
+            &mut mesh,
+            Vec3::new(-width * 0.5, 0.0, depth * 0.5),
+            Vec3::new(width, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, -depth),
+            width_segments.max(1) as usize,
+            depth_segments.max(1) as usize,
+        );
+        mesh
+    }
+
+    // line grid in the XZ plane, for editors and debug views
+    pub fn grid(size: f32, divisions: u32) -> Self {
+        let mut mesh = Mesh::new(PrimitiveMode::Lines);
+        let divisions = divisions.max(1);
+        let half = size * 0.5;
+        for i in 0..=divisions {
+            let t = -half + size * i as f32 / divisions as f32;
+            mesh.positions.extend_from_slice(&[
+                Vec3::new(t, 0.0, -half),
+                Vec3::new(t, 0.0, half),
+                Vec3::new(-half, 0.0, t),
+                Vec3::new(half, 0.0, t),
+            ]);
+        }
+        mesh
+    }
+
+    // segments per axis, each face is subdivided along its two axes
+    pub fn cuboid(size: Vec3, segments: [u32; 3]) -> Self {
+        let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+        let h = size * 0.5;
+        let [sx, sy, sz] = segments.map(|s| s.max(1) as usize);
+        let (x, y, z) = (
+            Vec3::new(size.x, 0.0, 0.0),
+            Vec3::new(0.0, size.y, 0.0),
+            Vec3::new(0.0, 0.0, size.z),
+        );
+        add_face(&mut mesh, Vec3::new(h.x, -h.y, h.z), -z, y, sz, sy);
+        add_face(&mut mesh, Vec3::new(-h.x, -h.y, -h.z), z, y, sz, sy);
+        add_face(&mut mesh, Vec3::new(-h.x, h.y, h.z), x, -z, sx, sz);
+        add_face(&mut mesh, Vec3::new(-h.x, -h.y, -h.z), x, z, sx, sz);
+        add_face(&mut mesh, Vec3::new(-h.x, -h.y, h.z), x, y, sx, sy);
+        add_face(&mut mesh, Vec3::new(h.x, -h.y, -h.z), -x, y, sx, sy);
+        mesh
+    }
+
+    pub fn uv_sphere(radius: f32, segments: u32, rings: u32) -> Self {
+        let rings = rings.max(2);
+        let profile: Vec<ProfilePoint> = (0..=rings)
+            .map(|k| {
+                let v = k as f32 / rings as f32;
+                let phi = -PI * 0.5 + PI * v;
+                // pin the poles so the collapsed rings are exact
+                let cos = if k == 0 || k == rings { 0.0 } else { phi.cos() };
+                let normal = Vec2::new(cos, phi.sin());
+                ProfilePoint::new(radius * normal.x, radius * normal.y, normal, v)
+            })
+            .collect();
+        let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+        add_lathe(&mut mesh, &profile, segments.max(3) as usize);
+        mesh
+    }
+
+    // subdivided icosahedron, vertices are split along the uv seam and at the poles
+    pub fn icosphere(radius: f32, subdivisions: u32) -> Self {
+        let t = (1.0 + 5f32.sqrt()) * 0.5;
+        let mut positions: Vec<Vec3> = [
+            (-1.0, t, 0.0),
+            (1.0, t, 0.0),
+            (-1.0, -t, 0.0),
+            (1.0, -t, 0.0),
+            (0.0, -1.0, t),
+            (0.0, 1.0, t),
+            (0.0, -1.0, -t),
+            (0.0, 1.0, -t),
+            (t, 0.0, -1.0),
+            (t, 0.0, 1.0),
+            (-t, 0.0, -1.0),
+            (-t, 0.0, 1.0),
+        ]
+        .iter()
+        .map(|(x, y, z)| Vec3::new(*x, *y, *z).normalize())
+        .collect();
+        let mut triangles: Vec<[u32; 3]> = vec![
+            [0, 11, 5],
+            [0, 5, 1],
+            [0, 1, 7],
+            [0, 7, 10],
+            [0, 10, 11],
+            [1, 5, 9],
+            [5, 11, 4],
+            [11, 10, 2],
+            [10, 7, 6],
+            [7, 1, 8],
+            [3, 9, 4],
+            [3, 4, 2],
+            [3, 2, 6],
+            [3, 6, 8],
+            [3, 8, 9],
+            [4, 9, 5],
+            [2, 4, 11],
+            [6, 2, 10],
+            [8, 6, 7],
+            [9, 8, 1],
+        ];
+
+        for _ in 0..subdivisions {
+            let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+            let mut midpoint = |a: u32, b: u32, positions: &mut Vec<Vec3>| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    positions.push((positions[a as usize] + positions[b as usize]).normalize());
+                    positions.len() as u32 - 1
+                })
+            };
+            triangles = triangles
+                .iter()
+                .flat_map(|&[a, b, c]| {
+                    let ab = midpoint(a, b, &mut positions);
+                    let bc = midpoint(b, c, &mut positions);
+                    let ca = midpoint(c, a, &mut positions);
+                    [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+                })
+                .collect();
+        }
+
+        let is_pole = |p: Vec3| p.x * p.x + p.z * p.z < 1e-10;
+        let get_u = |p: Vec3| p.x.atan2(p.z).rem_euclid(TAU) / TAU;
+        let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+        let add = |mesh: &mut Mesh, p: Vec3, u: f32| {
+            let v = 0.5 + p.y.clamp(-1.0, 1.0).asin() / PI;
+            let (sin, cos) = (u * TAU).sin_cos();
+            push_vertex(
+                mesh,
+                p * radius,
+                p,
+                Vec2::new(u, v),
+                Vec3::new(cos, 0.0, -sin),
+            )
+        };
+        let mut shared: HashMap<(u32, bool), u32> = HashMap::new();
+        for triangle in triangles {
+            let corners = triangle.map(|i| positions[i as usize]);
+            let mut us = corners.map(get_u);
+            let regular: Vec<usize> = (0..3).filter(|k| !is_pole(corners[*k])).collect();
+            let max = regular.iter().map(|k| us[*k]).fold(0.0, f32::max);
+            let min = regular.iter().map(|k| us[*k]).fold(1.0, f32::min);
+            let wrapped = max - min > 0.5;
+            if wrapped {
+                for k in &regular {
+                    if us[*k] < 0.5 {
+                        us[*k] += 1.0;
+                    }
+                }
+            }
+            let pole_u = regular.iter().map(|k| us[*k]).sum::<f32>() / regular.len() as f32;
+
+            let mut indices = [0; 3];
+            for k in 0..3 {
+                indices[k] = if is_pole(corners[k]) {
+                    add(&mut mesh, corners[k], pole_u)
+                } else {
+                    let key = (triangle[k], us[k] >= 1.0);
+                    match shared.get(&key) {
+                        Some(index) => *index,
+                        None => {
+                            let index = add(&mut mesh, corners[k], us[k]);
+                            shared.insert(key, index);
+                            index
+                        }
+                    }
+                };
+            }
+            push_triangle(&mut mesh, indices[0], indices[1], indices[2]);
+        }
+        mesh
+    }
+
+    pub fn cylinder(
+        radius_top: f32,
+        radius_bottom: f32,
+        height: f32,
+        radial_segments: u32,
+        height_segments: u32,
+        capped: bool,
+    ) -> Self {
+        let radial_segments = radial_segments.max(3) as usize;
+        let height_segments = height_segments.max(1);
+        let half = height * 0.5;
+        // side normals lean with the slope of the wall
+        let normal = Vec2::new(height, radius_bottom - radius_top);
+        let profile: Vec<ProfilePoint> = (0..=height_segments)
+            .map(|k| {
+                let v = k as f32 / height_segments as f32;
+                let radius = radius_bottom + (radius_top - radius_bottom) * v;
+                ProfilePoint::new(radius, -half + height * v, normal, v)
+            })
+            .collect();
+
+        let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+        add_lathe(&mut mesh, &profile, radial_segments);
+        if capped {
+            if radius_top > 0.0 {
+                add_disc(&mut mesh, half, 0.0, radius_top, radial_segments, true);
+            }
+            if radius_bottom > 0.0 {
+                add_disc(&mut mesh, -half, 0.0, radius_bottom, radial_segments, false);
+            }
+        }
+        mesh
+    }
+
+    // tip at +height / 2
+    pub fn cone(
+        radius: f32,
+        height: f32,
+        radial_segments: u32,
+        height_segments: u32,
+        capped: bool,
+    ) -> Self {
+        Self::cylinder(
+            0.0,
+            radius,
+            height,
+            radial_segments,
+            height_segments,
+            capped,
+        )
+    }
+
+    // length is the straight section between the two hemispheres
+    pub fn capsule(
+        radius: f32,
+        length: f32,
+        radial_segments: u32,
+        cap_rings: u32,
+        length_segments: u32,
+    ) -> Self {
+        let cap_rings = cap_rings.max(1);
+        let length_segments = length_segments.max(1);
+        let half = length * 0.5;
+        let mut points: Vec<(f32, f32, Vec2)> = Vec::new();
+        for k in 0..=cap_rings {
+            let phi = -PI * 0.5 + PI * 0.5 * k as f32 / cap_rings as f32;
+            let cos = if k == 0 { 0.0 } else { phi.cos() };
+            points.push((
+                radius * cos,
+                -half + radius * phi.sin(),
+                Vec2::new(cos, phi.sin()),
+            ));
+        }
+        for k in 1..length_segments {
+            let y = -half + length * k as f32 / length_segments as f32;
+            points.push((radius, y, Vec2::X));
+        }
+        for k in 0..=cap_rings {
+            let phi = PI * 0.5 * k as f32 / cap_rings as f32;
+            let cos = if k == cap_rings { 0.0 } else { phi.cos() };
+            points.push((
+                radius * cos,
+                half + radius * phi.sin(),
+                Vec2::new(cos, phi.sin()),
+            ));
+        }
+
+        // v follows arc length so the texture does not stretch over the caps
+        let mut distances = vec![0.0];
+        for w in points.windows(2) {
+            let d = Vec2::new(w[1].0 - w[0].0, w[1].1 - w[0].1).length();
+            distances.push(distances[distances.len() - 1] + d);
+        }
+        let total = distances[distances.len() - 1];
+        let profile: Vec<ProfilePoint> = points
+            .iter()
+            .zip(&distances)
+            .map(|((r, y, n), d)| ProfilePoint::new(*r, *y, *n, d / total))
+            .collect();
+
+        let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+        add_lathe(&mut mesh, &profile, radial_segments.max(3) as usize);
+        mesh
+    }
+
+    // lies in the XZ plane around +Y
+    pub fn torus(
+        radius: f32,
+        tube_radius: f32,
+        radial_segments: u32,
+        tubular_segments: u32,
+    ) -> Self {
+        let radial_segments = radial_segments.max(3);
+        let profile: Vec<ProfilePoint> = (0..=radial_segments)
+            .map(|k| {
+                let v = k as f32 / radial_segments as f32;
+                // start on the inside so the seam is hidden in the hole
+                let angle = -PI + TAU * (k % radial_segments) as f32 / radial_segments as f32;
+                let normal = Vec2::new(angle.cos(), angle.sin());
+                ProfilePoint::new(
+                    radius + tube_radius * normal.x,
+                    tube_radius * normal.y,
+                    normal,
+                    v,
+                )
+            })
+            .collect();
+        let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+        add_lathe(&mut mesh, &profile, tubular_segments.max(3) as usize);
+        mesh
+    }
+
+    // (p, q) torus knot, p windings around the axis and q through the hole
+    pub fn torus_knot(
+        radius: f32,
+        tube_radius: f32,
+        tubular_segments: u32,
+        radial_segments: u32,
+        p: u32,
+        q: u32,
+    ) -> Self {
+        let (p, q) = (p.max(1) as f32, q as f32);
+        let tubular_segments = tubular_segments.max(3) as usize;
+        let radial_segments = radial_segments.max(3) as usize;
+        let curve = |u: f32| {
+            let qu = q / p * u;
+            let cs = qu.cos();
+            Vec3::new(
+                radius * (2.0 + cs) * 0.5 * u.cos(),
+                radius * (2.0 + cs) * 0.5 * u.sin(),
+                radius * qu.sin() * 0.5,
+            )
+        };
+
+        // frames only depend on the curve position, so the seam closes exactly
+        let frames: Vec<(Vec3, Vec3, Vec3, Vec3)> = (0..=tubular_segments)
+            .map(|i| {
+                let u = p * TAU * (i % tubular_segments) as f32 / tubular_segments as f32;
+                let p1 = curve(u);
+                let p2 = curve(u + 0.01);
+                let tangent = (p2 - p1).normalize();
+                let binormal = tangent.cross(p1 + p2).normalize();
+                let normal = binormal.cross(tangent);
+                (p1, tangent, normal, binormal)
+            })
+            .collect();
+
+        let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+        add_grid(&mut mesh, tubular_segments, radial_segments, |i, j| {
+            let (center, tangent, normal, binormal) = frames[i];
+            let angle = TAU * (j % radial_segments) as f32 / radial_segments as f32;
+            let offset = normal * -angle.cos() + binormal * angle.sin();
+            (
+                center + offset * tube_radius,
+                offset,
+                Vec2::new(
+                    i as f32 / tubular_segments as f32,
+                    j as f32 / radial_segments as f32,
+                ),
+                tangent,
+            )
+        });
+        mesh
+    }
+
+    // XZ plane facing +Y
+    pub fn disc(radius: f32, segments: u32) -> Self {
+        let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+        add_disc(&mut mesh, 0.0, 0.0, radius, segments.max(3) as usize, true);
+        mesh
+    }
+
+    pub fn ring(inner_radius: f32, outer_radius: f32, segments: u32) -> Self {
+        let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+        add_disc(
+            &mut mesh,
+            0.0,
+            inner_radius.max(0.0),
+            outer_radius,
+            segments.max(3) as usize,
+            true,
+        );
+        mesh
+    }
+
+    // points along +Y from the origin, closed at both ends
+    pub fn arrow(
+        length: f32,
+        shaft_radius: f32,
+        head_radius: f32,
+        head_length: f32,
+        segments: u32,
+    ) -> Self {
+        let neck = (length - head_length).max(0.0);
+        let down = -Vec2::Y;
+        let side = Vec2::X;
+        let slope = Vec2::new(head_length, head_radius);
+        // repeated points at creases give each face its own normals
+        let points = [
+            (0.0, 0.0, down),
+            (shaft_radius, 0.0, down),
+            (shaft_radius, 0.0, side),
+            (shaft_radius, neck, side),
+            (shaft_radius, neck, down),
+            (head_radius, neck, down),
+            (head_radius, neck, slope),
+            (0.0, length, slope),
+        ];
+        let profile: Vec<ProfilePoint> = points
+            .iter()
+            .enumerate()
+            .map(|(k, (r, y, n))| {
+                ProfilePoint::new(*r, *y, *n, k as f32 / (points.len() - 1) as f32)
+            })
+            .collect();
+        let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+        add_lathe(&mut mesh, &profile, segments.max(3) as usize);
+        mesh
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // welds seam duplicates and checks every edge is shared by exactly two opposite triangles
+    fn assert_watertight(mesh: &Mesh) {
+        assert!(mesh.validate().is_ok());
+        let mut welded: HashMap<(i64, i64, i64), u32> = HashMap::new();
+        let ids: Vec<u32> = mesh
+            .positions
+            .iter()
+            .map(|p| {
+                let key = (
+                    (p.x * 1e4).round() as i64,
+                    (p.y * 1e4).round() as i64,
+                    (p.z * 1e4).round() as i64,
+                );
+                let next = welded.len() as u32;
+                *welded.entry(key).or_insert(next)
+            })
+            .collect();
+        let mut edges: HashMap<(u32, u32), i32> = HashMap::new();
+        for t in mesh.get_triangles() {
+            let t = t.map(|i| ids[i as usize]);
+            for k in 0..3 {
+                *edges.entry((t[k], t[(k + 1) % 3])).or_insert(0) += 1;
+            }
+        }
+        for ((a, b), count) in &edges {
+            assert_eq!(*count, 1, "edge {}-{} is used {} times", a, b, count);
+            assert!(edges.contains_key(&(*b, *a)), "edge {}-{} is open", a, b);
+        }
+
+        // positive signed volume means the faces point outward
+        let volume: f32 = mesh
+            .get_triangles()
+            .iter()
+            .map(|t| {
+                let [a, b, c] = t.map(|i| mesh.positions[i as usize]);
+                a.dot(b.cross(c)) / 6.0
+            })
+            .sum();
+        assert!(volume > 0.0);
+    }
+
+    fn assert_frames(mesh: &Mesh) {
+        for ((n, t), p) in mesh.normals.iter().zip(&mesh.tangents).zip(&mesh.positions) {
+            assert!((n.length() - 1.0).abs() < 1e-4, "bad normal at {:?}", p);
+            assert!(
+                t.truncate().dot(*n).abs() < 1e-3,
+                "tangent not orthogonal at {:?}",
+                p
+            );
+        }
+        // face winding agrees with the vertex normals
+        for [a, b, c] in mesh.get_triangles() {
+            let [pa, pb, pc] = [a, b, c].map(|i| mesh.positions[i as usize]);
+            let face = (pb - pa).cross(pc - pa);
+            let normal =
+                mesh.normals[a as usize] + mesh.normals[b as usize] + mesh.normals[c as usize];
+            assert!(face.dot(normal) > 0.0);
+        }
+    }
+
+    #[test]
+    fn vertex_counts() {
+        let plane = Mesh::plane(2.0, 1.0, 4, 3);
+        assert_eq!(plane.get_vertex_count(), 5 * 4);
+        assert_eq!(plane.get_primitive_count(), 2 * 4 * 3);
+        let cuboid = Mesh::cuboid(Vec3::ONE, [2, 3, 4]);
+        assert_eq!(cuboid.get_vertex_count(), 2 * (4 * 5 + 3 * 5 + 3 * 4));
+        assert_eq!(cuboid.get_primitive_count(), 4 * (3 * 4 + 2 * 4 + 2 * 3));
+        let sphere = Mesh::uv_sphere(1.0, 16, 8);
+        assert_eq!(sphere.get_vertex_count(), 17 * 9);
+        assert_eq!(sphere.get_primitive_count(), 2 * 16 * 7);
+        let ico = Mesh::icosphere(1.0, 2);
+        assert_eq!(ico.get_primitive_count(), 20 * 16);
+        let torus = Mesh::torus(1.0, 0.25, 8, 12);
+        assert_eq!(torus.get_vertex_count(), 9 * 13);
+        assert_eq!(Mesh::grid(4.0, 4).get_primitive_count(), 10);
+        assert_eq!(Mesh::disc(1.0, 12).get_primitive_count(), 12);
+        assert_eq!(Mesh::ring(0.5, 1.0, 12).get_primitive_count(), 24);
+    }
+
+    #[test]
+    fn closed_primitives_are_watertight() {
+        let meshes = [
+            Mesh::cuboid(Vec3::new(1.0, 2.0, 3.0), [2, 3, 4]),
+            Mesh::uv_sphere(1.0, 16, 8),
+            Mesh::icosphere(1.0, 0),
+            Mesh::icosphere(2.0, 3),
+            Mesh::cylinder(0.5, 1.0, 2.0, 12, 3, true),
+            Mesh::cone(1.0, 2.0, 12, 2, true),
+            Mesh::capsule(0.5, 1.0, 12, 4, 2),
+            Mesh::torus(1.0, 0.25, 8, 12),
+            Mesh::torus_knot(1.0, 0.2, 64, 8, 2, 3),
+            Mesh::arrow(1.0, 0.05, 0.1, 0.25, 12),
+        ];
+        for mesh in &meshes {
+            assert_watertight(mesh);
+            assert_frames(mesh);
+        }
+        assert_frames(&Mesh::plane(1.0, 1.0, 2, 2));
+        assert_frames(&Mesh::ring(0.5, 1.0, 12));
+    }
+
+    #[test]
+    fn uv_tangents_follow_u() {
+        // along each triangle, the tangent must agree with the direction of increasing u
+        for mesh in [
+            Mesh::uv_sphere(1.0, 16, 8),
+            Mesh::icosphere(1.0, 2),
+            Mesh::cuboid(Vec3::ONE, [1, 1, 1]),
+            Mesh::torus(1.0, 0.25, 8, 12),
+            Mesh::plane(1.0, 1.0, 1, 1),
+        ] {
+            for [a, b, c] in mesh.get_triangles() {
+                let [p0, p1, p2] = [a, b, c].map(|i| mesh.positions[i as usize]);
+                let [t0, t1, t2] = [a, b, c].map(|i| mesh.uvs[i as usize]);
+                let (e1, e2) = (p1 - p0, p2 - p0);
+                let (d1, d2) = (t1 - t0, t2 - t0);
+                let det = d1.x * d2.y - d2.x * d1.y;
+                if det.abs() < 1e-8 {
+                    continue;
+                }
+                let tangent = (e1 * d2.y - e2 * d1.y) / det;
+                let bitangent = (e2 * d1.x - e1 * d2.x) / det;
+                assert!(tangent.dot(mesh.tangents[a as usize].truncate()) > 0.0);
+                let n = mesh.normals[a as usize];
+                assert!(n.cross(tangent).dot(bitangent) > 0.0);
+            }
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use super::mesh::{Mesh, PrimitiveMode};
+use paxil_math::*;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StlFormat {
+    Ascii,
+    #[default]
+    Binary,
+}
+
+// facet normals that are zero or missing are recomputed from the winding
+fn push_facet(mesh: &mut Mesh, normal: Vec3, vertices: [Vec3; 3]) {
+    let [a, b, c] = vertices;
+    let normal = if normal.length_squared() > 0.0 {
+        normal.normalize()
+    } else {
+        (b - a).cross(c - a).normalize()
+    };
+    mesh.positions.extend_from_slice(&vertices);
+    mesh.normals.extend_from_slice(&[normal; 3]);
+}
+
+fn is_binary_stl(bytes: &[u8]) -> bool {
+    // binary files may also start with "solid", so trust the size first
+    if bytes.len() >= 84 {
+        let count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]) as usize;
+        if 84 + count * 50 == bytes.len() {
+            return true;
+        }
+    }
+    !bytes.trim_ascii_start().starts_with(b"solid")
+}
+
+fn parse_binary_stl(bytes: &[u8]) -> Result<Mesh, String> {
+    if bytes.len() < 84 {
+        return Err("Binary STL is shorter than its header".to_string());
+    }
+    let count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]) as usize;
+    if bytes.len() < 84 + count * 50 {
+        return Err(format!(
+            "Binary STL has {} bytes for {} triangles",
+            bytes.len(),
+            count
+        ));
+    }
+    let read_vec3 = |offset: usize| {
+        let f = |i: usize| {
+            let b = &bytes[offset + i * 4..offset + i * 4 + 4];
+            f32::from_le_bytes([b[0], b[1], b[2], b[3]])
+        };
+        Vec3::new(f(0), f(1), f(2))
+    };
+
+    let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+    mesh.positions.reserve(count * 3);
+    mesh.normals.reserve(count * 3);
+    for i in 0..count {
+        let offset = 84 + i * 50;
+        let vertices = [
+            read_vec3(offset + 12),
+            read_vec3(offset + 24),
+            read_vec3(offset + 36),
+        ];
+        push_facet(&mut mesh, read_vec3(offset), vertices);
+    }
+    Ok(mesh)
+}
+
+fn parse_ascii_stl(src: &str) -> Result<Mesh, String> {
+    let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+    let mut normal = Vec3::ZERO;
+    let mut vertices = Vec::with_capacity(3);
+    for (number, line) in src.lines().enumerate() {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let error = |message: &str| format!("Line {}: {}", number + 1, message);
+        let parse_vec3 = |tokens: &[&str]| -> Result<Vec3, String> {
+            let values = tokens
+                .iter()
+                .map(|t| t.parse::<f32>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| error("Invalid number"))?;
+            match values.as_slice() {
+                [x, y, z] => Ok(Vec3::new(*x, *y, *z)),
+                _ => Err(error("Expected 3 numbers")),
+            }
+        };
+        match tokens.as_slice() {
+            ["facet", "normal", rest @ ..] => {
+                normal = parse_vec3(rest)?;
+                vertices.clear();
+            }
+            ["vertex", rest @ ..] => {
+                if vertices.len() == 3 {
+                    return Err(error("Facet has more than 3 vertices"));
+                }
+                vertices.push(parse_vec3(rest)?);
+            }
+            ["endfacet"] => {
+                let [a, b, c] = vertices[..] else {
+                    return Err(error("Facet does not have 3 vertices"));
+                };
+                push_facet(&mut mesh, normal, [a, b, c]);
+                vertices.clear();
+            }
+            _ => {}
+        }
+    }
+    Ok(mesh)
+}
+
+// returns an unindexed triangle list with flat normals
+pub fn parse_stl(bytes: &[u8]) -> Result<Mesh, String> {
+    if is_binary_stl(bytes) {
+        parse_binary_stl(bytes)
+    } else {
+        let src =
+            std::str::from_utf8(bytes).map_err(|_| "ASCII STL is not valid text".to_string())?;
+        parse_ascii_stl(src)
+    }
+}
+
+pub fn write_stl(mesh: &Mesh, format: StlFormat) -> Result<Vec<u8>, String> {
+    mesh.validate()?;
+    if !mesh.mode.is_triangles() {
+        return Err(format!("Cannot write {:?} primitives to STL", mesh.mode));
+    }
+    let facets: Vec<(Vec3, [Vec3; 3])> = mesh
+        .get_triangles()
+        .iter()
+        .map(|t| {
+            let [a, b, c] = t.map(|i| mesh.positions[i as usize]);
+            ((b - a).cross(c - a).normalize(), [a, b, c])
+        })
+        .collect();
+
+    match format {
+        StlFormat::Ascii => {
+            let mut src = String::from("solid mesh\n");
+            for (normal, vertices) in &facets {
+                let _ = writeln!(src, "facet normal {} {} {}", normal.x, normal.y, normal.z);
+                src.push_str("  outer loop\n");
+                for v in vertices {
+                    let _ = writeln!(src, "    vertex {} {} {}", v.x, v.y, v.z);
+                }
+                src.push_str("  endloop\nendfacet\n");
+            }
+            src.push_str("endsolid mesh\n");
+            Ok(src.into_bytes())
+        }
+        StlFormat::Binary => {
+            let count = u32::try_from(facets.len())
+                .map_err(|_| format!("{} triangles do not fit a binary STL", facets.len()))?;
+            let mut out = vec![0u8; 80];
+            out.extend_from_slice(&count.to_le_bytes());
+            for (normal, vertices) in &facets {
+                for v in std::iter::once(normal).chain(vertices) {
+                    for c in [v.x, v.y, v.z] {
+                        out.extend_from_slice(&c.to_le_bytes());
+                    }
+                }
+                out.extend_from_slice(&[0, 0]);
+            }
+            Ok(out)
+        }
+    }
+}
+
+pub fn load_stl<P: AsRef<Path>>(path: P) -> Result<Mesh, String> {
+    let path = path.as_ref();
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    parse_stl(&bytes).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+pub fn save_stl<P: AsRef<Path>>(path: P, mesh: &Mesh, format: StlFormat) -> Result<(), String> {
+    let path = path.as_ref();
+    let bytes = write_stl(mesh, format)?;
+    std::fs::write(path, bytes).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ascii_facets() {
+        let src = "solid test
+            facet normal 0 0 0
+              outer loop
+                vertex 0 0 0
+                vertex 1 0 0
+                vertex 0 1 0
+              endloop
+            endfacet
+            endsolid test";
+        let mesh = parse_stl(src.as_bytes()).unwrap();
+        assert_eq!(mesh.get_primitive_count(), 1);
+        assert_eq!(mesh.normals[0], Vec3::Z);
+
+        let broken = src.replace("vertex 0 1 0", "");
+        assert!(parse_stl(broken.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn round_trips_both_formats() {
+        let mesh = Mesh::uv_sphere(1.0, 8, 4);
+        let expected: Vec<Vec3> = mesh
+            .get_triangles()
+            .iter()
+            .flat_map(|t| t.map(|i| mesh.positions[i as usize]))
+            .collect();
+        for format in [StlFormat::Ascii, StlFormat::Binary] {
+            let mut bytes = write_stl(&mesh, format).unwrap();
+            if format == StlFormat::Binary {
+                // headers starting with "solid" must still be read as binary
+                bytes[..5].copy_from_slice(b"solid");
+            }
+            let loaded = parse_stl(&bytes).unwrap();
+            assert_eq!(loaded.positions, expected);
+            assert!(loaded.validate().is_ok());
+        }
+    }
+}
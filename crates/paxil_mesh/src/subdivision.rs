@@ -0,0 +1,327 @@
+use std::collections::HashMap;
+use std::f32::consts::{FRAC_1_SQRT_2, PI};
+
+use super::half_edge::HalfEdgeMesh;
+use super::mesh::Mesh;
+use paxil_math::*;
+
+// corner uvs are interpolated linearly inside each face, so uv seams stay where they are
+fn build(
+    positions: &[Vec3],
+    faces: &[Vec<usize>],
+    uvs: Option<&[Vec<Vec2>]>,
+) -> Result<HalfEdgeMesh, String> {
+    let mut mesh = HalfEdgeMesh::from_polygons(positions, faces)?;
+    if let Some(uvs) = uvs {
+        mesh.uvs = vec![Vec2::ZERO; mesh.halfedges.len()];
+        for (f, corners) in uvs.iter().enumerate() {
+            for (h, uv) in mesh.get_face_halfedges(f).into_iter().zip(corners) {
+                mesh.uvs[h] = *uv;
+            }
+        }
+    }
+    Ok(mesh)
+}
+
+impl HalfEdgeMesh {
+    // boundary vertices follow the cubic B-spline rule, corners where the boundary
+    // turns by more than 45 degrees stay put
+    fn get_boundary_position(&self, v: usize) -> Option<Vec3> {
+        let [a, b] = self.get_boundary_neighbours(v)?;
+        let (p, pa, pb) = (
+            self.get_position(v),
+            self.get_position(a),
+            self.get_position(b),
+        );
+        if (pa - p).normalize().dot((pb - p).normalize()) > -FRAC_1_SQRT_2 {
+            return Some(p);
+        }
+        Some(p * 0.75 + (pa + pb) * 0.125)
+    }
+
+    // the two neighbours along the boundary, None for interior vertices
+    fn get_boundary_neighbours(&self, v: usize) -> Option<[usize; 2]> {
+        let h = self.vertices[v].halfedge?;
+        if !self.is_boundary_halfedge(h) {
+            return None;
+        }
+        let prev = self.halfedges[h].prev;
+        Some([self.get_destination(h), self.halfedges[prev].origin])
+    }
+
+    fn get_position(&self, v: usize) -> Vec3 {
+        self.vertices[v].position
+    }
+
+    // one point per edge, shared by both half-edges
+    fn get_edge_points(&self, point: impl Fn(usize) -> Vec3) -> (HashMap<usize, usize>, Vec<Vec3>) {
+        let mut indices = HashMap::new();
+        let mut points = Vec::new();
+        for h in self.get_edges() {
+            indices.insert(h, self.vertices.len() + points.len());
+            indices.insert(self.halfedges[h].twin, self.vertices.len() + points.len());
+            points.push(point(h));
+        }
+        (indices, points)
+    }
+
+    // Loop subdivision of a triangle mesh, boundaries follow the cubic B-spline rules
+    pub fn subdivide_loop(&self) -> Result<Self, String> {
+        let mut mesh = self.clone();
+        mesh.compact();
+        if mesh
+            .get_faces()
+            .any(|f| mesh.get_face_halfedges(f).len() != 3)
+        {
+            return Err("Loop subdivision needs a triangle mesh".to_string());
+        }
+
+        let mut positions: Vec<Vec3> = mesh
+            .get_vertices()
+            .map(|v| {
+                let p = mesh.get_position(v);
+                if let Some(boundary) = mesh.get_boundary_position(v) {
+                    return boundary;
+                }
+                let ring = mesh.get_one_ring(v);
+                let n = ring.len() as f32;
+                let beta = if ring.len() == 3 {
+                    3.0 / 16.0
+                } else {
+                    3.0 / (8.0 * n)
+                };
+                let sum = ring
+                    .iter()
+                    .fold(Vec3::ZERO, |sum, r| sum + mesh.get_position(*r));
+                p * (1.0 - n * beta) + sum * beta
+            })
+            .collect();
+
+        let (edge_points, points) = mesh.get_edge_points(|h| {
+            let twin = mesh.halfedges[h].twin;
+            let a = mesh.get_position(mesh.halfedges[h].origin);
+            let b = mesh.get_position(mesh.halfedges[twin].origin);
+            if mesh.is_boundary_edge(h) {
+                return (a + b) * 0.5;
+            }
+            let c = mesh.get_position(mesh.halfedges[mesh.halfedges[h].prev].origin);
+            let d = mesh.get_position(mesh.halfedges[mesh.halfedges[twin].prev].origin);
+            (a + b) * 0.375 + (c + d) * 0.125
+        });
+        positions.extend(points);
+
+        let has_uvs = !mesh.uvs.is_empty();
+        let mut faces = Vec::with_capacity(mesh.faces.len() * 4);
+        let mut uvs = Vec::new();
+        for f in mesh.get_faces() {
+            let l = mesh.get_face_halfedges(f);
+            let loop_ = [l[0], l[1], l[2]];
+            let v = loop_.map(|h| mesh.halfedges[h].origin);
+            let e = loop_.map(|h| edge_points[&h]);
+            faces.extend([
+                vec![v[0], e[0], e[2]],
+                vec![v[1], e[1], e[0]],
+                vec![v[2], e[2], e[1]],
+                vec![e[0], e[1], e[2]],
+            ]);
+            if has_uvs {
+                let t = loop_.map(|h| mesh.uvs[h]);
+                let m = [0, 1, 2].map(|i| t[i].lerp(t[(i + 1) % 3], 0.5));
+                uvs.extend([
+                    vec![t[0], m[0], m[2]],
+                    vec![t[1], m[1], m[0]],
+                    vec![t[2], m[2], m[1]],
+                    vec![m[0], m[1], m[2]],
+                ]);
+            }
+        }
+        build(&positions, &faces, has_uvs.then_some(uvs.as_slice()))
+    }
+
+    // Catmull-Clark subdivision of any polygon mesh, every face becomes quads
+    pub fn subdivide_catmull_clark(&self) -> Result<Self, String> {
+        let mut mesh = self.clone();
+        mesh.compact();
+
+        let face_points: Vec<Vec3> = mesh.get_faces().map(|f| mesh.get_face_center(f)).collect();
+        let (edge_points, points) = mesh.get_edge_points(|h| {
+            let twin = mesh.halfedges[h].twin;
+            let a = mesh.get_position(mesh.halfedges[h].origin);
+            let b = mesh.get_position(mesh.halfedges[twin].origin);
+            match (mesh.halfedges[h].face, mesh.halfedges[twin].face) {
+                (Some(f1), Some(f2)) => (a + b + face_points[f1] + face_points[f2]) * 0.25,
+                _ => (a + b) * 0.5,
+            }
+        });
+
+        let mut positions: Vec<Vec3> = mesh
+            .get_vertices()
+            .map(|v| {
+                let p = mesh.get_position(v);
+                if let Some(boundary) = mesh.get_boundary_position(v) {
+                    return boundary;
+                }
+                let outgoing = mesh.get_outgoing(v);
+                if outgoing.is_empty() {
+                    return p;
+                }
+                let n = outgoing.len() as f32;
+                let (mut q, mut r) = (Vec3::ZERO, Vec3::ZERO);
+                for h in &outgoing {
+                    q += face_points[mesh.halfedges[*h].face.unwrap()];
+                    r += (p + mesh.get_position(mesh.get_destination(*h))) * 0.5;
+                }
+                (q / n + r / n * 2.0 + p * (n - 3.0)) / n
+            })
+            .collect();
+        positions.extend(points);
+        let face_base = positions.len();
+        positions.extend(&face_points);
+
+        let has_uvs = !mesh.uvs.is_empty();
+        let mut faces = Vec::new();
+        let mut uvs = Vec::new();
+        for f in mesh.get_faces() {
+            let loop_ = mesh.get_face_halfedges(f);
+            let n = loop_.len();
+            let center_uv = if has_uvs {
+                loop_.iter().fold(Vec2::ZERO, |sum, h| sum + mesh.uvs[*h]) / n as f32
+            } else {
+                Vec2::ZERO
+            };
+            for i in 0..n {
+                let prev = loop_[(i + n - 1) % n];
+                let h = loop_[i];
+                faces.push(vec![
+                    mesh.halfedges[h].origin,
+                    edge_points[&h],
+                    face_base + f,
+                    edge_points[&prev],
+                ]);
+                if has_uvs {
+                    let next = loop_[(i + 1) % n];
+                    uvs.push(vec![
+                        mesh.uvs[h],
+                        mesh.uvs[h].lerp(mesh.uvs[next], 0.5),
+                        center_uv,
+                        mesh.uvs[prev].lerp(mesh.uvs[h], 0.5),
+                    ]);
+                }
+            }
+        }
+        build(&positions, &faces, has_uvs.then_some(uvs.as_slice()))
+    }
+}
+
+impl Mesh {
+    fn subdivide(
+        &self,
+        iterations: u32,
+        step: impl Fn(&HalfEdgeMesh) -> Result<HalfEdgeMesh, String>,
+    ) -> Result<Mesh, String> {
+        let mut mesh = HalfEdgeMesh::from_mesh(self)?;
+        for _ in 0..iterations {
+            mesh = step(&mesh)?;
+        }
+        let mut result = mesh.to_mesh();
+        if self.has_normals() {
+            // hard edges that survive subdivision keep their crease
+            result.compute_normals(PI / 3.0)?;
+        }
+        Ok(result)
+    }
+
+    pub fn subdivide_loop(&self, iterations: u32) -> Result<Mesh, String> {
+        self.subdivide(iterations, HalfEdgeMesh::subdivide_loop)
+    }
+
+    // triangles become three quads each on the first step, output is triangulated
+    pub fn subdivide_catmull_clark(&self, iterations: u32) -> Result<Mesh, String> {
+        self.subdivide(iterations, HalfEdgeMesh::subdivide_catmull_clark)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loop_converges_towards_a_sphere() {
+        let mesh = Mesh::icosphere(1.0, 0);
+        let smooth = mesh.subdivide_loop(2).unwrap();
+        assert_eq!(smooth.get_primitive_count(), 20 * 16);
+        assert!(smooth.validate().is_ok());
+        // Loop shrinks the control mesh but keeps it round
+        let radii: Vec<f32> = smooth.positions.iter().map(|p| p.length()).collect();
+        let (min, max) = radii
+            .iter()
+            .fold((f32::MAX, 0.0f32), |(lo, hi), r| (lo.min(*r), hi.max(*r)));
+        assert!(max - min < 0.05 && max < 1.0);
+
+        let half_edge = HalfEdgeMesh::from_mesh(&smooth).unwrap();
+        assert!(half_edge.is_closed() && half_edge.get_euler_characteristic() == 2);
+    }
+
+    #[test]
+    fn catmull_clark_on_a_cube() {
+        let cube = HalfEdgeMesh::from_polygons(
+            &[
+                Vec3::new(-1.0, -1.0, -1.0),
+                Vec3::new(1.0, -1.0, -1.0),
+                Vec3::new(1.0, 1.0, -1.0),
+                Vec3::new(-1.0, 1.0, -1.0),
+                Vec3::new(-1.0, -1.0, 1.0),
+                Vec3::new(1.0, -1.0, 1.0),
+                Vec3::new(1.0, 1.0, 1.0),
+                Vec3::new(-1.0, 1.0, 1.0),
+            ],
+            &[
+                vec![0, 3, 2, 1],
+                vec![4, 5, 6, 7],
+                vec![0, 1, 5, 4],
+                vec![2, 3, 7, 6],
+                vec![1, 2, 6, 5],
+                vec![0, 4, 7, 3],
+            ],
+        )
+        .unwrap();
+        let once = cube.subdivide_catmull_clark().unwrap();
+        assert!(once.validate().is_ok());
+        assert_eq!(once.get_face_count(), 24);
+        assert_eq!(once.get_vertex_count(), 26);
+        // corner of a cube moves to 5/9 of the way, face points stay on the faces
+        assert!(once.vertices[6].position.distance(Vec3::splat(5.0 / 9.0)) < 1e-5);
+        assert!(once.get_vertices().skip(8 + 12).all(|v| {
+            let p = once.vertices[v].position.abs();
+            p.x.max(p.y).max(p.z) == 1.0
+        }));
+    }
+
+    #[test]
+    fn boundaries_and_seams_are_kept() {
+        let plane = Mesh::plane(2.0, 2.0, 1, 1);
+        for mesh in [
+            plane.subdivide_loop(2).unwrap(),
+            plane.subdivide_catmull_clark(2).unwrap(),
+        ] {
+            for p in &mesh.positions {
+                assert_eq!(p.y, 0.0);
+                assert!(p.x.abs() <= 1.0 && p.z.abs() <= 1.0);
+            }
+            // corners stay pinned along with their uvs
+            let corners: Vec<(Vec3, Vec2)> = mesh
+                .positions
+                .iter()
+                .zip(&mesh.uvs)
+                .filter(|(p, _)| p.x.abs() == 1.0 && p.z.abs() == 1.0)
+                .map(|(p, uv)| (*p, *uv))
+                .collect();
+            assert_eq!(corners.len(), 4);
+            for (p, uv) in corners {
+                assert_eq!(uv, Vec2::new((p.x + 1.0) * 0.5, (1.0 - p.z) * 0.5));
+            }
+            assert!(mesh.has_normals());
+        }
+        assert!(Mesh::grid(1.0, 1).subdivide_loop(1).is_err());
+    }
+}
@@ -0,0 +1,600 @@
+use std::f32::consts::PI;
+
+use super::mesh::{Mesh, PrimitiveMode};
+use paxil_math::*;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FillRule {
+    #[default]
+    NonZero,
+    EvenOdd,
+}
+
+impl FillRule {
+    pub fn is_inside(self, winding: i32) -> bool {
+        match self {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineJoin {
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineCap {
+    #[default]
+    Butt,
+    Round,
+    Square,
+}
+
+#[derive(Clone, Debug)]
+pub struct StrokeOptions {
+    pub width: f32,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    // miters longer than this many stroke widths fall back to bevels, as in SVG
+    pub miter_limit: f32,
+    // largest distance between a round join or cap and its true arc
+    pub tolerance: f32,
+}
+
+impl Default for StrokeOptions {
+    fn default() -> Self {
+        Self {
+            width: 1.0,
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            miter_limit: 4.0,
+            tolerance: 0.01,
+        }
+    }
+}
+
+fn cross(a: Vec2, b: Vec2, c: Vec2) -> f32 {
+    (b - a).perp_dot(c - a)
+}
+
+// counter-clockwise polygons have a positive area
+pub fn get_signed_area(points: &[Vec2]) -> f32 {
+    let mut area = 0.0;
+    for (i, a) in points.iter().enumerate() {
+        area += a.perp_dot(points[(i + 1) % points.len()]);
+    }
+    area * 0.5
+}
+
+// inclusive test against a counter-clockwise triangle
+fn is_in_triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+    cross(a, b, p) >= 0.0 && cross(b, c, p) >= 0.0 && cross(c, a, p) >= 0.0
+}
+
+// joins a hole into the polygon with a pair of coincident edges from its rightmost vertex
+fn bridge_hole(points: &[Vec2], polygon: &mut Vec<usize>, hole: &[usize]) -> Result<(), String> {
+    let start = (0..hole.len())
+        .max_by(|a, b| points[hole[*a]].x.total_cmp(&points[hole[*b]].x))
+        .unwrap();
+    let m = points[hole[start]];
+
+    // nearest edge hit by a ray from m towards +x
+    let n = polygon.len();
+    let mut hit: Option<(f32, usize)> = None;
+    for i in 0..n {
+        let (a, b) = (points[polygon[i]], points[polygon[(i + 1) % n]]);
+        if (a.y > m.y) == (b.y > m.y) {
+            continue;
+        }
+        let x = a.x + (m.y - a.y) * (b.x - a.x) / (b.y - a.y);
+        if x >= m.x && hit.is_none_or(|(best, _)| x < best) {
+            hit = Some((x, i));
+        }
+    }
+    let Some((x, i)) = hit else {
+        return Err("Hole is not inside the polygon".to_string());
+    };
+    let hit_point = Vec2::new(x, m.y);
+    let mut target = if points[polygon[i]].x > points[polygon[(i + 1) % n]].x {
+        i
+    } else {
+        (i + 1) % n
+    };
+
+    // vertices inside the triangle m, hit, target can hide the target, take the one closest to the ray
+    let p = points[polygon[target]];
+    let (a, b, c) = if cross(m, hit_point, p) >= 0.0 {
+        (m, hit_point, p)
+    } else {
+        (m, p, hit_point)
+    };
+    let mut best = (f32::INFINITY, f32::INFINITY);
+    for (k, index) in polygon.iter().enumerate() {
+        let v = points[*index];
+        if k == target || v == m || v == p || v.x <= m.x || !is_in_triangle(v, a, b, c) {
+            continue;
+        }
+        let key = ((v.y - m.y).abs() / (v.x - m.x), v.distance_squared(m));
+        if key < best {
+            best = key;
+            target = k;
+        }
+    }
+
+    let mut bridged = Vec::with_capacity(polygon.len() + hole.len() + 2);
+    bridged.extend_from_slice(&polygon[..=target]);
+    bridged.extend((0..=hole.len()).map(|k| hole[(start + k) % hole.len()]));
+    bridged.extend_from_slice(&polygon[target..]);
+    *polygon = bridged;
+    Ok(())
+}
+
+fn clip_ears(points: &[Vec2], mut remaining: Vec<usize>) -> Vec<[usize; 3]> {
+    let mut triangles = Vec::with_capacity(remaining.len().saturating_sub(2));
+    let mut i = 0;
+    let mut misses = 0;
+    while remaining.len() > 3 {
+        let m = remaining.len();
+        i %= m;
+        let corner = |i: usize| {
+            [
+                remaining[(i + m - 1) % m],
+                remaining[i],
+                remaining[(i + 1) % m],
+            ]
+        };
+        let [a, b, c] = corner(i);
+        let (pa, pb, pc) = (points[a], points[b], points[c]);
+        let area = cross(pa, pb, pc);
+        // vertices repeated by hole bridges share positions, so compare those rather than indices
+        let is_ear = area > 0.0
+            && remaining.iter().all(|k| {
+                let p = points[*k];
+                p == pa || p == pb || p == pc || !is_in_triangle(p, pa, pb, pc)
+            });
+
+        if is_ear || area == 0.0 {
+            if area > 0.0 {
+                triangles.push([a, b, c]);
+            }
+            remaining.remove(i);
+            i = i.saturating_sub(1);
+            misses = 0;
+        } else if misses > m {
+            // self intersecting input never runs out of reflex vertices, clip the largest corner
+            let i = (0..m)
+                .max_by(|x, y| {
+                    let area = |i| {
+                        let [a, b, c] = corner(i);
+                        cross(points[a], points[b], points[c])
+                    };
+                    area(*x).total_cmp(&area(*y))
+                })
+                .unwrap();
+            let [a, b, c] = corner(i);
+            if cross(points[a], points[b], points[c]) > 0.0 {
+                triangles.push([a, b, c]);
+            }
+            remaining.remove(i);
+            misses = 0;
+        } else {
+            i += 1;
+            misses += 1;
+        }
+    }
+    if let [a, b, c] = remaining[..] {
+        if cross(points[a], points[b], points[c]) > 0.0 {
+            triangles.push([a, b, c]);
+        }
+    }
+    triangles
+}
+
+// ear clipping of a simple polygon with simple holes inside it, either winding is accepted
+// indices address the outer points followed by the points of each hole in turn
+pub fn triangulate_polygon(outer: &[Vec2], holes: &[Vec<Vec2>]) -> Result<Vec<[usize; 3]>, String> {
+    if outer.len() < 3 {
+        return Err(format!("Polygon needs 3 points, got {}", outer.len()));
+    }
+    let mut points = outer.to_vec();
+    let oriented = |points: &[Vec2], start: usize, counter_clockwise: bool| {
+        let mut indices: Vec<usize> = (start..points.len()).collect();
+        if (get_signed_area(&points[start..]) > 0.0) != counter_clockwise {
+            indices.reverse();
+        }
+        indices
+    };
+    let mut polygon = oriented(&points, 0, true);
+
+    let mut loops = Vec::with_capacity(holes.len());
+    for hole in holes {
+        if hole.len() < 3 {
+            return Err(format!("Hole needs 3 points, got {}", hole.len()));
+        }
+        let start = points.len();
+        points.extend_from_slice(hole);
+        loops.push(oriented(&points, start, false));
+    }
+    // right to left, so a bridge never has to cross a hole that is still unbridged
+    let get_max_x = |hole: &Vec<usize>| {
+        hole.iter()
+            .map(|i| points[*i].x)
+            .fold(f32::NEG_INFINITY, f32::max)
+    };
+    loops.sort_by(|a, b| get_max_x(b).total_cmp(&get_max_x(a)));
+    for hole in &loops {
+        bridge_hole(&points, &mut polygon, hole)?;
+    }
+    Ok(clip_ears(&points, polygon))
+}
+
+pub fn fill_polygon(outer: &[Vec2], holes: &[Vec<Vec2>]) -> Result<Mesh, String> {
+    let triangles = triangulate_polygon(outer, holes)?;
+    let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+    mesh.positions = outer
+        .iter()
+        .chain(holes.iter().flatten())
+        .map(|p| p.extend(0.0))
+        .collect();
+    mesh.indices = triangles.iter().flatten().map(|i| *i as u32).collect();
+    Ok(mesh)
+}
+
+struct PathEdge {
+    low: Vec2,
+    high: Vec2,
+    winding: i32,
+}
+
+impl PathEdge {
+    fn get_x(&self, y: f32) -> f32 {
+        self.low.x + (self.high.x - self.low.x) * (y - self.low.y) / (self.high.y - self.low.y)
+    }
+
+    fn get_crossing_y(&self, other: &PathEdge) -> Option<f32> {
+        if self.high.y <= other.low.y || other.high.y <= self.low.y {
+            return None;
+        }
+        let (r, s) = (self.high - self.low, other.high - other.low);
+        let denom = r.perp_dot(s);
+        if denom == 0.0 {
+            return None;
+        }
+        let d = other.low - self.low;
+        let (t, u) = (d.perp_dot(s) / denom, d.perp_dot(r) / denom);
+        (t > 0.0 && t < 1.0 && u > 0.0 && u < 1.0).then_some(self.low.y + r.y * t)
+    }
+}
+
+// fills any set of closed contours, self intersecting or overlapping, by cutting them into
+// trapezoids between every vertex and crossing height and keeping those the rule counts as inside
+pub fn fill_path(contours: &[Vec<Vec2>], rule: FillRule) -> Mesh {
+    let mut edges = Vec::new();
+    for contour in contours {
+        for (i, a) in contour.iter().enumerate() {
+            let b = contour[(i + 1) % contour.len()];
+            if a.y < b.y {
+                edges.push(PathEdge {
+                    low: *a,
+                    high: b,
+                    winding: 1,
+                });
+            } else if a.y > b.y {
+                edges.push(PathEdge {
+                    low: b,
+                    high: *a,
+                    winding: -1,
+                });
+            }
+        }
+    }
+
+    let mut heights: Vec<f32> = edges.iter().flat_map(|e| [e.low.y, e.high.y]).collect();
+    for (i, a) in edges.iter().enumerate() {
+        heights.extend(edges[i + 1..].iter().filter_map(|b| a.get_crossing_y(b)));
+    }
+    heights.sort_by(f32::total_cmp);
+    heights.dedup();
+
+    let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+    for band in heights.windows(2) {
+        let (y0, y1) = (band[0], band[1]);
+        let middle = (y0 + y1) * 0.5;
+        let mut active: Vec<(f32, &PathEdge)> = edges
+            .iter()
+            .filter(|e| e.low.y <= y0 && e.high.y >= y1)
+            .map(|e| (e.get_x(middle), e))
+            .collect();
+        active.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut winding = 0;
+        let mut left: Option<&PathEdge> = None;
+        for (_, edge) in active {
+            let was_inside = rule.is_inside(winding);
+            winding += edge.winding;
+            if !was_inside && rule.is_inside(winding) {
+                left = Some(edge);
+            } else if was_inside && !rule.is_inside(winding) {
+                let Some(left) = left.take() else {
+                    continue;
+                };
+                let corners = [
+                    Vec2::new(left.get_x(y0), y0),
+                    Vec2::new(edge.get_x(y0), y0),
+                    Vec2::new(edge.get_x(y1), y1),
+                    Vec2::new(left.get_x(y1), y1),
+                ];
+                push_triangle(&mut mesh, [corners[0], corners[1], corners[2]]);
+                push_triangle(&mut mesh, [corners[0], corners[2], corners[3]]);
+            }
+        }
+    }
+    mesh
+}
+
+// stroke geometry is an unindexed triangle list, counter-clockwise, skipping degenerate triangles
+fn push_triangle(mesh: &mut Mesh, [a, b, c]: [Vec2; 3]) {
+    let area = cross(a, b, c);
+    if area > 0.0 {
+        mesh.positions
+            .extend([a.extend(0.0), b.extend(0.0), c.extend(0.0)]);
+    } else if area < 0.0 {
+        mesh.positions
+            .extend([a.extend(0.0), c.extend(0.0), b.extend(0.0)]);
+    }
+}
+
+const MAX_ARC_STEPS: f32 = 256.0;
+
+fn push_arc(mesh: &mut Mesh, center: Vec2, from: Vec2, angle: f32, tolerance: f32) {
+    let radius = from.length();
+    let step = if tolerance < radius {
+        2.0 * (1.0 - tolerance / radius).acos()
+    } else {
+        PI / 2.0
+    };
+    // tolerances far below the radius round the step to zero
+    let steps = (angle.abs() / step).ceil().clamp(1.0, MAX_ARC_STEPS) as usize;
+    for k in 0..steps {
+        let a = from.rotate(angle * k as f32 / steps as f32);
+        let b = from.rotate(angle * (k + 1) as f32 / steps as f32);
+        push_triangle(mesh, [center, center + a, center + b]);
+    }
+}
+
+fn push_join(mesh: &mut Mesh, p: Vec2, d0: Vec2, d1: Vec2, options: &StrokeOptions) {
+    let turn = d0.perp_dot(d1);
+    if turn.abs() < 1e-6 && d0.dot(d1) > 0.0 {
+        return;
+    }
+    // the segments overlap on the inside of the turn and leave a gap on the outside
+    let half_width = options.width * 0.5;
+    let side = if turn > 0.0 { -1.0 } else { 1.0 };
+    let (o0, o1) = (d0.perp() * half_width * side, d1.perp() * half_width * side);
+    let bevel = |mesh: &mut Mesh| push_triangle(mesh, [p, p + o0, p + o1]);
+
+    match options.join {
+        LineJoin::Bevel => bevel(mesh),
+        LineJoin::Miter => {
+            let bisector = (o0 + o1).normalize();
+            let cos_half = bisector.dot(o0) / half_width;
+            if cos_half > 0.0 && 1.0 / cos_half <= options.miter_limit {
+                let tip = p + bisector * (half_width / cos_half);
+                push_triangle(mesh, [p, p + o0, tip]);
+                push_triangle(mesh, [p, tip, p + o1]);
+            } else {
+                bevel(mesh);
+            }
+        }
+        LineJoin::Round => {
+            let angle = o0.perp_dot(o1).atan2(o0.dot(o1));
+            push_arc(mesh, p, o0, angle, options.tolerance);
+        }
+    }
+}
+
+// segments are separate quads joined by wedges, so overlaps show with translucent colours
+pub fn stroke_polyline(
+    points: &[Vec2],
+    closed: bool,
+    options: &StrokeOptions,
+) -> Result<Mesh, String> {
+    if options.tolerance.is_nan() || options.tolerance <= 0.0 {
+        return Err(format!(
+            "Stroke tolerance must be positive, got {}",
+            options.tolerance
+        ));
+    }
+    let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+    let mut points = points.to_vec();
+    points.dedup();
+    if closed && points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    let half_width = options.width * 0.5;
+    if points.len() < 2 || half_width <= 0.0 {
+        return Ok(mesh);
+    }
+
+    let n = points.len();
+    let segments = if closed { n } else { n - 1 };
+    let get_direction = |i: usize| (points[(i + 1) % n] - points[i]).normalize();
+    for i in 0..segments {
+        let (mut a, mut b) = (points[i], points[(i + 1) % n]);
+        let direction = get_direction(i);
+        if !closed && options.cap == LineCap::Square {
+            if i == 0 {
+                a -= direction * half_width;
+            }
+            if i == segments - 1 {
+                b += direction * half_width;
+            }
+        }
+        let offset = direction.perp() * half_width;
+        push_triangle(&mut mesh, [a - offset, b - offset, b + offset]);
+        push_triangle(&mut mesh, [a - offset, b + offset, a + offset]);
+    }
+
+    let joins = if closed { 0..n } else { 1..n - 1 };
+    for i in joins {
+        let d0 = get_direction((i + n - 1) % n);
+        push_join(&mut mesh, points[i], d0, get_direction(i), options);
+    }
+
+    if !closed && options.cap == LineCap::Round {
+        let first = get_direction(0);
+        push_arc(
+            &mut mesh,
+            points[0],
+            first.perp() * half_width,
+            PI,
+            options.tolerance,
+        );
+        let last = get_direction(n - 2);
+        push_arc(
+            &mut mesh,
+            points[n - 1],
+            -last.perp() * half_width,
+            PI,
+            options.tolerance,
+        );
+    }
+    Ok(mesh)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_area(mesh: &Mesh) -> f32 {
+        mesh.get_triangles()
+            .iter()
+            .map(|t| {
+                let [a, b, c] = t.map(|i| mesh.positions[i as usize].truncate());
+                let area = cross(a, b, c) * 0.5;
+                assert!(area > 0.0);
+                area
+            })
+            .sum()
+    }
+
+    fn square(min: f32, max: f32) -> Vec<Vec2> {
+        vec![
+            Vec2::new(min, min),
+            Vec2::new(max, min),
+            Vec2::new(max, max),
+            Vec2::new(min, max),
+        ]
+    }
+
+    #[test]
+    fn polygons_with_holes() {
+        // the holes wind the same way as the outline and get flipped
+        let holes = vec![square(1.0, 2.0), square(3.0, 5.0)];
+        let outer = square(0.0, 6.0);
+        let triangles = triangulate_polygon(&outer, &holes).unwrap();
+        assert_eq!(triangles.len(), 4 + 2 * 4 + 2 * 2 - 2);
+        let mesh = fill_polygon(&outer, &holes).unwrap();
+        assert!(mesh.validate().is_ok());
+        assert!((get_area(&mesh) - (36.0 - 1.0 - 4.0)).abs() < 1e-4);
+
+        // a comb with deep concave notches
+        let mut comb = vec![Vec2::new(0.0, 0.0), Vec2::new(7.0, 0.0)];
+        for i in (0..7).rev().step_by(2) {
+            let x = i as f32;
+            comb.extend([Vec2::new(x, 3.0), Vec2::new(x, 1.0)]);
+            comb.extend([Vec2::new(x - 1.0, 1.0), Vec2::new(x - 1.0, 3.0)]);
+        }
+        comb.truncate(comb.len() - 3);
+        let mesh = fill_polygon(&comb, &[]).unwrap();
+        assert_eq!(mesh.get_primitive_count(), comb.len() - 2);
+        assert!((get_area(&mesh) - get_signed_area(&comb)).abs() < 1e-4);
+
+        assert!(fill_polygon(&outer, &[square(7.0, 8.0)]).is_err());
+    }
+
+    #[test]
+    fn fill_rules() {
+        let nested = vec![square(0.0, 4.0), square(1.0, 3.0)];
+        assert!((get_area(&fill_path(&nested, FillRule::NonZero)) - 16.0).abs() < 1e-4);
+        assert!((get_area(&fill_path(&nested, FillRule::EvenOdd)) - 12.0).abs() < 1e-4);
+
+        // the pentagon in the middle of a star is wound twice
+        let star = vec![(0..5)
+            .map(|i| Vec2::from_angle(PI / 2.0 + i as f32 * 4.0 * PI / 5.0))
+            .collect()];
+        let non_zero = get_area(&fill_path(&star, FillRule::NonZero));
+        let even_odd = get_area(&fill_path(&star, FillRule::EvenOdd));
+        let inner_radius = (PI / 5.0).cos() - (PI / 5.0).sin() * (PI / 5.0).tan();
+        let pentagon = 2.5 * inner_radius * inner_radius * (2.0 * PI / 5.0).sin();
+        assert!((non_zero - even_odd - pentagon).abs() < 1e-3);
+        assert!(fill_path(&star, FillRule::NonZero).validate().is_ok());
+    }
+
+    #[test]
+    fn strokes() {
+        let line = [Vec2::ZERO, Vec2::new(2.0, 0.0)];
+        let mut options = StrokeOptions {
+            width: 0.5,
+            ..Default::default()
+        };
+        assert!((get_area(&stroke_polyline(&line, false, &options).unwrap()) - 1.0).abs() < 1e-5);
+        options.cap = LineCap::Square;
+        assert!((get_area(&stroke_polyline(&line, false, &options).unwrap()) - 1.25).abs() < 1e-5);
+        options.cap = LineCap::Round;
+        let round = get_area(&stroke_polyline(&line, false, &options).unwrap());
+        assert!((round - 1.0 - PI * 0.0625).abs() < 0.01);
+
+        let has_point = |mesh: &Mesh, p: Vec2| {
+            mesh.positions
+                .iter()
+                .any(|q| q.truncate().distance(p) < 1e-5)
+        };
+        let outline = square(0.0, 1.0);
+        options.join = LineJoin::Miter;
+        let miter = stroke_polyline(&outline, true, &options).unwrap();
+        assert!(has_point(&miter, Vec2::new(1.25, -0.25)));
+        options.join = LineJoin::Bevel;
+        let bevel = stroke_polyline(&outline, true, &options).unwrap();
+        assert!(!has_point(&bevel, Vec2::new(1.25, -0.25)));
+        assert_eq!(bevel.get_primitive_count(), miter.get_primitive_count() - 4);
+        // round joins keep to the stroke width all the way around the corner
+        let get_distance = |p: &Vec3| {
+            let outside = Vec2::new((-p.x).max(p.x - 1.0), (-p.y).max(p.y - 1.0));
+            if outside.x > 0.0 || outside.y > 0.0 {
+                outside.max(Vec2::ZERO).length()
+            } else {
+                p.x.min(1.0 - p.x).min(p.y).min(1.0 - p.y)
+            }
+        };
+        options.join = LineJoin::Round;
+        let round = stroke_polyline(&outline, true, &options).unwrap();
+        assert!(round.get_primitive_count() > miter.get_primitive_count());
+        assert!(round
+            .positions
+            .iter()
+            .all(|p| get_distance(p) < 0.25 + 1e-5));
+        assert!(miter.positions.iter().any(|p| get_distance(p) > 0.3));
+
+        // sharp turns exceed the miter limit
+        options.join = LineJoin::Miter;
+        let spike = [Vec2::ZERO, Vec2::new(1.0, 0.0), Vec2::new(0.0, 0.1)];
+        let mesh = stroke_polyline(&spike, false, &options).unwrap();
+        assert!(mesh.positions.iter().all(|p| p.x < 1.3));
+
+        // tiny tolerances cap the arc, zero or negative ones are rejected
+        options.join = LineJoin::Round;
+        options.tolerance = 1e-9;
+        let fine = stroke_polyline(&outline, true, &options).unwrap();
+        assert!(fine.get_primitive_count() <= 8 + 4 * 256);
+        for tolerance in [0.0, -1.0, f32::NAN] {
+            options.tolerance = tolerance;
+            assert!(stroke_polyline(&outline, true, &options).is_err());
+        }
+    }
+}
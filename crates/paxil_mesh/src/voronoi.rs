@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+
+use super::delaunay::Delaunay;
+use super::mesh::{Mesh, PrimitiveMode};
+use paxil_math::*;
+
+// keeps the side of the line through point facing away from normal
+fn clip_half_plane(polygon: &[Vec2], point: Vec2, normal: Vec2) -> Vec<Vec2> {
+    let mut clipped = Vec::with_capacity(polygon.len() + 1);
+    for (i, a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        let (da, db) = ((*a - point).dot(normal), (b - point).dot(normal));
+        if da <= 0.0 {
+            clipped.push(*a);
+        }
+        if (da < 0.0 && db > 0.0) || (da > 0.0 && db < 0.0) {
+            clipped.push(a.lerp(b, da / (da - db)));
+        }
+    }
+    clipped
+}
+
+fn get_polygon_centroid(polygon: &[Vec2]) -> Option<Vec2> {
+    let mut area = 0.0;
+    let mut centroid = Vec2::ZERO;
+    for (i, a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        let cross = a.perp_dot(b);
+        area += cross;
+        centroid += (*a + b) * cross;
+    }
+    (area.abs() > 0.0).then(|| centroid / (3.0 * area))
+}
+
+// one convex counter-clockwise cell per site, clipped to the rectangle min..max
+// repeated sites and sites whose cell misses the rectangle get an empty cell
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Voronoi {
+    pub sites: Vec<Vec2>,
+    pub cells: Vec<Vec<Vec2>>,
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Voronoi {
+    pub fn new(sites: &[Vec2], min: Vec2, max: Vec2) -> Result<Self, String> {
+        Ok(Self::from_delaunay(&Delaunay::new(sites)?, min, max))
+    }
+
+    // each cell is the rectangle cut by the bisectors towards the site's Delaunay neighbours
+    pub fn from_delaunay(delaunay: &Delaunay, min: Vec2, max: Vec2) -> Self {
+        let sites = &delaunay.points;
+        let neighbours = if delaunay.triangles.is_empty() {
+            // collinear sites, every other site can bound the cell
+            (0..sites.len())
+                .map(|i| (0..sites.len()).filter(|j| *j != i).collect())
+                .collect()
+        } else {
+            delaunay.get_neighbours()
+        };
+        let mut first = HashMap::new();
+        let bounds = [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)];
+
+        let cells = sites
+            .iter()
+            .enumerate()
+            .map(|(i, site)| {
+                let key = [site.x + 0.0, site.y + 0.0].map(f32::to_bits);
+                if *first.entry(key).or_insert(i) != i {
+                    return Vec::new();
+                }
+                let mut cell = bounds.to_vec();
+                for j in &neighbours[i] {
+                    let other = sites[*j];
+                    if other == *site {
+                        continue;
+                    }
+                    cell = clip_half_plane(&cell, (*site + other) * 0.5, other - *site);
+                    if cell.is_empty() {
+                        break;
+                    }
+                }
+                cell
+            })
+            .collect();
+
+        Self {
+            sites: sites.clone(),
+            cells,
+            min,
+            max,
+        }
+    }
+
+    pub fn get_cell_area(&self, cell: usize) -> f32 {
+        super::tessellate::get_signed_area(&self.cells[cell])
+    }
+
+    pub fn get_centroid(&self, cell: usize) -> Option<Vec2> {
+        get_polygon_centroid(&self.cells[cell])
+    }
+
+    // moves every site to the centroid of its cell, sites without a cell stay put
+    pub fn relax(&self) -> Result<Self, String> {
+        let sites: Vec<Vec2> = (0..self.sites.len())
+            .map(|i| self.get_centroid(i).unwrap_or(self.sites[i]))
+            .collect();
+        Self::new(&sites, self.min, self.max)
+    }
+
+    // closed outlines as a line list, shared edges appear once per cell
+    pub fn to_outline_mesh(&self) -> Mesh {
+        let mut mesh = Mesh::new(PrimitiveMode::Lines);
+        for cell in &self.cells {
+            let start = mesh.positions.len() as u32;
+            let count = cell.len() as u32;
+            mesh.positions.extend(cell.iter().map(|p| p.extend(0.0)));
+            mesh.indices
+                .extend((0..count).flat_map(|k| [start + k, start + (k + 1) % count]));
+        }
+        mesh
+    }
+
+    // cells do not share vertices, so per vertex colours can tint each cell on its own
+    pub fn to_fill_mesh(&self) -> Mesh {
+        let mut mesh = Mesh::new(PrimitiveMode::Triangles);
+        for cell in &self.cells {
+            let start = mesh.positions.len() as u32;
+            mesh.positions.extend(cell.iter().map(|p| p.extend(0.0)));
+            mesh.indices.extend(
+                (1..cell.len().saturating_sub(1) as u32)
+                    .flat_map(|k| [start, start + k, start + k + 1]),
+            );
+        }
+        mesh
+    }
+}
+
+// Lloyd relaxation towards a centroidal Voronoi tessellation of the rectangle
+pub fn lloyd_relaxation(
+    sites: &[Vec2],
+    min: Vec2,
+    max: Vec2,
+    iterations: usize,
+) -> Result<Vec<Vec2>, String> {
+    let mut voronoi = Voronoi::new(sites, min, max)?;
+    for _ in 0..iterations {
+        voronoi = voronoi.relax()?;
+    }
+    Ok(voronoi.sites)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_mean_offset(voronoi: &Voronoi) -> f32 {
+        let total: f32 = (0..voronoi.sites.len())
+            .map(|i| voronoi.sites[i].distance(voronoi.get_centroid(i).unwrap()))
+            .sum();
+        total / voronoi.sites.len() as f32
+    }
+
+    #[test]
+    fn cells_partition_the_bounds() {
+        let mut rng = Rng::new(3);
+        let (min, max) = (Vec2::new(-1.0, 0.0), Vec2::new(2.0, 1.0));
+        let sites: Vec<Vec2> = (0..200).map(|_| rng.in_rect(min, max)).collect();
+        let voronoi = Voronoi::new(&sites, min, max).unwrap();
+
+        let area: f32 = (0..sites.len()).map(|i| voronoi.get_cell_area(i)).sum();
+        assert!((area - 3.0).abs() < 1e-3);
+        for (i, cell) in voronoi.cells.iter().enumerate() {
+            // every corner is at least as close to its own site as to any other
+            for corner in cell {
+                let own = corner.distance(sites[i]);
+                assert!(sites.iter().all(|s| corner.distance(*s) > own - 1e-4));
+            }
+        }
+
+        let fill = voronoi.to_fill_mesh();
+        assert!(fill.validate().is_ok());
+        let outline = voronoi.to_outline_mesh();
+        assert!(outline.validate().is_ok());
+        let corners: usize = voronoi.cells.iter().map(|c| c.len()).sum();
+        assert_eq!(outline.get_primitive_count(), corners);
+        assert_eq!(fill.get_primitive_count(), corners - 2 * sites.len());
+    }
+
+    #[test]
+    fn grid_cells_are_unit_squares() {
+        for n in [4, 6, 10] {
+            let sites: Vec<Vec2> = (0..n * n)
+                .map(|i| Vec2::new((i % n) as f32, (i / n) as f32))
+                .collect();
+            let (min, max) = (Vec2::splat(-0.5), Vec2::splat(n as f32 - 0.5));
+            let voronoi = Voronoi::new(&sites, min, max).unwrap();
+            for (i, site) in sites.iter().enumerate() {
+                assert!(
+                    (voronoi.get_cell_area(i) - 1.0).abs() < 1e-4,
+                    "{0}x{0} grid",
+                    n
+                );
+                let centroid = voronoi.get_centroid(i).unwrap();
+                assert!(centroid.abs_diff_eq(*site, 1e-4));
+            }
+        }
+    }
+
+    #[test]
+    fn degenerate_sites() {
+        let (min, max) = (Vec2::ZERO, Vec2::splat(4.0));
+        let sites = [
+            Vec2::new(1.0, 1.0),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(3.0, 3.0),
+        ];
+        let voronoi = Voronoi::new(&sites, min, max).unwrap();
+        assert!(voronoi.cells[2].is_empty());
+        let area: f32 = (0..sites.len()).map(|i| voronoi.get_cell_area(i)).sum();
+        assert!((area - 16.0).abs() < 1e-4);
+
+        let single = Voronoi::new(&[Vec2::splat(5.0)], min, max).unwrap();
+        assert_eq!(single.get_cell_area(0), 16.0);
+    }
+
+    #[test]
+    fn lloyd_evens_out_cells() {
+        let mut rng = Rng::new(11);
+        let (min, max) = (Vec2::ZERO, Vec2::ONE);
+        let sites: Vec<Vec2> = (0..100)
+            .map(|_| rng.in_rect(min, Vec2::splat(0.3)))
+            .collect();
+        let before = Voronoi::new(&sites, min, max).unwrap();
+        let relaxed = lloyd_relaxation(&sites, min, max, 20).unwrap();
+        let after = Voronoi::new(&relaxed, min, max).unwrap();
+        assert!(get_mean_offset(&after) < get_mean_offset(&before) * 0.2);
+        // the cells far from the initial cluster shrink the most
+        let get_largest = |voronoi: &Voronoi| {
+            (0..voronoi.sites.len())
+                .map(|i| voronoi.get_cell_area(i))
+                .fold(0.0f32, f32::max)
+        };
+        assert!(get_largest(&after) < get_largest(&before) * 0.25);
+        assert!(relaxed.iter().all(|p| p.clamp(min, max) == *p));
+    }
+}
@@ -3,9 +3,39 @@ use anyhow::Result;
 use std::rc::Rc;
 use thiserror::Error;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    Other(u16),
+}
+
+// positions and sizes are in physical pixels, y grows downwards
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AppEvent {
+    Resized { width: u32, height: u32 },
+    MouseMoved { x: f32, y: f32 },
+    MousePressed { button: MouseButton, x: f32, y: f32 },
+    MouseReleased { button: MouseButton, x: f32, y: f32 },
+    // positive y scrolls up, in lines
+    MouseScrolled { x: f32, y: f32 },
+}
+
+// timing of the frame about to be drawn, in seconds
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FrameTime {
+    pub elapsed: f32,
+    pub delta: f32,
+    pub frame: u64,
+}
+
 pub trait App: Sized {
     fn new(gl: Rc<glow::Context>) -> Self;
+    // called once per frame before draw, drives tweens and keyframe tracks
+    fn update(&mut self, _gl: &glow::Context, _time: &FrameTime) {}
     fn draw(&mut self, gl: &glow::Context);
+    fn event(&mut self, _gl: &glow::Context, _event: &AppEvent) {}
 }
 
 #[derive(Debug, Error)]
@@ -44,7 +74,7 @@ impl AppRunner {
             // };
 
             #[cfg(feature = "glutin_winit")]
-            let (gl, gl_surface, gl_context, _window, event_loop) = {
+            let (gl, gl_surface, gl_context, window, event_loop) = {
                 use glutin::{
                     config::{ConfigTemplateBuilder, GlConfig},
                     context::{ContextApi, ContextAttributesBuilder, NotCurrentGlContext},
@@ -121,19 +151,98 @@ impl AppRunner {
 
             #[cfg(feature = "glutin_winit")]
             {
+                use glow::HasContext;
                 use glutin::prelude::GlSurface;
-                use winit::event::{Event, WindowEvent};
+                use std::num::NonZeroU32;
+                use winit::event::{ElementState, Event, MouseScrollDelta, WindowEvent};
+
+                let size = window.inner_size();
+                gl.viewport(0, 0, size.width as i32, size.height as i32);
+                app.event(
+                    &gl,
+                    &AppEvent::Resized {
+                        width: size.width,
+                        height: size.height,
+                    },
+                );
+
+                let mut cursor = (0.0f32, 0.0f32);
+                let start = std::time::Instant::now();
+                let mut time = FrameTime::default();
                 let _ = event_loop.run(move |event, elwt| {
+                    // keep redrawing so animations advance, the swap interval paces the loop
+                    if let Event::AboutToWait = event {
+                        window.request_redraw();
+                    }
                     if let Event::WindowEvent { event, .. } = event {
-                        match event {
+                        let app_event = match event {
                             WindowEvent::CloseRequested => {
                                 elwt.exit();
+                                None
                             }
                             WindowEvent::RedrawRequested => {
+                                let elapsed = start.elapsed().as_secs_f32();
+                                time.delta = elapsed - time.elapsed;
+                                time.elapsed = elapsed;
+                                app.update(&gl, &time);
                                 app.draw(&gl);
+                                time.frame += 1;
                                 gl_surface.swap_buffers(&gl_context).unwrap();
+                                None
+                            }
+                            WindowEvent::Resized(size) => {
+                                if let (Some(width), Some(height)) =
+                                    (NonZeroU32::new(size.width), NonZeroU32::new(size.height))
+                                {
+                                    gl_surface.resize(&gl_context, width, height);
+                                    gl.viewport(0, 0, size.width as i32, size.height as i32);
+                                }
+                                Some(AppEvent::Resized {
+                                    width: size.width,
+                                    height: size.height,
+                                })
+                            }
+                            WindowEvent::CursorMoved { position, .. } => {
+                                cursor = (position.x as f32, position.y as f32);
+                                Some(AppEvent::MouseMoved {
+                                    x: cursor.0,
+                                    y: cursor.1,
+                                })
                             }
-                            _ => (),
+                            WindowEvent::MouseInput { state, button, .. } => {
+                                let button = match button {
+                                    winit::event::MouseButton::Left => MouseButton::Left,
+                                    winit::event::MouseButton::Right => MouseButton::Right,
+                                    winit::event::MouseButton::Middle => MouseButton::Middle,
+                                    winit::event::MouseButton::Back => MouseButton::Other(3),
+                                    winit::event::MouseButton::Forward => MouseButton::Other(4),
+                                    winit::event::MouseButton::Other(b) => MouseButton::Other(b),
+                                };
+                                let (x, y) = cursor;
+                                Some(match state {
+                                    ElementState::Pressed => {
+                                        AppEvent::MousePressed { button, x, y }
+                                    }
+                                    ElementState::Released => {
+                                        AppEvent::MouseReleased { button, x, y }
+                                    }
+                                })
+                            }
+                            WindowEvent::MouseWheel { delta, .. } => {
+                                let (x, y) = match delta {
+                                    MouseScrollDelta::LineDelta(x, y) => (x, y),
+                                    // roughly one line per 20 pixels
+                                    MouseScrollDelta::PixelDelta(p) => {
+                                        (p.x as f32 / 20.0, p.y as f32 / 20.0)
+                                    }
+                                };
+                                Some(AppEvent::MouseScrolled { x, y })
+                            }
+                            _ => None,
+                        };
+
+                        if let Some(app_event) = app_event {
+                            app.event(&gl, &app_event);
                         }
                     }
                 });
@@ -0,0 +1,331 @@
+use paxil_math::*;
+
+use super::app_runner::{AppEvent, MouseButton};
+
+pub trait Camera {
+    fn get_transform(&self) -> &Transform;
+    fn get_transform_mut(&mut self) -> &mut Transform;
+    fn get_projection_matrix(&self) -> Mat4;
+    fn get_viewport(&self) -> (u32, u32);
+    fn set_viewport(&mut self, width: u32, height: u32);
+
+    fn get_aspect(&self) -> f32 {
+        let (width, height) = self.get_viewport();
+        width.max(1) as f32 / height.max(1) as f32
+    }
+
+    fn get_view_matrix(&self) -> Mat4 {
+        let t = self.get_transform();
+        Mat4::from_trs(t.translation, t.rotation, Vec3::ONE)
+            .inverse()
+            .unwrap_or(Mat4::IDENTITY)
+    }
+
+    fn get_view_projection_matrix(&self) -> Mat4 {
+        self.get_projection_matrix() * self.get_view_matrix()
+    }
+
+    fn get_position(&self) -> Vec3 {
+        self.get_transform().translation
+    }
+
+    fn look_at(&mut self, target: Vec3, up: Vec3) {
+        self.get_transform_mut().look_at(target, up);
+    }
+
+    // keeps the projection in sync with the window, minimized windows keep the last size
+    fn handle_event(&mut self, event: &AppEvent) {
+        if let AppEvent::Resized { width, height } = *event {
+            if width == 0 || height == 0 {
+                return;
+            }
+            self.set_viewport(width, height);
+        }
+    }
+
+    // screen position in window pixels (y down) to world space
+    fn screen_to_world(&self, x: f32, y: f32, ndc_z: f32) -> Vec3 {
+        let (width, height) = self.get_viewport();
+        let ndc = Vec3::new(
+            2.0 * x / width.max(1) as f32 - 1.0,
+            1.0 - 2.0 * y / height.max(1) as f32,
+            ndc_z,
+        );
+        self.get_view_projection_matrix()
+            .inverse()
+            .unwrap_or(Mat4::IDENTITY)
+            .project_point3(ndc)
+    }
+
+    fn world_to_screen(&self, p: Vec3) -> Vec3 {
+        let (width, height) = self.get_viewport();
+        let ndc = self.get_view_projection_matrix().project_point3(p);
+        Vec3::new(
+            (ndc.x + 1.0) * 0.5 * width as f32,
+            (1.0 - ndc.y) * 0.5 * height as f32,
+            ndc.z,
+        )
+    }
+
+    // starts on the near plane, for picking
+    fn screen_to_world_ray(&self, x: f32, y: f32) -> Ray {
+        let near = self.screen_to_world(x, y, -1.0);
+        let far = self.screen_to_world(x, y, 1.0);
+        Ray::from_points(near, far)
+    }
+
+    fn get_frustum(&self) -> Frustum {
+        Frustum::from_view_projection(&self.get_view_projection_matrix())
+    }
+}
+
+pub struct PerspectiveCamera {
+    pub transform: Transform,
+    // vertical field of view in radians
+    pub fov_y: f32,
+    pub near: f32,
+    pub far: f32,
+    viewport: (u32, u32),
+}
+
+impl Default for PerspectiveCamera {
+    fn default() -> Self {
+        Self::new(60f32.to_radians(), 0.1, 1000.0)
+    }
+}
+
+impl PerspectiveCamera {
+    pub fn new(fov_y: f32, near: f32, far: f32) -> Self {
+        Self {
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 5.0)),
+            fov_y,
+            near,
+            far,
+            viewport: (1, 1),
+        }
+    }
+}
+
+impl Camera for PerspectiveCamera {
+    fn get_transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn get_transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+
+    fn get_projection_matrix(&self) -> Mat4 {
+        Mat4::perspective(self.fov_y, self.get_aspect(), self.near, self.far)
+    }
+
+    fn get_viewport(&self) -> (u32, u32) {
+        self.viewport
+    }
+
+    fn set_viewport(&mut self, width: u32, height: u32) {
+        self.viewport = (width, height);
+    }
+}
+
+pub struct OrthoCamera {
+    pub transform: Transform,
+    // half of the visible height in world units, the width follows the aspect ratio
+    pub size: f32,
+    pub near: f32,
+    pub far: f32,
+    // when set the view spans the viewport in pixels with the origin at the top left
+    pub pixel_space: bool,
+    viewport: (u32, u32),
+}
+
+impl Default for OrthoCamera {
+    fn default() -> Self {
+        Self::new(1.0, -1000.0, 1000.0)
+    }
+}
+
+impl OrthoCamera {
+    pub fn new(size: f32, near: f32, far: f32) -> Self {
+        Self {
+            transform: Transform::IDENTITY,
+            size,
+            near,
+            far,
+            pixel_space: false,
+            viewport: (1, 1),
+        }
+    }
+
+    pub fn new_pixel_space() -> Self {
+        Self {
+            pixel_space: true,
+            ..Self::default()
+        }
+    }
+}
+
+impl Camera for OrthoCamera {
+    fn get_transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn get_transform_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+
+    fn get_projection_matrix(&self) -> Mat4 {
+        if self.pixel_space {
+            let (width, height) = self.viewport;
+            return Mat4::orthographic(0.0, width as f32, height as f32, 0.0, self.near, self.far);
+        }
+        let half_width = self.size * self.get_aspect();
+        Mat4::orthographic(
+            -half_width,
+            half_width,
+            -self.size,
+            self.size,
+            self.near,
+            self.far,
+        )
+    }
+
+    fn get_viewport(&self) -> (u32, u32) {
+        self.viewport
+    }
+
+    fn set_viewport(&mut self, width: u32, height: u32) {
+        self.viewport = (width, height);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DragMode {
+    Orbit,
+    Pan,
+}
+
+// "easy cam": left drag orbits, right or middle drag pans, scroll zooms
+pub struct OrbitController {
+    pub target: Vec3,
+    pub distance: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    // radians per pixel
+    pub rotate_speed: f32,
+    // fraction of the distance per pixel
+    pub pan_speed: f32,
+    // fraction of the distance per scroll line
+    pub zoom_speed: f32,
+    initial: (Vec3, f32, f32, f32),
+    drag: Option<DragMode>,
+    last_cursor: Vec2,
+    changed: bool,
+}
+
+impl Default for OrbitController {
+    fn default() -> Self {
+        Self::new(Vec3::ZERO, 5.0)
+    }
+}
+
+impl OrbitController {
+    pub fn new(target: Vec3, distance: f32) -> Self {
+        Self {
+            target,
+            distance,
+            yaw: 0.0,
+            pitch: 0.0,
+            min_distance: 0.01,
+            max_distance: f32::MAX,
+            rotate_speed: 0.005,
+            pan_speed: 0.0015,
+            zoom_speed: 0.1,
+            initial: (target, distance, 0.0, 0.0),
+            drag: None,
+            last_cursor: Vec2::ZERO,
+            changed: true,
+        }
+    }
+
+    // the current state becomes the one reset() returns to
+    pub fn save_state(&mut self) {
+        self.initial = (self.target, self.distance, self.yaw, self.pitch);
+    }
+
+    pub fn reset(&mut self) {
+        (self.target, self.distance, self.yaw, self.pitch) = self.initial;
+        self.drag = None;
+        self.changed = true;
+    }
+
+    pub fn get_rotation(&self) -> Quat {
+        Quat::from_axis_angle(Vec3::Y, self.yaw) * Quat::from_axis_angle(Vec3::X, self.pitch)
+    }
+
+    pub fn get_position(&self) -> Vec3 {
+        self.target + self.get_rotation() * Vec3::new(0.0, 0.0, self.distance)
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    pub fn orbit(&mut self, dx: f32, dy: f32) {
+        let limit = std::f32::consts::FRAC_PI_2 - 0.001;
+        self.yaw -= dx * self.rotate_speed;
+        self.pitch = (self.pitch - dy * self.rotate_speed).clamp(-limit, limit);
+        self.changed = true;
+    }
+
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        let rotation = self.get_rotation();
+        let scale = self.distance * self.pan_speed;
+        self.target += rotation * Vec3::new(-dx * scale, dy * scale, 0.0);
+        self.changed = true;
+    }
+
+    pub fn zoom(&mut self, lines: f32) {
+        self.distance = (self.distance * (1.0 - self.zoom_speed).powf(lines))
+            .clamp(self.min_distance, self.max_distance);
+        self.changed = true;
+    }
+
+    // returns true when the camera needs to be updated
+    pub fn handle_event(&mut self, event: &AppEvent) -> bool {
+        match *event {
+            AppEvent::MousePressed { button, x, y } => {
+                self.drag = match button {
+                    MouseButton::Left => Some(DragMode::Orbit),
+                    MouseButton::Right | MouseButton::Middle => Some(DragMode::Pan),
+                    MouseButton::Other(_) => self.drag,
+                };
+                self.last_cursor = Vec2::new(x, y);
+            }
+            AppEvent::MouseReleased { .. } => {
+                self.drag = None;
+            }
+            AppEvent::MouseMoved { x, y } => {
+                let cursor = Vec2::new(x, y);
+                let delta = cursor - self.last_cursor;
+                self.last_cursor = cursor;
+                match self.drag {
+                    Some(DragMode::Orbit) => self.orbit(delta.x, delta.y),
+                    Some(DragMode::Pan) => self.pan(delta.x, delta.y),
+                    None => (),
+                }
+            }
+            AppEvent::MouseScrolled { y, .. } => self.zoom(y),
+            AppEvent::Resized { .. } => (),
+        }
+        std::mem::take(&mut self.changed)
+    }
+
+    pub fn apply<C: Camera>(&self, camera: &mut C) {
+        let transform = camera.get_transform_mut();
+        transform.translation = self.get_position();
+        transform.rotation = self.get_rotation();
+    }
+}
@@ -0,0 +1,378 @@
+use std::path::Path;
+
+use super::utils::*;
+
+pub struct CompressedLevel {
+    pub width: usize,
+    pub height: usize,
+    pub faces: Vec<Vec<u8>>,
+}
+
+pub struct CompressedImage {
+    pub width: usize,
+    pub height: usize,
+    pub internal_format: u32,
+    pub face_count: usize,
+    pub levels: Vec<CompressedLevel>,
+}
+
+impl CompressedImage {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let bytes = std::fs::read(path.as_ref()).map_err(|e| e.to_string())?;
+        if bytes.starts_with(&KTX2_IDENTIFIER) {
+            Self::from_ktx2(&bytes)
+        } else if bytes.starts_with(DDS_MAGIC) {
+            Self::from_dds(&bytes)
+        } else {
+            Err(format!(
+                "Unknown compressed container: {}",
+                path.as_ref().display()
+            ))
+        }
+    }
+
+    pub fn is_cubemap(&self) -> bool {
+        self.face_count == 6
+    }
+
+    pub fn from_ktx2(bytes: &[u8]) -> Result<Self, String> {
+        if !bytes.starts_with(&KTX2_IDENTIFIER) {
+            return Err("Invalid KTX2 identifier".to_string());
+        }
+
+        let vk_format = read_u32(bytes, 12)?;
+        let width = read_u32(bytes, 20)? as usize;
+        let height = (read_u32(bytes, 24)? as usize).max(1);
+        let depth = read_u32(bytes, 28)?;
+        let layer_count = read_u32(bytes, 32)?;
+        let face_count = read_u32(bytes, 36)? as usize;
+        let level_count = (read_u32(bytes, 40)? as usize).max(1);
+        let supercompression = read_u32(bytes, 44)?;
+
+        if depth > 1 || layer_count > 1 {
+            return Err("KTX2 3D textures and texture arrays are not supported".to_string());
+        }
+        if supercompression != 0 {
+            return Err(format!(
+                "KTX2 supercompression scheme {} is not supported",
+                supercompression
+            ));
+        }
+        if face_count != 1 && face_count != 6 {
+            return Err(format!("Invalid KTX2 face count: {}", face_count));
+        }
+        if level_count > MAX_LEVELS {
+            return Err(format!("Invalid KTX2 level count: {}", level_count));
+        }
+
+        let internal_format = get_gl_format_from_vk(vk_format)
+            .ok_or_else(|| format!("Unsupported KTX2 vkFormat: {}", vk_format))?;
+        let block_size = get_compressed_block_size(internal_format)
+            .ok_or_else(|| format!("Unsupported KTX2 vkFormat: {}", vk_format))?;
+
+        // the level index follows the 48 byte header and the 32 byte section index
+        let mut levels = Vec::with_capacity(level_count);
+        for level in 0..level_count {
+            let entry = 80 + level * 24;
+            let offset = read_u64(bytes, entry)? as usize;
+            let length = read_u64(bytes, entry + 8)? as usize;
+
+            let level_width = (width >> level).max(1);
+            let level_height = (height >> level).max(1);
+            let face_size = get_compressed_image_size(block_size, level_width, level_height);
+            if face_size * face_count != length {
+                return Err(format!("Invalid KTX2 level {} size", level));
+            }
+
+            let data = read_slice(bytes, offset, length)?;
+            levels.push(CompressedLevel {
+                width: level_width,
+                height: level_height,
+                faces: data.chunks(face_size).map(|c| c.to_vec()).collect(),
+            });
+        }
+
+        Ok(Self {
+            width,
+            height,
+            internal_format,
+            face_count,
+            levels,
+        })
+    }
+
+    pub fn from_dds(bytes: &[u8]) -> Result<Self, String> {
+        if !bytes.starts_with(DDS_MAGIC) || read_u32(bytes, 4)? != 124 {
+            return Err("Invalid DDS header".to_string());
+        }
+
+        let height = read_u32(bytes, 12)? as usize;
+        let width = read_u32(bytes, 16)? as usize;
+        let level_count = (read_u32(bytes, 28)? as usize).max(1);
+        let pf_flags = read_u32(bytes, 80)?;
+        let four_cc = read_slice(bytes, 84, 4)?;
+        let caps2 = read_u32(bytes, 112)?;
+
+        if level_count > MAX_LEVELS {
+            return Err(format!("Invalid DDS mip count: {}", level_count));
+        }
+        if pf_flags & DDPF_FOURCC == 0 {
+            return Err("Uncompressed DDS files are not supported".to_string());
+        }
+
+        let mut data_offset = 128;
+        let mut face_count = if caps2 & DDSCAPS2_CUBEMAP != 0 { 6 } else { 1 };
+
+        let internal_format = match four_cc {
+            b"DXT1" => glow::COMPRESSED_RGBA_S3TC_DXT1_EXT,
+            b"DXT3" => glow::COMPRESSED_RGBA_S3TC_DXT3_EXT,
+            b"DXT5" => glow::COMPRESSED_RGBA_S3TC_DXT5_EXT,
+            b"ATI1" | b"BC4U" => glow::COMPRESSED_RED_RGTC1,
+            b"BC4S" => glow::COMPRESSED_SIGNED_RED_RGTC1,
+            b"ATI2" | b"BC5U" => glow::COMPRESSED_RG_RGTC2,
+            b"BC5S" => glow::COMPRESSED_SIGNED_RG_RGTC2,
+            b"DX10" => {
+                let dxgi_format = read_u32(bytes, 128)?;
+                let misc_flag = read_u32(bytes, 136)?;
+                let array_size = read_u32(bytes, 140)?;
+                if array_size > 1 {
+                    return Err("DDS texture arrays are not supported".to_string());
+                }
+                if misc_flag & DDS_RESOURCE_MISC_TEXTURECUBE != 0 {
+                    face_count = 6;
+                }
+                data_offset += 20;
+                get_gl_format_from_dxgi(dxgi_format)
+                    .ok_or_else(|| format!("Unsupported DXGI format: {}", dxgi_format))?
+            }
+            _ => {
+                return Err(format!(
+                    "Unsupported DDS FourCC: {}",
+                    String::from_utf8_lossy(four_cc)
+                ))
+            }
+        };
+        let block_size = get_compressed_block_size(internal_format)
+            .ok_or_else(|| "Unsupported DDS format".to_string())?;
+
+        let mut levels = (0..level_count)
+            .map(|level| CompressedLevel {
+                width: (width >> level).max(1),
+                height: (height >> level).max(1),
+                faces: Vec::with_capacity(face_count),
+            })
+            .collect::<Vec<_>>();
+
+        // DDS stores every mip of a face before moving on to the next face
+        let mut offset = data_offset;
+        for _ in 0..face_count {
+            for level in levels.iter_mut() {
+                let size = get_compressed_image_size(block_size, level.width, level.height);
+                level.faces.push(read_slice(bytes, offset, size)?.to_vec());
+                offset += size;
+            }
+        }
+
+        Ok(Self {
+            width,
+            height,
+            internal_format,
+            face_count,
+            levels,
+        })
+    }
+}
+
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+const DDS_MAGIC: &[u8] = b"DDS ";
+const DDPF_FOURCC: u32 = 0x4;
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+const DDS_RESOURCE_MISC_TEXTURECUBE: u32 = 0x4;
+// a full mip chain for a u32 sized image
+const MAX_LEVELS: usize = 32;
+
+fn read_slice(bytes: &[u8], offset: usize, length: usize) -> Result<&[u8], String> {
+    offset
+        .checked_add(length)
+        .and_then(|end| bytes.get(offset..end))
+        .ok_or_else(|| "Unexpected end of file".to_string())
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, String> {
+    let b = read_slice(bytes, offset, 4)?;
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Result<u64, String> {
+    let lo = read_u32(bytes, offset)? as u64;
+    let hi = read_u32(bytes, offset + 4)? as u64;
+    Ok(lo | (hi << 32))
+}
+
+fn get_gl_format_from_vk(vk_format: u32) -> Option<u32> {
+    use glow::*;
+    let format = match vk_format {
+        131 => COMPRESSED_RGB_S3TC_DXT1_EXT,
+        132 => COMPRESSED_SRGB_S3TC_DXT1_EXT,
+        133 => COMPRESSED_RGBA_S3TC_DXT1_EXT,
+        134 => COMPRESSED_SRGB_ALPHA_S3TC_DXT1_EXT,
+        135 => COMPRESSED_RGBA_S3TC_DXT3_EXT,
+        136 => COMPRESSED_SRGB_ALPHA_S3TC_DXT3_EXT,
+        137 => COMPRESSED_RGBA_S3TC_DXT5_EXT,
+        138 => COMPRESSED_SRGB_ALPHA_S3TC_DXT5_EXT,
+        139 => COMPRESSED_RED_RGTC1,
+        140 => COMPRESSED_SIGNED_RED_RGTC1,
+        141 => COMPRESSED_RG_RGTC2,
+        142 => COMPRESSED_SIGNED_RG_RGTC2,
+        143 => COMPRESSED_RGB_BPTC_UNSIGNED_FLOAT,
+        144 => COMPRESSED_RGB_BPTC_SIGNED_FLOAT,
+        145 => COMPRESSED_RGBA_BPTC_UNORM,
+        146 => COMPRESSED_SRGB_ALPHA_BPTC_UNORM,
+        147 => COMPRESSED_RGB8_ETC2,
+        148 => COMPRESSED_SRGB8_ETC2,
+        149 => COMPRESSED_RGB8_PUNCHTHROUGH_ALPHA1_ETC2,
+        150 => COMPRESSED_SRGB8_PUNCHTHROUGH_ALPHA1_ETC2,
+        151 => COMPRESSED_RGBA8_ETC2_EAC,
+        152 => COMPRESSED_SRGB8_ALPHA8_ETC2_EAC,
+        153 => COMPRESSED_R11_EAC,
+        154 => COMPRESSED_SIGNED_R11_EAC,
+        155 => COMPRESSED_RG11_EAC,
+        156 => COMPRESSED_SIGNED_RG11_EAC,
+        // ASTC formats alternate UNORM / SRGB from 4x4 up to 12x12
+        157..=184 => {
+            let index = vk_format - 157;
+            if index.is_multiple_of(2) {
+                COMPRESSED_RGBA_ASTC_4x4_KHR + index / 2
+            } else {
+                COMPRESSED_SRGB8_ALPHA8_ASTC_4x4_KHR + index / 2
+            }
+        }
+        _ => return None,
+    };
+    Some(format)
+}
+
+fn get_gl_format_from_dxgi(dxgi_format: u32) -> Option<u32> {
+    use glow::*;
+    let format = match dxgi_format {
+        70 | 71 => COMPRESSED_RGBA_S3TC_DXT1_EXT,
+        72 => COMPRESSED_SRGB_ALPHA_S3TC_DXT1_EXT,
+        73 | 74 => COMPRESSED_RGBA_S3TC_DXT3_EXT,
+        75 => COMPRESSED_SRGB_ALPHA_S3TC_DXT3_EXT,
+        76 | 77 => COMPRESSED_RGBA_S3TC_DXT5_EXT,
+        78 => COMPRESSED_SRGB_ALPHA_S3TC_DXT5_EXT,
+        79 | 80 => COMPRESSED_RED_RGTC1,
+        81 => COMPRESSED_SIGNED_RED_RGTC1,
+        82 | 83 => COMPRESSED_RG_RGTC2,
+        84 => COMPRESSED_SIGNED_RG_RGTC2,
+        94 | 95 => COMPRESSED_RGB_BPTC_UNSIGNED_FLOAT,
+        96 => COMPRESSED_RGB_BPTC_SIGNED_FLOAT,
+        97 | 98 => COMPRESSED_RGBA_BPTC_UNORM,
+        99 => COMPRESSED_SRGB_ALPHA_BPTC_UNORM,
+        _ => return None,
+    };
+    Some(format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put_u32(bytes: &mut [u8], offset: usize, value: u32) {
+        bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    // an 8x4 BC1 image with two levels, filled with the byte index
+    fn get_ktx2() -> Vec<u8> {
+        let mut bytes = vec![0; 128 + 16 + 8];
+        bytes[..12].copy_from_slice(&KTX2_IDENTIFIER);
+        for (offset, value) in [(12, 133), (20, 8), (24, 4), (36, 1), (40, 2)] {
+            put_u32(&mut bytes, offset, value);
+        }
+        for (level, (offset, length)) in [(128, 16), (144, 8)].into_iter().enumerate() {
+            put_u32(&mut bytes, 80 + level * 24, offset);
+            put_u32(&mut bytes, 88 + level * 24, length);
+        }
+        for (i, b) in bytes.iter_mut().enumerate().skip(128) {
+            *b = i as u8;
+        }
+        bytes
+    }
+
+    fn get_dds(four_cc: &[u8], width: u32, height: u32, levels: u32, caps2: u32) -> Vec<u8> {
+        let mut bytes = vec![0; 128];
+        bytes[..4].copy_from_slice(DDS_MAGIC);
+        bytes[84..88].copy_from_slice(four_cc);
+        for (offset, value) in [
+            (4, 124),
+            (12, height),
+            (16, width),
+            (28, levels),
+            (80, DDPF_FOURCC),
+            (112, caps2),
+        ] {
+            put_u32(&mut bytes, offset, value);
+        }
+        bytes
+    }
+
+    #[test]
+    fn ktx2() {
+        let bytes = get_ktx2();
+        let image = CompressedImage::from_ktx2(&bytes).unwrap();
+        assert_eq!((image.width, image.height), (8, 4));
+        assert_eq!(image.internal_format, glow::COMPRESSED_RGBA_S3TC_DXT1_EXT);
+        assert!(!image.is_cubemap());
+        assert_eq!(image.levels.len(), 2);
+        assert_eq!((image.levels[1].width, image.levels[1].height), (4, 2));
+        assert_eq!(image.levels[0].faces, vec![bytes[128..144].to_vec()]);
+        assert_eq!(image.levels[1].faces, vec![bytes[144..152].to_vec()]);
+
+        // truncated header, level index and level data
+        assert!(CompressedImage::from_ktx2(&bytes[..40]).is_err());
+        assert!(CompressedImage::from_ktx2(&bytes[..100]).is_err());
+        assert!(CompressedImage::from_ktx2(&bytes[..150]).is_err());
+
+        let mut wrong_length = bytes.clone();
+        put_u32(&mut wrong_length, 88, 8);
+        assert!(CompressedImage::from_ktx2(&wrong_length).is_err());
+        let mut past_end = bytes.clone();
+        put_u32(&mut past_end, 84, u32::MAX);
+        assert!(CompressedImage::from_ktx2(&past_end).is_err());
+        let mut too_many_levels = bytes.clone();
+        put_u32(&mut too_many_levels, 40, u32::MAX);
+        assert!(CompressedImage::from_ktx2(&too_many_levels).is_err());
+        let mut unknown_format = bytes;
+        put_u32(&mut unknown_format, 12, 37);
+        assert!(CompressedImage::from_ktx2(&unknown_format).is_err());
+    }
+
+    #[test]
+    fn dds() {
+        let mut bytes = get_dds(b"DXT5", 8, 8, 2, 0);
+        bytes.extend((0..64 + 16).map(|i| i as u8));
+        let image = CompressedImage::from_dds(&bytes).unwrap();
+        assert_eq!(image.internal_format, glow::COMPRESSED_RGBA_S3TC_DXT5_EXT);
+        assert_eq!(image.levels.len(), 2);
+        assert_eq!(image.levels[0].faces[0], bytes[128..192].to_vec());
+        assert_eq!(image.levels[1].faces[0], bytes[192..208].to_vec());
+
+        // each face holds its whole mip chain
+        let mut cube = get_dds(b"DXT1", 4, 4, 1, DDSCAPS2_CUBEMAP);
+        cube.extend((0..6 * 8).map(|i| i as u8));
+        let image = CompressedImage::from_dds(&cube).unwrap();
+        assert!(image.is_cubemap());
+        assert_eq!(image.levels[0].faces.len(), 6);
+        assert_eq!(image.levels[0].faces[5], cube[168..176].to_vec());
+
+        // truncated header and level data
+        assert!(CompressedImage::from_dds(&bytes[..60]).is_err());
+        assert!(CompressedImage::from_dds(&bytes[..200]).is_err());
+        assert!(CompressedImage::from_dds(&cube[..170]).is_err());
+        assert!(CompressedImage::from_dds(&get_dds(b"DX10", 4, 4, 1, 0)).is_err());
+        assert!(CompressedImage::from_dds(&get_dds(b"DXT1", 4, 4, u32::MAX, 0)).is_err());
+        assert!(CompressedImage::from_dds(&get_dds(b"RGBG", 4, 4, 1, 0)).is_err());
+    }
+}
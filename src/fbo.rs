@@ -0,0 +1,207 @@
+use glow::HasContext;
+use std::cell::Cell;
+use std::rc::Rc;
+
+use super::renderbuffer::Renderbuffer;
+use super::texture::*;
+
+pub struct FBO {
+    gl: Rc<glow::Context>,
+    fbo: glow::Framebuffer,
+    width: usize,
+    height: usize,
+    // viewport to restore on unbind
+    saved_viewport: Cell<[i32; 4]>,
+}
+
+impl FBO {
+    pub fn new(gl: Rc<glow::Context>, width: usize, height: usize) -> Result<Self, String> {
+        unsafe {
+            let fbo = gl.create_framebuffer()?;
+            Ok(Self {
+                gl,
+                fbo,
+                width,
+                height,
+                saved_viewport: Cell::new([0; 4]),
+            })
+        }
+    }
+
+    pub fn get_id(&self) -> glow::Framebuffer {
+        self.fbo
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            let mut viewport = [0; 4];
+            self.gl
+                .get_parameter_i32_slice(glow::VIEWPORT, &mut viewport);
+            self.saved_viewport.set(viewport);
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.fbo));
+            self.gl
+                .viewport(0, 0, self.width as i32, self.height as i32);
+        }
+    }
+
+    pub fn unbind(&self) {
+        unsafe {
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+            let [x, y, width, height] = self.saved_viewport.get();
+            self.gl.viewport(x, y, width, height);
+        }
+    }
+
+    pub fn attach_texture<T: TextureTrait>(
+        &self,
+        attachment: u32,
+        texture: &T,
+    ) -> Result<(), String> {
+        if texture.get_target() == glow::TEXTURE_CUBE_MAP {
+            return Err("Cube maps are attached one face at a time with attach_cube_face".into());
+        }
+        self.attach_texture_target(attachment, texture.get_target(), texture.get_id());
+        Ok(())
+    }
+
+    // face is 0..6 in the order +X, -X, +Y, -Y, +Z, -Z
+    pub fn attach_cube_face(
+        &self,
+        attachment: u32,
+        texture: &TextureCube,
+        face: u32,
+    ) -> Result<(), String> {
+        if face >= 6 {
+            return Err(format!("Cube face {} is out of range", face));
+        }
+        self.attach_texture_target(
+            attachment,
+            glow::TEXTURE_CUBE_MAP_POSITIVE_X + face,
+            texture.get_id(),
+        );
+        Ok(())
+    }
+
+    fn attach_texture_target(&self, attachment: u32, target: u32, texture: glow::Texture) {
+        unsafe {
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.fbo));
+            self.gl
+                .framebuffer_texture_2d(glow::FRAMEBUFFER, attachment, target, Some(texture), 0);
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+    }
+
+    pub fn attach_renderbuffer(&self, attachment: u32, renderbuffer: &Renderbuffer) {
+        unsafe {
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.fbo));
+            self.gl.framebuffer_renderbuffer(
+                glow::FRAMEBUFFER,
+                attachment,
+                glow::RENDERBUFFER,
+                Some(renderbuffer.get_id()),
+            );
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+    }
+
+    pub fn set_draw_buffers(&self, attachments: &[u32]) {
+        unsafe {
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.fbo));
+            self.gl.draw_buffers(attachments);
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+    }
+
+    pub fn check_status(&self) -> Result<(), String> {
+        unsafe {
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.fbo));
+            let status = self.gl.check_framebuffer_status(glow::FRAMEBUFFER);
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+
+            if status == glow::FRAMEBUFFER_COMPLETE {
+                Ok(())
+            } else {
+                Err(format!("Framebuffer incomplete: {:#06x}", status))
+            }
+        }
+    }
+
+    // dst of None blits into the default framebuffer
+    pub fn blit(&self, dst: Option<&FBO>, dst_width: usize, dst_height: usize, mask: u32) {
+        // scaling depth or stencil requires NEAREST
+        let filter = if mask == glow::COLOR_BUFFER_BIT
+            && (dst_width != self.width || dst_height != self.height)
+        {
+            glow::LINEAR
+        } else {
+            glow::NEAREST
+        };
+
+        unsafe {
+            self.gl
+                .bind_framebuffer(glow::READ_FRAMEBUFFER, Some(self.fbo));
+            self.gl
+                .bind_framebuffer(glow::DRAW_FRAMEBUFFER, dst.map(|fbo| fbo.fbo));
+            self.gl.blit_framebuffer(
+                0,
+                0,
+                self.width as i32,
+                self.height as i32,
+                0,
+                0,
+                dst_width as i32,
+                dst_height as i32,
+                mask,
+                filter,
+            );
+            self.gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+            self.gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, None);
+        }
+    }
+
+    // resolves the multisampled color attachment 0 into a regular texture
+    pub fn resolve_to_texture(&self, texture: &Texture2D) -> Result<(), String> {
+        if texture.get_width() != self.width || texture.get_height() != self.height {
+            return Err(format!(
+                "Resolve target is {}x{} but the framebuffer is {}x{}",
+                texture.get_width(),
+                texture.get_height(),
+                self.width,
+                self.height
+            ));
+        }
+
+        let resolve = FBO::new(self.gl.clone(), self.width, self.height)?;
+        resolve.attach_texture(glow::COLOR_ATTACHMENT0, texture)?;
+        resolve.check_status()?;
+
+        unsafe {
+            self.gl
+                .bind_framebuffer(glow::READ_FRAMEBUFFER, Some(self.fbo));
+            self.gl.read_buffer(glow::COLOR_ATTACHMENT0);
+        }
+        self.blit(
+            Some(&resolve),
+            self.width,
+            self.height,
+            glow::COLOR_BUFFER_BIT,
+        );
+        Ok(())
+    }
+}
+
+impl Drop for FBO {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_framebuffer(self.fbo);
+        }
+    }
+}
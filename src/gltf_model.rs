@@ -0,0 +1,106 @@
+use std::{path::Path, rc::Rc};
+
+use crate::gpu_mesh::GpuMesh;
+use crate::image::{Image, ImageLoadOptions};
+use crate::sampler::Sampler;
+use crate::texture::TextureTrait;
+use paxil_mesh::{load_gltf, GltfScene};
+
+// a glTF scene with its images, samplers and primitives uploaded to the GPU
+pub struct GltfModel {
+    pub scene: GltfScene,
+    pub images: Vec<Image>,
+    // one per scene texture, bind it to the unit of the texture's image
+    pub samplers: Vec<Sampler>,
+    // one list per scene mesh, one entry per primitive, skinned primitives get joints and
+    // weights, posed with scene.get_joint_matrices
+    pub meshes: Vec<Vec<GpuMesh>>,
+}
+
+fn needs_mipmaps(min_filter: Option<u32>) -> bool {
+    matches!(
+        min_filter,
+        None | Some(glow::NEAREST_MIPMAP_NEAREST)
+            | Some(glow::LINEAR_MIPMAP_NEAREST)
+            | Some(glow::NEAREST_MIPMAP_LINEAR)
+            | Some(glow::LINEAR_MIPMAP_LINEAR)
+    )
+}
+
+impl GltfModel {
+    pub fn load<P: AsRef<Path>>(gl: Rc<glow::Context>, path: P) -> Result<Self, String> {
+        Self::from_scene(gl, load_gltf(path)?)
+    }
+
+    pub fn from_scene(gl: Rc<glow::Context>, scene: GltfScene) -> Result<Self, String> {
+        // base colour and emissive maps are sRGB encoded, every other map holds linear data
+        let mut srgb = vec![false; scene.images.len()];
+        for material in &scene.materials {
+            for texture in [&material.base_color_texture, &material.emissive_texture]
+                .into_iter()
+                .flatten()
+            {
+                if let Some(t) = scene.textures.get(texture.texture) {
+                    srgb[t.image] = true;
+                }
+            }
+        }
+
+        // glTF uv origin is the top left, so rows are uploaded as stored
+        let mut images = Vec::with_capacity(scene.images.len());
+        for (i, image) in scene.images.iter().enumerate() {
+            let options = ImageLoadOptions {
+                srgb: srgb[i],
+                ..Default::default()
+            };
+            let image = Image::load_from_memory_with_options(gl.clone(), &image.data, &options)
+                .map_err(|e| format!("Image {}: {}", i, e))?;
+            images.push(image);
+        }
+
+        let mut samplers = Vec::with_capacity(scene.textures.len());
+        for texture in &scene.textures {
+            let sampler = Sampler::new(gl.clone())?;
+            let min_filter = texture.sampler.min_filter;
+            sampler.set_min_filter(min_filter.unwrap_or(glow::LINEAR_MIPMAP_LINEAR));
+            sampler.set_mag_filter(texture.sampler.mag_filter.unwrap_or(glow::LINEAR));
+            sampler.set_wrap_s(texture.sampler.wrap_s);
+            sampler.set_wrap_t(texture.sampler.wrap_t);
+            if needs_mipmaps(min_filter) {
+                if let Some(image) = images.get(texture.image) {
+                    image.texture.generate_mipmaps();
+                }
+            }
+            samplers.push(sampler);
+        }
+
+        let mut meshes = Vec::with_capacity(scene.meshes.len());
+        for mesh in &scene.meshes {
+            let primitives = mesh
+                .primitives
+                .iter()
+                .map(|p| {
+                    let mut gpu_mesh = GpuMesh::new(gl.clone(), &p.mesh)?;
+                    gpu_mesh.set_uvs1(&p.uvs1)?;
+                    gpu_mesh.set_skin(&p.joints, &p.weights)?;
+                    Ok(gpu_mesh)
+                })
+                .collect::<Result<Vec<_>, String>>()
+                .map_err(|e| format!("Mesh '{}': {}", mesh.name, e))?;
+            meshes.push(primitives);
+        }
+
+        Ok(Self {
+            scene,
+            images,
+            samplers,
+            meshes,
+        })
+    }
+
+    // image and sampler of a scene texture, ready to bind
+    pub fn get_texture(&self, texture: usize) -> Option<(&Image, &Sampler)> {
+        let image = self.images.get(self.scene.textures.get(texture)?.image)?;
+        Some((image, self.samplers.get(texture)?))
+    }
+}
@@ -0,0 +1,220 @@
+use glow::HasContext;
+use std::rc::Rc;
+
+use crate::gpu_mesh::{ATTRIB_COUNT, ATTRIB_DEFAULTS, ATTRIB_NORMAL, ATTRIB_POSITION};
+use crate::shader::Shader;
+use crate::texture::{Texture3D, TextureTrait};
+use crate::vao::VAO;
+use crate::vbo::VBO;
+use paxil_math::*;
+
+// interleaved vec4 position and vec4 normal
+const VERTEX_SIZE: usize = 8 * std::mem::size_of::<f32>();
+const COMMAND_SIZE: usize = 5 * std::mem::size_of::<u32>();
+const GROUP_SIZE: usize = 4;
+
+// marching cubes on the GPU, the triangles never leave video memory and are drawn indirectly
+// the volume is a single channel float Texture3D, values below the iso level are inside
+pub struct GpuIsosurface {
+    gl: Rc<glow::Context>,
+    shader: Shader,
+    vao: VAO,
+    vertices: VBO,
+    command: glow::Buffer,
+    table: glow::Buffer,
+    max_triangles: usize,
+}
+
+impl GpuIsosurface {
+    // needs a GL 4.3 context, triangles beyond max_triangles are dropped
+    pub fn new(gl: Rc<glow::Context>, max_triangles: usize) -> Result<Self, String> {
+        // the shader is GLSL 4.30 with storage buffers, which the compute extension alone does not give
+        let version = gl.version();
+        if version.is_embedded || (version.major, version.minor) < (4, 3) {
+            return Err(format!(
+                "Compute shaders need GL 4.3 for GPU isosurfaces, the context is {}{}.{}",
+                if version.is_embedded { "GLES " } else { "GL " },
+                version.major,
+                version.minor
+            ));
+        }
+        let shader = Shader::new_compute(gl.clone(), include_str!("shader/marching_cubes.comp"))?;
+        let table: Vec<i32> = paxil_mesh::get_marching_cubes_table()
+            .iter()
+            .flatten()
+            .map(|e| *e as i32)
+            .collect();
+
+        let vao = VAO::new(gl.clone());
+        let vertices = VBO::new(
+            gl.clone(),
+            &vec![0; max_triangles.max(1) * 3 * VERTEX_SIZE],
+            glow::DYNAMIC_COPY,
+        )?;
+        unsafe {
+            for (location, offset) in [(ATTRIB_POSITION, 0), (ATTRIB_NORMAL, 16)] {
+                gl.enable_vertex_attrib_array(location);
+                gl.vertex_attrib_pointer_f32(
+                    location,
+                    3,
+                    glow::FLOAT,
+                    false,
+                    VERTEX_SIZE as i32,
+                    offset,
+                );
+            }
+            vao.unbind();
+            vertices.unbind();
+
+            let command = gl.create_buffer()?;
+            gl.bind_buffer(glow::DRAW_INDIRECT_BUFFER, Some(command));
+            gl.buffer_data_size(
+                glow::DRAW_INDIRECT_BUFFER,
+                COMMAND_SIZE as i32,
+                glow::DYNAMIC_COPY,
+            );
+            gl.bind_buffer(glow::DRAW_INDIRECT_BUFFER, None);
+
+            let table_buffer = gl.create_buffer()?;
+            gl.bind_buffer(glow::SHADER_STORAGE_BUFFER, Some(table_buffer));
+            gl.buffer_data_u8_slice(
+                glow::SHADER_STORAGE_BUFFER,
+                bytemuck::cast_slice(&table),
+                glow::STATIC_DRAW,
+            );
+            gl.bind_buffer(glow::SHADER_STORAGE_BUFFER, None);
+
+            let mut isosurface = Self {
+                gl,
+                shader,
+                vao,
+                vertices,
+                command,
+                table: table_buffer,
+                max_triangles,
+            };
+            isosurface.clear();
+            Ok(isosurface)
+        }
+    }
+
+    pub fn clear(&mut self) {
+        unsafe {
+            self.gl
+                .bind_buffer(glow::DRAW_INDIRECT_BUFFER, Some(self.command));
+            self.gl.buffer_sub_data_u8_slice(
+                glow::DRAW_INDIRECT_BUFFER,
+                0,
+                bytemuck::cast_slice(&[0u32, 1, 0, 0, 0]),
+            );
+            self.gl.bind_buffer(glow::DRAW_INDIRECT_BUFFER, None);
+        }
+    }
+
+    // replaces the surface with the one extracted from the volume, which spans min..max
+    pub fn extract(
+        &mut self,
+        volume: &Texture3D,
+        min: Vec3,
+        max: Vec3,
+        iso: f32,
+    ) -> Result<(), String> {
+        let dims = [volume.get_width(), volume.get_height(), volume.get_depth()];
+        if dims.iter().any(|d| *d < 2) {
+            return Err(format!(
+                "Volume needs at least 2 samples per axis, got {:?}",
+                dims
+            ));
+        }
+        self.clear();
+
+        self.shader.bind();
+        self.shader.set_uniform_1i("u_volume", 0)?;
+        self.shader.set_uniform("u_min", &min)?;
+        self.shader.set_uniform("u_max", &max)?;
+        self.shader.set_uniform("u_iso", &iso)?;
+        self.shader
+            .set_uniform("u_capacity", &((self.max_triangles * 3) as u32))?;
+        unsafe {
+            self.gl.active_texture(glow::TEXTURE0);
+            self.gl
+                .bind_texture(glow::TEXTURE_3D, Some(volume.get_id()));
+            self.gl.bind_sampler(0, None);
+            self.gl
+                .bind_buffer_base(glow::SHADER_STORAGE_BUFFER, 0, Some(self.vertices.get_id()));
+            self.gl
+                .bind_buffer_base(glow::SHADER_STORAGE_BUFFER, 1, Some(self.command));
+            self.gl
+                .bind_buffer_base(glow::SHADER_STORAGE_BUFFER, 2, Some(self.table));
+        }
+
+        let [x, y, z] = dims.map(|d| (d - 1).div_ceil(GROUP_SIZE) as u32);
+        self.shader.dispatch(x, y, z);
+
+        unsafe {
+            self.gl.memory_barrier(
+                glow::VERTEX_ATTRIB_ARRAY_BARRIER_BIT
+                    | glow::COMMAND_BARRIER_BIT
+                    | glow::BUFFER_UPDATE_BARRIER_BIT,
+            );
+            for binding in 0..3 {
+                self.gl
+                    .bind_buffer_base(glow::SHADER_STORAGE_BUFFER, binding, None);
+            }
+            self.gl.bind_texture(glow::TEXTURE_3D, None);
+        }
+        self.shader.unbind();
+        Ok(())
+    }
+
+    pub fn draw(&self, shader: &Shader) {
+        shader.bind();
+        self.vao.bind();
+        unsafe {
+            for location in 0..ATTRIB_COUNT as u32 {
+                if location != ATTRIB_POSITION && location != ATTRIB_NORMAL {
+                    let [x, y, z, w] = ATTRIB_DEFAULTS[location as usize];
+                    self.gl.vertex_attrib_4_f32(location, x, y, z, w);
+                }
+            }
+            self.gl
+                .bind_buffer(glow::DRAW_INDIRECT_BUFFER, Some(self.command));
+            self.gl.draw_arrays_indirect_offset(glow::TRIANGLES, 0);
+            self.gl.bind_buffer(glow::DRAW_INDIRECT_BUFFER, None);
+        }
+        self.vao.unbind();
+    }
+
+    // reads back from the GPU, so this stalls until the last extraction finished
+    // the count can exceed get_max_triangles, in which case the surface was cut short
+    pub fn get_triangle_count(&self) -> usize {
+        let mut data = [0u8; COMMAND_SIZE];
+        unsafe {
+            self.gl
+                .bind_buffer(glow::DRAW_INDIRECT_BUFFER, Some(self.command));
+            self.gl
+                .get_buffer_sub_data(glow::DRAW_INDIRECT_BUFFER, 0, &mut data);
+            self.gl.bind_buffer(glow::DRAW_INDIRECT_BUFFER, None);
+        }
+        let command: [u32; 5] = bytemuck::cast(data);
+        command[4] as usize / 3
+    }
+
+    pub fn get_max_triangles(&self) -> usize {
+        self.max_triangles
+    }
+
+    // the raw vertices, 8 floats each, for use as a storage buffer elsewhere
+    pub fn get_vertex_buffer(&self) -> &VBO {
+        &self.vertices
+    }
+}
+
+impl Drop for GpuIsosurface {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_buffer(self.command);
+            self.gl.delete_buffer(self.table);
+        }
+    }
+}
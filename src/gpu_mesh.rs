@@ -0,0 +1,261 @@
+use glow::HasContext;
+use std::rc::Rc;
+
+use crate::ibo::IBO;
+use crate::shader::Shader;
+use crate::vao::VAO;
+use crate::vbo::VBO;
+use paxil_mesh::{Mesh, PrimitiveMode};
+
+// fixed attribute locations, declare them with layout(location = N) in the vertex shader
+pub const ATTRIB_POSITION: u32 = 0;
+pub const ATTRIB_NORMAL: u32 = 1;
+pub const ATTRIB_UV: u32 = 2;
+pub const ATTRIB_COLOR: u32 = 3;
+pub const ATTRIB_TANGENT: u32 = 4;
+// second uv set and skinning, set with set_uvs1 and set_skin
+pub const ATTRIB_UV1: u32 = 5;
+// joint indices are uploaded as floats, convert them with ivec4 in the shader
+pub const ATTRIB_JOINTS: u32 = 6;
+pub const ATTRIB_WEIGHTS: u32 = 7;
+
+pub(crate) const ATTRIB_COUNT: usize = 8;
+// attributes that come from the Mesh itself
+const MESH_ATTRIB_COUNT: usize = 5;
+const ATTRIB_SIZES: [i32; ATTRIB_COUNT] = [3, 3, 2, 4, 4, 2, 4, 4];
+// values the shader sees for attributes the mesh does not have
+pub(crate) const ATTRIB_DEFAULTS: [[f32; 4]; ATTRIB_COUNT] = [
+    [0.0, 0.0, 0.0, 1.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 0.0],
+    [1.0, 1.0, 1.0, 1.0],
+    [1.0, 0.0, 0.0, 1.0],
+    [0.0, 0.0, 0.0, 0.0],
+    [0.0, 0.0, 0.0, 0.0],
+    [0.0, 0.0, 0.0, 0.0],
+];
+
+pub fn get_gl_primitive_mode(mode: PrimitiveMode) -> u32 {
+    match mode {
+        PrimitiveMode::Points => glow::POINTS,
+        PrimitiveMode::Lines => glow::LINES,
+        PrimitiveMode::LineStrip => glow::LINE_STRIP,
+        PrimitiveMode::LineLoop => glow::LINE_LOOP,
+        PrimitiveMode::Triangles => glow::TRIANGLES,
+        PrimitiveMode::TriangleStrip => glow::TRIANGLE_STRIP,
+        PrimitiveMode::TriangleFan => glow::TRIANGLE_FAN,
+    }
+}
+
+pub struct GpuMesh {
+    gl: Rc<glow::Context>,
+    vao: VAO,
+    buffers: [Option<VBO>; ATTRIB_COUNT],
+    ibo: Option<IBO>,
+    vertex_count: usize,
+    mode: PrimitiveMode,
+    usage: u32,
+}
+
+impl GpuMesh {
+    pub fn new(gl: Rc<glow::Context>, mesh: &Mesh) -> Result<Self, String> {
+        Self::with_usage(gl, mesh, glow::STATIC_DRAW)
+    }
+
+    // for meshes that are updated every frame
+    pub fn new_dynamic(gl: Rc<glow::Context>, mesh: &Mesh) -> Result<Self, String> {
+        Self::with_usage(gl, mesh, glow::DYNAMIC_DRAW)
+    }
+
+    pub fn with_usage(gl: Rc<glow::Context>, mesh: &Mesh, usage: u32) -> Result<Self, String> {
+        let vao = VAO::new(gl.clone());
+        let mut gpu_mesh = Self {
+            gl,
+            vao,
+            buffers: Default::default(),
+            ibo: None,
+            vertex_count: 0,
+            mode: mesh.mode,
+            usage,
+        };
+        let result = gpu_mesh.update(mesh);
+        gpu_mesh.vao.unbind();
+        result.map(|_| gpu_mesh)
+    }
+
+    // re-uploads every attribute, buffers are reused while the data fits
+    pub fn update(&mut self, mesh: &Mesh) -> Result<(), String> {
+        mesh.validate()?;
+        self.vao.bind();
+
+        let data: [Vec<f32>; MESH_ATTRIB_COUNT] = [
+            mesh.positions.iter().flat_map(|v| v.to_array()).collect(),
+            mesh.normals.iter().flat_map(|v| v.to_array()).collect(),
+            mesh.uvs.iter().flat_map(|v| v.to_array()).collect(),
+            mesh.colors.iter().flat_map(|c| c.to_array()).collect(),
+            mesh.tangents.iter().flat_map(|v| v.to_array()).collect(),
+        ];
+        for (location, data) in data.iter().enumerate() {
+            self.set_attribute(location, data)?;
+        }
+        // extra attributes no longer match a mesh with a different vertex count
+        if mesh.get_vertex_count() != self.vertex_count {
+            for location in MESH_ATTRIB_COUNT..ATTRIB_COUNT {
+                self.set_attribute(location, &[])?;
+            }
+        }
+
+        if mesh.is_indexed() {
+            match &mut self.ibo {
+                Some(ibo) => ibo.set_data(&mesh.indices),
+                None => self.ibo = Some(IBO::new(self.gl.clone(), &mesh.indices, self.usage)?),
+            }
+        } else if let Some(ibo) = self.ibo.take() {
+            ibo.unbind();
+        }
+
+        self.vertex_count = mesh.get_vertex_count();
+        self.mode = mesh.mode;
+        self.vao.unbind();
+        Ok(())
+    }
+
+    // cheaper than update when only the positions move
+    pub fn update_positions(&mut self, positions: &[paxil_math::Vec3]) -> Result<(), String> {
+        if positions.len() != self.vertex_count {
+            return Err(format!(
+                "Expected {} positions, got {}",
+                self.vertex_count,
+                positions.len()
+            ));
+        }
+        let data: Vec<f32> = positions.iter().flat_map(|v| v.to_array()).collect();
+        self.vao.bind();
+        let result = self.set_attribute(ATTRIB_POSITION as usize, &data);
+        self.vao.unbind();
+        result
+    }
+
+    // empty clears the second uv set
+    pub fn set_uvs1(&mut self, uvs: &[paxil_math::Vec2]) -> Result<(), String> {
+        self.check_vertex_count("uvs", uvs.len())?;
+        let data: Vec<f32> = uvs.iter().flat_map(|v| v.to_array()).collect();
+        self.vao.bind();
+        let result = self.set_attribute(ATTRIB_UV1 as usize, &data);
+        self.vao.unbind();
+        result
+    }
+
+    // four joints and weights per vertex, empty slices clear the skin
+    pub fn set_skin(
+        &mut self,
+        joints: &[[u16; 4]],
+        weights: &[paxil_math::Vec4],
+    ) -> Result<(), String> {
+        self.check_vertex_count("joints", joints.len())?;
+        self.check_vertex_count("weights", weights.len())?;
+        let joints: Vec<f32> = joints.iter().flatten().map(|j| *j as f32).collect();
+        let weights: Vec<f32> = weights.iter().flat_map(|v| v.to_array()).collect();
+        self.vao.bind();
+        let result = self
+            .set_attribute(ATTRIB_JOINTS as usize, &joints)
+            .and_then(|_| self.set_attribute(ATTRIB_WEIGHTS as usize, &weights));
+        self.vao.unbind();
+        result
+    }
+
+    fn check_vertex_count(&self, name: &str, count: usize) -> Result<(), String> {
+        if count != 0 && count != self.vertex_count {
+            return Err(format!(
+                "Expected {} {}, got {}",
+                self.vertex_count, name, count
+            ));
+        }
+        Ok(())
+    }
+
+    fn set_attribute(&mut self, location: usize, data: &[f32]) -> Result<(), String> {
+        let bytes: &[u8] = bytemuck::cast_slice(data);
+        unsafe {
+            if data.is_empty() {
+                self.buffers[location] = None;
+                self.gl.disable_vertex_attrib_array(location as u32);
+                let [x, y, z, w] = ATTRIB_DEFAULTS[location];
+                self.gl.vertex_attrib_4_f32(location as u32, x, y, z, w);
+                return Ok(());
+            }
+            match &mut self.buffers[location] {
+                Some(vbo) => vbo.set_data(bytes),
+                None => {
+                    self.buffers[location] = Some(VBO::new(self.gl.clone(), bytes, self.usage)?)
+                }
+            }
+            let size = ATTRIB_SIZES[location];
+            self.gl.enable_vertex_attrib_array(location as u32);
+            self.gl.vertex_attrib_pointer_f32(
+                location as u32,
+                size,
+                glow::FLOAT,
+                false,
+                size * std::mem::size_of::<f32>() as i32,
+                0,
+            );
+        }
+        Ok(())
+    }
+
+    pub fn draw(&self, shader: &Shader) {
+        self.draw_instanced(shader, 1);
+    }
+
+    pub fn draw_instanced(&self, shader: &Shader, instances: usize) {
+        let mode = get_gl_primitive_mode(self.mode);
+        shader.bind();
+        self.vao.bind();
+        unsafe {
+            // constant attribute values are not part of the VAO state
+            for (location, buffer) in self.buffers.iter().enumerate() {
+                if buffer.is_none() {
+                    let [x, y, z, w] = ATTRIB_DEFAULTS[location];
+                    self.gl.vertex_attrib_4_f32(location as u32, x, y, z, w);
+                }
+            }
+            match &self.ibo {
+                Some(ibo) => self.gl.draw_elements_instanced(
+                    mode,
+                    ibo.get_count() as i32,
+                    glow::UNSIGNED_INT,
+                    0,
+                    instances as i32,
+                ),
+                None => self.gl.draw_arrays_instanced(
+                    mode,
+                    0,
+                    self.vertex_count as i32,
+                    instances as i32,
+                ),
+            }
+        }
+        self.vao.unbind();
+    }
+
+    pub fn get_vertex_count(&self) -> usize {
+        self.vertex_count
+    }
+
+    pub fn get_index_count(&self) -> usize {
+        self.ibo.as_ref().map_or(0, |ibo| ibo.get_count())
+    }
+
+    pub fn get_mode(&self) -> PrimitiveMode {
+        self.mode
+    }
+
+    pub fn is_indexed(&self) -> bool {
+        self.ibo.is_some()
+    }
+
+    pub fn get_vao(&self) -> &VAO {
+        &self.vao
+    }
+}
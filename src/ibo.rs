@@ -0,0 +1,84 @@
+use glow::HasContext;
+use std::rc::Rc;
+
+// 32 bit indices, the element array binding is part of the VAO state so bind one first
+pub struct IBO {
+    gl: Rc<glow::Context>,
+    ibo: glow::Buffer,
+    count: usize,
+    capacity: usize,
+    usage: u32,
+}
+
+impl IBO {
+    pub fn new(gl: Rc<glow::Context>, indices: &[u32], usage: u32) -> Result<Self, String> {
+        unsafe {
+            let ibo = gl.create_buffer()?;
+            gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(ibo));
+            gl.buffer_data_u8_slice(
+                glow::ELEMENT_ARRAY_BUFFER,
+                bytemuck::cast_slice(indices),
+                usage,
+            );
+            Ok(Self {
+                gl,
+                ibo,
+                count: indices.len(),
+                capacity: indices.len(),
+                usage,
+            })
+        }
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            self.gl
+                .bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(self.ibo));
+        }
+    }
+
+    pub fn unbind(&self) {
+        unsafe {
+            self.gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, None);
+        }
+    }
+
+    // reallocates only when the indices no longer fit
+    pub fn set_data(&mut self, indices: &[u32]) {
+        unsafe {
+            self.gl
+                .bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(self.ibo));
+            if indices.len() > self.capacity {
+                self.gl.buffer_data_u8_slice(
+                    glow::ELEMENT_ARRAY_BUFFER,
+                    bytemuck::cast_slice(indices),
+                    self.usage,
+                );
+                self.capacity = indices.len();
+            } else {
+                self.gl.buffer_sub_data_u8_slice(
+                    glow::ELEMENT_ARRAY_BUFFER,
+                    0,
+                    bytemuck::cast_slice(indices),
+                );
+            }
+        }
+        self.count = indices.len();
+    }
+
+    pub fn get_id(&self) -> glow::Buffer {
+        self.ibo
+    }
+
+    pub fn get_count(&self) -> usize {
+        self.count
+    }
+}
+
+impl Drop for IBO {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_buffer(self.ibo);
+        }
+    }
+}
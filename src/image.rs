@@ -1,52 +1,526 @@
-use image::GenericImageView;
+use half::f16;
+use image::{DynamicImage, GenericImageView};
 use std::{path::Path, rc::Rc};
 
 use crate::texture;
 
-use super::texture::Texture2D;
+use super::texture::{Texture2D, TextureTrait};
+
+pub enum PixelData {
+    U8(Vec<u8>),
+    U16(Vec<u16>),
+    F16(Vec<f16>),
+    F32(Vec<f32>),
+}
+
+impl PixelData {
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            PixelData::U8(data) => data,
+            PixelData::U16(data) => bytemuck::cast_slice(data),
+            PixelData::F16(data) => bytemuck::cast_slice(data),
+            PixelData::F32(data) => bytemuck::cast_slice(data),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            PixelData::U8(data) => data.len(),
+            PixelData::U16(data) => data.len(),
+            PixelData::F16(data) => data.len(),
+            PixelData::F32(data) => data.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get_texture_type(&self) -> u32 {
+        match self {
+            PixelData::U8(_) => glow::UNSIGNED_BYTE,
+            PixelData::U16(_) => glow::UNSIGNED_SHORT,
+            PixelData::F16(_) => glow::HALF_FLOAT,
+            PixelData::F32(_) => glow::FLOAT,
+        }
+    }
+
+    pub fn get_f32(&self, index: usize) -> f32 {
+        match self {
+            PixelData::U8(data) => data[index] as f32 / 255.0,
+            PixelData::U16(data) => data[index] as f32 / 65535.0,
+            PixelData::F16(data) => data[index].to_f32(),
+            PixelData::F32(data) => data[index],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageConversion {
+    Rgba8,
+    Rgba16F,
+    Rgba32F,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ImageLoadOptions {
+    // flip rows so the first row ends up at the bottom, matching GL's origin
+    pub flip_y: bool,
+    // treat 8-bit colour as sRGB encoded, float conversions are linearised instead
+    pub srgb: bool,
+    pub convert_to: Option<ImageConversion>,
+    pub premultiply_alpha: bool,
+}
+
 pub struct Image {
     pub texture: texture::Texture2D,
-    pub data: Vec<u8>,
+    pub pixels: PixelData,
+    pub width: usize,
+    pub height: usize,
+    pub channels: usize,
 }
 
 impl Image {
     pub fn load<P: AsRef<Path>>(gl: Rc<glow::Context>, path: P) -> Result<Self, String> {
+        Self::load_with_options(gl, path, &ImageLoadOptions::default())
+    }
+
+    pub fn load_with_options<P: AsRef<Path>>(
+        gl: Rc<glow::Context>,
+        path: P,
+        options: &ImageLoadOptions,
+    ) -> Result<Self, String> {
+        let img = image::open(path).map_err(|e| e.to_string())?;
+        Self::from_dynamic_image(gl, img, options)
+    }
+
+    // decodes an encoded file held in memory, the format is guessed from its contents
+    pub fn load_from_memory_with_options(
+        gl: Rc<glow::Context>,
+        bytes: &[u8],
+        options: &ImageLoadOptions,
+    ) -> Result<Self, String> {
+        let img = image::load_from_memory(bytes).map_err(|e| e.to_string())?;
+        Self::from_dynamic_image(gl, img, options)
+    }
+
+    fn from_dynamic_image(
+        gl: Rc<glow::Context>,
+        mut img: DynamicImage,
+        options: &ImageLoadOptions,
+    ) -> Result<Self, String> {
+        if options.flip_y {
+            img = img.flipv();
+        }
+        let (width, height) = img.dimensions();
+        let (format, mut pixels) = match options.convert_to {
+            Some(conversion) => convert_image(&img, conversion, options.srgb),
+            None => get_native_image(img, options.srgb),
+        };
+        if options.premultiply_alpha {
+            let channels = get_channel_count(pixels.len(), width as usize, height as usize)?;
+            premultiply_alpha(&mut pixels, channels, format == glow::SRGB8_ALPHA8);
+        }
+
+        Self::from_pixels(gl, width as usize, height as usize, format, pixels)
+    }
+
+    // Radiance .hdr files are stored as RGB16F
+    pub fn load_hdr<P: AsRef<Path>>(gl: Rc<glow::Context>, path: P) -> Result<Self, String> {
         let img = image::open(path).map_err(|e| e.to_string())?;
         let (width, height) = img.dimensions();
-        let data = img.as_bytes();
-
-        let format = match img {
-            image::DynamicImage::ImageLuma8(_) => glow::R8,
-            image::DynamicImage::ImageLumaA8(_) => glow::RG8,
-            image::DynamicImage::ImageRgb8(_) => glow::RGB8,
-            image::DynamicImage::ImageRgba8(_) => glow::RGBA8,
-            image::DynamicImage::ImageLuma16(_) => glow::R16,
-            image::DynamicImage::ImageLumaA16(_) => glow::R16,
-            image::DynamicImage::ImageRgb16(_) => glow::RGB16,
-            image::DynamicImage::ImageRgba16(_) => glow::RGBA16,
-            image::DynamicImage::ImageRgb32F(_) => glow::RGB32F,
-            image::DynamicImage::ImageRgba32F(_) => glow::RGBA32F,
-            _ => return Err("Unsupported image format".to_string()),
+        let pixels = PixelData::F32(img.to_rgb32f().into_raw());
+        Self::from_pixels(gl, width as usize, height as usize, glow::RGB16F, pixels)
+    }
+
+    // layer of None picks the first layer, see get_exr_layers for the available names
+    pub fn load_exr<P: AsRef<Path>>(
+        gl: Rc<glow::Context>,
+        path: P,
+        layer: Option<&str>,
+    ) -> Result<Self, String> {
+        let layers = read_exr_layers(path)?;
+        let exr_layer = match layer {
+            Some(name) => layers
+                .into_iter()
+                .find(|l| l.name == name)
+                .ok_or_else(|| format!("EXR layer '{}' not found", name))?,
+            None => layers
+                .into_iter()
+                .next()
+                .ok_or_else(|| "EXR file has no layers".to_string())?,
         };
 
-        let texture = Texture2D::new(
+        Self::from_pixels(
             gl,
-            width as usize,
-            height as usize,
-            format,
-            None,
-            None,
-            Some(data),
+            exr_layer.width,
+            exr_layer.height,
+            glow::RGBA32F,
+            PixelData::F32(exr_layer.pixels),
         )
-        .map_err(|e| e.to_string())?;
+    }
 
-        //
-        texture.load_data(data, 0, 0, width as usize, height as usize);
-        //
+    pub fn get_exr_layers<P: AsRef<Path>>(path: P) -> Result<Vec<String>, String> {
+        Ok(read_exr_layers(path)?.into_iter().map(|l| l.name).collect())
+    }
+
+    pub fn from_pixels(
+        gl: Rc<glow::Context>,
+        width: usize,
+        height: usize,
+        internal_format: u32,
+        pixels: PixelData,
+    ) -> Result<Self, String> {
+        let channels = get_channel_count(pixels.len(), width, height)?;
+        let format = match channels {
+            1 => glow::RED,
+            2 => glow::RG,
+            3 => glow::RGB,
+            _ => glow::RGBA,
+        };
+
+        let texture = Texture2D::new(
+            gl,
+            width,
+            height,
+            internal_format,
+            Some(format),
+            Some(pixels.get_texture_type()),
+            Some(pixels.as_bytes()),
+        )?;
 
         Ok(Self {
             texture,
-            data: data.to_vec(),
+            pixels,
+            width,
+            height,
+            channels,
         })
     }
+
+    pub fn save_exr<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        write_exr(path, self.width, self.height, self.channels, &self.pixels)
+    }
+}
+
+// the pixel data has to hold exactly width * height pixels of 1 to 4 channels
+fn get_channel_count(len: usize, width: usize, height: usize) -> Result<usize, String> {
+    let pixel_count = width * height;
+    match len.checked_div(pixel_count) {
+        Some(channels @ 1..=4) if channels * pixel_count == len => Ok(channels),
+        _ => Err(format!(
+            "Pixel data of {} values does not match a {}x{} image",
+            len, width, height
+        )),
+    }
+}
+
+// one channel is grey, two are grey and alpha
+fn write_exr<P: AsRef<Path>>(
+    path: P,
+    width: usize,
+    height: usize,
+    channels: usize,
+    pixels: &PixelData,
+) -> Result<(), String> {
+    exr::prelude::write_rgba_file(path, width, height, |x, y| {
+        let i = (y * width + x) * channels;
+        let get = |c: usize| pixels.get_f32(i + c);
+        match channels {
+            1 => (get(0), get(0), get(0), 1.0),
+            2 => (get(0), get(0), get(0), get(1)),
+            3 => (get(0), get(1), get(2), 1.0),
+            _ => (get(0), get(1), get(2), get(3)),
+        }
+    })
+    .map_err(|e| e.to_string())
+}
+
+// reads the texture back from the GPU as RGBA32F and writes it out
+pub fn save_texture_exr<T: TextureTrait, P: AsRef<Path>>(
+    texture: &T,
+    path: P,
+) -> Result<(), String> {
+    let width = texture.get_width();
+    let height = texture.get_height();
+    let mut pixels = vec![0.0f32; width * height * 4];
+    texture.read_data(
+        0,
+        glow::RGBA,
+        glow::FLOAT,
+        bytemuck::cast_slice_mut(&mut pixels),
+    )?;
+
+    exr::prelude::write_rgba_file(path, width, height, |x, y| {
+        let i = (y * width + x) * 4;
+        (pixels[i], pixels[i + 1], pixels[i + 2], pixels[i + 3])
+    })
+    .map_err(|e| e.to_string())
+}
+
+struct ExrLayer {
+    name: String,
+    width: usize,
+    height: usize,
+    pixels: Vec<f32>,
+}
+
+// channels like "diffuse.R" are grouped into a "diffuse" layer, unprefixed ones into the part name
+fn read_exr_layers<P: AsRef<Path>>(path: P) -> Result<Vec<ExrLayer>, String> {
+    let image = exr::prelude::read_all_flat_layers_from_file(path).map_err(|e| e.to_string())?;
+
+    let mut layers = Vec::new();
+    for layer in image.layer_data.iter() {
+        let part_name = layer.attributes.layer_name.as_ref().map(|n| n.to_string());
+        let (width, height) = (layer.size.x(), layer.size.y());
+
+        let mut groups: Vec<(String, Vec<(String, &exr::prelude::FlatSamples)>)> = Vec::new();
+        for channel in layer.channel_data.list.iter() {
+            let full_name = channel.name.to_string();
+            let (prefix, suffix) = match full_name.rsplit_once('.') {
+                Some((prefix, suffix)) => (prefix.to_string(), suffix.to_string()),
+                None => (String::new(), full_name.clone()),
+            };
+            let name = match (&part_name, prefix.is_empty()) {
+                (Some(part), true) => part.clone(),
+                (Some(part), false) => format!("{}.{}", part, prefix),
+                (None, _) => prefix,
+            };
+
+            match groups.iter_mut().find(|(n, _)| *n == name) {
+                Some((_, channels)) => channels.push((suffix, &channel.sample_data)),
+                None => groups.push((name, vec![(suffix, &channel.sample_data)])),
+            }
+        }
+
+        for (name, channels) in groups {
+            let find = |names: &[&str]| {
+                channels
+                    .iter()
+                    .find(|(suffix, _)| names.contains(&suffix.as_str()))
+                    .map(|(_, samples)| *samples)
+            };
+            let luma = find(&["Y", "y"]);
+            let sources = [
+                find(&["R", "r", "red"])
+                    .or(luma)
+                    .or(channels.first().map(|c| c.1)),
+                find(&["G", "g", "green"])
+                    .or(luma)
+                    .or(channels.get(1).map(|c| c.1)),
+                find(&["B", "b", "blue"])
+                    .or(luma)
+                    .or(channels.get(2).map(|c| c.1)),
+                find(&["A", "a", "alpha"]),
+            ];
+
+            let mut pixels = vec![0.0; width * height * 4];
+            for (i, pixel) in pixels.chunks_exact_mut(4).enumerate() {
+                for (c, source) in sources.iter().enumerate() {
+                    pixel[c] = match source {
+                        Some(samples) => samples.value_by_flat_index(i).to_f32(),
+                        None if c == 3 => 1.0,
+                        None => 0.0,
+                    };
+                }
+            }
+
+            layers.push(ExrLayer {
+                name,
+                width,
+                height,
+                pixels,
+            });
+        }
+    }
+    Ok(layers)
+}
+
+fn get_native_image(img: DynamicImage, srgb: bool) -> (u32, PixelData) {
+    use PixelData::*;
+    match img {
+        DynamicImage::ImageLuma8(_) if srgb => (glow::SRGB8, U8(img.to_rgb8().into_raw())),
+        DynamicImage::ImageLumaA8(_) if srgb => (glow::SRGB8_ALPHA8, U8(img.to_rgba8().into_raw())),
+        DynamicImage::ImageLuma8(buf) => (glow::R8, U8(buf.into_raw())),
+        DynamicImage::ImageLumaA8(buf) => (glow::RG8, U8(buf.into_raw())),
+        DynamicImage::ImageRgb8(buf) if srgb => (glow::SRGB8, U8(buf.into_raw())),
+        DynamicImage::ImageRgba8(buf) if srgb => (glow::SRGB8_ALPHA8, U8(buf.into_raw())),
+        DynamicImage::ImageRgb8(buf) => (glow::RGB8, U8(buf.into_raw())),
+        DynamicImage::ImageRgba8(buf) => (glow::RGBA8, U8(buf.into_raw())),
+        DynamicImage::ImageLuma16(buf) => (glow::R16, U16(buf.into_raw())),
+        DynamicImage::ImageLumaA16(buf) => (glow::RG16, U16(buf.into_raw())),
+        DynamicImage::ImageRgb16(buf) => (glow::RGB16, U16(buf.into_raw())),
+        DynamicImage::ImageRgba16(buf) => (glow::RGBA16, U16(buf.into_raw())),
+        DynamicImage::ImageRgb32F(buf) => (glow::RGB32F, F32(buf.into_raw())),
+        DynamicImage::ImageRgba32F(buf) => (glow::RGBA32F, F32(buf.into_raw())),
+        _ => convert_image(&img, ImageConversion::Rgba32F, false),
+    }
+}
+
+fn convert_image(img: &DynamicImage, conversion: ImageConversion, srgb: bool) -> (u32, PixelData) {
+    match conversion {
+        ImageConversion::Rgba8 if srgb => {
+            (glow::SRGB8_ALPHA8, PixelData::U8(img.to_rgba8().into_raw()))
+        }
+        ImageConversion::Rgba8 => (glow::RGBA8, PixelData::U8(img.to_rgba8().into_raw())),
+        ImageConversion::Rgba16F => {
+            let data = get_float_pixels(img, srgb)
+                .into_iter()
+                .map(f16::from_f32)
+                .collect();
+            (glow::RGBA16F, PixelData::F16(data))
+        }
+        ImageConversion::Rgba32F => (glow::RGBA32F, PixelData::F32(get_float_pixels(img, srgb))),
+    }
+}
+
+fn get_float_pixels(img: &DynamicImage, srgb: bool) -> Vec<f32> {
+    let mut pixels = img.to_rgba32f().into_raw();
+    if srgb {
+        for pixel in pixels.chunks_exact_mut(4) {
+            for c in pixel.iter_mut().take(3) {
+                *c = srgb_to_linear(*c);
+            }
+        }
+    }
+    pixels
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+// runs after any conversion, so float data is already linear
+// sRGB encoded bytes are decoded, scaled and encoded again, the texture then filters them as linear
+fn premultiply_alpha(pixels: &mut PixelData, channels: usize, srgb: bool) {
+    if channels != 2 && channels != 4 {
+        return;
+    }
+    match pixels {
+        PixelData::U8(data) if srgb => {
+            for p in data.chunks_exact_mut(channels) {
+                let a = p[channels - 1] as f32 / 255.0;
+                for c in &mut p[..channels - 1] {
+                    let linear = srgb_to_linear(*c as f32 / 255.0) * a;
+                    *c = (linear_to_srgb(linear) * 255.0).round() as u8;
+                }
+            }
+        }
+        PixelData::U8(data) => {
+            for p in data.chunks_exact_mut(channels) {
+                let a = p[channels - 1] as u32;
+                for c in &mut p[..channels - 1] {
+                    *c = ((*c as u32 * a + 127) / 255) as u8;
+                }
+            }
+        }
+        PixelData::U16(data) => {
+            for p in data.chunks_exact_mut(channels) {
+                let a = p[channels - 1] as u32;
+                for c in &mut p[..channels - 1] {
+                    *c = ((*c as u32 * a + 32767) / 65535) as u16;
+                }
+            }
+        }
+        PixelData::F16(data) => {
+            for p in data.chunks_exact_mut(channels) {
+                let a = p[channels - 1].to_f32();
+                for c in &mut p[..channels - 1] {
+                    *c = f16::from_f32(c.to_f32() * a);
+                }
+            }
+        }
+        PixelData::F32(data) => {
+            for p in data.chunks_exact_mut(channels) {
+                let a = p[channels - 1];
+                for c in &mut p[..channels - 1] {
+                    *c *= a;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_count_needs_exact_size() {
+        assert_eq!(get_channel_count(2 * 3 * 3, 2, 3), Ok(3));
+        assert_eq!(get_channel_count(2 * 3, 2, 3), Ok(1));
+        assert!(get_channel_count(2 * 3 * 3 + 1, 2, 3).is_err());
+        assert!(get_channel_count(2 * 3 * 5, 2, 3).is_err());
+        assert!(get_channel_count(5, 2, 3).is_err());
+        assert!(get_channel_count(0, 0, 3).is_err());
+    }
+
+    #[test]
+    fn premultiplies_in_linear_space() {
+        let mut pixels = PixelData::U8(vec![255, 128, 1, 128, 2, 192]);
+        premultiply_alpha(&mut pixels, 2, false);
+        // rounded rather than truncated
+        assert_eq!(pixels.as_bytes(), [128, 128, 1, 128, 2, 192]);
+
+        let mut pixels = PixelData::U8(vec![188, 255, 0, 128]);
+        premultiply_alpha(&mut pixels, 4, true);
+        // the red channel is scaled after decoding, encoding it naively would give 94
+        let bytes = pixels.as_bytes();
+        let expected = srgb_to_linear(188.0 / 255.0) * 128.0 / 255.0;
+        assert_eq!(bytes[0], (linear_to_srgb(expected) * 255.0).round() as u8);
+        assert_eq!(bytes[0], 138);
+        assert_eq!(bytes[1], 188);
+        assert_eq!(bytes[2], 0);
+        assert_eq!(bytes[3], 128);
+
+        let mut pixels = PixelData::F32(vec![0.5, 0.5, 0.5, 0.5, 1.0, 0.0]);
+        premultiply_alpha(&mut pixels, 3, false);
+        assert_eq!(pixels.get_f32(0), 0.5);
+        let mut pixels = PixelData::U16(vec![65535, 32768]);
+        premultiply_alpha(&mut pixels, 2, false);
+        assert_eq!(pixels.get_f32(0), 32768.0 / 65535.0);
+    }
+
+    #[test]
+    fn exr_round_trip() {
+        let (width, height) = (3, 2);
+        let path = std::env::temp_dir().join(format!("paxil_exr_{}.exr", std::process::id()));
+        for channels in 1..=4 {
+            let values: Vec<f32> = (0..width * height * channels)
+                .map(|i| i as f32 * 0.25)
+                .collect();
+            let pixels = if channels == 2 {
+                PixelData::F16(values.iter().map(|v| f16::from_f32(*v)).collect())
+            } else {
+                PixelData::F32(values.clone())
+            };
+            write_exr(&path, width, height, channels, &pixels).unwrap();
+            let layers = read_exr_layers(&path).unwrap();
+            assert_eq!((layers[0].width, layers[0].height), (width, height));
+
+            for (i, pixel) in layers[0].pixels.chunks_exact(4).enumerate() {
+                let get = |c: usize| values[i * channels + c];
+                let expected = match channels {
+                    1 => [get(0), get(0), get(0), 1.0],
+                    2 => [get(0), get(0), get(0), get(1)],
+                    3 => [get(0), get(1), get(2), 1.0],
+                    _ => [get(0), get(1), get(2), get(3)],
+                };
+                assert_eq!(pixel, expected, "{} channels", channels);
+            }
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
 }
@@ -10,11 +10,44 @@ pub use shader::*;
 pub mod vao;
 pub use vao::*;
 
+pub mod vbo;
+pub use vbo::*;
+
+pub mod ibo;
+pub use ibo::*;
+
+pub mod gpu_mesh;
+pub use gpu_mesh::*;
+
 pub mod texture;
 pub use texture::*;
 
+pub mod sampler;
+pub use sampler::*;
+
+pub mod renderbuffer;
+pub use renderbuffer::*;
+
+pub mod fbo;
+pub use fbo::*;
+
 pub mod utils;
 pub use utils::*;
 
 pub mod image;
 pub use image::*;
+
+pub mod compressed_image;
+pub use compressed_image::*;
+
+pub mod node;
+pub use node::*;
+
+pub mod camera;
+pub use camera::*;
+
+pub mod gltf_model;
+pub use gltf_model::*;
+
+pub mod gpu_isosurface;
+pub use gpu_isosurface::*;
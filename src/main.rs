@@ -1,5 +1,6 @@
 use glow::HasContext;
 use paxil::AppConfig;
+use paxil_math::Color;
 
 use std::rc::Rc;
 
@@ -7,6 +8,7 @@ use paxil::app_runner::*;
 use paxil::image::*;
 use paxil::shader::*;
 use paxil::texture::*;
+use paxil::utils::*;
 use paxil::vao::*;
 
 struct MyApp {
@@ -32,12 +34,12 @@ impl App for MyApp {
 
     fn draw(&mut self, gl: &glow::Context) {
         unsafe {
-            gl.clear_color(0.1, 0.2, 0.3, 1.0);
+            clear_color(gl, &Color::rgb(0.1, 0.2, 0.3));
             gl.clear(glow::COLOR_BUFFER_BIT);
 
             self.shader.bind();
             self.shader
-                .set_uniform_texture("u_texture", 0, &self.image.texture.get_id())
+                .set_uniform_texture("u_texture", 0, &self.image.texture.get_id(), None)
                 .unwrap();
             self.shader.set_uniform_1f("u_b", 0.0);
             self.vao.bind();
@@ -0,0 +1,351 @@
+use paxil_math::*;
+use std::cell::Cell;
+
+// slot index and generation, so ids of removed nodes never address the slot's next occupant
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId(usize, u32);
+
+pub struct Node {
+    pub name: String,
+    transform: Transform,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    world_matrix: Cell<Mat4>,
+    dirty: Cell<bool>,
+}
+
+impl Node {
+    pub fn get_transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    pub fn get_parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    pub fn get_children(&self) -> &[NodeId] {
+        &self.children
+    }
+}
+
+#[derive(Default)]
+struct Slot {
+    generation: u32,
+    node: Option<Node>,
+}
+
+// nodes live in an arena and refer to each other by NodeId
+#[derive(Default)]
+pub struct SceneGraph {
+    nodes: Vec<Slot>,
+    free: Vec<usize>,
+}
+
+impl SceneGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_node(&mut self, name: &str, transform: Transform) -> NodeId {
+        let node = Node {
+            name: name.to_string(),
+            transform,
+            parent: None,
+            children: Vec::new(),
+            world_matrix: Cell::new(Mat4::IDENTITY),
+            dirty: Cell::new(true),
+        };
+        match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.nodes[index];
+                slot.node = Some(node);
+                NodeId(index, slot.generation)
+            }
+            None => {
+                self.nodes.push(Slot {
+                    generation: 0,
+                    node: Some(node),
+                });
+                NodeId(self.nodes.len() - 1, 0)
+            }
+        }
+    }
+
+    pub fn add_child(&mut self, parent: NodeId, name: &str, transform: Transform) -> NodeId {
+        let id = self.add_node(name, transform);
+        self.set_parent(id, Some(parent))
+            .expect("a new node cannot form a cycle");
+        id
+    }
+
+    // removes the node and its whole subtree
+    pub fn remove_node(&mut self, id: NodeId) {
+        if self.get(id).is_none() {
+            return;
+        }
+        let slot = &mut self.nodes[id.0];
+        let Some(node) = slot.node.take() else {
+            return;
+        };
+        slot.generation = slot.generation.wrapping_add(1);
+        if let Some(parent) = node.parent.and_then(|p| self.get_mut(p)) {
+            parent.children.retain(|c| *c != id);
+        }
+        self.free.push(id.0);
+        for child in node.children {
+            if let Some(child_node) = self.get_mut(child) {
+                child_node.parent = None;
+            }
+            self.remove_node(child);
+        }
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&Node> {
+        self.nodes
+            .get(id.0)
+            .filter(|slot| slot.generation == id.1)
+            .and_then(|slot| slot.node.as_ref())
+    }
+
+    fn get_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        self.nodes
+            .get_mut(id.0)
+            .filter(|slot| slot.generation == id.1)
+            .and_then(|slot| slot.node.as_mut())
+    }
+
+    fn get_nodes(&self) -> impl Iterator<Item = (NodeId, &Node)> {
+        self.nodes.iter().enumerate().filter_map(|(i, slot)| {
+            slot.node
+                .as_ref()
+                .map(|node| (NodeId(i, slot.generation), node))
+        })
+    }
+
+    pub fn find(&self, name: &str) -> Option<NodeId> {
+        self.get_nodes()
+            .find(|(_, node)| node.name == name)
+            .map(|(id, _)| id)
+    }
+
+    pub fn get_roots(&self) -> Vec<NodeId> {
+        self.get_nodes()
+            .filter(|(_, node)| node.parent.is_none())
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    // keeps the local transform, so the node moves with its new parent
+    pub fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>) -> Result<(), String> {
+        if self.get(id).is_none() {
+            return Err("Node does not exist".to_string());
+        }
+        if let Some(parent) = parent {
+            if self.get(parent).is_none() {
+                return Err("Parent node does not exist".to_string());
+            }
+            if self.is_ancestor(id, parent) {
+                return Err("Cannot parent a node to one of its descendants".to_string());
+            }
+        }
+
+        if let Some(old_parent) = self.get(id).and_then(|n| n.parent) {
+            if let Some(old_parent) = self.get_mut(old_parent) {
+                old_parent.children.retain(|c| *c != id);
+            }
+        }
+        if let Some(parent) = parent.and_then(|p| self.get_mut(p)) {
+            parent.children.push(id);
+        }
+        if let Some(node) = self.get_mut(id) {
+            node.parent = parent;
+        }
+        self.mark_dirty(id);
+        Ok(())
+    }
+
+    // true if ancestor is node itself or one of its parents
+    pub fn is_ancestor(&self, ancestor: NodeId, node: NodeId) -> bool {
+        let mut current = Some(node);
+        while let Some(id) = current {
+            if id == ancestor {
+                return true;
+            }
+            current = self.get(id).and_then(|n| n.parent);
+        }
+        false
+    }
+
+    pub fn get_transform(&self, id: NodeId) -> Transform {
+        self.get(id).map(|n| n.transform).unwrap_or_default()
+    }
+
+    pub fn set_transform(&mut self, id: NodeId, transform: Transform) {
+        if let Some(node) = self.get_mut(id) {
+            node.transform = transform;
+            self.mark_dirty(id);
+        }
+    }
+
+    pub fn update_transform<F: FnOnce(&mut Transform)>(&mut self, id: NodeId, f: F) {
+        if let Some(node) = self.get_mut(id) {
+            f(&mut node.transform);
+            self.mark_dirty(id);
+        }
+    }
+
+    pub fn set_translation(&mut self, id: NodeId, translation: Vec3) {
+        self.update_transform(id, |t| t.translation = translation);
+    }
+
+    pub fn set_rotation(&mut self, id: NodeId, rotation: Quat) {
+        self.update_transform(id, |t| t.rotation = rotation);
+    }
+
+    pub fn set_scale(&mut self, id: NodeId, scale: Vec3) {
+        self.update_transform(id, |t| t.scale = scale);
+    }
+
+    pub fn get_local_matrix(&self, id: NodeId) -> Mat4 {
+        self.get_transform(id).to_matrix()
+    }
+
+    // recomputed lazily, only along dirty paths
+    pub fn get_world_matrix(&self, id: NodeId) -> Mat4 {
+        let Some(node) = self.get(id) else {
+            return Mat4::IDENTITY;
+        };
+        if node.dirty.get() {
+            let local = node.transform.to_matrix();
+            let world = match node.parent {
+                Some(parent) => self.get_world_matrix(parent) * local,
+                None => local,
+            };
+            node.world_matrix.set(world);
+            node.dirty.set(false);
+        }
+        node.world_matrix.get()
+    }
+
+    pub fn get_world_position(&self, id: NodeId) -> Vec3 {
+        self.get_world_matrix(id).get_translation()
+    }
+
+    // sets the local transform so the node ends up at the given world transform
+    pub fn set_world_matrix(&mut self, id: NodeId, world: &Mat4) {
+        let parent_world = self
+            .get(id)
+            .and_then(|n| n.parent)
+            .map(|p| self.get_world_matrix(p))
+            .unwrap_or(Mat4::IDENTITY);
+        let local = parent_world.inverse().unwrap_or(Mat4::IDENTITY) * *world;
+        self.set_transform(id, Transform::from_matrix(&local));
+    }
+
+    fn mark_dirty(&self, id: NodeId) {
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            if let Some(node) = self.get(id) {
+                node.dirty.set(true);
+                stack.extend(node.children.iter().copied());
+            }
+        }
+    }
+
+    // depth first from the roots, parents are visited before their children
+    pub fn traverse<F: FnMut(NodeId, &Node, &Mat4)>(&self, mut f: F) {
+        for root in self.get_roots() {
+            self.traverse_from(root, &mut f);
+        }
+    }
+
+    pub fn traverse_from<F: FnMut(NodeId, &Node, &Mat4)>(&self, id: NodeId, f: &mut F) {
+        let Some(node) = self.get(id) else {
+            return;
+        };
+        let world = self.get_world_matrix(id);
+        f(id, node, &world);
+        for child in node.children.iter() {
+            self.traverse_from(*child, f);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_chain() -> (SceneGraph, [NodeId; 3]) {
+        let mut graph = SceneGraph::new();
+        let root = graph.add_node("root", Transform::from_translation(Vec3::X));
+        let arm = graph.add_child(root, "arm", Transform::from_translation(Vec3::Y));
+        let hand = graph.add_child(arm, "hand", Transform::from_scale(Vec3::splat(2.0)));
+        (graph, [root, arm, hand])
+    }
+
+    #[test]
+    fn world_matrices_follow_parents() {
+        let (mut graph, [root, arm, hand]) = get_chain();
+        assert_eq!(graph.get_world_position(hand), Vec3::new(1.0, 1.0, 0.0));
+
+        // moving the root dirties the cached matrices below it
+        graph.set_translation(root, Vec3::new(0.0, 0.0, 5.0));
+        assert_eq!(graph.get_world_position(hand), Vec3::new(0.0, 1.0, 5.0));
+        graph.set_parent(hand, None).unwrap();
+        assert_eq!(graph.get_world_position(hand), Vec3::ZERO);
+        assert_eq!(graph.get_roots(), vec![root, hand]);
+
+        let world = Mat4::from_translation(Vec3::new(3.0, 3.0, 3.0));
+        graph.set_world_matrix(arm, &world);
+        assert_eq!(
+            graph.get_transform(arm).translation,
+            Vec3::new(3.0, 3.0, -2.0)
+        );
+        assert_eq!(graph.get_world_matrix(arm), world);
+    }
+
+    #[test]
+    fn set_parent_rejects_cycles() {
+        let (mut graph, [root, arm, hand]) = get_chain();
+        assert!(graph.set_parent(root, Some(hand)).is_err());
+        assert!(graph.set_parent(arm, Some(arm)).is_err());
+        assert_eq!(graph.get(root).unwrap().get_parent(), None);
+        assert_eq!(graph.get(arm).unwrap().get_children(), &[hand]);
+        assert!(graph.is_ancestor(root, hand));
+        assert!(!graph.is_ancestor(hand, root));
+    }
+
+    #[test]
+    fn remove_node_removes_subtree() {
+        let (mut graph, [root, arm, hand]) = get_chain();
+        let other = graph.add_child(root, "other", Transform::default());
+        graph.remove_node(arm);
+        assert!(graph.get(arm).is_none());
+        assert!(graph.get(hand).is_none());
+        assert_eq!(graph.get(root).unwrap().get_children(), &[other]);
+        assert_eq!(graph.find("hand"), None);
+
+        let mut visited = Vec::new();
+        graph.traverse(|id, _, _| visited.push(id));
+        assert_eq!(visited, vec![root, other]);
+    }
+
+    #[test]
+    fn stale_ids_are_rejected() {
+        let (mut graph, [root, arm, hand]) = get_chain();
+        graph.remove_node(hand);
+        let reused = graph.add_node("reused", Transform::default());
+        assert_eq!(reused.0, hand.0);
+        assert_ne!(reused, hand);
+        assert!(graph.get(hand).is_none());
+        assert_eq!(graph.find("reused"), Some(reused));
+
+        // the stale id can neither move nor delete the node now in its slot
+        assert!(graph.set_parent(hand, Some(arm)).is_err());
+        assert!(graph.set_parent(root, Some(hand)).is_err());
+        graph.set_translation(hand, Vec3::ONE);
+        graph.remove_node(hand);
+        assert_eq!(graph.get_transform(reused), Transform::default());
+        assert_eq!(graph.get_roots(), vec![root, reused]);
+    }
+}
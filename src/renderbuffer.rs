@@ -0,0 +1,119 @@
+use super::utils::*;
+use glow::HasContext;
+use std::rc::Rc;
+
+pub struct Renderbuffer {
+    gl: Rc<glow::Context>,
+    id: glow::Renderbuffer,
+    width: usize,
+    height: usize,
+    internal_format: u32,
+    samples: usize,
+}
+
+impl Renderbuffer {
+    // samples of None or Some(0) allocates a single sampled renderbuffer
+    pub fn new(
+        gl: Rc<glow::Context>,
+        width: usize,
+        height: usize,
+        internal_format: u32,
+        samples: Option<usize>,
+    ) -> Result<Self, String> {
+        let samples = match samples {
+            Some(samples) if samples > 0 => {
+                clamp_samples(&gl, glow::RENDERBUFFER, internal_format, samples)
+            }
+            _ => 0,
+        };
+
+        unsafe {
+            let id = gl.create_renderbuffer()?;
+            gl.bind_renderbuffer(glow::RENDERBUFFER, Some(id));
+            if samples > 0 {
+                gl.renderbuffer_storage_multisample(
+                    glow::RENDERBUFFER,
+                    samples as i32,
+                    internal_format,
+                    width as i32,
+                    height as i32,
+                );
+            } else {
+                gl.renderbuffer_storage(
+                    glow::RENDERBUFFER,
+                    internal_format,
+                    width as i32,
+                    height as i32,
+                );
+            }
+            gl.bind_renderbuffer(glow::RENDERBUFFER, None);
+
+            Ok(Self {
+                gl,
+                id,
+                width,
+                height,
+                internal_format,
+                samples,
+            })
+        }
+    }
+
+    pub fn new_color(
+        gl: Rc<glow::Context>,
+        width: usize,
+        height: usize,
+        samples: Option<usize>,
+    ) -> Result<Self, String> {
+        Self::new(gl, width, height, glow::RGBA8, samples)
+    }
+
+    pub fn new_depth_stencil(
+        gl: Rc<glow::Context>,
+        width: usize,
+        height: usize,
+        samples: Option<usize>,
+    ) -> Result<Self, String> {
+        Self::new(gl, width, height, glow::DEPTH24_STENCIL8, samples)
+    }
+
+    pub fn get_id(&self) -> glow::Renderbuffer {
+        self.id
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get_internal_format(&self) -> u32 {
+        self.internal_format
+    }
+
+    pub fn get_samples(&self) -> usize {
+        self.samples
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            self.gl.bind_renderbuffer(glow::RENDERBUFFER, Some(self.id));
+        }
+    }
+
+    pub fn unbind(&self) {
+        unsafe {
+            self.gl.bind_renderbuffer(glow::RENDERBUFFER, None);
+        }
+    }
+}
+
+impl Drop for Renderbuffer {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_renderbuffer(self.id);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use glow::HasContext;
+use std::rc::Rc;
+
+pub struct Sampler {
+    gl: Rc<glow::Context>,
+    id: glow::Sampler,
+}
+
+impl Sampler {
+    pub fn new(gl: Rc<glow::Context>) -> Result<Self, String> {
+        unsafe {
+            let id = gl.create_sampler()?;
+            gl.sampler_parameter_i32(id, glow::TEXTURE_MIN_FILTER, glow::LINEAR as i32);
+            gl.sampler_parameter_i32(id, glow::TEXTURE_MAG_FILTER, glow::LINEAR as i32);
+            gl.sampler_parameter_i32(id, glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE as i32);
+            gl.sampler_parameter_i32(id, glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE as i32);
+            gl.sampler_parameter_i32(id, glow::TEXTURE_WRAP_R, glow::CLAMP_TO_EDGE as i32);
+            Ok(Self { gl, id })
+        }
+    }
+
+    pub fn get_id(&self) -> glow::Sampler {
+        self.id
+    }
+
+    pub fn bind(&self, unit: u32) {
+        unsafe {
+            self.gl.bind_sampler(unit, Some(self.id));
+        }
+    }
+
+    pub fn unbind(&self, unit: u32) {
+        unsafe {
+            self.gl.bind_sampler(unit, None);
+        }
+    }
+
+    pub fn set_min_filter(&self, filter: u32) {
+        self.set_parameter_i32(glow::TEXTURE_MIN_FILTER, filter as i32);
+    }
+
+    pub fn set_mag_filter(&self, filter: u32) {
+        self.set_parameter_i32(glow::TEXTURE_MAG_FILTER, filter as i32);
+    }
+
+    pub fn set_wrap_s(&self, wrap: u32) {
+        self.set_parameter_i32(glow::TEXTURE_WRAP_S, wrap as i32);
+    }
+
+    pub fn set_wrap_t(&self, wrap: u32) {
+        self.set_parameter_i32(glow::TEXTURE_WRAP_T, wrap as i32);
+    }
+
+    pub fn set_wrap_r(&self, wrap: u32) {
+        self.set_parameter_i32(glow::TEXTURE_WRAP_R, wrap as i32);
+    }
+
+    pub fn set_wrap(&self, wrap: u32) {
+        self.set_wrap_s(wrap);
+        self.set_wrap_t(wrap);
+        self.set_wrap_r(wrap);
+    }
+
+    pub fn set_border_color(&self, r: f32, g: f32, b: f32, a: f32) {
+        unsafe {
+            self.gl
+                .sampler_parameter_f32_slice(self.id, glow::TEXTURE_BORDER_COLOR, &[r, g, b, a]);
+        }
+    }
+
+    // clamped to the driver maximum, returns the value actually applied
+    pub fn set_max_anisotropy(&self, anisotropy: f32) -> Result<f32, String> {
+        unsafe {
+            let supported = self.gl.supported_extensions();
+            if !supported.contains("GL_ARB_texture_filter_anisotropic")
+                && !supported.contains("GL_EXT_texture_filter_anisotropic")
+                && !supported.contains("EXT_texture_filter_anisotropic")
+            {
+                return Err("Anisotropic filtering is not supported".to_string());
+            }
+
+            let max = self.gl.get_parameter_f32(glow::MAX_TEXTURE_MAX_ANISOTROPY);
+            let anisotropy = anisotropy.clamp(1.0, max.max(1.0));
+            self.gl
+                .sampler_parameter_f32(self.id, glow::TEXTURE_MAX_ANISOTROPY, anisotropy);
+            Ok(anisotropy)
+        }
+    }
+
+    // Some(func) enables depth comparison for shadow samplers, None disables it
+    pub fn set_compare_mode(&self, func: Option<u32>) {
+        match func {
+            Some(func) => {
+                self.set_parameter_i32(
+                    glow::TEXTURE_COMPARE_MODE,
+                    glow::COMPARE_REF_TO_TEXTURE as i32,
+                );
+                self.set_parameter_i32(glow::TEXTURE_COMPARE_FUNC, func as i32);
+            }
+            None => {
+                self.set_parameter_i32(glow::TEXTURE_COMPARE_MODE, glow::NONE as i32);
+            }
+        }
+    }
+
+    pub fn set_lod_range(&self, min_lod: f32, max_lod: f32) {
+        unsafe {
+            self.gl
+                .sampler_parameter_f32(self.id, glow::TEXTURE_MIN_LOD, min_lod);
+            self.gl
+                .sampler_parameter_f32(self.id, glow::TEXTURE_MAX_LOD, max_lod);
+        }
+    }
+
+    pub fn set_lod_bias(&self, bias: f32) {
+        unsafe {
+            self.gl
+                .sampler_parameter_f32(self.id, glow::TEXTURE_LOD_BIAS, bias);
+        }
+    }
+
+    fn set_parameter_i32(&self, name: u32, value: i32) {
+        unsafe {
+            self.gl.sampler_parameter_i32(self.id, name, value);
+        }
+    }
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_sampler(self.id);
+        }
+    }
+}
@@ -1,7 +1,52 @@
 use glow::HasContext;
 use std::rc::Rc;
 
-use crate::texture;
+use crate::sampler::Sampler;
+use crate::utils::check_compute_support;
+use paxil_math::*;
+
+pub trait UniformValue {
+    fn set_uniform(&self, gl: &glow::Context, location: &glow::UniformLocation);
+}
+
+unsafe fn compile_shader(
+    gl: &glow::Context,
+    shader_type: u32,
+    src: &str,
+) -> Result<glow::Shader, String> {
+    let shader = gl.create_shader(shader_type)?;
+    gl.shader_source(shader, src);
+    gl.compile_shader(shader);
+    if !gl.get_shader_compile_status(shader) {
+        let log = gl.get_shader_info_log(shader);
+        gl.delete_shader(shader);
+        return Err(log);
+    }
+    Ok(shader)
+}
+
+// links and then releases the shaders
+unsafe fn link_program(
+    gl: &glow::Context,
+    shaders: &[glow::Shader],
+) -> Result<glow::Program, String> {
+    let program = gl.create_program()?;
+    for shader in shaders {
+        gl.attach_shader(program, *shader);
+    }
+    gl.link_program(program);
+    let linked = gl.get_program_link_status(program);
+    for shader in shaders {
+        gl.detach_shader(program, *shader);
+        gl.delete_shader(*shader);
+    }
+    if !linked {
+        let log = gl.get_program_info_log(program);
+        gl.delete_program(program);
+        return Err(log);
+    }
+    Ok(program)
+}
 
 pub struct Shader {
     gl: Rc<glow::Context>,
@@ -15,38 +60,38 @@ impl Shader {
         fragment_shader_src: &str,
     ) -> Result<Self, String> {
         unsafe {
-            let compile_shader =
-                |gl: &glow::Context, shader_type, src: &str| -> Result<glow::Shader, String> {
-                    let shader = gl.create_shader(shader_type)?;
-                    gl.shader_source(shader, src);
-                    gl.compile_shader(shader);
-                    if !gl.get_shader_compile_status(shader) {
-                        return Err(gl.get_shader_info_log(shader));
+            let vertex_shader = compile_shader(&gl, glow::VERTEX_SHADER, vertex_shader_src)?;
+            let fragment_shader =
+                match compile_shader(&gl, glow::FRAGMENT_SHADER, fragment_shader_src) {
+                    Ok(shader) => shader,
+                    Err(e) => {
+                        gl.delete_shader(vertex_shader);
+                        return Err(e);
                     }
-                    Ok(shader)
                 };
+            let program = link_program(&gl, &[vertex_shader, fragment_shader])?;
+            Ok(Self { gl, program })
+        }
+    }
 
-            let vertex_shader = compile_shader(&gl, glow::VERTEX_SHADER, vertex_shader_src)?;
-            let fragment_shader = compile_shader(&gl, glow::FRAGMENT_SHADER, fragment_shader_src)?;
-
-            let program = gl.create_program()?;
-            gl.attach_shader(program, vertex_shader);
-            gl.attach_shader(program, fragment_shader);
-            gl.link_program(program);
-
-            if !gl.get_program_link_status(program) {
-                return Err(gl.get_program_info_log(program));
-            }
-
-            gl.detach_shader(program, vertex_shader);
-            gl.detach_shader(program, fragment_shader);
-            gl.delete_shader(vertex_shader);
-            gl.delete_shader(fragment_shader);
-
+    // needs GL 4.3, GLES 3.1 or GL_ARB_compute_shader, the default 4.1 context has none of them
+    pub fn new_compute(gl: Rc<glow::Context>, compute_shader_src: &str) -> Result<Self, String> {
+        check_compute_support(&gl)?;
+        unsafe {
+            let compute_shader = compile_shader(&gl, glow::COMPUTE_SHADER, compute_shader_src)?;
+            let program = link_program(&gl, &[compute_shader])?;
             Ok(Self { gl, program })
         }
     }
 
+    // binds the program and runs the given number of work groups
+    pub fn dispatch(&self, groups_x: u32, groups_y: u32, groups_z: u32) {
+        unsafe {
+            self.gl.use_program(Some(self.program));
+            self.gl.dispatch_compute(groups_x, groups_y, groups_z);
+        }
+    }
+
     pub fn bind(&self) {
         unsafe {
             self.gl.use_program(Some(self.program));
@@ -59,6 +104,21 @@ impl Shader {
         }
     }
 
+    pub fn set_uniform<T: UniformValue + ?Sized>(
+        &self,
+        name: &str,
+        value: &T,
+    ) -> Result<(), String> {
+        unsafe {
+            let location = self
+                .gl
+                .get_uniform_location(self.program, name)
+                .ok_or_else(|| format!("Uniform '{}' not found", name))?;
+            value.set_uniform(&self.gl, &location);
+        }
+        Ok(())
+    }
+
     pub fn set_uniform_1i(&self, name: &str, value: i32) -> Result<(), String> {
         unsafe {
             let location = self
@@ -154,6 +214,7 @@ impl Shader {
         name: &str,
         unit: u32,
         texture: &glow::Texture,
+        sampler: Option<&Sampler>,
     ) -> Result<(), String> {
         unsafe {
             let location = self
@@ -163,6 +224,7 @@ impl Shader {
 
             self.gl.active_texture(glow::TEXTURE0 + unit);
             self.gl.bind_texture(glow::TEXTURE_2D, Some(*texture));
+            self.gl.bind_sampler(unit, sampler.map(|s| s.get_id()));
             self.gl.uniform_1_i32(Some(&location), unit as i32);
         }
         Ok(())
@@ -176,3 +238,131 @@ impl Drop for Shader {
         }
     }
 }
+
+impl UniformValue for f32 {
+    fn set_uniform(&self, gl: &glow::Context, location: &glow::UniformLocation) {
+        unsafe { gl.uniform_1_f32(Some(location), *self) }
+    }
+}
+
+impl UniformValue for i32 {
+    fn set_uniform(&self, gl: &glow::Context, location: &glow::UniformLocation) {
+        unsafe { gl.uniform_1_i32(Some(location), *self) }
+    }
+}
+
+impl UniformValue for u32 {
+    fn set_uniform(&self, gl: &glow::Context, location: &glow::UniformLocation) {
+        unsafe { gl.uniform_1_u32(Some(location), *self) }
+    }
+}
+
+impl UniformValue for bool {
+    fn set_uniform(&self, gl: &glow::Context, location: &glow::UniformLocation) {
+        unsafe { gl.uniform_1_i32(Some(location), *self as i32) }
+    }
+}
+
+impl UniformValue for Vec2 {
+    fn set_uniform(&self, gl: &glow::Context, location: &glow::UniformLocation) {
+        unsafe { gl.uniform_2_f32(Some(location), self.x, self.y) }
+    }
+}
+
+impl UniformValue for Vec3 {
+    fn set_uniform(&self, gl: &glow::Context, location: &glow::UniformLocation) {
+        unsafe { gl.uniform_3_f32(Some(location), self.x, self.y, self.z) }
+    }
+}
+
+impl UniformValue for Vec4 {
+    fn set_uniform(&self, gl: &glow::Context, location: &glow::UniformLocation) {
+        unsafe { gl.uniform_4_f32(Some(location), self.x, self.y, self.z, self.w) }
+    }
+}
+
+impl UniformValue for Color {
+    fn set_uniform(&self, gl: &glow::Context, location: &glow::UniformLocation) {
+        unsafe { gl.uniform_4_f32(Some(location), self.r, self.g, self.b, self.a) }
+    }
+}
+
+impl UniformValue for IVec2 {
+    fn set_uniform(&self, gl: &glow::Context, location: &glow::UniformLocation) {
+        unsafe { gl.uniform_2_i32(Some(location), self.x, self.y) }
+    }
+}
+
+impl UniformValue for IVec3 {
+    fn set_uniform(&self, gl: &glow::Context, location: &glow::UniformLocation) {
+        unsafe { gl.uniform_3_i32(Some(location), self.x, self.y, self.z) }
+    }
+}
+
+impl UniformValue for IVec4 {
+    fn set_uniform(&self, gl: &glow::Context, location: &glow::UniformLocation) {
+        unsafe { gl.uniform_4_i32(Some(location), self.x, self.y, self.z, self.w) }
+    }
+}
+
+// quaternions are uploaded as vec4(x, y, z, w)
+impl UniformValue for Quat {
+    fn set_uniform(&self, gl: &glow::Context, location: &glow::UniformLocation) {
+        unsafe { gl.uniform_4_f32(Some(location), self.x, self.y, self.z, self.w) }
+    }
+}
+
+impl UniformValue for Mat2 {
+    fn set_uniform(&self, gl: &glow::Context, location: &glow::UniformLocation) {
+        unsafe { gl.uniform_matrix_2_f32_slice(Some(location), false, self.as_slice()) }
+    }
+}
+
+impl UniformValue for Mat3 {
+    fn set_uniform(&self, gl: &glow::Context, location: &glow::UniformLocation) {
+        unsafe { gl.uniform_matrix_3_f32_slice(Some(location), false, self.as_slice()) }
+    }
+}
+
+impl UniformValue for Mat4 {
+    fn set_uniform(&self, gl: &glow::Context, location: &glow::UniformLocation) {
+        unsafe { gl.uniform_matrix_4_f32_slice(Some(location), false, self.as_slice()) }
+    }
+}
+
+impl UniformValue for [f32] {
+    fn set_uniform(&self, gl: &glow::Context, location: &glow::UniformLocation) {
+        unsafe { gl.uniform_1_f32_slice(Some(location), self) }
+    }
+}
+
+impl UniformValue for [Vec2] {
+    fn set_uniform(&self, gl: &glow::Context, location: &glow::UniformLocation) {
+        let data = self.iter().flat_map(|v| v.to_array()).collect::<Vec<_>>();
+        unsafe { gl.uniform_2_f32_slice(Some(location), &data) }
+    }
+}
+
+impl UniformValue for [Vec3] {
+    fn set_uniform(&self, gl: &glow::Context, location: &glow::UniformLocation) {
+        let data = self.iter().flat_map(|v| v.to_array()).collect::<Vec<_>>();
+        unsafe { gl.uniform_3_f32_slice(Some(location), &data) }
+    }
+}
+
+impl UniformValue for [Vec4] {
+    fn set_uniform(&self, gl: &glow::Context, location: &glow::UniformLocation) {
+        let data = self.iter().flat_map(|v| v.to_array()).collect::<Vec<_>>();
+        unsafe { gl.uniform_4_f32_slice(Some(location), &data) }
+    }
+}
+
+impl UniformValue for [Mat4] {
+    fn set_uniform(&self, gl: &glow::Context, location: &glow::UniformLocation) {
+        let data = self
+            .iter()
+            .flat_map(|m| m.to_cols_array())
+            .collect::<Vec<_>>();
+        unsafe { gl.uniform_matrix_4_f32_slice(Some(location), false, &data) }
+    }
+}
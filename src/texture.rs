@@ -1,3 +1,4 @@
+use super::compressed_image::CompressedImage;
 use super::utils::*;
 use glow::HasContext;
 use std::rc::Rc;
@@ -51,6 +52,78 @@ pub trait TextureTrait {
             self.get_context().bind_texture(self.get_target(), None);
         }
     }
+    // GLES and WebGL have no glGetTexImage, there 2D textures are read through a framebuffer
+    fn read_data(
+        &self,
+        level: usize,
+        format: u32,
+        texture_type: u32,
+        data: &mut [u8],
+    ) -> Result<(), String> {
+        let gl = self.get_context();
+        if !gl.version().is_embedded {
+            self.bind();
+            unsafe {
+                gl.pixel_store_i32(glow::PACK_ALIGNMENT, 1);
+                gl.get_tex_image(
+                    self.get_target(),
+                    level as i32,
+                    format,
+                    texture_type,
+                    glow::PixelPackData::Slice(data),
+                );
+                gl.pixel_store_i32(glow::PACK_ALIGNMENT, 4);
+            }
+            self.unbind();
+            return Ok(());
+        }
+
+        if self.get_target() != glow::TEXTURE_2D {
+            return Err("GLES can only read back 2D textures".to_string());
+        }
+        unsafe {
+            let previous = gl.get_parameter_framebuffer(glow::READ_FRAMEBUFFER_BINDING);
+            let framebuffer = gl.create_framebuffer()?;
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(framebuffer));
+            gl.framebuffer_texture_2d(
+                glow::READ_FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::TEXTURE_2D,
+                Some(self.get_id()),
+                level as i32,
+            );
+            let status = gl.check_framebuffer_status(glow::READ_FRAMEBUFFER);
+            if status == glow::FRAMEBUFFER_COMPLETE {
+                gl.pixel_store_i32(glow::PACK_ALIGNMENT, 1);
+                gl.read_pixels(
+                    0,
+                    0,
+                    (self.get_width() >> level).max(1) as i32,
+                    (self.get_height() >> level).max(1) as i32,
+                    format,
+                    texture_type,
+                    glow::PixelPackData::Slice(data),
+                );
+                gl.pixel_store_i32(glow::PACK_ALIGNMENT, 4);
+            }
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, previous);
+            gl.delete_framebuffer(framebuffer);
+            if status != glow::FRAMEBUFFER_COMPLETE {
+                return Err(format!(
+                    "Texture format {:#06x} cannot be read back on GLES",
+                    self.get_internal_format()
+                ));
+            }
+        }
+        Ok(())
+    }
+    fn generate_mipmaps(&self) {
+        self.bind();
+        unsafe {
+            self.get_context().generate_mipmap(self.get_target());
+        }
+        self.unbind();
+    }
     fn set_min_filter(&self, filter: u32) {
         self.bind();
         unsafe {
@@ -133,6 +206,16 @@ impl TextureData {
         texture_type: Option<u32>,
         data: Option<&[u8]>,
     ) -> Result<Self, String> {
+        // cube map data is sliced into six equal faces below
+        if let (glow::TEXTURE_CUBE_MAP, Some(data)) = (target, data) {
+            if data.is_empty() || !data.len().is_multiple_of(6) {
+                return Err(format!(
+                    "Cube map data of {} bytes does not split into 6 faces",
+                    data.len()
+                ));
+            }
+        }
+
         unsafe {
             let id = gl.create_texture().map_err(|e| e.to_string())?;
             gl.bind_texture(target, Some(id));
@@ -147,6 +230,9 @@ impl TextureData {
             gl.tex_parameter_i32(target, glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE as i32);
             gl.tex_parameter_i32(target, glow::TEXTURE_WRAP_R, glow::CLAMP_TO_EDGE as i32);
 
+            let row_size = width * get_bytes_per_pixel(format, texture_type);
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, get_unpack_alignment(row_size));
+
             if target == glow::TEXTURE_1D {
                 gl.tex_image_1d(
                     target,
@@ -183,11 +269,28 @@ impl TextureData {
                     texture_type,
                     data,
                 );
+            } else if target == glow::TEXTURE_CUBE_MAP {
+                let face_size = data.map(|d| d.len() / 6).unwrap_or(0);
+                for face in 0..6 {
+                    gl.tex_image_2d(
+                        glow::TEXTURE_CUBE_MAP_POSITIVE_X + face as u32,
+                        0,
+                        internal_format as i32,
+                        width as i32,
+                        height as i32,
+                        0,
+                        format,
+                        texture_type,
+                        data.map(|d| &d[face * face_size..(face + 1) * face_size]),
+                    );
+                }
             } else {
+                gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 4);
                 gl.bind_texture(target, None);
                 return Err("Invalid texture target".to_string());
             }
 
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 4);
             gl.bind_texture(target, None);
 
             Ok(Self {
@@ -204,6 +307,114 @@ impl TextureData {
         }
     }
 
+    pub fn new_compressed(
+        gl: Rc<glow::Context>,
+        target: u32,
+        image: &CompressedImage,
+    ) -> Result<Self, String> {
+        check_compressed_format_support(&gl, image.internal_format)?;
+
+        let face_targets = if target == glow::TEXTURE_2D && image.face_count == 1 {
+            vec![glow::TEXTURE_2D]
+        } else if target == glow::TEXTURE_CUBE_MAP && image.is_cubemap() {
+            (0..6)
+                .map(|face| glow::TEXTURE_CUBE_MAP_POSITIVE_X + face)
+                .collect()
+        } else {
+            return Err(format!(
+                "Compressed image with {} faces cannot be loaded into target {:#06x}",
+                image.face_count, target
+            ));
+        };
+
+        unsafe {
+            let id = gl.create_texture().map_err(|e| e.to_string())?;
+            gl.bind_texture(target, Some(id));
+
+            let min_filter = if image.levels.len() > 1 {
+                glow::LINEAR_MIPMAP_LINEAR
+            } else {
+                glow::LINEAR
+            };
+            gl.tex_parameter_i32(target, glow::TEXTURE_MIN_FILTER, min_filter as i32);
+            gl.tex_parameter_i32(target, glow::TEXTURE_MAG_FILTER, glow::LINEAR as i32);
+            gl.tex_parameter_i32(target, glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE as i32);
+            gl.tex_parameter_i32(target, glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE as i32);
+            gl.tex_parameter_i32(target, glow::TEXTURE_WRAP_R, glow::CLAMP_TO_EDGE as i32);
+            gl.tex_parameter_i32(target, glow::TEXTURE_BASE_LEVEL, 0);
+            gl.tex_parameter_i32(
+                target,
+                glow::TEXTURE_MAX_LEVEL,
+                image.levels.len() as i32 - 1,
+            );
+
+            for (level, mip) in image.levels.iter().enumerate() {
+                for (face_target, face) in face_targets.iter().zip(mip.faces.iter()) {
+                    gl.compressed_tex_image_2d(
+                        *face_target,
+                        level as i32,
+                        image.internal_format as i32,
+                        mip.width as i32,
+                        mip.height as i32,
+                        0,
+                        face.len() as i32,
+                        face,
+                    );
+                }
+            }
+
+            gl.bind_texture(target, None);
+
+            Ok(Self {
+                gl,
+                id,
+                target,
+                width: image.width,
+                height: image.height,
+                depth: 1,
+                internal_format: image.internal_format,
+                format: get_gl_format_from_internal(image.internal_format),
+                texture_type: glow::UNSIGNED_BYTE,
+            })
+        }
+    }
+
+    pub fn new_multisample(
+        gl: Rc<glow::Context>,
+        width: usize,
+        height: usize,
+        internal_format: u32,
+        samples: usize,
+    ) -> Result<Self, String> {
+        let target = glow::TEXTURE_2D_MULTISAMPLE;
+
+        unsafe {
+            let id = gl.create_texture().map_err(|e| e.to_string())?;
+            gl.bind_texture(target, Some(id));
+            gl.tex_image_2d_multisample(
+                target,
+                samples as i32,
+                internal_format as i32,
+                width as i32,
+                height as i32,
+                true,
+            );
+            gl.bind_texture(target, None);
+
+            Ok(Self {
+                gl,
+                id,
+                target,
+                width,
+                height,
+                depth: 1,
+                internal_format,
+                format: get_gl_format_from_internal(internal_format),
+                texture_type: get_gl_type_from_internal(internal_format),
+            })
+        }
+    }
+
     pub fn load_data(
         &self,
         data: &[u8],
@@ -214,9 +425,18 @@ impl TextureData {
         height: usize,
         depth: usize,
     ) {
+        if is_compressed_format(self.internal_format) {
+            log::error!("load_data is not supported for compressed textures");
+            return;
+        }
+
         unsafe {
             self.gl.bind_texture(self.target, Some(self.id));
 
+            let row_size = width * get_bytes_per_pixel(self.format, self.texture_type);
+            self.gl
+                .pixel_store_i32(glow::UNPACK_ALIGNMENT, get_unpack_alignment(row_size));
+
             if (self.target == glow::TEXTURE_1D) {
                 log::error!("Texture 1D not supported");
             } else if self.target == glow::TEXTURE_2D {
@@ -247,6 +467,7 @@ impl TextureData {
                 );
             }
 
+            self.gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 4);
             self.gl.bind_texture(self.target, None);
         }
     }
@@ -337,6 +558,11 @@ impl Texture2D {
         self.data
             .load_data(data, x_offset, y_offset, 0, width, height, 1);
     }
+
+    pub fn from_compressed(gl: Rc<glow::Context>, image: &CompressedImage) -> Result<Self, String> {
+        let data = TextureData::new_compressed(gl, glow::TEXTURE_2D, image)?;
+        Ok(Self { data })
+    }
 }
 
 impl TextureTrait for Texture2D {
@@ -344,6 +570,76 @@ impl TextureTrait for Texture2D {
         &self.data
     }
 }
+
+pub struct Texture2DMultisample {
+    data: TextureData,
+    samples: usize,
+}
+
+impl Texture2DMultisample {
+    // the sample count is clamped to what the driver reports for the format
+    pub fn new(
+        gl: Rc<glow::Context>,
+        width: usize,
+        height: usize,
+        internal_format: u32,
+        samples: usize,
+    ) -> Result<Self, String> {
+        let samples = clamp_samples(&gl, glow::TEXTURE_2D_MULTISAMPLE, internal_format, samples);
+        let data = TextureData::new_multisample(gl, width, height, internal_format, samples)?;
+        Ok(Self { data, samples })
+    }
+
+    pub fn get_samples(&self) -> usize {
+        self.samples
+    }
+}
+
+impl TextureTrait for Texture2DMultisample {
+    fn get_texture_data(&self) -> &TextureData {
+        &self.data
+    }
+}
+
+pub struct TextureCube {
+    data: TextureData,
+}
+
+impl TextureCube {
+    // data holds the six faces back to back in +X, -X, +Y, -Y, +Z, -Z order
+    pub fn new(
+        gl: Rc<glow::Context>,
+        size: usize,
+        internal_format: u32,
+        format: Option<u32>,
+        texture_type: Option<u32>,
+        data: Option<&[u8]>,
+    ) -> Result<Self, String> {
+        let data = TextureData::new(
+            gl.clone(),
+            glow::TEXTURE_CUBE_MAP,
+            size,
+            size,
+            1,
+            internal_format,
+            format,
+            texture_type,
+            data,
+        )?;
+        Ok(Self { data })
+    }
+
+    pub fn from_compressed(gl: Rc<glow::Context>, image: &CompressedImage) -> Result<Self, String> {
+        let data = TextureData::new_compressed(gl, glow::TEXTURE_CUBE_MAP, image)?;
+        Ok(Self { data })
+    }
+}
+
+impl TextureTrait for TextureCube {
+    fn get_texture_data(&self) -> &TextureData {
+        &self.data
+    }
+}
 pub struct Texture3D {
     data: TextureData,
 }
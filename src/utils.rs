@@ -11,11 +11,11 @@ pub fn get_gl_format_from_internal(internal_format: u32) -> u32 {
 
         RG8I | RG8UI | RG16I | RG16UI | RG32I | RG32UI => RG_INTEGER,
 
-        RGB8 | RGB16 | RGB16F | RGB32F => RGB,
+        RGB8 | SRGB8 | RGB16 | RGB16F | RGB32F => RGB,
 
         RGB8I | RGB8UI | RGB16I | RGB16UI | RGB32I | RGB32UI => RGB_INTEGER,
 
-        RGBA8 | RGBA16 | RGBA16F | RGBA32F => RGBA,
+        RGBA8 | SRGB8_ALPHA8 | RGBA16 | RGBA16F | RGBA32F => RGBA,
 
         RGBA8I | RGBA8UI | RGBA16I | RGBA16UI | RGBA32I | RGBA32UI => RGBA_INTEGER,
 
@@ -27,6 +27,25 @@ pub fn get_gl_format_from_internal(internal_format: u32) -> u32 {
 
         STENCIL_INDEX | STENCIL_INDEX8 => STENCIL_INDEX,
 
+        COMPRESSED_RED_RGTC1
+        | COMPRESSED_SIGNED_RED_RGTC1
+        | COMPRESSED_R11_EAC
+        | COMPRESSED_SIGNED_R11_EAC => RED,
+
+        COMPRESSED_RG_RGTC2
+        | COMPRESSED_SIGNED_RG_RGTC2
+        | COMPRESSED_RG11_EAC
+        | COMPRESSED_SIGNED_RG11_EAC => RG,
+
+        COMPRESSED_RGB_S3TC_DXT1_EXT
+        | COMPRESSED_SRGB_S3TC_DXT1_EXT
+        | COMPRESSED_RGB_BPTC_SIGNED_FLOAT
+        | COMPRESSED_RGB_BPTC_UNSIGNED_FLOAT
+        | COMPRESSED_RGB8_ETC2
+        | COMPRESSED_SRGB8_ETC2 => RGB,
+
+        _ if is_compressed_format(internal_format) => RGBA,
+
         _ => {
             warn!(
                 "Unknown internal format: {}, returning RGBA",
@@ -39,7 +58,7 @@ pub fn get_gl_format_from_internal(internal_format: u32) -> u32 {
 
 pub fn get_gl_type_from_internal(internal_format: u32) -> u32 {
     match internal_format {
-        R8 | RG8 | RGB8 | RGBA8 => UNSIGNED_BYTE,
+        R8 | RG8 | RGB8 | RGBA8 | SRGB8 | SRGB8_ALPHA8 => UNSIGNED_BYTE,
 
         // normalized to 0-1
         R16 | RG16 | RGB16 | RGBA16 => UNSIGNED_SHORT,
@@ -75,3 +94,333 @@ pub fn get_gl_type_from_internal(internal_format: u32) -> u32 {
         }
     }
 }
+
+pub fn get_bytes_per_pixel(format: u32, texture_type: u32) -> usize {
+    let channels = match format {
+        RED | RED_INTEGER | DEPTH_COMPONENT | STENCIL_INDEX => 1,
+        RG | RG_INTEGER => 2,
+        RGB | RGB_INTEGER => 3,
+        RGBA | RGBA_INTEGER => 4,
+        DEPTH_STENCIL => return 4,
+        _ => 4,
+    };
+    let size = match texture_type {
+        UNSIGNED_BYTE | BYTE => 1,
+        UNSIGNED_SHORT | SHORT | HALF_FLOAT => 2,
+        UNSIGNED_INT | INT | FLOAT | UNSIGNED_INT_24_8 => 4,
+        _ => 1,
+    };
+    channels * size
+}
+
+// the largest alignment GL accepts that evenly divides a row
+pub fn get_unpack_alignment(row_size: usize) -> i32 {
+    match row_size {
+        s if s.is_multiple_of(8) => 8,
+        s if s.is_multiple_of(4) => 4,
+        s if s.is_multiple_of(2) => 2,
+        _ => 1,
+    }
+}
+
+pub fn is_compressed_format(internal_format: u32) -> bool {
+    get_compressed_block_size(internal_format).is_some()
+}
+
+// returns (block width, block height, bytes per block)
+pub fn get_compressed_block_size(internal_format: u32) -> Option<(usize, usize, usize)> {
+    let size = match internal_format {
+        COMPRESSED_RGB_S3TC_DXT1_EXT
+        | COMPRESSED_RGBA_S3TC_DXT1_EXT
+        | COMPRESSED_SRGB_S3TC_DXT1_EXT
+        | COMPRESSED_SRGB_ALPHA_S3TC_DXT1_EXT
+        | COMPRESSED_RED_RGTC1
+        | COMPRESSED_SIGNED_RED_RGTC1
+        | COMPRESSED_RGB8_ETC2
+        | COMPRESSED_SRGB8_ETC2
+        | COMPRESSED_RGB8_PUNCHTHROUGH_ALPHA1_ETC2
+        | COMPRESSED_SRGB8_PUNCHTHROUGH_ALPHA1_ETC2
+        | COMPRESSED_R11_EAC
+        | COMPRESSED_SIGNED_R11_EAC => (4, 4, 8),
+
+        COMPRESSED_RGBA_S3TC_DXT3_EXT
+        | COMPRESSED_RGBA_S3TC_DXT5_EXT
+        | COMPRESSED_SRGB_ALPHA_S3TC_DXT3_EXT
+        | COMPRESSED_SRGB_ALPHA_S3TC_DXT5_EXT
+        | COMPRESSED_RG_RGTC2
+        | COMPRESSED_SIGNED_RG_RGTC2
+        | COMPRESSED_RGBA_BPTC_UNORM
+        | COMPRESSED_SRGB_ALPHA_BPTC_UNORM
+        | COMPRESSED_RGB_BPTC_SIGNED_FLOAT
+        | COMPRESSED_RGB_BPTC_UNSIGNED_FLOAT
+        | COMPRESSED_RGBA8_ETC2_EAC
+        | COMPRESSED_SRGB8_ALPHA8_ETC2_EAC
+        | COMPRESSED_RG11_EAC
+        | COMPRESSED_SIGNED_RG11_EAC => (4, 4, 16),
+
+        COMPRESSED_RGBA_ASTC_4x4_KHR..=COMPRESSED_RGBA_ASTC_12x12_KHR => {
+            get_astc_block_size(internal_format - COMPRESSED_RGBA_ASTC_4x4_KHR)
+        }
+        COMPRESSED_SRGB8_ALPHA8_ASTC_4x4_KHR..=COMPRESSED_SRGB8_ALPHA8_ASTC_12x12_KHR => {
+            get_astc_block_size(internal_format - COMPRESSED_SRGB8_ALPHA8_ASTC_4x4_KHR)
+        }
+
+        _ => return None,
+    };
+    Some(size)
+}
+
+fn get_astc_block_size(index: u32) -> (usize, usize, usize) {
+    const BLOCKS: [(usize, usize); 14] = [
+        (4, 4),
+        (5, 4),
+        (5, 5),
+        (6, 5),
+        (6, 6),
+        (8, 5),
+        (8, 6),
+        (8, 8),
+        (10, 5),
+        (10, 6),
+        (10, 8),
+        (10, 10),
+        (12, 10),
+        (12, 12),
+    ];
+    let (w, h) = BLOCKS[index as usize];
+    (w, h, 16)
+}
+
+pub fn get_compressed_image_size(
+    block_size: (usize, usize, usize),
+    width: usize,
+    height: usize,
+) -> usize {
+    let (block_width, block_height, block_bytes) = block_size;
+    width.div_ceil(block_width) * height.div_ceil(block_height) * block_bytes
+}
+
+// any one of the returned extensions is enough to upload the format
+pub fn get_compressed_format_extensions(internal_format: u32) -> &'static [&'static str] {
+    match internal_format {
+        COMPRESSED_RGB_S3TC_DXT1_EXT
+        | COMPRESSED_RGBA_S3TC_DXT1_EXT
+        | COMPRESSED_RGBA_S3TC_DXT3_EXT
+        | COMPRESSED_RGBA_S3TC_DXT5_EXT => &[
+            "GL_EXT_texture_compression_s3tc",
+            "WEBGL_compressed_texture_s3tc",
+        ],
+
+        COMPRESSED_SRGB_S3TC_DXT1_EXT
+        | COMPRESSED_SRGB_ALPHA_S3TC_DXT1_EXT
+        | COMPRESSED_SRGB_ALPHA_S3TC_DXT3_EXT
+        | COMPRESSED_SRGB_ALPHA_S3TC_DXT5_EXT => &[
+            "GL_EXT_texture_sRGB",
+            "GL_EXT_texture_compression_s3tc_srgb",
+            "WEBGL_compressed_texture_s3tc_srgb",
+        ],
+
+        COMPRESSED_RED_RGTC1
+        | COMPRESSED_SIGNED_RED_RGTC1
+        | COMPRESSED_RG_RGTC2
+        | COMPRESSED_SIGNED_RG_RGTC2 => &[
+            "GL_ARB_texture_compression_rgtc",
+            "GL_EXT_texture_compression_rgtc",
+            "EXT_texture_compression_rgtc",
+        ],
+
+        COMPRESSED_RGBA_BPTC_UNORM
+        | COMPRESSED_SRGB_ALPHA_BPTC_UNORM
+        | COMPRESSED_RGB_BPTC_SIGNED_FLOAT
+        | COMPRESSED_RGB_BPTC_UNSIGNED_FLOAT => &[
+            "GL_ARB_texture_compression_bptc",
+            "GL_EXT_texture_compression_bptc",
+            "EXT_texture_compression_bptc",
+        ],
+
+        COMPRESSED_RGB8_ETC2..=COMPRESSED_SRGB8_ALPHA8_ETC2_EAC
+        | COMPRESSED_R11_EAC..=COMPRESSED_SIGNED_RG11_EAC => {
+            &["GL_ARB_ES3_compatibility", "WEBGL_compressed_texture_etc"]
+        }
+
+        COMPRESSED_RGBA_ASTC_4x4_KHR..=COMPRESSED_RGBA_ASTC_12x12_KHR
+        | COMPRESSED_SRGB8_ALPHA8_ASTC_4x4_KHR..=COMPRESSED_SRGB8_ALPHA8_ASTC_12x12_KHR => &[
+            "GL_KHR_texture_compression_astc_ldr",
+            "WEBGL_compressed_texture_astc",
+        ],
+
+        _ => &[],
+    }
+}
+
+// formats that the context supports without advertising an extension
+// WebGL reports itself as ES but leaves every compressed format to extensions
+pub fn is_compressed_format_core(version: &glow::Version, internal_format: u32) -> bool {
+    if cfg!(target_arch = "wasm32") {
+        return false;
+    }
+    let at_least = |major: u32, minor: u32| (version.major, version.minor) >= (major, minor);
+    match internal_format {
+        COMPRESSED_RED_RGTC1
+        | COMPRESSED_SIGNED_RED_RGTC1
+        | COMPRESSED_RG_RGTC2
+        | COMPRESSED_SIGNED_RG_RGTC2 => !version.is_embedded && at_least(3, 0),
+
+        COMPRESSED_RGBA_BPTC_UNORM
+        | COMPRESSED_SRGB_ALPHA_BPTC_UNORM
+        | COMPRESSED_RGB_BPTC_SIGNED_FLOAT
+        | COMPRESSED_RGB_BPTC_UNSIGNED_FLOAT => !version.is_embedded && at_least(4, 2),
+
+        COMPRESSED_RGB8_ETC2..=COMPRESSED_SRGB8_ALPHA8_ETC2_EAC
+        | COMPRESSED_R11_EAC..=COMPRESSED_SIGNED_RG11_EAC => {
+            if version.is_embedded {
+                at_least(3, 0)
+            } else {
+                at_least(4, 3)
+            }
+        }
+
+        COMPRESSED_RGBA_ASTC_4x4_KHR..=COMPRESSED_RGBA_ASTC_12x12_KHR
+        | COMPRESSED_SRGB8_ALPHA8_ASTC_4x4_KHR..=COMPRESSED_SRGB8_ALPHA8_ASTC_12x12_KHR => {
+            version.is_embedded && at_least(3, 2)
+        }
+
+        _ => false,
+    }
+}
+
+pub fn check_compressed_format_support(
+    gl: &glow::Context,
+    internal_format: u32,
+) -> Result<(), String> {
+    let required = get_compressed_format_extensions(internal_format);
+    if required.is_empty() {
+        return Err(format!(
+            "Unknown compressed internal format: {:#06x}",
+            internal_format
+        ));
+    }
+    if is_compressed_format_core(gl.version(), internal_format) {
+        return Ok(());
+    }
+
+    let supported = gl.supported_extensions();
+    if required.iter().any(|ext| supported.contains(*ext)) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Compressed internal format {:#06x} requires one of the extensions: {}",
+            internal_format,
+            required.join(", ")
+        ))
+    }
+}
+
+// compute shaders are core from GL 4.3 and GLES 3.1, older desktop contexts may have the extension
+pub fn check_compute_support(gl: &glow::Context) -> Result<(), String> {
+    let version = gl.version();
+    let core = if version.is_embedded {
+        (version.major, version.minor) >= (3, 1)
+    } else {
+        (version.major, version.minor) >= (4, 3)
+    };
+    if core || gl.supported_extensions().contains("GL_ARB_compute_shader") {
+        Ok(())
+    } else {
+        Err(format!(
+            "Compute shaders need GL 4.3, GLES 3.1 or GL_ARB_compute_shader, the context is {}{}.{}",
+            if version.is_embedded { "GLES " } else { "GL " },
+            version.major,
+            version.minor
+        ))
+    }
+}
+
+pub fn is_integer_format(internal_format: u32) -> bool {
+    matches!(
+        get_gl_format_from_internal(internal_format),
+        RED_INTEGER | RG_INTEGER | RGB_INTEGER | RGBA_INTEGER
+    )
+}
+
+pub fn is_depth_format(internal_format: u32) -> bool {
+    matches!(
+        internal_format,
+        DEPTH_COMPONENT
+            | DEPTH_COMPONENT16
+            | DEPTH_COMPONENT24
+            | DEPTH_COMPONENT32
+            | DEPTH_COMPONENT32F
+            | DEPTH_STENCIL
+            | DEPTH24_STENCIL8
+            | DEPTH32F_STENCIL8
+    )
+}
+
+pub fn get_max_samples(gl: &glow::Context, target: u32, internal_format: u32) -> usize {
+    let parameter = if is_integer_format(internal_format) {
+        MAX_INTEGER_SAMPLES
+    } else if target == TEXTURE_2D_MULTISAMPLE && is_depth_format(internal_format) {
+        MAX_DEPTH_TEXTURE_SAMPLES
+    } else if target == TEXTURE_2D_MULTISAMPLE {
+        MAX_COLOR_TEXTURE_SAMPLES
+    } else {
+        MAX_SAMPLES
+    };
+    unsafe { gl.get_parameter_i32(parameter).max(1) as usize }
+}
+
+pub fn clamp_samples(
+    gl: &glow::Context,
+    target: u32,
+    internal_format: u32,
+    samples: usize,
+) -> usize {
+    let max_samples = get_max_samples(gl, target, internal_format);
+    if samples > max_samples {
+        warn!(
+            "Requested {} samples but the driver supports {}, clamping",
+            samples, max_samples
+        );
+    }
+    samples.clamp(1, max_samples)
+}
+
+pub fn clear_color(gl: &glow::Context, color: &paxil_math::Color) {
+    unsafe { gl.clear_color(color.r, color.g, color.b, color.a) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_per_pixel() {
+        for (format, texture_type, size) in [
+            (RED, UNSIGNED_BYTE, 1),
+            (RG, UNSIGNED_BYTE, 2),
+            (RGB, UNSIGNED_BYTE, 3),
+            (RGBA, UNSIGNED_BYTE, 4),
+            (RGB, HALF_FLOAT, 6),
+            (RG_INTEGER, UNSIGNED_SHORT, 4),
+            (RGBA, FLOAT, 16),
+            (RGB_INTEGER, INT, 12),
+            (DEPTH_COMPONENT, FLOAT, 4),
+            (DEPTH_STENCIL, UNSIGNED_INT_24_8, 4),
+        ] {
+            assert_eq!(get_bytes_per_pixel(format, texture_type), size);
+        }
+    }
+
+    #[test]
+    fn unpack_alignment() {
+        for (row_size, alignment) in [(1, 1), (3, 1), (6, 2), (12, 4), (16, 8), (24, 8), (30, 2)] {
+            assert_eq!(get_unpack_alignment(row_size), alignment);
+        }
+        // a 5 pixel wide RGB8 row is 15 bytes, tightly packed rows need alignment 1
+        assert_eq!(
+            get_unpack_alignment(5 * get_bytes_per_pixel(RGB, UNSIGNED_BYTE)),
+            1
+        );
+    }
+}
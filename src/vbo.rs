@@ -0,0 +1,90 @@
+use glow::HasContext;
+use std::rc::Rc;
+
+pub struct VBO {
+    gl: Rc<glow::Context>,
+    vbo: glow::Buffer,
+    size: usize,
+    usage: u32,
+}
+
+impl VBO {
+    // usage is one of glow::STATIC_DRAW, glow::DYNAMIC_DRAW or glow::STREAM_DRAW
+    pub fn new(gl: Rc<glow::Context>, data: &[u8], usage: u32) -> Result<Self, String> {
+        unsafe {
+            let vbo = gl.create_buffer()?;
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+            gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, data, usage);
+            Ok(Self {
+                gl,
+                vbo,
+                size: data.len(),
+                usage,
+            })
+        }
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            self.gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
+        }
+    }
+
+    pub fn unbind(&self) {
+        unsafe {
+            self.gl.bind_buffer(glow::ARRAY_BUFFER, None);
+        }
+    }
+
+    // reallocates only when the data no longer fits
+    pub fn set_data(&mut self, data: &[u8]) {
+        unsafe {
+            self.gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
+            if data.len() > self.size {
+                self.gl
+                    .buffer_data_u8_slice(glow::ARRAY_BUFFER, data, self.usage);
+                self.size = data.len();
+            } else {
+                self.gl
+                    .buffer_sub_data_u8_slice(glow::ARRAY_BUFFER, 0, data);
+            }
+        }
+    }
+
+    pub fn set_sub_data(&self, offset: usize, data: &[u8]) -> Result<(), String> {
+        if offset + data.len() > self.size {
+            return Err(format!(
+                "Write of {} bytes at {} overflows buffer of {} bytes",
+                data.len(),
+                offset,
+                self.size
+            ));
+        }
+        unsafe {
+            self.gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
+            self.gl
+                .buffer_sub_data_u8_slice(glow::ARRAY_BUFFER, offset as i32, data);
+        }
+        Ok(())
+    }
+
+    pub fn get_id(&self) -> glow::Buffer {
+        self.vbo
+    }
+
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+
+    pub fn get_usage(&self) -> u32 {
+        self.usage
+    }
+}
+
+impl Drop for VBO {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_buffer(self.vbo);
+        }
+    }
+}